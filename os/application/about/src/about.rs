@@ -28,7 +28,7 @@ struct Dependency {
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let rust_dep_file = include_str!("rust-dependencies.json");
     let other_dep_file = include_str!("other-dependencies.json");
 
@@ -74,7 +74,7 @@ pub fn main() {
             match input {
                 Some(DecodedKey::Unicode('q')) | Some(DecodedKey::Unicode('Q')) | None => {
                     print!("\n");
-                    return; // Exit the application
+                    return 0; // Exit the application
                 },
                 Some(DecodedKey::Unicode('\n')) => {
                     print!("\n");
@@ -84,4 +84,5 @@ pub fn main() {
             }
         }
     }
+    0
 }
\ No newline at end of file
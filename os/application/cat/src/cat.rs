@@ -0,0 +1,57 @@
+#![no_std]
+
+extern crate alloc;
+
+use naming::shared_types::{OpenOptions, STDOUT};
+#[allow(unused_imports)]
+use runtime::*;
+use syscall::return_vals::Errno;
+use terminal::println;
+
+const BUFFER_SIZE: usize = 512;
+
+/// Copy everything readable from `read` to stdout
+fn copy_to_stdout(mut read: impl FnMut(&mut [u8]) -> Result<usize, Errno>) -> Result<(), Errno> {
+    let mut buffer = [0u8; BUFFER_SIZE];
+    loop {
+        let len = read(&mut buffer)?;
+        if len == 0 {
+            return Ok(());
+        }
+
+        let mut written = 0;
+        while written < len {
+            written += naming::write(STDOUT, &buffer[written..len])?;
+        }
+    }
+}
+
+fn cat_file(path: &str) -> Result<(), Errno> {
+    let fd = naming::open(path, OpenOptions::READONLY)?;
+    let res = copy_to_stdout(|buffer| naming::read(fd, buffer));
+    let _ = naming::close(fd);
+    res
+}
+
+#[unsafe(no_mangle)]
+pub fn main() -> isize {
+    let mut args = env::args().skip(1).peekable();
+
+    // without file arguments, copy stdin to stdout
+    if args.peek().is_none() {
+        if let Err(e) = copy_to_stdout(terminal::read::read_bytes) {
+            println!("cat: {:?}", e);
+            return 1;
+        }
+        return 0;
+    }
+
+    let mut exit_code = 0;
+    for path in args {
+        if let Err(e) = cat_file(&path) {
+            println!("cat: {}: {:?}", path, e);
+            exit_code = 1;
+        }
+    }
+    exit_code
+}
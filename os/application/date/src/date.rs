@@ -8,7 +8,8 @@ use terminal::println;
 use time::date;
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let date = date();
     println!("{}", date.format("%Y-%m-%d %H:%M:%S"));
+    0
 }
@@ -2,20 +2,46 @@
 
 extern crate alloc;
 
+use alloc::vec;
 use naming::shared_types::{OpenOptions, SeekOrigin};
 #[allow(unused_imports)]
 use runtime::*;
 use terminal::{print, println};
 
+/// Block devices tried for `test_large_block_read`
+const BLOCK_DEVICES: [&str; 3] = ["/dev/ata0", "/dev/sata0", "/dev/vd0"];
+/// Bytes read at once by `test_large_block_read` (more than the 512 KiB block cache of a device)
+const LARGE_READ_SIZE: usize = 1024 * 1024;
+
+/// Read more than the capacity of the block cache with a single request (twice, the second read partly hits the cache).
+/// Returns `false`, if a read fails or both reads differ.
+fn test_large_block_read() -> bool {
+    let Some((path, fd)) = BLOCK_DEVICES.iter().find_map(|path| naming::open(path, OpenOptions::READONLY).ok().map(|fd| (path, fd))) else {
+        println!("large block read: no block device found, skipped");
+        return true;
+    };
+
+    let size = naming::fstat(fd).map(|metadata| metadata.size.min(LARGE_READ_SIZE)).unwrap_or(0);
+    let mut first = vec![0u8; size];
+    let mut second = vec![0u8; size];
+    let first_res = naming::read(fd, &mut first);
+    let _ = naming::seek(fd, 0, SeekOrigin::Start);
+    let second_res = naming::read(fd, &mut second);
+    let _ = naming::close(fd);
+
+    println!("large block read of [{}] bytes from [{}] = {:?}, {:?}", size, path, first_res, second_res);
+    first_res == Ok(size) && second_res == Ok(size) && first == second
+}
+
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     println!("naming tests");
 
     // opening file
     let res = naming::open("/file.txt", OpenOptions::READWRITE | OpenOptions::CREATE);
     if res.is_err() {
         println!("open error = {:?}", res);
-        return;
+        return 1;
     }
     let fd = res.unwrap();
 
@@ -70,7 +96,7 @@ pub fn main() {
     let res = naming::open("/test", OpenOptions::DIRECTORY);
     if res.is_err() {
         println!("open error = {:?}", res);
-        return;
+        return 1;
     }
     let fd = res.unwrap();
     println!("open dir '/test'");
@@ -94,5 +120,11 @@ pub fn main() {
     let close_res = naming::close(fd);
     println!("close result = {:?}", close_res);
 
+    if !test_large_block_read() {
+        println!("large block read failed");
+        return 1;
+    }
+
     println!("naming test: end");
+    0
 }
@@ -0,0 +1,94 @@
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use naming::shared_types::OpenOptions;
+#[allow(unused_imports)]
+use runtime::*;
+use syscall::return_vals::Errno;
+use terminal::println;
+
+const BUFFER_SIZE: usize = 512;
+
+fn print_usage() {
+    println!("usage: grep PATTERN [FILE]...");
+}
+
+/// Read everything readable from `read`
+fn read_all(mut read: impl FnMut(&mut [u8]) -> Result<usize, Errno>) -> Result<Vec<u8>, Errno> {
+    let mut content = Vec::new();
+    let mut buffer = [0u8; BUFFER_SIZE];
+    loop {
+        let len = read(&mut buffer)?;
+        if len == 0 {
+            return Ok(content);
+        }
+        content.extend_from_slice(&buffer[..len]);
+    }
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, Errno> {
+    let fd = naming::open(path, OpenOptions::READONLY)?;
+    let res = read_all(|buffer| naming::read(fd, buffer));
+    let _ = naming::close(fd);
+    res
+}
+
+/// Print all lines of `content` containing `pattern`, prefixed with `prefix` (if any).
+/// Returns true, if at least one line matched.
+fn grep(pattern: &str, content: &[u8], prefix: Option<&str>) -> bool {
+    let content = String::from_utf8_lossy(content);
+    let mut matched = false;
+    for line in content.lines().filter(|line| line.contains(pattern)) {
+        match prefix {
+            Some(prefix) => println!("{}:{}", prefix, line),
+            None => println!("{}", line),
+        }
+        matched = true;
+    }
+    matched
+}
+
+/// Exit codes as on Unix: 0 if a line matched, 1 if no line matched, 2 on errors
+#[unsafe(no_mangle)]
+pub fn main() -> isize {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((pattern, paths)) = args.split_first() else {
+        print_usage();
+        return 2;
+    };
+
+    // without file arguments, search stdin
+    if paths.is_empty() {
+        return match read_all(terminal::read::read_bytes) {
+            Ok(content) if grep(pattern, &content, None) => 0,
+            Ok(_) => 1,
+            Err(e) => {
+                println!("grep: {:?}", e);
+                2
+            }
+        };
+    }
+
+    let mut matched = false;
+    let mut failed = false;
+    for path in paths {
+        match read_file(path) {
+            Ok(content) => matched |= grep(pattern, &content, (paths.len() > 1).then_some(path.as_str())),
+            Err(e) => {
+                println!("grep: {}: {:?}", path, e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        2
+    } else if matched {
+        0
+    } else {
+        1
+    }
+}
@@ -7,7 +7,7 @@ use runtime::*;
 use terminal::{println, read::read_fluid, DecodedKey};
 
 #[unsafe(no_mangle)]
-fn main() {
+fn main() -> isize {
     let mut allocations = Vec::new();
 
     println!("heap test");
@@ -30,4 +30,5 @@ fn main() {
             }
         }
     }
+    0
 }
@@ -9,7 +9,7 @@ use terminal::println;
 
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let process = process::current().unwrap();
     let thread = thread::current().unwrap();
 
@@ -31,5 +31,5 @@ pub fn main() {
     } else {
         println!("Failed to create second thread");
     }
-
+    0
 }
@@ -9,10 +9,11 @@ use network::get_ip_addresses;
 use terminal::println;
 
 #[unsafe(no_mangle)]
-fn main() {
+fn main() -> isize {
     // ignore all args for now
 
     for ip in get_ip_addresses() {
         println!("{}", ip)
     }
+    0
 }
@@ -13,14 +13,14 @@ use terminal::{
 ///
 /// Author: Sebastian Keller
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let mut args = env::args();
 
     let arg = match args.nth(1) {
         Some(arg) => arg,
         None => {
             println!("Require terminal mode as argument (canonical | fluid | raw)");
-            return;
+            return 1;
         }
     };
 
@@ -30,7 +30,7 @@ pub fn main() {
             loop {
                 print!("Echo: ");
                 match read().as_str() {
-                    "exit" => return,
+                    "exit" => return 0,
                     line => println!("Read: {}", line),
                 }
             }
@@ -40,7 +40,7 @@ pub fn main() {
             loop {
                 loop {
                     match read_fluid() {
-                        Some(DecodedKey::Unicode('\u{1b}')) => return,
+                        Some(DecodedKey::Unicode('\u{1b}')) => return 0,
                         Some(key) => println!("{:?}", key),
                         None => continue,
                     };
@@ -55,7 +55,7 @@ pub fn main() {
                     match read_raw() {
                         Some(KeyEvent {
                             code: KeyCode::Escape, state: KeyState::Down,
-                        }) => return,
+                        }) => return 0,
                         Some(event) => println!("{:?}", event),
                         None => continue,
                     };
@@ -65,6 +65,7 @@ pub fn main() {
         }
         _ => {
             println!("Invalid argument. Valid terminal modes are (canonical | fluid | raw)");
+            1
         }
     }
 }
@@ -4,7 +4,7 @@ extern crate alloc;
 
 use alloc::string::String;
 use alloc::vec::Vec;
-use concurrent::thread;
+use concurrent::process;
 use naming::{cd, cwd, mkdir, touch};
 #[allow(unused_imports)]
 use runtime::*;
@@ -73,16 +73,18 @@ fn process_line(line: String) {
     let split = line.split_whitespace().collect::<Vec<&str>>();
     if !split.is_empty() {
         if !process_internal_command(&split) {
-            match thread::start_application(split[0], split[1..].iter().map(|&s| s).collect()) {
-                Some(app) => app.join(),
-                None => println!("Command not found!"),
+            match process::start_application(split[0], split[1..].iter().map(|&s| s).collect()) {
+                Ok(app) => {
+                    let _ = app.wait();
+                }
+                Err(_) => println!("Command not found!"),
             }
         }
     }
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     loop {
         print!("> ");
         let line = read();
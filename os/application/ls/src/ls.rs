@@ -2,8 +2,10 @@
 
 extern crate alloc;
 
+use alloc::format;
 use alloc::string::String;
 use alloc::vec::Vec;
+use chrono::DateTime;
 use naming::cwd;
 
 use naming::shared_types::{DirEntry, FileType, OpenOptions};
@@ -12,20 +14,43 @@ use runtime::*;
 use terminal::println;
 
 fn print_usage() {
-    println!("usage: ls [directory_name");
+    println!("usage: ls [-l] [directory_name]");
 }
 
-fn print_dir_entry(dentry: DirEntry) {
-    if dentry.file_type == FileType::Directory {
-        println!("d {}", dentry.name);
-    } else if dentry.file_type == FileType::NamedPipe {
-        println!("p {}", dentry.name);
+/// Format a time stamp in milliseconds since the Unix epoch (0 = unknown)
+fn format_time(time_ms: u64) -> String {
+    match DateTime::from_timestamp_millis(time_ms as i64) {
+        Some(date) if time_ms != 0 => format!("{}", date.format("%Y-%m-%d %H:%M")),
+        _ => String::from("----------------"),
+    }
+}
+
+fn print_dir_entry(path: &str, dentry: DirEntry, long_listing: bool) {
+    let entry_path = format!("{}/{}", path.trim_end_matches('/'), dentry.name);
+    let name = if dentry.file_type == FileType::Link {
+        format!("{} -> {}", dentry.name, naming::readlink(&entry_path).unwrap_or_default())
     } else {
-        println!("- {}", dentry.name);
+        dentry.name
+    };
+    let type_char = match dentry.file_type {
+        FileType::Directory => 'd',
+        FileType::NamedPipe => 'p',
+        FileType::Link => 'l',
+        _ => '-',
+    };
+
+    if long_listing {
+        // meta data of the link itself is not available, show the one of its target
+        match naming::stat(&entry_path) {
+            Ok(metadata) => println!("{} {:>10} {} {}", type_char, metadata.size, format_time(metadata.modified_time), name),
+            Err(_) => println!("{} {:>10} {} {}", type_char, "?", format_time(0), name),
+        }
+    } else {
+        println!("{} {}", type_char, name);
     }
 }
 
-fn process_ls(path: &str) {
+fn process_ls(path: &str, long_listing: bool) {
     // open directory
     let res = naming::open(path, OpenOptions::DIRECTORY);
     if res.is_err() {
@@ -40,7 +65,7 @@ fn process_ls(path: &str) {
         match res {
             Ok(data) => {
                 match data {
-                    Some(content) => print_dir_entry(content),
+                    Some(content) => print_dir_entry(path, content, long_listing),
                     None => break,
                 }
             },
@@ -62,20 +87,25 @@ pub fn args_to_vec() -> Vec<String> {
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
-    let args_vec = args_to_vec();
+pub fn main() -> isize {
+    let mut args_vec = args_to_vec();
+    let long_listing = args_vec.len() > 1 && args_vec[1] == "-l";
+    if long_listing {
+        args_vec.remove(1);
+    }
     let args_count = args_vec.len();
 
 
     if args_count == 1 {
         let res = cwd();
         match res {
-            Ok(path) =>  process_ls(&path),
+            Ok(path) =>  process_ls(&path, long_listing),
             Err(_) => print_usage(),
         }
     } else if args_count == 2 {
-        process_ls(&args_vec[1]);
+        process_ls(&args_vec[1], long_listing);
     } else {
         print_usage();
     }
+    0
 }
@@ -4,10 +4,11 @@ extern crate alloc;
 use core::net::{IpAddr, Ipv6Addr, SocketAddr};
 
 use alloc::string::String;
+use naming::shared_types::PollEvents;
 use network::{resolve_hostname, TcpListener, TcpStream, UdpSocket};
 #[allow(unused_imports)]
 use runtime::*;
-use terminal::{print, println, read::read};
+use terminal::{print, println, read::{self, read}};
 
 enum Protocol {
     Udp, Tcp,
@@ -24,7 +25,7 @@ enum Socket {
 }
 
 #[unsafe(no_mangle)]
-fn main() {
+fn main() -> isize {
     let mut args = env::args().peekable();
     // the first argument is the program name, ignore it
     args.next();
@@ -44,7 +45,7 @@ Examples:
         open a TCP connection to example.net:5678
     nc -u -l 0.0.0.0 1234
         bind to 0.0.0.0:1234, UDP");
-                return;
+                return 0;
             }
             Some("-l") => {
                 mode = Mode::Listen;
@@ -58,7 +59,7 @@ Examples:
             Some(_) => break,
             None => {
                 println!("Usage: nc [-u] [-l] host port");
-                return;
+                return 1;
             },
         }
     }
@@ -73,7 +74,7 @@ Examples:
         SocketAddr::new(ip, port)
     } else {
         println!("Usage: nc [-u] [-l] host port");
-        return;
+        return 1;
     };
 
     let socket = match mode {
@@ -95,27 +96,46 @@ Examples:
         },
     };
 
-    // loop: send and receive
+    // loop: wait until the user has entered a line or data has been received
     let mut buf = [0u8; 1024];
     loop {
-        let mut message = read();
-        message.push('\n');
-        match socket {
-            Socket::Udp(ref sock) => sock.send_to(message.as_bytes(), addr)
-                .expect("failed to send char"),
-            Socket::Tcp(ref sock) => sock.write(message.as_bytes())
-                .expect("failed to send char"),
-        };
-        let len = match socket {
-            Socket::Udp(ref sock) => sock.recv_from(&mut buf)
-                .expect("failed to receive char").0,
-            Socket::Tcp(ref sock) => sock.read(&mut buf)
-                .expect("failed to receive char"),
-        };
-        if len > 0 {
-            let text = str::from_utf8(&buf[0..len]).expect("failed to parse received string");
-            print!("{text}");
+        let mut fds = [
+            read::poll_fd(),
+            match socket {
+                Socket::Udp(ref sock) => sock.poll_fd(PollEvents::IN),
+                Socket::Tcp(ref sock) => sock.poll_fd(PollEvents::IN),
+            },
+        ];
+        naming::poll(&mut fds, None).expect("failed to poll");
+
+        if fds[0].revents().contains(PollEvents::IN) {
+            let mut message = read();
+            message.push('\n');
+            match socket {
+                Socket::Udp(ref sock) => sock.send_to(message.as_bytes(), addr)
+                    .expect("failed to send char"),
+                Socket::Tcp(ref sock) => sock.write(message.as_bytes())
+                    .expect("failed to send char"),
+            };
         }
-    }
 
+        let socket_events = fds[1].revents();
+        let mut len = 0;
+        if socket_events.contains(PollEvents::IN) {
+            len = match socket {
+                Socket::Udp(ref sock) => sock.recv_from(&mut buf)
+                    .expect("failed to receive char").0,
+                Socket::Tcp(ref sock) => sock.read(&mut buf)
+                    .expect("failed to receive char"),
+            };
+            if len > 0 {
+                let text = str::from_utf8(&buf[0..len]).expect("failed to parse received string");
+                print!("{text}");
+            }
+        }
+        if len == 0 && socket_events.intersects(PollEvents::HUP | PollEvents::ERR | PollEvents::NVAL) {
+            // the connection has been closed and all data has been received
+            return 0;
+        }
+    }
 }
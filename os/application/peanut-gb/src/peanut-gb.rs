@@ -241,7 +241,7 @@ fn read_rom(path: &str) {
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     // Read the rom file into the `ROM` buffer.
     let mut args = env::args();
     let path = args.nth(1).expect("Usage: peanut-gb <rom_path>");
@@ -347,7 +347,7 @@ pub fn main() {
                     KeyCode::D => Some(JoypadButton::Right),
                     KeyCode::J => Some(JoypadButton::A),
                     KeyCode::K => Some(JoypadButton::B),
-                    KeyCode::Q => return,
+                    KeyCode::Q => return 0,
                     _ => None
                 };
 
@@ -5,7 +5,7 @@
 extern crate alloc;
 
 use alloc::{string::String, vec};
-use concurrent::thread::sleep;
+use naming::shared_types::PollEvents;
 use network::{resolve_hostname, IcmpSocket};
 #[allow(unused_imports)]
 use runtime::*;
@@ -13,7 +13,7 @@ use smoltcp::{phy::ChecksumCapabilities, wire::{Icmpv4Packet, Icmpv4Repr}};
 use terminal::println;
 
 #[unsafe(no_mangle)]
-fn main() {
+fn main() -> isize {
     let mut args = env::args().peekable();
     // the first argument is the program name, ignore it
     args.next();
@@ -30,7 +30,7 @@ fn main() {
 Examples:
     ping -c 2 1.2.3.4
         ping 1.2.3.4 two times");
-                return;
+                return 0;
             }
             Some("-c") => {
                 args.next();
@@ -40,7 +40,7 @@ Examples:
             Some(_) => break,
             None => {
                 println!("Usage: ping [-c count] host");
-                return;
+                return 1;
             },
         }
     }
@@ -48,7 +48,7 @@ Examples:
     // the next argument should be the host
     let Some(host) = args.next() else {
         println!("Usage: ping [-c count] host");
-        return;
+        return 1;
     };
     // just take the first IP address
     let ip = resolve_hostname(&host).into_iter().next().unwrap();
@@ -72,7 +72,8 @@ Examples:
             if len != 0 {
                 break addr;
             }
-            sleep(50);
+            // wait until the reply has been received
+            naming::poll(&mut [socket.poll_fd(PollEvents::IN)], None).expect("failed to poll");
         };
         let response_packet = Icmpv4Packet::new_checked(&recv_buffer).expect("received packet is invalid");
         let response = Icmpv4Repr::parse(&response_packet, &ChecksumCapabilities::ignored()).expect("received packet is invalid");
@@ -84,4 +85,5 @@ Examples:
             println!("ignoring unexpected ICMP packet")
         }
     }
+    0
 }
@@ -87,13 +87,13 @@ fn reader_thread() {
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     println!("named pipe demo: start");
 
     let res = mkfifo("/mypipe");
     if res.is_err() {
         println!("mkfifo failed, error: {:?}", res);
-        return;
+        return 1;
     }
     println!("mkfifo: ok");
 
@@ -112,4 +112,5 @@ pub fn main() {
     }
 
     println!("named pipe demo: done");
+    0
 }
@@ -0,0 +1,34 @@
+use runtime::env;
+use terminal::println;
+
+use crate::built_in::built_in::BuiltIn;
+
+pub struct EnvBuiltIn {}
+
+impl BuiltIn for EnvBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "env"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        if !args.is_empty() {
+            Self::print_usage();
+            return 1;
+        }
+
+        for (key, value) in env::vars() {
+            println!("{}={}", key, value);
+        }
+        0
+    }
+}
+
+impl EnvBuiltIn {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn print_usage() {
+        println!("Usage: env");
+    }
+}
@@ -1,4 +1,5 @@
 use concurrent::process;
+use terminal::println;
 
 use crate::built_in::built_in::BuiltIn;
 
@@ -9,9 +10,23 @@ impl BuiltIn for ExitBuiltIn {
         "exit"
     }
 
-    fn run(&mut self, _args: &[&str]) -> usize {
-        process::exit();
-        0
+    fn run(&mut self, args: &[&str]) -> usize {
+        let exit_code = match args {
+            [] => 0,
+            [code] => match code.parse::<usize>() {
+                Ok(code) => code,
+                Err(_) => {
+                    Self::print_usage();
+                    return 1;
+                }
+            },
+            _ => {
+                Self::print_usage();
+                return 1;
+            }
+        };
+
+        process::exit(exit_code);
     }
 }
 
@@ -19,4 +34,8 @@ impl ExitBuiltIn {
     pub fn new() -> Self {
         Self {}
     }
+
+    fn print_usage() {
+        println!("Usage: exit [CODE]");
+    }
 }
@@ -0,0 +1,51 @@
+use alloc::vec::Vec;
+use runtime::env;
+use terminal::println;
+
+use crate::built_in::built_in::BuiltIn;
+
+pub struct ExportBuiltIn {}
+
+impl BuiltIn for ExportBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "export"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        if args.is_empty() {
+            for (key, value) in env::vars() {
+                println!("export {}={}", key, value);
+            }
+            return 0;
+        }
+
+        // Validate all assignments first, so that nothing is set on error
+        let assignments: Option<Vec<(&str, &str)>> =
+            args.iter().map(|arg| arg.split_once('=').filter(|(key, _)| Self::is_valid_key(key))).collect();
+        let Some(assignments) = assignments else {
+            Self::print_usage();
+            return 1;
+        };
+
+        for (key, value) in assignments {
+            env::set_var(key, value);
+        }
+        0
+    }
+}
+
+impl ExportBuiltIn {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn is_valid_key(key: &str) -> bool {
+        !key.is_empty()
+            && !key.starts_with(|ch: char| ch.is_ascii_digit())
+            && key.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    }
+
+    fn print_usage() {
+        println!("Usage: export [KEY=VALUE]...");
+    }
+}
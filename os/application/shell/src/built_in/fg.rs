@@ -0,0 +1,54 @@
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, job_context::JobContext},
+};
+
+pub struct FgBuiltIn {
+    job_provider: ContextProvider<JobContext>,
+}
+
+impl BuiltIn for FgBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "fg"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let id = match args {
+            [] => None,
+            [id] => match id.trim_start_matches('%').parse::<usize>() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    Self::print_usage();
+                    return 1;
+                }
+            },
+            _ => {
+                Self::print_usage();
+                return 1;
+            }
+        };
+
+        // Release the context before blocking, the job is not managed in the background anymore
+        let Some(mut job) = self.job_provider.borrow_mut().remove(id) else {
+            println!("No such job");
+            return 1;
+        };
+
+        println!("{}", job.command_line);
+        let exit_code = job.wait();
+        job.cleanup();
+        exit_code
+    }
+}
+
+impl FgBuiltIn {
+    pub fn new(job_provider: ContextProvider<JobContext>) -> Self {
+        Self { job_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: fg [JOB]");
+    }
+}
@@ -23,6 +23,8 @@ impl BuiltIn for HelpBuiltIn {
             "controls" => println!("{}", include_str!("help_controls.txt")),
             "built-in-1" => println!("{}", include_str!("help_built_in_1.txt")),
             "built-in-2" => println!("{}", include_str!("help_built_in_2.txt")),
+            "built-in-3" => println!("{}", include_str!("help_built_in_3.txt")),
+            "built-in-4" => println!("{}", include_str!("help_built_in_4.txt")),
             _ => {
                 Self::print_usage();
                 return 1;
@@ -39,6 +41,6 @@ impl HelpBuiltIn {
     }
 
     fn print_usage() {
-        println!("Usage: help [tokens / controls / built-in-1 / built-in-2]");
+        println!("Usage: help [tokens / controls / built-in-1 / built-in-2 / built-in-3 / built-in-4]");
     }
 }
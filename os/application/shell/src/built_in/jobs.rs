@@ -0,0 +1,44 @@
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, job_context::JobContext},
+};
+
+pub struct JobsBuiltIn {
+    job_provider: ContextProvider<JobContext>,
+}
+
+impl BuiltIn for JobsBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "jobs"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        if !args.is_empty() {
+            Self::print_usage();
+            return 1;
+        }
+
+        let mut job_clx = self.job_provider.borrow_mut();
+        for mut job in job_clx.remove_finished() {
+            job.cleanup();
+            let state = job.state();
+            println!("[{}] {:<10} {}", job.id, state, job.command_line);
+        }
+        for job in job_clx.get_jobs() {
+            println!("[{}] {:<10} {}", job.id, "Running", job.command_line);
+        }
+        0
+    }
+}
+
+impl JobsBuiltIn {
+    pub fn new(job_provider: ContextProvider<JobContext>) -> Self {
+        Self { job_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: jobs");
+    }
+}
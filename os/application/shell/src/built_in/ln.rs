@@ -0,0 +1,43 @@
+use naming::symlink;
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, working_directory_context::WorkingDirectoryContext},
+};
+
+pub struct LnBuiltIn {
+    wd_provider: ContextProvider<WorkingDirectoryContext>,
+}
+
+impl BuiltIn for LnBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "ln"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let wd_clx = self.wd_provider.borrow();
+        let (Some(&"-s"), Some(target), Some(link), None) = (args.get(0), args.get(1), args.get(2), args.get(3)) else {
+            Self::print_usage();
+            return 1;
+        };
+
+        // The target is stored as given, a relative target is resolved against the directory of the link
+        let link_path = wd_clx.resolve(link);
+        if let Err(error) = symlink(target, &link_path) {
+            println!("Unable to create link {} ({:?})", link_path, error);
+            return 1;
+        }
+        0
+    }
+}
+
+impl LnBuiltIn {
+    pub fn new(wd_provider: ContextProvider<WorkingDirectoryContext>) -> Self {
+        Self { wd_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: ln -s TARGET LINK");
+    }
+}
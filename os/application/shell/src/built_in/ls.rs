@@ -1,5 +1,6 @@
 use alloc::{format, string::String};
-use naming::shared_types::{FileType, OpenOptions};
+use chrono::DateTime;
+use naming::shared_types::{DirEntry, FileType, OpenOptions};
 use terminal::println;
 
 use crate::{
@@ -18,23 +19,37 @@ impl BuiltIn for LsBuiltIn {
 
     fn run(&mut self, args: &[&str]) -> usize {
         let wd_clx = self.wd_provider.borrow();
-        let path = args.get(0).unwrap_or(&"");
+        let (long_listing, args) = match args.first() {
+            Some(&"-l") => (true, &args[1..]),
+            _ => (false, args),
+        };
+        if args.len() > 1 {
+            Self::print_usage();
+            return 1;
+        }
+        let path = wd_clx.resolve(args.first().unwrap_or(&""));
 
-        let Ok(fd) = naming::open(&wd_clx.resolve(path), OpenOptions::DIRECTORY) else {
+        let Ok(fd) = naming::open(&path, OpenOptions::DIRECTORY) else {
             Self::print_usage();
             return 1;
         };
 
         let mut contents = String::new();
         while let Ok(Some(content)) = naming::readdir(fd) {
-            contents.push_str(&format!(
-                "{}{}\x1b[0m  ",
-                Self::color_code_file_type(content.file_type),
-                &content.name
-            ));
+            if long_listing {
+                println!("{}", Self::long_entry(&path, &content));
+            } else {
+                contents.push_str(&format!(
+                    "{}{}\x1b[0m  ",
+                    Self::color_code_file_type(content.file_type),
+                    &content.name
+                ));
+            }
         }
 
-        println!("{}", contents);
+        if !long_listing {
+            println!("{}", contents);
+        }
         naming::close(fd).expect("Unable to close directory");
 
         0
@@ -55,7 +70,39 @@ impl LsBuiltIn {
         }
     }
 
+    /// Line of the long listing (`-l`): type, size, modification time and name
+    fn long_entry(dir: &str, entry: &DirEntry) -> String {
+        let entry_path = format!("{}/{}", dir.trim_end_matches('/'), entry.name);
+        let type_char = match entry.file_type {
+            FileType::Directory => 'd',
+            FileType::NamedPipe => 'p',
+            FileType::Link => 'l',
+            _ => '-',
+        };
+        let (size, modified) = match naming::stat(&entry_path) {
+            Ok(metadata) => (format!("{}", metadata.size), Self::format_time(metadata.modified_time)),
+            Err(_) => (String::from("?"), Self::format_time(0)),
+        };
+        let name = format!("{}{}\x1b[0m", Self::color_code_file_type(entry.file_type), entry.name);
+
+        match entry.file_type {
+            FileType::Link => {
+                let target = naming::readlink(&entry_path).unwrap_or_default();
+                format!("{} {:>10} {} {} -> {}", type_char, size, modified, name, target)
+            }
+            _ => format!("{} {:>10} {} {}", type_char, size, modified, name),
+        }
+    }
+
+    /// Format a time stamp in milliseconds since the Unix epoch (0 = unknown)
+    fn format_time(time_ms: u64) -> String {
+        match DateTime::from_timestamp_millis(time_ms as i64) {
+            Some(date) if time_ms != 0 => format!("{}", date.format("%Y-%m-%d %H:%M")),
+            _ => String::from("----------------"),
+        }
+    }
+
     fn print_usage() {
-        println!("Usage: ls [DIRECTORY]");
+        println!("Usage: ls [-l] [DIRECTORY]");
     }
 }
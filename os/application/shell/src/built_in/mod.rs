@@ -5,11 +5,23 @@ pub mod clear;
 pub mod debug_error;
 pub mod debug_success;
 pub mod echo;
+pub mod env;
 pub mod exit;
+pub mod export;
+pub mod fg;
 pub mod help;
+pub mod jobs;
+pub mod ln;
 pub mod ls;
 pub mod mkdir;
+pub mod mount;
+pub mod mv;
 pub mod pwd;
+pub mod rm;
+pub mod rmdir;
+pub mod sync;
 pub mod theme;
+pub mod umount;
 pub mod unalias;
+pub mod wait;
 pub mod window_manager;
@@ -0,0 +1,44 @@
+use naming::mount;
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, working_directory_context::WorkingDirectoryContext},
+};
+
+pub struct MountBuiltIn {
+    wd_provider: ContextProvider<WorkingDirectoryContext>,
+}
+
+impl BuiltIn for MountBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "mount"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let wd_clx = self.wd_provider.borrow();
+        let (Some(device), Some(dir)) = (args.get(0), args.get(1)) else {
+            Self::print_usage();
+            return 1;
+        };
+        let fs_type = args.get(2).unwrap_or(&"fat32");
+
+        let path = wd_clx.resolve(dir);
+        if let Err(error) = mount(device, &path, fs_type) {
+            println!("Unable to mount {} at {} ({:?})", device, path, error);
+            return 1;
+        }
+        0
+    }
+}
+
+impl MountBuiltIn {
+    pub fn new(wd_provider: ContextProvider<WorkingDirectoryContext>) -> Self {
+        Self { wd_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: mount DEVICE DIRECTORY [TYPE]");
+        println!("TYPE is fat32 (default), tmpfs, devfs or procfs (DEVICE is only used by fat32)");
+    }
+}
@@ -0,0 +1,55 @@
+use alloc::format;
+use naming::{rename, shared_types::OpenOptions};
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, working_directory_context::WorkingDirectoryContext},
+};
+
+pub struct MvBuiltIn {
+    wd_provider: ContextProvider<WorkingDirectoryContext>,
+}
+
+impl BuiltIn for MvBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "mv"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let wd_clx = self.wd_provider.borrow();
+        let (Some(source), Some(destination), None) = (args.get(0), args.get(1), args.get(2)) else {
+            Self::print_usage();
+            return 1;
+        };
+
+        let source_path = wd_clx.resolve(source);
+        let mut destination_path = wd_clx.resolve(destination);
+
+        // Moving into an existing directory keeps the name
+        if let Ok(fd) = naming::open(&destination_path, OpenOptions::DIRECTORY) {
+            naming::close(fd).expect("Unable to close directory");
+            let name = source_path.rsplit('/').next().unwrap_or_default();
+            destination_path = match destination_path.as_str() {
+                "/" => format!("/{}", name),
+                _ => format!("{}/{}", destination_path, name),
+            };
+        }
+
+        if let Err(error) = rename(&source_path, &destination_path) {
+            println!("Unable to move {} to {} ({:?})", source_path, destination_path, error);
+            return 1;
+        }
+        0
+    }
+}
+
+impl MvBuiltIn {
+    pub fn new(wd_provider: ContextProvider<WorkingDirectoryContext>) -> Self {
+        Self { wd_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: mv SOURCE DESTINATION");
+    }
+}
@@ -0,0 +1,45 @@
+use naming::unlink;
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, working_directory_context::WorkingDirectoryContext},
+};
+
+pub struct RmBuiltIn {
+    wd_provider: ContextProvider<WorkingDirectoryContext>,
+}
+
+impl BuiltIn for RmBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "rm"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let wd_clx = self.wd_provider.borrow();
+        if args.is_empty() {
+            Self::print_usage();
+            return 1;
+        }
+
+        let mut exit_code = 0;
+        for file in args {
+            let path = wd_clx.resolve(file);
+            if let Err(error) = unlink(&path) {
+                println!("Unable to remove {} ({:?})", path, error);
+                exit_code = 1;
+            }
+        }
+        exit_code
+    }
+}
+
+impl RmBuiltIn {
+    pub fn new(wd_provider: ContextProvider<WorkingDirectoryContext>) -> Self {
+        Self { wd_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: rm FILE...");
+    }
+}
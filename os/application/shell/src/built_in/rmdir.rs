@@ -0,0 +1,45 @@
+use naming::rmdir;
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, working_directory_context::WorkingDirectoryContext},
+};
+
+pub struct RmdirBuiltIn {
+    wd_provider: ContextProvider<WorkingDirectoryContext>,
+}
+
+impl BuiltIn for RmdirBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "rmdir"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let wd_clx = self.wd_provider.borrow();
+        if args.is_empty() {
+            Self::print_usage();
+            return 1;
+        }
+
+        let mut exit_code = 0;
+        for dir in args {
+            let path = wd_clx.resolve(dir);
+            if let Err(error) = rmdir(&path) {
+                println!("Unable to remove {} ({:?})", path, error);
+                exit_code = 1;
+            }
+        }
+        exit_code
+    }
+}
+
+impl RmdirBuiltIn {
+    pub fn new(wd_provider: ContextProvider<WorkingDirectoryContext>) -> Self {
+        Self { wd_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: rmdir DIRECTORY...");
+    }
+}
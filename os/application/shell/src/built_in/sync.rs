@@ -0,0 +1,35 @@
+use naming::sync;
+use terminal::println;
+
+use crate::built_in::built_in::BuiltIn;
+
+pub struct SyncBuiltIn {}
+
+impl BuiltIn for SyncBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "sync"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        if !args.is_empty() {
+            Self::print_usage();
+            return 1;
+        }
+
+        if let Err(error) = sync() {
+            println!("Unable to write cached data ({:?})", error);
+            return 1;
+        }
+        0
+    }
+}
+
+impl SyncBuiltIn {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn print_usage() {
+        println!("Usage: sync");
+    }
+}
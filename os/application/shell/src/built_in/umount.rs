@@ -0,0 +1,42 @@
+use naming::umount;
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, working_directory_context::WorkingDirectoryContext},
+};
+
+pub struct UmountBuiltIn {
+    wd_provider: ContextProvider<WorkingDirectoryContext>,
+}
+
+impl BuiltIn for UmountBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "umount"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let wd_clx = self.wd_provider.borrow();
+        let Some(dir) = args.get(0) else {
+            Self::print_usage();
+            return 1;
+        };
+
+        let path = wd_clx.resolve(dir);
+        if let Err(error) = umount(&path) {
+            println!("Unable to unmount {} ({:?})", path, error);
+            return 1;
+        }
+        0
+    }
+}
+
+impl UmountBuiltIn {
+    pub fn new(wd_provider: ContextProvider<WorkingDirectoryContext>) -> Self {
+        Self { wd_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: umount DIRECTORY");
+    }
+}
@@ -0,0 +1,58 @@
+use alloc::vec;
+use terminal::println;
+
+use crate::{
+    built_in::built_in::BuiltIn,
+    context::{context::ContextProvider, job_context::JobContext},
+};
+
+pub struct WaitBuiltIn {
+    job_provider: ContextProvider<JobContext>,
+}
+
+impl BuiltIn for WaitBuiltIn {
+    fn namespace(&self) -> &'static str {
+        "wait"
+    }
+
+    fn run(&mut self, args: &[&str]) -> usize {
+        let jobs = match args {
+            [] => self.job_provider.borrow_mut().remove_all(),
+            [id] => {
+                let Ok(id) = id.trim_start_matches('%').parse::<usize>() else {
+                    Self::print_usage();
+                    return 1;
+                };
+                let Some(job) = self.job_provider.borrow_mut().remove(Some(id)) else {
+                    println!("No such job: {}", id);
+                    return 1;
+                };
+                vec![job]
+            }
+            _ => {
+                Self::print_usage();
+                return 1;
+            }
+        };
+
+        // Like in other shells, the exit code is the one of the last job waited for
+        let mut exit_code = 0;
+        for mut job in jobs {
+            exit_code = job.wait();
+            job.cleanup();
+            let state = job.state();
+            println!("[{}] {:<10} {}", job.id, state, job.command_line);
+        }
+        exit_code
+    }
+}
+
+impl WaitBuiltIn {
+    pub fn new(job_provider: ContextProvider<JobContext>) -> Self {
+        Self { job_provider }
+    }
+
+    fn print_usage() {
+        println!("Usage: wait [JOB]");
+    }
+}
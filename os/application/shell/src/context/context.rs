@@ -2,11 +2,18 @@ use core::cell::{Ref, RefCell, RefMut};
 
 use alloc::rc::Rc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct ContextProvider<T> {
     clx: Rc<RefCell<T>>,
 }
 
+// Not derived, as cloning the provider must not require cloning the context
+impl<T> Clone for ContextProvider<T> {
+    fn clone(&self) -> Self {
+        Self { clx: Rc::clone(&self.clx) }
+    }
+}
+
 impl<T> ContextProvider<T> {
     pub fn new(clx: T) -> Self {
         Self {
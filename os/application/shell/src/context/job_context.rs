@@ -0,0 +1,129 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use concurrent::process::Process;
+
+/// Exit code used if the exit code of an application is not available
+const UNKNOWN_EXIT_CODE: usize = 1;
+
+/// State of a single command of a pipeline
+pub enum CommandState {
+    Running(Process),
+    Done(usize), // exit code
+}
+
+impl CommandState {
+    /// Block until the command has terminated and return its exit code
+    pub fn wait(&mut self) -> usize {
+        if let CommandState::Running(process) = self {
+            *self = CommandState::Done(process.wait().unwrap_or(UNKNOWN_EXIT_CODE));
+        }
+        self.exit_code().unwrap()
+    }
+
+    /// Return the exit code, if the command has terminated
+    pub fn exit_code(&mut self) -> Option<usize> {
+        if let CommandState::Running(process) = self {
+            match process.try_wait() {
+                Ok(Some(exit_code)) => *self = CommandState::Done(exit_code),
+                Ok(None) => return None,
+                Err(_) => *self = CommandState::Done(UNKNOWN_EXIT_CODE),
+            }
+        }
+        match self {
+            CommandState::Done(exit_code) => Some(*exit_code),
+            CommandState::Running(_) => None,
+        }
+    }
+}
+
+/// A command line running in the background (`&`).
+/// The commands are in pipeline order, the exit code of the job is the one of the last command.
+/// The pipes connecting the applications are removed, once the job is done.
+pub struct Job {
+    pub id: usize,
+    pub command_line: String,
+    pub commands: Vec<CommandState>,
+    pub pipes: Vec<String>,
+}
+
+impl Job {
+    pub fn is_running(&mut self) -> bool {
+        self.commands.iter_mut().any(|command| command.exit_code().is_none())
+    }
+
+    /// Block until all commands of the job have terminated, returns the exit code of the job
+    pub fn wait(&mut self) -> usize {
+        self.commands.iter_mut().map(CommandState::wait).last().unwrap_or(0)
+    }
+
+    /// Text describing the state of the job, as shown by `jobs`
+    pub fn state(&mut self) -> String {
+        if self.is_running() {
+            return "Running".to_string();
+        }
+        match self.wait() {
+            0 => "Done".to_string(),
+            exit_code => format!("Exit {}", exit_code),
+        }
+    }
+
+    /// Remove the pipes of the job (call only if the job is done)
+    pub fn cleanup(&self) {
+        for pipe in &self.pipes {
+            let _ = naming::unlink(pipe);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct JobContext {
+    jobs: Vec<Job>,
+}
+
+impl JobContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a job, returns the assigned job id (lowest unused id, starting at 1)
+    pub fn add(&mut self, command_line: String, commands: Vec<CommandState>, pipes: Vec<String>) -> usize {
+        let id = (1..).find(|id| self.find_position(*id).is_none()).unwrap();
+        self.jobs.push(Job {
+            id,
+            command_line,
+            commands,
+            pipes,
+        });
+        id
+    }
+
+    /// Remove the job with `id`, or the most recent job if `id` is None
+    pub fn remove(&mut self, id: Option<usize>) -> Option<Job> {
+        let pos = match id {
+            Some(id) => self.find_position(id)?,
+            None => self.jobs.len().checked_sub(1)?,
+        };
+        Some(self.jobs.remove(pos))
+    }
+
+    /// Remove all jobs
+    pub fn remove_all(&mut self) -> Vec<Job> {
+        core::mem::take(&mut self.jobs)
+    }
+
+    /// Remove all jobs that are done
+    pub fn remove_finished(&mut self) -> Vec<Job> {
+        self.jobs.extract_if(.., |job| !job.is_running()).collect()
+    }
+
+    pub fn get_jobs(&self) -> &Vec<Job> {
+        &self.jobs
+    }
+
+    fn find_position(&self, id: usize) -> Option<usize> {
+        self.jobs.iter().position(|job| job.id == id)
+    }
+}
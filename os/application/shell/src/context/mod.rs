@@ -1,6 +1,7 @@
 pub mod alias_context;
 pub mod context;
 pub mod executable_context;
+pub mod job_context;
 pub mod line_context;
 pub mod suggestion_context;
 pub mod theme_context;
@@ -3,7 +3,7 @@ use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
-use naming::cd;
+use naming::{cd, cwd};
 
 use crate::event::event_handler::Error;
 
@@ -13,8 +13,11 @@ pub struct WorkingDirectoryContext {
 }
 
 impl WorkingDirectoryContext {
+    /// Start in the working directory inherited from the parent process
     pub fn new() -> Self {
-        Self { components: Vec::new() }
+        let cwd = cwd().unwrap_or_default();
+        let components = cwd.split('/').filter(|part| !part.is_empty()).map(|part| part.to_string()).collect();
+        Self { components }
     }
 
     pub fn pwd(&self) -> String {
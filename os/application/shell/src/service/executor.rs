@@ -2,22 +2,28 @@ use alloc::{
     boxed::Box,
     format,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
-use concurrent::thread;
+use concurrent::process::{self, Process};
+use naming::shared_types::{OpenOptions, STDIN, STDOUT};
+use syscall::return_vals::Errno;
 use terminal::println;
 
 use crate::{
     built_in::{
         alias::AliasBuiltIn, built_in::BuiltIn, cd::CdBuiltIn, clear::ClearBuiltIn, debug_error::DebugErrorBuiltIn,
-        debug_success::DebugSuccessBuiltIn, echo::EchoBuiltIn, exit::ExitBuiltIn, help::HelpBuiltIn, ls::LsBuiltIn,
-        mkdir::MkdirBuiltIn, pwd::PwdBuiltIn, theme::ThemeBuiltIn, unalias::UnaliasBuiltIn,
+        debug_success::DebugSuccessBuiltIn, echo::EchoBuiltIn, env::EnvBuiltIn, exit::ExitBuiltIn,
+        export::ExportBuiltIn, fg::FgBuiltIn, help::HelpBuiltIn, jobs::JobsBuiltIn, ln::LnBuiltIn, ls::LsBuiltIn,
+        mkdir::MkdirBuiltIn, mount::MountBuiltIn, mv::MvBuiltIn, pwd::PwdBuiltIn, rm::RmBuiltIn, rmdir::RmdirBuiltIn,
+        sync::SyncBuiltIn, theme::ThemeBuiltIn, umount::UmountBuiltIn, unalias::UnaliasBuiltIn, wait::WaitBuiltIn,
         window_manager::WindowManagerBuiltIn,
     },
     context::{
         alias_context::AliasContext,
         context::ContextProvider,
         executable_context::{Executable, ExecutableContext, IoTarget},
+        job_context::{CommandState, JobContext},
         theme_context::ThemeContext,
         working_directory_context::WorkingDirectoryContext,
     },
@@ -28,10 +34,29 @@ use crate::{
     },
 };
 
+/// Directory for the named pipes (and files) connecting the commands of a pipeline
+const PIPE_DIR: &str = "/tmp";
+
 pub struct ExecutorService {
     executable_provider: ContextProvider<ExecutableContext>,
+    job_provider: ContextProvider<JobContext>,
 
     built_ins: Vec<Box<dyn BuiltIn>>,
+    pipe_counter: usize,
+}
+
+/// Result of starting a single command
+enum Started {
+    BuiltIn(usize), // built-in has already been executed, with exit code
+    Application(Process),
+    Failed(Errno), // application could not be started
+}
+
+/// Standard streams of the shell replaced by the input and output of a command.
+/// Started applications inherit them, built-ins use them directly.
+struct Redirection {
+    saved_stdin: Option<usize>,
+    saved_stdout: Option<usize>,
 }
 
 impl EventHandler for ExecutorService {
@@ -46,6 +71,7 @@ impl ExecutorService {
         alias_provider: &ContextProvider<AliasContext>,
         theme_provider: &ContextProvider<ThemeContext>,
         wd_provider: &ContextProvider<WorkingDirectoryContext>,
+        job_provider: &ContextProvider<JobContext>,
     ) -> Self {
         let mut built_ins: Vec<Box<dyn BuiltIn>> = Vec::new();
         built_ins.push(Box::new(AliasBuiltIn::new(alias_provider.clone())));
@@ -62,61 +88,162 @@ impl ExecutorService {
         built_ins.push(Box::new(DebugErrorBuiltIn::new()));
         built_ins.push(Box::new(HelpBuiltIn::new()));
         built_ins.push(Box::new(LsBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(MountBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(UmountBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(SyncBuiltIn::new()));
+        built_ins.push(Box::new(RmBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(RmdirBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(MvBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(LnBuiltIn::new(wd_provider.clone())));
+        built_ins.push(Box::new(JobsBuiltIn::new(job_provider.clone())));
+        built_ins.push(Box::new(FgBuiltIn::new(job_provider.clone())));
+        built_ins.push(Box::new(WaitBuiltIn::new(job_provider.clone())));
+        built_ins.push(Box::new(ExportBuiltIn::new()));
+        built_ins.push(Box::new(EnvBuiltIn::new()));
 
         Self {
             executable_provider,
+            job_provider: job_provider.clone(),
             built_ins,
+            pipe_counter: 0,
         }
     }
 
     fn execute(&mut self, event_bus: &mut EventBus) -> Result<Response, Error> {
         let executables = { self.executable_provider.borrow().get_executables().clone() };
+        self.report_finished_jobs();
 
-        // WORKAROUND ////////////////////////////////////////
-        // Check if executables contain unsupported operations
-        // Remove this once these features are supported
-        for executable in &executables {
-            if executable.input != IoTarget::Std
-                || executable.output != IoTarget::Std
-                || executable.background_execution
-            {
-                return Err(self.handle_unsupported_error(&executables));
-            }
+        // The parser marks all executables for background execution, if the line ends with `&`
+        let background_execution = executables.iter().any(|executable| executable.background_execution);
+        if background_execution && executables.iter().any(|executable| executable.requires_executable.is_some()) {
+            return Err(Error::new_mid_execution(
+                "Conditional execution (&&, ||) is not supported for background jobs".to_string(),
+                None,
+            ));
         }
-        //////////////////////////////////////////////////////
 
+        // Exit codes by executable id, all commands of a pipeline get the exit code of the last one
         let mut exit_codes = Vec::with_capacity(executables.len());
-        for executable in executables {
-            if Self::should_stop_on_dependency(&executable, &exit_codes) {
+        let mut start = 0;
+        while start < executables.len() {
+            let end = Self::pipeline_end(&executables, start);
+            if Self::should_stop_on_dependency(&executables[start], &exit_codes) {
                 break;
             }
 
-            let exit_code = self.execute_executable(&executable);
-            exit_codes.push(exit_code);
+            let exit_code = self.execute_pipeline(&executables[start..end], background_execution);
+            exit_codes.resize(end, exit_code);
+            start = end;
         }
 
         event_bus.trigger(Event::PrepareNewLine);
         Ok(Response::Ok)
     }
 
-    fn execute_executable(&mut self, executable: &Executable) -> usize {
+    /// Execute the commands of a pipeline (connected by `|`). Returns the exit code of the last command.
+    fn execute_pipeline(&mut self, pipeline: &[Executable], background_execution: bool) -> usize {
+        let pipes = match self.create_pipes(pipeline) {
+            Ok(pipes) => pipes,
+            Err(error) => {
+                println!("Unable to create pipe ({:?})", error);
+                return 1;
+            }
+        };
+
+        // Built-ins run synchronously in the shell, so the applications are started first, otherwise a built-in
+        // could wait forever for input from (or space in a pipe to) an application that has not been started yet.
+        // Applications are started from last to first, so that no writer waits for a reader that has not been started yet.
+        // Then the built-ins run from first to last (connected by files instead of pipes, see `create_pipes`).
+        let mut commands: Vec<Option<CommandState>> = pipeline.iter().map(|_| None).collect();
+        for (idx, command) in commands.iter_mut().enumerate().rev() {
+            if !self.is_built_in(&pipeline[idx].command) {
+                *command = Some(self.start_pipeline_command(pipeline, idx, &pipes));
+            }
+        }
+        for (idx, command) in commands.iter_mut().enumerate() {
+            if command.is_none() {
+                *command = Some(self.start_pipeline_command(pipeline, idx, &pipes));
+            }
+        }
+        let mut commands: Vec<CommandState> = commands.into_iter().flatten().collect();
+
+        let background_execution =
+            background_execution && commands.iter().any(|command| matches!(command, CommandState::Running(_)));
+        if background_execution {
+            let command_line = Self::command_line(pipeline);
+            let id = self.job_provider.borrow_mut().add(command_line.clone(), commands, pipes);
+            println!("[{}] {}", id, command_line);
+            return 0;
+        }
+
+        // The exit code of a pipeline is the one of its last command
+        let exit_code = commands.iter_mut().map(CommandState::wait).last().unwrap_or(0);
+        Self::remove_pipes(&pipes);
+        exit_code
+    }
+
+    /// Start the command at `idx` of `pipeline`, connected to its neighbours by `pipes`
+    fn start_pipeline_command(&mut self, pipeline: &[Executable], idx: usize, pipes: &[String]) -> CommandState {
+        let executable = &pipeline[idx];
+        let input_pipe = idx.checked_sub(1).and_then(|prev| pipes.get(prev));
+        match self.start_command(executable, input_pipe, pipes.get(idx)) {
+            Ok(Started::BuiltIn(code)) => CommandState::Done(code),
+            Ok(Started::Application(process)) => CommandState::Running(process),
+            Ok(Started::Failed(Errno::ENOENT)) => {
+                println!("Command not found: {}", &executable.command);
+                CommandState::Done(1)
+            }
+            Ok(Started::Failed(error)) => {
+                println!("Unable to start {} ({:?})", &executable.command, error);
+                CommandState::Done(1)
+            }
+            Err(error) => {
+                println!("Unable to redirect input/output of {} ({:?})", &executable.command, error);
+                CommandState::Done(1)
+            }
+        }
+    }
+
+    /// Start a single command with its input and output redirected (if requested).
+    /// `input_pipe` and `output_pipe` are the pipes to the previous and next command in the pipeline (if any).
+    fn start_command(
+        &mut self,
+        executable: &Executable,
+        input_pipe: Option<&String>,
+        output_pipe: Option<&String>,
+    ) -> Result<Started, Errno> {
+        let input = Self::open_input(&executable.input, input_pipe)?;
+        let output = match Self::open_output(&executable.output, output_pipe) {
+            Ok(output) => output,
+            Err(error) => {
+                if let Some(fd) = input {
+                    let _ = naming::close(fd);
+                }
+                return Err(error);
+            }
+        };
+
+        let redirection = Redirection::apply(input, output)?;
+        let started = self.start(executable);
+        redirection.restore();
+        Ok(started)
+    }
+
+    fn start(&mut self, executable: &Executable) -> Started {
         let args: Vec<&str> = executable.arguments.iter().map(String::as_str).collect();
 
         if let Ok(built_in_exit_code) = self.execute_built_in(&executable.command, &args) {
-            return built_in_exit_code;
+            return Started::BuiltIn(built_in_exit_code);
         }
 
-        let Some(thread) = thread::start_application(&executable.command, args) else {
-            println!("Command not found: {}", &executable.command);
-            return 1;
-        };
+        match process::start_application(&executable.command, args) {
+            Ok(process) => Started::Application(process),
+            Err(error) => Started::Failed(error),
+        }
+    }
 
-        // WORKAROUND ///////////////////////////////////////////////////////////
-        // Extern applications don't yet provide a exit code => We assume success
-        // Replace this with real exit code once supported
-        thread.join();
-        0
-        /////////////////////////////////////////////////////////////////////////
+    fn is_built_in(&self, cmd: &str) -> bool {
+        self.built_ins.iter().any(|built_in| built_in.namespace() == cmd)
     }
 
     fn execute_built_in(&mut self, cmd: &str, args: &[&str]) -> Result<usize, ()> {
@@ -127,33 +254,106 @@ impl ExecutorService {
             .ok_or(())
     }
 
-    /// Check if executables contain unsupported operations
-    /// Remove this function, once these features are supported
-    fn handle_unsupported_error(&self, executables: &Vec<Executable>) -> Error {
-        let message = "Pipes, redirections, and background execution are not yet supported by D3OS".to_string();
-        let mut hint = "Assume the following execution:\n".to_string();
+    /// Open the input of a command, returns `None` for stdin
+    fn open_input(target: &IoTarget, pipe: Option<&String>) -> Result<Option<usize>, Errno> {
+        let path = match target {
+            IoTarget::Std => return Ok(None),
+            IoTarget::Job(_) => pipe.expect("Pipe for previous command should exist"),
+            IoTarget::FileTruncate(path) | IoTarget::FileAppend(path) => path,
+        };
+        naming::open(path, OpenOptions::READONLY).map(Some)
+    }
 
-        for executable in executables {
-            let input = match &executable.input {
-                IoTarget::Std => "StdIn",
-                IoTarget::Job(id) => &format!("previous command ({})", executables[*id].command),
-                IoTarget::FileTruncate(file) => &format!("File ({})", file),
-                IoTarget::FileAppend(file) => &format!("Append file ({})", file),
-            };
-            let output = match &executable.output {
-                IoTarget::Std => "StdOut",
-                IoTarget::Job(id) => &format!("next command ({})", executables[*id].command),
-                IoTarget::FileTruncate(file) => &format!("File ({})", file),
-                IoTarget::FileAppend(file) => &format!("Append file ({})", file),
+    /// Open the output of a command, returns `None` for stdout
+    fn open_output(target: &IoTarget, pipe: Option<&String>) -> Result<Option<usize>, Errno> {
+        match target {
+            IoTarget::Std => Ok(None),
+            IoTarget::Job(_) => {
+                let pipe = pipe.expect("Pipe for next command should exist");
+                naming::open(pipe, OpenOptions::WRITEONLY).map(Some)
+            }
+            IoTarget::FileTruncate(path) => {
+                naming::open(path, OpenOptions::WRITEONLY | OpenOptions::CREATE | OpenOptions::TRUNCATE).map(Some)
+            }
+            IoTarget::FileAppend(path) => {
+                naming::open(path, OpenOptions::WRITEONLY | OpenOptions::CREATE | OpenOptions::APPEND).map(Some)
+            }
+        }
+    }
+
+    /// Create the named pipes in `PIPE_DIR`, connecting each command of `pipeline` with the next one.
+    /// Two built-ins are connected by a file, because they run one after the other
+    /// (a pipe would block the first one forever, once it is full).
+    fn create_pipes(&mut self, pipeline: &[Executable]) -> Result<Vec<String>, Errno> {
+        let count = pipeline.len() - 1;
+        let mut pipes = Vec::with_capacity(count);
+        if count == 0 {
+            return Ok(pipes);
+        }
+
+        let _ = naming::mkdir(PIPE_DIR); // fails if it already exists
+        let pid = process::current().map(|process| process.id()).unwrap_or_default();
+        for idx in 0..count {
+            self.pipe_counter += 1;
+            let path = format!("{}/pipe-{}-{}", PIPE_DIR, pid, self.pipe_counter);
+            let created = if self.is_built_in(&pipeline[idx].command) && self.is_built_in(&pipeline[idx + 1].command) {
+                naming::touch(&path)
+            } else {
+                naming::mkfifo(&path)
             };
+            if let Err(error) = created {
+                Self::remove_pipes(&pipes);
+                return Err(error);
+            }
+            pipes.push(path);
+        }
+        Ok(pipes)
+    }
 
-            hint.push_str(&format!(
-                "Execute: {}, with arguments: {:?}\n\tInput from: {}\n\tOutput to: {}\n\tBackground execution: {:?}\n",
-                executable.command, executable.arguments, input, output, executable.background_execution
-            ));
+    fn remove_pipes(pipes: &[String]) {
+        for pipe in pipes {
+            let _ = naming::unlink(pipe);
         }
+    }
 
-        Error::new_mid_execution(message, Some(hint))
+    /// Print and remove all background jobs that are done
+    fn report_finished_jobs(&self) {
+        for mut job in self.job_provider.borrow_mut().remove_finished() {
+            job.cleanup();
+            let state = job.state();
+            println!("[{}] {:<10} {}", job.id, state, job.command_line);
+        }
+    }
+
+    /// Index after the last executable of the pipeline starting at `start`
+    fn pipeline_end(executables: &[Executable], start: usize) -> usize {
+        let mut end = start + 1;
+        while end < executables.len() && executables[end].input == IoTarget::Job(executables[end - 1].id) {
+            end += 1;
+        }
+        end
+    }
+
+    /// Text of a pipeline, as shown for background jobs
+    fn command_line(pipeline: &[Executable]) -> String {
+        let commands: Vec<String> = pipeline
+            .iter()
+            .map(|executable| {
+                let mut command = vec![executable.command.clone()];
+                command.extend(executable.arguments.iter().cloned());
+                match &executable.input {
+                    IoTarget::FileTruncate(file) | IoTarget::FileAppend(file) => command.push(format!("< {}", file)),
+                    _ => {}
+                }
+                match &executable.output {
+                    IoTarget::FileTruncate(file) => command.push(format!("> {}", file)),
+                    IoTarget::FileAppend(file) => command.push(format!(">> {}", file)),
+                    _ => {}
+                }
+                command.join(" ")
+            })
+            .collect();
+        commands.join(" | ")
     }
 
     fn should_stop_on_dependency(executable: &Executable, exit_codes: &[usize]) -> bool {
@@ -165,3 +365,58 @@ impl ExecutorService {
         false
     }
 }
+
+impl Redirection {
+    /// Replace stdin by `input` and stdout by `output` (if given). Both handles are closed.
+    fn apply(input: Option<usize>, output: Option<usize>) -> Result<Self, Errno> {
+        let mut redirection = Self {
+            saved_stdin: None,
+            saved_stdout: None,
+        };
+
+        if let Some(fd) = input {
+            match Self::replace(fd, STDIN) {
+                Ok(saved) => redirection.saved_stdin = Some(saved),
+                Err(error) => {
+                    if let Some(fd) = output {
+                        let _ = naming::close(fd);
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        if let Some(fd) = output {
+            match Self::replace(fd, STDOUT) {
+                Ok(saved) => redirection.saved_stdout = Some(saved),
+                Err(error) => {
+                    redirection.restore();
+                    return Err(error);
+                }
+            }
+        }
+        Ok(redirection)
+    }
+
+    /// Restore the original stdin and stdout
+    fn restore(self) {
+        for (saved, std) in [(self.saved_stdin, STDIN), (self.saved_stdout, STDOUT)] {
+            if let Some(saved) = saved {
+                let _ = naming::dup2(saved, std);
+                let _ = naming::close(saved);
+            }
+        }
+    }
+
+    /// Use `fd` as `std` and close `fd`. Returns a copy of the previous `std`.
+    fn replace(fd: usize, std: usize) -> Result<usize, Errno> {
+        let result = naming::dup(std).and_then(|saved| match naming::dup2(fd, std) {
+            Ok(_) => Ok(saved),
+            Err(error) => {
+                let _ = naming::close(saved);
+                Err(error)
+            }
+        });
+        let _ = naming::close(fd);
+        result
+    }
+}
@@ -1,4 +1,5 @@
 use alloc::string::{String, ToString};
+use runtime::env;
 
 use crate::{
     context::{
@@ -115,11 +116,14 @@ impl LexerService {
             .get()
             .iter()
             .map(|token| {
-                if token.is_ambiguous() && token.clx().in_quote.is_none() {
-                    alias_clx.get(token.as_str()).unwrap_or(token.as_str())
-                } else {
-                    token.as_str()
+                if !token.is_ambiguous() || token.is_in_quote_of('\'') {
+                    return token.to_string();
                 }
+                let content = match token.clx().in_quote {
+                    None => alias_clx.get(token.as_str()).unwrap_or(token.as_str()),
+                    Some(_) => token.as_str(),
+                };
+                Self::expand_variables(content)
             })
             .collect();
 
@@ -132,6 +136,34 @@ impl LexerService {
         Ok(Response::Ok)
     }
 
+    /// Replace `$NAME` and `${NAME}` with the value of the environment variable (empty, if not set)
+    fn expand_variables(content: &str) -> String {
+        let mut expanded = String::new();
+        let mut rest = content;
+        while let Some(pos) = rest.find('$') {
+            expanded.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            let (name, remaining) = match rest.strip_prefix('{').and_then(|braced| braced.split_once('}')) {
+                Some((name, remaining)) => (name, remaining),
+                None => {
+                    let end = rest.find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_').unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+
+            if name.is_empty() {
+                // A lone `$` is kept as it is
+                expanded.push('$');
+            } else {
+                expanded.push_str(&env::var(name).unwrap_or_default());
+                rest = remaining;
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
     fn add(line_clx: &mut LineContext, tokens_clx: &mut TokensContext, ch: char) {
         if tokens_clx
             .last()
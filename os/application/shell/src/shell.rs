@@ -24,8 +24,8 @@ use terminal::{init_logger, println, read::read_fluid};
 use crate::{
     context::{
         alias_context::AliasContext, context::ContextProvider, executable_context::ExecutableContext,
-        line_context::LineContext, suggestion_context::SuggestionContext, theme_context::ThemeContext,
-        tokens_context::TokensContext, working_directory_context::WorkingDirectoryContext,
+        job_context::JobContext, line_context::LineContext, suggestion_context::SuggestionContext,
+        theme_context::ThemeContext, tokens_context::TokensContext, working_directory_context::WorkingDirectoryContext,
     },
     event::{
         event::Event,
@@ -94,6 +94,7 @@ impl Shell {
         let alias_provider = ContextProvider::new(AliasContext::new());
         let theme_provider = ContextProvider::new(ThemeContext::new());
         let wd_provider = ContextProvider::new(WorkingDirectoryContext::new());
+        let job_provider = ContextProvider::new(JobContext::new());
 
         let mut services: Vec<Box<dyn EventHandler>> = Vec::new();
         services.push(Box::new(CommandLineService::new(line_provider.clone())));
@@ -129,6 +130,7 @@ impl Shell {
             &alias_provider,
             &theme_provider,
             &wd_provider,
+            &job_provider,
         )));
 
         Self { event_bus, services }
@@ -188,11 +190,11 @@ impl Shell {
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let args = env::args();
     let Ok(cfg) = Config::from_args(args) else {
         println!("Usage: shell [--no-history] [--no-auto-completion]");
-        return;
+        return 1;
     };
 
     println!("Welcome to \x1b[38;2;0;106;179mD\x1b[0m\x1b[38;2;140;177;16m3\x1b[0m\x1b[38;2;0;106;179mOS\x1b[0m!");
@@ -200,5 +202,6 @@ pub fn main() {
     init_logger();
 
     let mut shell = Shell::new(cfg);
-    shell.run()
+    shell.run();
+    0
 }
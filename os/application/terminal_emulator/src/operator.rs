@@ -1,6 +1,9 @@
 use alloc::vec;
 use log::info;
-use concurrent::thread::{self, Thread};
+use concurrent::{
+    process,
+    thread::{self, Thread},
+};
 
 pub struct Operator {
     thread: Option<Thread>,
@@ -18,9 +21,9 @@ impl Operator {
     pub fn create(&mut self) {
         assert!(self.thread.is_none());
         self.thread = thread::create(|| loop {
-            thread::start_application("shell", vec![])
+            let _ = process::start_application("shell", vec![])
                 .expect("Unable to start operator")
-                .join();
+                .wait();
             info!("Restarting shell...");
         });
     }
@@ -13,7 +13,7 @@ use core::cell::RefCell;
 
 use alloc::rc::Rc;
 use alloc::vec;
-use concurrent::thread::{self, sleep};
+use concurrent::{process, thread::sleep};
 use event_handler::{Event, EventHandler};
 use graphic::lfb::map_framebuffer;
 use operator::Operator;
@@ -72,7 +72,7 @@ impl TerminalEmulator {
     pub fn enter_gui(&self) {
         let mut display = self.terminal.display.lock();
         display.lfb.direct_lfb().draw_loader();
-        thread::start_application("window_manager", vec![]).unwrap().join(); // Wait for window manager to exit, then continue
+        let _ = process::start_application("window_manager", vec![]).unwrap().wait(); // Wait for window manager to exit, then continue
         display.lfb.direct_lfb().draw_loader();
         sleep(500); // Solves an issue where sometimes workspaces from the window manager are still visible when toggling quickly between text and gui
         display.lfb.flush();
@@ -103,7 +103,7 @@ impl TerminalEmulator {
 }
 
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let lfb_info = map_framebuffer().expect("failed to get framebuffer");
     let mut emulator = TerminalEmulator::new(
         lfb_info.addr as *mut u8,
@@ -114,5 +114,6 @@ pub fn main() {
     );
     init_logger();
     emulator.init();
-    emulator.run()
+    emulator.run();
+    0
 }
@@ -2,11 +2,48 @@
 
 extern crate alloc;
 
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use concurrent::barrier::Barrier;
+use concurrent::condvar::Condvar;
+use concurrent::mpsc::{self, Sender};
+use concurrent::mutex::Mutex;
+use concurrent::rwlock::RwLock;
+use concurrent::semaphore::Semaphore;
 use concurrent::{process, thread};
 #[allow(unused_imports)]
 use runtime::*;
 use terminal::println;
 
+const COUNTER_THREADS: usize = 4;
+const INCREMENTS: usize = 10000;
+
+static COUNTER: Mutex<usize> = Mutex::new(0);
+static START: Barrier = Barrier::new(COUNTER_THREADS);
+
+// The following tests make threads block on the primitives, by holding them while sleeping or switching
+const TEST_THREADS: usize = 4;
+const ROUNDS: usize = 50;
+
+static RWLOCK: RwLock<usize> = RwLock::new(0);
+static TORN_READS: AtomicUsize = AtomicUsize::new(0);
+
+static TURN: Mutex<usize> = Mutex::new(0);
+static TURN_CHANGED: Condvar = Condvar::new();
+
+static SEMAPHORE: Semaphore = Semaphore::new(2);
+static HOLDERS: AtomicUsize = AtomicUsize::new(0);
+static MAX_HOLDERS: AtomicUsize = AtomicUsize::new(0);
+
+static BARRIER: Barrier = Barrier::new(TEST_THREADS);
+static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+static EARLY_DEPARTURES: AtomicUsize = AtomicUsize::new(0);
+static LEADERS: AtomicUsize = AtomicUsize::new(0);
+
+static SENDER: Mutex<Option<Sender<usize>>> = Mutex::new(None);
+static SENDERS_READY: Barrier = Barrier::new(TEST_THREADS + 1);
+
+static FAILED: AtomicBool = AtomicBool::new(false);
 
 fn second_thread() {
     let process = process::current().unwrap();
@@ -20,8 +57,134 @@ fn second_thread() {
     }
 }
 
+fn counter_thread() {
+    START.wait();
+    for _ in 0..INCREMENTS {
+        *COUNTER.lock() += 1;
+    }
+}
+
+fn rwlock_writer() {
+    // Readers must never see the intermediate odd value
+    for _ in 0..ROUNDS {
+        let mut value = RWLOCK.write();
+        *value += 1;
+        thread::switch();
+        *value += 1;
+    }
+}
+
+fn rwlock_reader() {
+    for _ in 0..ROUNDS {
+        let value = RWLOCK.read();
+        if *value % 2 != 0 {
+            TORN_READS.fetch_add(1, Ordering::Relaxed);
+        }
+        thread::switch();
+    }
+}
+
+fn condvar_ping() {
+    take_turns(0);
+}
+
+fn condvar_pong() {
+    take_turns(1);
+}
+
+/// Wait until `TURN` has the parity `parity`, then increment it (the two threads alternate)
+fn take_turns(parity: usize) {
+    for _ in 0..ROUNDS {
+        let mut turn = TURN_CHANGED.wait_while(TURN.lock(), |turn| *turn % 2 != parity);
+        *turn += 1;
+        drop(turn);
+        TURN_CHANGED.notify_all();
+    }
+}
+
+fn semaphore_holder() {
+    for _ in 0..ROUNDS / 10 {
+        SEMAPHORE.acquire();
+        let holders = HOLDERS.fetch_add(1, Ordering::SeqCst) + 1;
+        MAX_HOLDERS.fetch_max(holders, Ordering::SeqCst);
+        thread::sleep(1);
+        HOLDERS.fetch_sub(1, Ordering::SeqCst);
+        SEMAPHORE.release();
+    }
+}
+
+fn barrier_thread() {
+    for round in 1..=ROUNDS {
+        ARRIVED.fetch_add(1, Ordering::SeqCst);
+        if BARRIER.wait() {
+            LEADERS.fetch_add(1, Ordering::SeqCst);
+        }
+        // All threads of this round must have arrived before any of them leaves
+        if ARRIVED.load(Ordering::SeqCst) < round * TEST_THREADS {
+            EARLY_DEPARTURES.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+fn mpsc_sender() {
+    let sender = SENDER.lock().as_ref().unwrap().clone();
+    SENDERS_READY.wait();
+
+    // Let the receiver block, before sending
+    thread::sleep(10);
+    for _ in 0..ROUNDS {
+        sender.send(1).unwrap();
+    }
+}
+
+/// Run `entry` in `count` new threads and wait for them to finish
+fn run_threads(count: usize, entry: fn()) {
+    let threads: Vec<_> = (0..count).filter_map(|_| thread::create(entry)).collect();
+    if threads.len() != count {
+        println!("Failed to create all threads");
+        FAILED.store(true, Ordering::SeqCst);
+    }
+    threads.iter().for_each(|thread| thread.join());
+}
+
+fn check(name: &str, ok: bool) {
+    println!("{}: {}", name, if ok { "ok" } else { "FAILED" });
+    if !ok {
+        FAILED.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Test all blocking synchronization primitives with several threads contending for them
+fn test_primitives() {
+    let writer = thread::create(rwlock_writer);
+    run_threads(TEST_THREADS - 1, rwlock_reader);
+    writer.iter().for_each(|thread| thread.join());
+    check("RwLock", *RWLOCK.read() == 2 * ROUNDS && TORN_READS.load(Ordering::SeqCst) == 0);
+
+    let ping = thread::create(condvar_ping);
+    let pong = thread::create(condvar_pong);
+    ping.iter().chain(pong.iter()).for_each(|thread| thread.join());
+    check("Condvar", *TURN.lock() == 2 * ROUNDS);
+
+    run_threads(TEST_THREADS, semaphore_holder);
+    check("Semaphore", MAX_HOLDERS.load(Ordering::SeqCst) <= 2 && SEMAPHORE.available_permits() == 2);
+
+    run_threads(TEST_THREADS, barrier_thread);
+    check("Barrier", EARLY_DEPARTURES.load(Ordering::SeqCst) == 0 && LEADERS.load(Ordering::SeqCst) == ROUNDS);
+
+    let (sender, receiver) = mpsc::channel();
+    *SENDER.lock() = Some(sender);
+    let senders: Vec<_> = (0..TEST_THREADS).filter_map(|_| thread::create(mpsc_sender)).collect();
+    SENDERS_READY.wait();
+    // Drop the original sender, so that the channel is closed when all threads have finished
+    SENDER.lock().take();
+    let received: usize = receiver.iter().sum();
+    senders.iter().for_each(|thread| thread.join());
+    check("Channel", received == TEST_THREADS * ROUNDS);
+}
+
 #[unsafe(no_mangle)]
-pub fn main() {
+pub fn main() -> isize {
     let process = process::current().unwrap();
     let thread = thread::current().unwrap();
 
@@ -34,6 +197,15 @@ pub fn main() {
     } else {
         println!("Failed to create second thread");
     }
+
+    // Several threads incrementing a counter protected by a mutex
+    let threads: Vec<_> = (0..COUNTER_THREADS).filter_map(|_| thread::create(counter_thread)).collect();
+    threads.iter().for_each(|thread| thread.join());
+    println!("Counter is [{}] (expected [{}])", *COUNTER.lock(), threads.len() * INCREMENTS);
+    check("Mutex", *COUNTER.lock() == COUNTER_THREADS * INCREMENTS);
+
+    test_primitives();
+
     println!("main thread [{}] in process [{}]!", thread.id(), process.id());
- 
+    if FAILED.load(Ordering::SeqCst) { 1 } else { 0 }
 }
\ No newline at end of file
@@ -653,13 +653,13 @@ impl WindowManager {
 
     fn enter_text_mode(&mut self) {
         Drawer::full_clear_screen(true);
-        process::exit();
+        process::exit(0);
     }
 }
 
 #[cfg(feature = "with_runtime")]
 #[no_mangle]
-fn main() {
+fn main() -> isize {
     use terminal::init_logger;
 
     init_logger();
@@ -667,4 +667,5 @@ fn main() {
     let (mut window_manager, senders) = WindowManager::new(resolution);
     window_manager.init(senders);
     window_manager.run();
+    0
 }
@@ -153,6 +153,130 @@ clear_bss:
     mov esi, ebx
     call start
 
+; Startup code for the application processors, copied to 'AP_TRAMPOLINE_ADDRESS' by 'boot.rs'.
+; Each processor starts in real mode, switches to long mode via protected mode and calls the
+; entry function with the page table, control registers and stack given in the data block.
+AP_TRAMPOLINE_ADDRESS equ 0x8000 ; Must match 'consts.rs'
+%define AP_ADDRESS(label) (AP_TRAMPOLINE_ADDRESS + (label - ap_trampoline))
+
+; Offsets in the data block (must match 'TrampolineData' in 'boot.rs')
+AP_DATA_CR0 equ 0x00
+AP_DATA_CR3 equ 0x08
+AP_DATA_CR4 equ 0x10
+AP_DATA_EFER equ 0x18
+AP_DATA_XCR0 equ 0x20
+AP_DATA_STACK equ 0x28
+AP_DATA_ENTRY equ 0x30
+AP_DATA_CORE_ID equ 0x38
+
+CR0_PE equ 1 << 0
+CR0_PG equ 1 << 31
+CR4_PAE equ 1 << 5
+CR4_LA57 equ 1 << 12
+CR4_OSXSAVE_BIT equ 18
+EFER_MSR equ 0xc0000080
+EFER_LMA equ 1 << 10
+
+global ap_trampoline
+global ap_trampoline_data
+global ap_trampoline_end
+
+[BITS 16]
+align 16
+ap_trampoline:
+    cli
+    cld
+
+    ; Load temporary GDT (the start address of the code is 'AP_TRAMPOLINE_ADDRESS' with cs = 'AP_TRAMPOLINE_ADDRESS' >> 4)
+    xor ax, ax
+    mov ds, ax
+    lgdt [AP_ADDRESS(ap_gdtr)]
+
+    ; Enable protected mode
+    mov eax, cr0
+    or eax, CR0_PE
+    mov cr0, eax
+    jmp dword 0x08:AP_ADDRESS(ap_protected_mode)
+
+[BITS 32]
+ap_protected_mode:
+    mov ax, 0x10
+    mov ds, ax
+    mov es, ax
+    mov ss, ax
+
+    ; Enable PAE (and 5-level paging, if used by the bootstrap processor)
+    mov eax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_CR4]
+    and eax, CR4_LA57
+    or eax, CR4_PAE
+    mov cr4, eax
+
+    ; Load page table of the kernel (located below 4 GiB)
+    mov eax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_CR3]
+    mov cr3, eax
+
+    ; Enable long mode (with the same features as on the bootstrap processor)
+    mov ecx, EFER_MSR
+    mov eax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_EFER]
+    and eax, ~EFER_LMA
+    mov edx, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_EFER + 4]
+    wrmsr
+
+    ; Enable paging
+    mov eax, cr0
+    or eax, CR0_PG
+    mov cr0, eax
+    jmp 0x18:AP_ADDRESS(ap_long_mode)
+
+[BITS 64]
+ap_long_mode:
+    mov ax, 0x10
+    mov ds, ax
+    mov es, ax
+    mov ss, ax
+
+    ; Use the control registers of the bootstrap processor
+    mov rax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_CR4]
+    mov cr4, rax
+    mov rax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_CR0]
+    mov cr0, rax
+
+    ; Enable the same extended states as on the bootstrap processor (if XSAVE is enabled)
+    mov rax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_CR4]
+    bt rax, CR4_OSXSAVE_BIT
+    jnc .setup_stack
+    xor ecx, ecx
+    mov eax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_XCR0]
+    mov edx, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_XCR0 + 4]
+    xsetbv
+
+.setup_stack:
+    ; Call rust function with the core id
+    mov rsp, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_STACK]
+    mov rdi, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_CORE_ID]
+    mov rax, [AP_ADDRESS(ap_trampoline_data) + AP_DATA_ENTRY]
+    call rax
+
+.halt:
+    cli
+    hlt
+    jmp .halt
+
+align 8
+ap_gdt:
+    dq 0x0000000000000000 ; Null descriptor
+    dq 0x00cf9a000000ffff ; 32-bit code segment
+    dq 0x00cf92000000ffff ; Data segment
+    dq 0x00af9a000000ffff ; 64-bit code segment
+ap_gdtr:
+    dw ap_gdtr - ap_gdt - 1
+    dd AP_ADDRESS(ap_gdt)
+
+align 8
+ap_trampoline_data:
+    times 8 dq 0
+ap_trampoline_end:
+
 [SECTION .bss]
 
 global init_stack:data (init_stack.end - init_stack)
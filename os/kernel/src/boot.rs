@@ -20,9 +20,9 @@ use crate::memory::{dram, nvmem, PAGE_SIZE};
 use crate::process::thread::Thread;
 use crate::syscall::{sys_vmem, syscall_dispatcher};
 use crate::{
-    acpi_tables, allocator, apic, gdt, get_initrd_frames,
+    acpi_tables, allocator, apic, cpu, gdt, get_initrd_frames,
     efi_services_available, init_acpi_tables, init_apic, init_boot_info,
-    init_cpu_info, init_initrd, init_lfb, init_lfb_info, init_pci,
+    init_core_local_storage, init_cpu_info, init_initrd, init_lfb, init_lfb_info, init_pci,
     init_serial_port, init_tty, initrd, keyboard, logger, mouse,
     process_manager, scheduler, serial_port, timer, tss,
 };
@@ -31,11 +31,13 @@ use crate::{built_info, memory, naming, network, storage};
 use alloc::format;
 use alloc::string::ToString;
 use alloc::sync::Arc;
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate};
 use core::ffi::c_void;
+use core::hint::spin_loop;
 use core::mem::size_of;
 use core::ops::Deref;
 use core::ptr;
+use core::sync::atomic::{AtomicU8, Ordering};
 use log::{trace, debug, info, warn, LevelFilter};
 use multiboot2::{BootInformation, BootInformationHeader, EFIMemoryMapTag, MemoryAreaType, MemoryMapTag, TagHeader};
 use uefi::data_types::Handle;
@@ -48,6 +50,8 @@ use x86_64::instructions::interrupts;
 use x86_64::instructions::segmentation::{CS, DS, ES, FS, GS, SS, Segment};
 use x86_64::instructions::tables::load_tss;
 use x86_64::registers::control::{Cr0, Cr0Flags, Cr3, Cr4, Cr4Flags};
+use x86_64::registers::model_specific::{Efer, EferFlags};
+use x86_64::registers::xcontrol::XCr0;
 use x86_64::registers::segmentation::SegmentSelector;
 use x86_64::structures::gdt::Descriptor;
 use x86_64::structures::paging::frame::PhysFrameRange;
@@ -60,6 +64,36 @@ unsafe extern "C" {
     static ___KERNEL_DATA_END__: c_void; // end address of OS image
 }
 
+// import labels from 'boot.asm'
+unsafe extern "C" {
+    static ap_trampoline: c_void; // start of the startup code for application processors
+    static ap_trampoline_data: c_void; // data block of the startup code (see 'TrampolineData')
+    static ap_trampoline_end: c_void; // end of the startup code
+}
+
+/// Time to wait for an application processor to initialize itself
+const AP_STARTUP_TIMEOUT_MS: usize = 1000;
+
+/// Startup state of the application processor, which is currently being started
+static AP_STATE: AtomicU8 = AtomicU8::new(AP_WAITING);
+const AP_WAITING: u8 = 0;   // the bootstrap processor waits for the application processor
+const AP_STARTING: u8 = 1;  // set by the application processor, when it enters `ap_entry`
+const AP_RUNNING: u8 = 2;   // set by the application processor, after it has initialized itself
+const AP_CANCELLED: u8 = 3; // set by the bootstrap processor on timeout (the application processor must not run then)
+
+/// Data block at the end of the startup code for application processors (layout must match 'boot.asm')
+#[repr(C)]
+struct TrampolineData {
+    cr0: u64,
+    cr3: u64,
+    cr4: u64,
+    efer: u64,
+    xcr0: u64,
+    stack: u64,
+    entry: u64,
+    core_id: u64,
+}
+
 const BOOT_TO_GUI: bool = false; // Immediately start the GUI instead of terminal (Debug)
 
 /// First Rust function called from assembly code `boot.asm` \
@@ -83,6 +117,9 @@ pub extern "C" fn start(multiboot2_magic: u32, multiboot2_addr: *const BootInfor
     // Search memory map, provided by bootloader or EFI, for usable memory and initialize physical memory management with free memory regions
     let multiboot = multiboot2_search_memory_map(multiboot2_addr);
 
+    // Setup the core local storage of the bootstrap processor (needed for accessing the GDT and TSS of the core)
+    init_core_local_storage(0);
+
     // Setup the GDT (Global Descriptor Table)
     // Has to be done after EFI boot services have been exited, since they rely on their own GDT
     info!("Initializing GDT");
@@ -107,6 +144,11 @@ pub extern "C" fn start(multiboot2_magic: u32, multiboot2_addr: *const BootInfor
     unsafe {
         memory::frames::boot_reserve(multiboot_region);
     }
+    // and the page for the startup code of the application processors
+    let trampoline_frame = PhysFrame::containing_address(PhysAddr::new(consts::AP_TRAMPOLINE_ADDRESS as u64));
+    unsafe {
+        memory::frames::boot_reserve(PhysFrame::range(trampoline_frame, trampoline_frame + 1));
+    }
 
     // and initialize kernel heap, after which formatted strings may be used in logs and panics.
     info!("Initializing kernel heap");
@@ -148,6 +190,11 @@ pub extern "C" fn start(multiboot2_magic: u32, multiboot2_addr: *const BootInfor
     // Initialize CPU information
     init_cpu_info();
 
+    // Allow page table entries to forbid executing code (application processors copy EFER from this core)
+    if cpu().supports_no_execute() {
+        unsafe { Efer::update(|flags| flags.insert(EferFlags::NO_EXECUTE_ENABLE)) };
+    }
+
     // Create kernel process (and initialize virtual memory management)
     info!("Create kernel process and initialize paging");
     let kernel_process = process_manager().write().create_kernel_process(kernel_image_region, heap_region);
@@ -271,6 +318,13 @@ pub extern "C" fn start(multiboot2_magic: u32, multiboot2_addr: *const BootInfor
         uefi::system::uefi_revision()
     );
 
+    // Initialize the wall clock once, it is advanced by the timer afterwards
+    // (EFI runtime services are not reentrant and cannot be used concurrently by several cores)
+    match efi_date_ms() {
+        Some(date_ms) => timer.set_date_ms(date_ms),
+        None => warn!("Current date is not available"),
+    }
+
     // Initialize keyboard
     if let Some(keyboard) = keyboard() {
         Keyboard::plugin(keyboard);
@@ -342,6 +396,10 @@ pub extern "C" fn start(multiboot2_magic: u32, multiboot2_addr: *const BootInfor
     }
     scheduler().ready(Thread::new_kernel_thread(cleanup, "cleanup"));
 
+    // Create idle threads and start the application processors, which wait for threads to become ready
+    scheduler().init_idle_threads(apic().core_count());
+    start_application_processors();
+
     //Initialize tty buffer (Workaround for missing pipes)
     init_tty();
 
@@ -374,7 +432,109 @@ pub extern "C" fn start(multiboot2_magic: u32, multiboot2_addr: *const BootInfor
     scheduler().start();
 }
 
-/// Set up the GDT
+/// Start all application processors (one after another) and wait until each has initialized itself.
+/// Processors, which do not start in time, are stopped and skipped. The next one gets their core id,
+/// so that the ids of the running cores stay contiguous.
+fn start_application_processors() {
+    if apic().core_count() < 2 {
+        return;
+    }
+
+    // Copy the startup code to its address in the first MiB (reserved during boot)
+    let data_ptr = unsafe {
+        let start = ptr::from_ref(&ap_trampoline) as usize;
+        let size = ptr::from_ref(&ap_trampoline_end) as usize - start;
+        let data_offset = ptr::from_ref(&ap_trampoline_data) as usize - start;
+        ptr::copy_nonoverlapping(start as *const u8, consts::AP_TRAMPOLINE_ADDRESS as *mut u8, size);
+        (consts::AP_TRAMPOLINE_ADDRESS + data_offset) as *mut TrampolineData
+    };
+
+    // The application processors use the page table and control registers of the bootstrap processor
+    let cr3 = Cr3::read().0.start_address().as_u64();
+    assert!(cr3 < 0x1_0000_0000, "Kernel page table must be located below 4 GiB for starting application processors!");
+    let cr4 = Cr4::read_raw();
+    let xcr0 = if Cr4::read().contains(Cr4Flags::OSXSAVE) { XCr0::read_raw() } else { 0 };
+
+    info!("Starting [{}] application processors", apic().core_count() - 1);
+    let mut core_id = 1;
+    while core_id < apic().core_count() {
+        let stack = unsafe { memory::vmm::alloc_frames(consts::KERNEL_STACK_PAGES) };
+        let data = TrampolineData {
+            cr0: Cr0::read_raw(),
+            cr3,
+            cr4,
+            efer: Efer::read_raw(),
+            xcr0,
+            stack: stack.end.start_address().as_u64(),
+            entry: ap_entry as usize as u64,
+            core_id: core_id as u64,
+        };
+        unsafe { ptr::write_volatile(data_ptr, data) };
+
+        AP_STATE.store(AP_WAITING, Ordering::Release);
+        apic().start_application_processor(core_id, consts::AP_TRAMPOLINE_ADDRESS);
+
+        let end_time = timer().systime_ms() + AP_STARTUP_TIMEOUT_MS;
+        while AP_STATE.load(Ordering::Acquire) == AP_WAITING && timer().systime_ms() < end_time {
+            spin_loop();
+        }
+
+        // The processor may still start after the timeout, so it is stopped before the trampoline data is reused.
+        // Its stack is not freed, as it may have been used already.
+        if AP_STATE.compare_exchange(AP_WAITING, AP_CANCELLED, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            warn!("Application processor [{}] did not start, skipping it!", core_id);
+            apic().remove_application_processor(core_id);
+            continue;
+        }
+
+        // The processor has entered `ap_entry` in time, wait until it has initialized itself
+        while AP_STATE.load(Ordering::Acquire) != AP_RUNNING {
+            spin_loop();
+        }
+        core_id += 1;
+    }
+}
+
+/// First Rust function called on an application processor (from the startup code in `boot.asm`) \
+///   `core_id` is the id of the core, assigned by the bootstrap processor
+extern "C" fn ap_entry(core_id: usize) {
+    // The bootstrap processor has given up waiting, so this processor is not used
+    if AP_STATE.compare_exchange(AP_WAITING, AP_STARTING, Ordering::AcqRel, Ordering::Acquire).is_err() {
+        loop {
+            interrupts::disable();
+            x86_64::instructions::hlt();
+        }
+    }
+
+    init_core_local_storage(core_id);
+    init_gdt();
+    interrupt_dispatcher::load_idt();
+    syscall_dispatcher::init();
+    apic().init_application_processor();
+
+    AP_STATE.store(AP_RUNNING, Ordering::Release);
+
+    // Start APIC timer & scheduler of this core
+    apic().start_timer(10);
+    scheduler().start();
+}
+
+/// Read the current date from the RTC (via EFI runtime services) in milliseconds since the Unix epoch
+fn efi_date_ms() -> Option<u64> {
+    if !efi_services_available() {
+        return None;
+    }
+
+    let time = uefi::runtime::get_time().ok().filter(|time| time.is_valid().is_ok())?;
+    let date_time = NaiveDate::from_ymd_opt(time.year() as i32, time.month() as u32, time.day() as u32)?
+        .and_hms_nano_opt(time.hour() as u32, time.minute() as u32, time.second() as u32, time.nanosecond())?;
+
+    // EFI dates are in local time, `time_zone` is its offset to UTC in minutes
+    let utc_ms = date_time.and_utc().timestamp_millis() - time.time_zone().unwrap_or(0) as i64 * 60_000;
+    u64::try_from(utc_ms).ok()
+}
+
+/// Set up the GDT of the calling core
 fn init_gdt() {
     let mut gdt = gdt().lock();
     let tss = tss().lock();
@@ -15,3 +15,14 @@ pub const KERNEL_STACK_PAGES: usize = 64;
 pub const STACK_ENTRY_SIZE: usize = 8;  
 
 pub const KERNEL_HEAP_PAGES: usize = 0x700; // number of heap pages for the kernel heap
+
+// Maximum number of cores used by the kernel (additional application processors are not started)
+pub const MAX_CORES: usize = 64;
+// Physical address, the startup code of the application processors is copied to (must match 'boot.asm')
+pub const AP_TRAMPOLINE_ADDRESS: usize = 0x8000;
+
+// Number of thread priorities (0 is the lowest priority, must match 'concurrent::thread')
+pub const THREAD_PRIORITY_LEVELS: usize = 8;
+pub const DEFAULT_THREAD_PRIORITY: usize = 4;
+// Highest priority user threads may set (kernel threads run at the default priority, so they cannot be starved)
+pub const MAX_USER_THREAD_PRIORITY: usize = DEFAULT_THREAD_PRIORITY;
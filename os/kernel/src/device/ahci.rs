@@ -0,0 +1,873 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: ahci                                                            ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Driver for SATA drives connected to an AHCI controller. Each port with  ║
+   ║ a drive gets registered as block device 'sata<n>'. Transfers use DMA    ║
+   ║ via bounce buffers (one per command slot). Requests are split into      ║
+   ║ commands of up to 64 KiB, which are issued at once, as native command   ║
+   ║ queued (NCQ) commands, if supported by the drive and the controller.    ║
+   ║ Completed commands are collected by the interrupt handler, which wakes  ║
+   ║ up the threads waiting for them.                                        ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::BitOr;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use log::{error, info, warn};
+use pci_types::{CommandRegister, EndpointHeader};
+use spin::{Mutex, RwLock};
+use x86_64::instructions::interrupts;
+use x86_64::structures::paging::frame::PhysFrameRange;
+use x86_64::structures::paging::PageTableFlags;
+
+use crate::interrupt::interrupt_dispatcher::InterruptVector;
+use crate::interrupt::interrupt_handler::InterruptHandler;
+use crate::memory::vma::VmaType;
+use crate::memory::{vmm, PAGE_SIZE};
+use crate::storage::add_block_device;
+use crate::storage::block::BlockDevice;
+use crate::sync::wait_queue::WaitQueue;
+use crate::{apic, interrupt_dispatcher, pci_bus, process_manager, scheduler, timer};
+
+/// Initialize all AHCI controllers found on the PCI bus.
+/// Each connected SATA drive gets registered as a block device in the storage module.
+pub fn init() {
+    let devices = pci_bus().search_by_class(0x01, 0x06);
+    for device in devices {
+        let device_id = device.read().header().id(pci_bus().config_space());
+        info!("Found AHCI controller [{}:{}]", device_id.0, device_id.1);
+
+        let controller = Arc::new(AhciController::new(device));
+        let drives = controller.init_ports();
+        AhciController::plugin(Arc::clone(&controller));
+
+        for (port, info) in drives {
+            add_block_device("sata", Arc::new(AhciDrive::new(port, info)));
+        }
+    }
+}
+
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Constants needed for the driver.                                        ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+const MAX_PORTS: usize = 32;
+const MAX_SLOTS: usize = 8;            // Command slots used per port (the HBA supports up to 32)
+const SLOT_BUFFER_PAGES: usize = 16;   // 64 KiB bounce buffer per command slot
+const COMMAND_TABLE_SIZE: usize = 256; // Command FIS, ATAPI command and one PRD entry (128 byte aligned)
+const FIS_RECEIVE_OFFSET: usize = 0x400;
+const COMMAND_TIMEOUT: usize = 30000;
+const PORT_TIMEOUT: usize = 500;
+const HBA_RESET_TIMEOUT: usize = 1000;
+const SATA_SIGNATURE: u32 = 0x0000_0101;
+
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Registers of the HBA and its ports (offsets and bits).                  ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+const HBA_CAP: usize = 0x00;
+const HBA_GHC: usize = 0x04;
+const HBA_IS: usize = 0x08;
+const HBA_PI: usize = 0x0c;
+const HBA_PORTS: usize = 0x100;
+const HBA_PORT_SIZE: usize = 0x80;
+
+const CAP_NCS_SHIFT: u32 = 8;
+const CAP_NCS_MASK: u32 = 0x1f;
+const CAP_SNCQ: u32 = 1 << 30;
+const GHC_HR: u32 = 1 << 0;
+const GHC_IE: u32 = 1 << 1;
+const GHC_AE: u32 = 1 << 31;
+
+const PORT_CLB: usize = 0x00;
+const PORT_CLBU: usize = 0x04;
+const PORT_FB: usize = 0x08;
+const PORT_FBU: usize = 0x0c;
+const PORT_IS: usize = 0x10;
+const PORT_IE: usize = 0x14;
+const PORT_CMD: usize = 0x18;
+const PORT_TFD: usize = 0x20;
+const PORT_SIG: usize = 0x24;
+const PORT_SSTS: usize = 0x28;
+const PORT_SERR: usize = 0x30;
+const PORT_SACT: usize = 0x34;
+const PORT_CI: usize = 0x38;
+
+const CMD_ST: u32 = 1 << 0;
+const CMD_SUD: u32 = 1 << 1;
+const CMD_POD: u32 = 1 << 2;
+const CMD_FRE: u32 = 1 << 4;
+const CMD_FR: u32 = 1 << 14;
+const CMD_CR: u32 = 1 << 15;
+
+const IS_DHRS: u32 = 1 << 0; // Device to host register FIS received
+const IS_PSS: u32 = 1 << 1;  // PIO setup FIS received
+const IS_DSS: u32 = 1 << 2;  // DMA setup FIS received
+const IS_SDBS: u32 = 1 << 3; // Set device bits FIS received (completion of NCQ commands)
+const IS_IFS: u32 = 1 << 27;
+const IS_HBDS: u32 = 1 << 28;
+const IS_HBFS: u32 = 1 << 29;
+const IS_TFES: u32 = 1 << 30;
+const IS_ERRORS: u32 = IS_IFS | IS_HBDS | IS_HBFS | IS_TFES;
+
+const SSTS_DET_PRESENT: u32 = 0x3;
+const TFD_ERR: u32 = 1 << 0;
+const TFD_DRQ: u32 = 1 << 3;
+const TFD_BSY: u32 = 1 << 7;
+
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ ATA commands and structures shared with the HBA.                        ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+enum Command {
+    Identify = 0xec,
+    ReadDmaExt = 0x25,
+    WriteDmaExt = 0x35,
+    ReadFpdmaQueued = 0x60,
+    WriteFpdmaQueued = 0x61,
+    FlushCacheExt = 0xea,
+}
+
+impl Command {
+    fn is_write(self) -> bool {
+        self == Command::WriteDmaExt || self == Command::WriteFpdmaQueued
+    }
+
+    fn is_queued(self) -> bool {
+        self == Command::ReadFpdmaQueued || self == Command::WriteFpdmaQueued
+    }
+}
+
+enum IdentifyFieldOffset {
+    Serial = 10,
+    Firmware = 23,
+    Model = 27,
+    MaxLba28 = 60,
+    QueueDepth = 75,
+    SataCapabilities = 76,
+    CommandSets = 83,
+    MaxLba48 = 100,
+    SectorSizeInfo = 106,
+    LogicalSectorSize = 117,
+}
+
+/// Entry of the command list (one per command slot)
+#[repr(C)]
+struct CommandHeader {
+    flags: u32, // Command FIS length (in dwords), write bit and number of PRD entries
+    prd_byte_count: u32,
+    table_address: u32,
+    table_address_upper: u32,
+    reserved: [u32; 4],
+}
+
+/// Physical region descriptor (located in the command table after the command FIS)
+#[repr(C)]
+struct PrdEntry {
+    data_address: u32,
+    data_address_upper: u32,
+    reserved: u32,
+    byte_count: u32, // Number of bytes - 1 and interrupt on completion bit
+}
+
+const PRD_OFFSET: usize = 0x80;
+const FIS_TYPE_H2D: u8 = 0x27;
+const FIS_COMMAND_BIT: u8 = 0x80;
+const DEVICE_LBA: u8 = 0x40;
+
+/// Information about a drive read with the IDENTIFY DEVICE command
+#[derive(Debug, Clone)]
+struct DriveInfo {
+    model: String,
+    serial: String,
+    firmware: String,
+    sector_count: u64,
+    sector_size: u16,
+    queue_depth: usize, // 0, if the drive does not support NCQ
+}
+
+impl DriveInfo {
+    fn from_identify(words: &[u16; 256]) -> Self {
+        let lba48 = words[IdentifyFieldOffset::CommandSets as usize] & (1 << 10) != 0;
+        let sector_count = if lba48 {
+            let offset = IdentifyFieldOffset::MaxLba48 as usize;
+            (0..4).fold(0, |count, i| count | (words[offset + i] as u64) << (16 * i))
+        } else {
+            let offset = IdentifyFieldOffset::MaxLba28 as usize;
+            words[offset] as u64 | (words[offset + 1] as u64) << 16
+        };
+
+        // Word 106 is valid, if bit 14 is set and bit 15 is clear; bit 12 indicates a logical sector size > 512 bytes
+        let size_info = words[IdentifyFieldOffset::SectorSizeInfo as usize];
+        let sector_size = if size_info & 0xc000 == 0x4000 && size_info & (1 << 12) != 0 {
+            let offset = IdentifyFieldOffset::LogicalSectorSize as usize;
+            ((words[offset] as u32 | (words[offset + 1] as u32) << 16) * 2) as u16
+        } else {
+            512
+        };
+
+        let queue_depth = if words[IdentifyFieldOffset::SataCapabilities as usize] & (1 << 8) != 0 {
+            (words[IdentifyFieldOffset::QueueDepth as usize] & 0x1f) as usize + 1
+        } else {
+            0
+        };
+
+        Self {
+            model: Self::byte_swapped_string(&words[IdentifyFieldOffset::Model as usize..][..20]),
+            serial: Self::byte_swapped_string(&words[IdentifyFieldOffset::Serial as usize..][..10]),
+            firmware: Self::byte_swapped_string(&words[IdentifyFieldOffset::Firmware as usize..][..4]),
+            sector_count,
+            sector_size,
+            queue_depth,
+        }
+    }
+
+    /// Strings in the identify data have the two bytes of each word swapped
+    fn byte_swapped_string(words: &[u16]) -> String {
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+        String::from_utf8_lossy(&bytes).trim().into()
+    }
+}
+
+/// Volatile access to memory mapped registers, relative to a base address
+#[derive(Debug, Clone, Copy)]
+struct Registers {
+    base: u64,
+}
+
+impl Registers {
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { ptr::read_volatile((self.base + offset as u64) as *const u32) }
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        unsafe { ptr::write_volatile((self.base + offset as u64) as *mut u32, value) }
+    }
+
+    /// Wait until all bits of `mask` are cleared in the register at `offset`
+    fn wait_clear(&self, offset: usize, mask: u32, timeout: usize) -> bool {
+        let end_time = timer().systime_ms() + timeout;
+        while self.read(offset) & mask != 0 {
+            if timer().systime_ms() >= end_time {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Controller (HBA)                                                        ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+struct AhciController {
+    registers: Registers,
+    interrupt: Option<InterruptVector>,
+    command_slots: usize, // Number of command slots supported by the HBA
+    supports_ncq: bool,
+    ports: RwLock<Vec<Arc<AhciPort>>>,
+}
+
+impl AhciController {
+    fn new(pci_device: &RwLock<EndpointHeader>) -> Self {
+        let pci_config_space = pci_bus().config_space();
+        let mut pci_device = pci_device.write();
+
+        // Enable bus master and memory space for DMA and MMIO register access and allow legacy interrupts
+        pci_device.update_command(pci_config_space, |command| {
+            command.bitor(CommandRegister::BUS_MASTER_ENABLE | CommandRegister::MEMORY_ENABLE).difference(CommandRegister::INTERRUPT_DISABLE)
+        });
+
+        // The registers are located at the AHCI base address (ABAR) in BAR5
+        let bar5 = pci_device.bar(5, pci_config_space).expect("Failed to read AHCI base address!");
+        let (abar, size) = bar5.unwrap_mem();
+        info!("AHCI base address: [0x{abar:x}]");
+
+        let kernel_process = process_manager().read().kernel_process().unwrap();
+        let registers_page = kernel_process.virtual_address_space.kernel_map_devm_identity(
+            abar as u64,
+            (abar + size.max(PAGE_SIZE)) as u64,
+            PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_CACHE,
+            VmaType::DeviceMemory,
+            "ahci",
+        );
+        let registers = Registers { base: registers_page.start_address().as_u64() };
+
+        // Reset the HBA and enable AHCI mode (which is cleared by the reset)
+        registers.write(HBA_GHC, registers.read(HBA_GHC) | GHC_AE);
+        registers.write(HBA_GHC, registers.read(HBA_GHC) | GHC_HR);
+        if !registers.wait_clear(HBA_GHC, GHC_HR, HBA_RESET_TIMEOUT) {
+            warn!("AHCI controller did not complete reset");
+        }
+        registers.write(HBA_GHC, registers.read(HBA_GHC) | GHC_AE);
+
+        let capabilities = registers.read(HBA_CAP);
+        let interrupt = match InterruptVector::try_from(pci_device.interrupt(pci_config_space).1 + 32) {
+            Ok(interrupt) => Some(interrupt),
+            Err(_) => {
+                warn!("AHCI controller has no usable interrupt line, polling for completion");
+                None
+            }
+        };
+
+        Self {
+            registers,
+            interrupt,
+            command_slots: ((capabilities >> CAP_NCS_SHIFT & CAP_NCS_MASK) + 1) as usize,
+            supports_ncq: capabilities & CAP_SNCQ != 0,
+            ports: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Initialize all implemented ports with a SATA drive attached and identify the drives
+    fn init_ports(&self) -> Vec<(Arc<AhciPort>, DriveInfo)> {
+        let implemented = self.registers.read(HBA_PI);
+        let mut drives = Vec::new();
+
+        for index in (0..MAX_PORTS).filter(|index| implemented & (1 << index) != 0) {
+            let registers = Registers { base: self.registers.base + (HBA_PORTS + index * HBA_PORT_SIZE) as u64 };
+            if registers.read(PORT_SSTS) & 0xf != SSTS_DET_PRESENT || registers.read(PORT_SIG) != SATA_SIGNATURE {
+                continue;
+            }
+
+            let mut port = AhciPort::new(index, registers, self.command_slots.min(MAX_SLOTS));
+            if !port.start() {
+                error!("Failed to start AHCI port [{}]", index);
+                continue;
+            }
+
+            match port.identify() {
+                Some(info) => {
+                    info!(
+                        "Found SATA drive on port [{}]: {} (Serial: {}, Firmware: {}, Sectors: {}, NCQ depth: {})",
+                        index, info.model, info.serial, info.firmware, info.sector_count, info.queue_depth
+                    );
+
+                    if self.supports_ncq && info.queue_depth > 0 {
+                        port.ncq = true;
+                        port.set_slot_count(port.slot_count.min(info.queue_depth));
+                    }
+
+                    let port = Arc::new(port);
+                    self.ports.write().push(Arc::clone(&port));
+                    drives.push((port, info));
+                }
+                None => error!("Failed to identify SATA drive on port [{}]", index),
+            }
+        }
+
+        drives
+    }
+
+    /// Register the interrupt handler and enable interrupts of the HBA and its ports
+    fn plugin(controller: Arc<AhciController>) {
+        let Some(interrupt) = controller.interrupt else {
+            return;
+        };
+
+        interrupt_dispatcher().assign(interrupt, Box::new(AhciInterruptHandler::new(Arc::clone(&controller))));
+        apic().allow(interrupt);
+
+        for port in controller.ports.read().iter() {
+            port.enable_interrupts();
+        }
+        controller.registers.write(HBA_IS, u32::MAX);
+        controller.registers.write(HBA_GHC, controller.registers.read(HBA_GHC) | GHC_IE);
+    }
+}
+
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Port                                                                    ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+/// State of the command slots of a port, shared between the issuing threads and the interrupt handler.
+/// It is only accessed with interrupts disabled, so the interrupt handler can never wait for a lock held on the same core.
+#[derive(Debug, Default)]
+struct SlotState {
+    free: u32,       // Slots, which can be allocated
+    issued: u32,     // Slots with a command being executed by the HBA
+    completed: u32,  // Slots with a successfully completed command
+    failed: u32,     // Slots with a failed command
+    exclusive: bool, // A non-queued command waits for the port to drain or is executed (no slots are allocated)
+}
+
+/// Memory of a command slot: the command table and a bounce buffer for the data
+struct Slot {
+    table: u64,
+    buffer: PhysFrameRange,
+}
+
+struct AhciPort {
+    index: usize,
+    registers: Registers,
+    command_list: PhysFrameRange, // Command list (1 KiB) followed by the received FIS area (256 bytes)
+    slots: Vec<Slot>,
+    slot_count: usize,
+    ncq: bool,
+    interrupts_enabled: AtomicBool,
+    state: Mutex<SlotState>,
+    slot_events: WaitQueue, // notified, when commands have finished or slots have been freed
+}
+
+impl AhciPort {
+    fn new(index: usize, registers: Registers, slot_count: usize) -> Self {
+        let command_list = vmm::alloc_dma_frames(1);
+        let tables = vmm::alloc_dma_frames((MAX_SLOTS * COMMAND_TABLE_SIZE).div_ceil(PAGE_SIZE));
+        let slots = (0..MAX_SLOTS)
+            .map(|slot| Slot {
+                table: tables.start.start_address().as_u64() + (slot * COMMAND_TABLE_SIZE) as u64,
+                buffer: vmm::alloc_dma_frames(SLOT_BUFFER_PAGES),
+            })
+            .collect();
+
+        let mut port = Self {
+            index,
+            registers,
+            command_list,
+            slots,
+            slot_count,
+            ncq: false,
+            interrupts_enabled: AtomicBool::new(false),
+            state: Mutex::new(SlotState::default()),
+            slot_events: WaitQueue::new(),
+        };
+        port.set_slot_count(slot_count);
+        port
+    }
+
+    fn set_slot_count(&mut self, slot_count: usize) {
+        self.slot_count = slot_count;
+        self.state.get_mut().free = if slot_count >= 32 { u32::MAX } else { (1 << slot_count) - 1 };
+    }
+
+    /// Stop command processing and FIS reception (needed before reconfiguring the port)
+    fn stop(&self) -> bool {
+        let cmd = self.registers.read(PORT_CMD);
+        self.registers.write(PORT_CMD, cmd & !CMD_ST);
+        if !self.registers.wait_clear(PORT_CMD, CMD_CR, PORT_TIMEOUT) {
+            return false;
+        }
+
+        let cmd = self.registers.read(PORT_CMD);
+        self.registers.write(PORT_CMD, cmd & !CMD_FRE);
+        self.registers.wait_clear(PORT_CMD, CMD_FR, PORT_TIMEOUT)
+    }
+
+    /// Configure the command list and received FIS area and start command processing
+    fn start(&self) -> bool {
+        if !self.stop() {
+            return false;
+        }
+
+        let command_list = self.command_list.start.start_address().as_u64();
+        let fis_area = command_list + FIS_RECEIVE_OFFSET as u64;
+        self.registers.write(PORT_CLB, command_list as u32);
+        self.registers.write(PORT_CLBU, (command_list >> 32) as u32);
+        self.registers.write(PORT_FB, fis_area as u32);
+        self.registers.write(PORT_FBU, (fis_area >> 32) as u32);
+
+        self.registers.write(PORT_SERR, u32::MAX);
+        self.registers.write(PORT_IS, u32::MAX);
+
+        let cmd = self.registers.read(PORT_CMD);
+        self.registers.write(PORT_CMD, cmd | CMD_SUD | CMD_POD | CMD_FRE);
+        if !self.registers.wait_clear(PORT_TFD, TFD_BSY | TFD_DRQ, PORT_TIMEOUT) {
+            return false;
+        }
+
+        let cmd = self.registers.read(PORT_CMD);
+        self.registers.write(PORT_CMD, cmd | CMD_ST);
+        true
+    }
+
+    fn enable_interrupts(&self) {
+        self.registers.write(PORT_IS, u32::MAX);
+        self.registers.write(PORT_IE, IS_DHRS | IS_PSS | IS_DSS | IS_SDBS | IS_ERRORS);
+        self.interrupts_enabled.store(true, Ordering::Release);
+    }
+
+    /// Read the identify data of the attached drive
+    fn identify(&self) -> Option<DriveInfo> {
+        let slot = self.alloc_slot()?;
+        self.prepare(slot, Command::Identify, 0, 1, 512);
+        self.issue(slot, false);
+        let success = self.wait(slot);
+
+        let info = if success {
+            let words = unsafe { &*(self.slots[slot].buffer.start.start_address().as_u64() as *const [u16; 256]) };
+            Some(DriveInfo::from_identify(words))
+        } else {
+            None
+        };
+
+        self.free_slot(slot);
+        info
+    }
+
+    fn alloc_slot(&self) -> Option<usize> {
+        interrupts::without_interrupts(|| {
+            let mut state = self.state.lock();
+            if state.free == 0 || state.exclusive {
+                return None;
+            }
+
+            let slot = state.free.trailing_zeros() as usize;
+            state.free &= !(1 << slot);
+            Some(slot)
+        })
+    }
+
+    /// Allocate a slot for a non-queued command, which must not be mixed with queued (NCQ) commands.
+    /// Blocks new allocations and waits until all pending commands have finished.
+    /// The slot must be freed with `free_exclusive_slot`, which allows allocations again.
+    fn alloc_exclusive_slot(&self) -> usize {
+        // Only one non-queued command at a time can claim the port
+        self.slot_events.wait(|| {
+            interrupts::without_interrupts(|| {
+                let mut state = self.state.lock();
+                !mem::replace(&mut state.exclusive, true)
+            })
+        });
+
+        let mut slot = None;
+        self.slot_events.wait(|| {
+            interrupts::without_interrupts(|| {
+                let mut state = self.state.lock();
+                if slot.is_none() && state.issued == 0 && state.free != 0 && self.registers.read(PORT_SACT) == 0 {
+                    let free = state.free.trailing_zeros() as usize;
+                    state.free &= !(1 << free);
+                    slot = Some(free);
+                }
+
+                slot.is_some()
+            })
+        });
+
+        slot.unwrap()
+    }
+
+    fn free_exclusive_slot(&self, slot: usize) {
+        interrupts::without_interrupts(|| self.state.lock().exclusive = false);
+        self.free_slot(slot);
+    }
+
+    fn free_slot(&self, slot: usize) {
+        interrupts::without_interrupts(|| {
+            let mut state = self.state.lock();
+            state.completed &= !(1 << slot);
+            state.failed &= !(1 << slot);
+            state.free |= 1 << slot;
+        });
+        self.slot_events.notify_all();
+    }
+
+    /// Check if a slot can be allocated
+    fn has_free_slot(&self) -> bool {
+        interrupts::without_interrupts(|| {
+            let state = self.state.lock();
+            state.free != 0 && !state.exclusive
+        })
+    }
+
+    /// Check if the command in `slot` has completed or failed
+    fn is_finished(&self, slot: usize) -> bool {
+        interrupts::without_interrupts(|| {
+            let state = self.state.lock();
+            (state.completed | state.failed) & (1 << slot) != 0
+        })
+    }
+
+    /// Set up the command header, the command FIS and the PRD entry of `slot`.
+    /// The data (`bytes` long) is transferred from/to the bounce buffer of the slot.
+    fn prepare(&self, slot: usize, command: Command, sector: u64, count: usize, bytes: usize) {
+        let table = self.slots[slot].table;
+        let buffer = self.slots[slot].buffer.start.start_address().as_u64();
+        let prd_count = if bytes > 0 { 1 } else { 0 };
+
+        // Command FIS (register host to device)
+        let mut fis = [0u8; 20];
+        fis[0] = FIS_TYPE_H2D;
+        fis[1] = FIS_COMMAND_BIT;
+        fis[2] = command as u8;
+        if command != Command::Identify && command != Command::FlushCacheExt {
+            fis[4..7].copy_from_slice(&sector.to_le_bytes()[0..3]);
+            fis[7] = DEVICE_LBA;
+            fis[8..11].copy_from_slice(&sector.to_le_bytes()[3..6]);
+            if command.is_queued() {
+                // The sector count is passed in the feature registers and the tag in the count register
+                fis[3] = count as u8;
+                fis[11] = (count >> 8) as u8;
+                fis[12] = (slot << 3) as u8;
+            } else {
+                fis[12] = count as u8;
+                fis[13] = (count >> 8) as u8;
+            }
+        }
+
+        unsafe {
+            ptr::write_bytes(table as *mut u8, 0, COMMAND_TABLE_SIZE);
+            ptr::copy_nonoverlapping(fis.as_ptr(), table as *mut u8, fis.len());
+            if prd_count > 0 {
+                ptr::write_volatile((table + PRD_OFFSET as u64) as *mut PrdEntry, PrdEntry {
+                    data_address: buffer as u32,
+                    data_address_upper: (buffer >> 32) as u32,
+                    reserved: 0,
+                    byte_count: (bytes - 1) as u32 | 1 << 31,
+                });
+            }
+
+            let header = (self.command_list.start.start_address().as_u64() as *mut CommandHeader).add(slot);
+            ptr::write_volatile(header, CommandHeader {
+                flags: (fis.len() / 4) as u32 | if command.is_write() { 1 << 6 } else { 0 } | prd_count << 16,
+                prd_byte_count: 0,
+                table_address: table as u32,
+                table_address_upper: (table >> 32) as u32,
+                reserved: [0; 4],
+            });
+        }
+    }
+
+    /// Hand the prepared command in `slot` over to the HBA
+    fn issue(&self, slot: usize, queued: bool) {
+        interrupts::without_interrupts(|| {
+            let mut state = self.state.lock();
+            state.issued |= 1 << slot;
+            if queued {
+                self.registers.write(PORT_SACT, 1 << slot);
+            }
+            self.registers.write(PORT_CI, 1 << slot);
+        });
+    }
+
+    /// Move finished commands from `issued` to `completed` (called by the interrupt handler, or by waiting threads
+    /// as long as interrupts are not enabled). If the HBA reports an error, all issued commands are considered failed.
+    fn collect(&self, state: &mut SlotState) {
+        let status = self.registers.read(PORT_IS);
+        self.registers.write(PORT_IS, status);
+
+        if status & IS_ERRORS != 0 || self.registers.read(PORT_TFD) & TFD_ERR != 0 {
+            warn!("AHCI port [{}] reported an error (IS: 0x{:08x}, TFD: 0x{:08x})", self.index, status, self.registers.read(PORT_TFD));
+            self.recover(state);
+            return;
+        }
+
+        let active = self.registers.read(PORT_CI) | self.registers.read(PORT_SACT);
+        let done = state.issued & !active;
+        state.issued &= !done;
+        state.completed |= done;
+    }
+
+    /// Restart the port after an error, which aborts all issued commands
+    fn recover(&self, state: &mut SlotState) {
+        state.failed |= state.issued;
+        state.issued = 0;
+        if !self.start() {
+            error!("Failed to restart AHCI port [{}]", self.index);
+        }
+    }
+
+    /// Wait until the command in `slot` has finished. Returns false, if it failed or timed out. \
+    /// The calling thread is blocked until the interrupt handler has collected the command.
+    /// As long as interrupts are not enabled, it polls the port instead.
+    fn wait(&self, slot: usize) -> bool {
+        let end_time = timer().systime_ms() + COMMAND_TIMEOUT;
+        loop {
+            let result = interrupts::without_interrupts(|| {
+                let mut state = self.state.lock();
+                if !self.interrupts_enabled.load(Ordering::Acquire) {
+                    self.collect(&mut state);
+                }
+
+                if state.completed & (1 << slot) != 0 {
+                    Some(true)
+                } else if state.failed & (1 << slot) != 0 {
+                    Some(false)
+                } else if timer().systime_ms() >= end_time {
+                    error!("Command timed out on AHCI port [{}]", self.index);
+                    self.recover(&mut state);
+                    Some(false)
+                } else {
+                    None
+                }
+            });
+
+            match result {
+                Some(success) => return success,
+                None if self.interrupts_enabled.load(Ordering::Acquire) => {
+                    let remaining = end_time.saturating_sub(timer().systime_ms());
+                    self.slot_events.wait_timeout(|| self.is_finished(slot), remaining);
+                }
+                None => scheduler().switch_thread_no_interrupt(),
+            }
+        }
+    }
+
+    /// Transfer `count` sectors starting at `sector`. Large requests are split into multiple commands,
+    /// which are issued together (up to the number of free slots). Returns the number of transferred sectors.
+    fn transfer(&self, write: bool, sector: u64, count: usize, sector_size: usize, buffer: *mut u8) -> usize {
+        let sectors_per_command = SLOT_BUFFER_PAGES * PAGE_SIZE / sector_size;
+        let command = match (write, self.ncq) {
+            (false, false) => Command::ReadDmaExt,
+            (true, false) => Command::WriteDmaExt,
+            (false, true) => Command::ReadFpdmaQueued,
+            (true, true) => Command::WriteFpdmaQueued,
+        };
+
+        let mut transferred = 0;
+        while transferred < count {
+            // Issue commands for the following chunks as long as slots are available
+            let mut batch = Vec::new();
+            let mut next = transferred;
+            while next < count {
+                let Some(slot) = self.alloc_slot() else {
+                    break;
+                };
+
+                let sectors = sectors_per_command.min(count - next);
+                let bytes = sectors * sector_size;
+                let slot_buffer = self.slots[slot].buffer.start.start_address().as_u64() as *mut u8;
+                if write {
+                    unsafe { ptr::copy_nonoverlapping(buffer.add(next * sector_size), slot_buffer, bytes) };
+                }
+
+                self.prepare(slot, command, sector + next as u64, sectors, bytes);
+                self.issue(slot, command.is_queued());
+                batch.push((slot, next, sectors));
+                next += sectors;
+            }
+
+            // All slots are used by other threads
+            if batch.is_empty() {
+                self.slot_events.wait(|| self.has_free_slot());
+                continue;
+            }
+
+            // Wait for all commands of this batch, counting only sectors up to the first failed command
+            let mut success = true;
+            for (slot, start, sectors) in batch {
+                let completed = self.wait(slot);
+                if completed && success && !write {
+                    let slot_buffer = self.slots[slot].buffer.start.start_address().as_u64() as *const u8;
+                    unsafe { ptr::copy_nonoverlapping(slot_buffer, buffer.add(start * sector_size), sectors * sector_size) };
+                }
+
+                success &= completed;
+                if success {
+                    transferred = start + sectors;
+                }
+                self.free_slot(slot);
+            }
+
+            if !success {
+                error!("Failed to {} sectors on AHCI port [{}]", if write { "write" } else { "read" }, self.index);
+                break;
+            }
+        }
+
+        transferred
+    }
+
+    /// Write the volatile cache of the drive to the medium.
+    /// FLUSH CACHE is not queued, so the port is drained first and no new commands are issued while it runs.
+    fn flush(&self) -> bool {
+        let slot = self.alloc_exclusive_slot();
+
+        self.prepare(slot, Command::FlushCacheExt, 0, 0, 0);
+        self.issue(slot, false);
+        let success = self.wait(slot);
+        self.free_exclusive_slot(slot);
+        success
+    }
+}
+
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Block device and interrupt handler                                      ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+/// A SATA drive, accessed via its port
+pub struct AhciDrive {
+    port: Arc<AhciPort>,
+    info: DriveInfo,
+}
+
+impl AhciDrive {
+    fn new(port: Arc<AhciPort>, info: DriveInfo) -> Self {
+        Self { port, info }
+    }
+
+    /// Helper function limiting `count` to the sectors of the drive and the size of a buffer with `buffer_len` bytes
+    fn limit_count(&self, sector: u64, count: usize, buffer_len: usize) -> usize {
+        let available = self.info.sector_count.saturating_sub(sector);
+        count.min(available.min(usize::MAX as u64) as usize).min(buffer_len / self.info.sector_size as usize)
+    }
+}
+
+impl BlockDevice for AhciDrive {
+    fn read(&self, sector: u64, count: usize, buffer: &mut [u8]) -> usize {
+        let count = self.limit_count(sector, count, buffer.len());
+        self.port.transfer(false, sector, count, self.info.sector_size as usize, buffer.as_mut_ptr())
+    }
+
+    fn write(&self, sector: u64, count: usize, buffer: &[u8]) -> usize {
+        // The buffer is only read by AhciPort::transfer() for write requests
+        let count = self.limit_count(sector, count, buffer.len());
+        self.port.transfer(true, sector, count, self.info.sector_size as usize, buffer.as_ptr().cast_mut())
+    }
+
+    fn sector_count(&self) -> u64 {
+        self.info.sector_count
+    }
+
+    fn sector_size(&self) -> u16 {
+        self.info.sector_size
+    }
+
+    fn flush(&self) -> bool {
+        self.port.flush()
+    }
+}
+
+/// The handler collects completed commands of all ports, which signal an interrupt,
+/// and wakes up the threads waiting on the port. They check the `completed` and `failed` bits of their slots.
+pub struct AhciInterruptHandler {
+    controller: Arc<AhciController>,
+}
+
+impl AhciInterruptHandler {
+    fn new(controller: Arc<AhciController>) -> Self {
+        Self { controller }
+    }
+}
+
+impl InterruptHandler for AhciInterruptHandler {
+    fn trigger(&self) {
+        let registers = &self.controller.registers;
+        let pending = registers.read(HBA_IS);
+        if pending == 0 {
+            return;
+        }
+
+        for port in self.controller.ports.read().iter().filter(|port| pending & (1 << port.index) != 0) {
+            // The state lock must be released before notifying, as waiters check their slots while holding the queue lock
+            port.collect(&mut port.state.lock());
+            port.slot_events.notify_all_from_interrupt();
+        }
+
+        // The port interrupt status must be cleared before the bit in the HBA interrupt status
+        registers.write(HBA_IS, pending);
+    }
+}
@@ -1,23 +1,28 @@
 use crate::interrupt::interrupt_dispatcher::InterruptVector;
 use crate::interrupt::interrupt_handler::InterruptHandler;
+use crate::memory::tlb;
 use crate::memory::vma::VmaType;
-use crate::{acpi_tables, allocator, interrupt_dispatcher, process_manager, scheduler, timer};
+use crate::consts::MAX_CORES;
+use crate::{acpi_tables, allocator, core_id, interrupt_dispatcher, process_manager, scheduler, timer};
 use acpi::InterruptModel;
 use acpi::madt::Madt;
+use acpi::platform::ProcessorState;
 use acpi::platform::interrupt::{InterruptSourceOverride, NmiSource, Polarity, TriggerMode};
 use alloc::boxed::Box;
 use alloc::format;
 use alloc::vec::Vec;
 use log::{info, warn};
 use raw_cpuid::CpuId;
-use spin::Mutex;
+use spin::{Mutex, RwLock};
 use uefi::boot::PAGE_SIZE;
 use x2apic::ioapic::{IoApic, IrqFlags, IrqMode, RedirectionTableEntry};
 use x2apic::lapic::{LocalApic, LocalApicBuilder, TimerDivide, TimerMode};
+use x86_64::instructions::interrupts;
 use x86_64::structures::paging::PageTableFlags;
 
 pub struct Apic {
-    local_apic: Mutex<LocalApic>,
+    local_apics: Vec<Mutex<LocalApic>>, // One Local APIC instance per core (indexed by core id)
+    apic_ids: RwLock<Vec<u32>>, // Local APIC id of each core (the bootstrap processor is core 0)
     io_apics: Vec<(Mutex<IoApic>, u32)>, // (0: IO APIC instance, 1: Base Global System Interrupt)
     irq_overrides: Vec<InterruptSourceOverride>,
     nmi_sources: Vec<NmiSource>,
@@ -36,6 +41,26 @@ impl InterruptHandler for ApicTimerInterruptHandler {
     }
 }
 
+/// Sent by another core, after it has inserted a thread into the ready queue of an idle core
+#[derive(Default)]
+struct RescheduleInterruptHandler {}
+
+impl InterruptHandler for RescheduleInterruptHandler {
+    fn trigger(&self) {
+        scheduler().switch_thread_from_interrupt();
+    }
+}
+
+/// Sent by another core, after it has changed or removed page table entries (see `tlb::shootdown()`)
+#[derive(Default)]
+struct TlbShootdownInterruptHandler {}
+
+impl InterruptHandler for TlbShootdownInterruptHandler {
+    fn trigger(&self) {
+        tlb::handle_shootdown();
+    }
+}
+
 impl Apic {
     pub fn new() -> Self {
         info!("Initializing APIC");
@@ -82,6 +107,20 @@ impl Apic {
             cpu_info.boot_processor.processor_uid
         );
 
+        // Assign core ids (the bootstrap processor is core 0, followed by all usable application processors)
+        let mut apic_ids = Vec::<u32>::new();
+        apic_ids.push(cpu_info.boot_processor.local_apic_id);
+        for processor in cpu_info.application_processors.iter() {
+            if processor.state == ProcessorState::Disabled {
+                continue;
+            }
+            if apic_ids.len() == MAX_CORES {
+                warn!("   Only [{MAX_CORES}] cores are supported, ignoring CPU [{}]", processor.processor_uid);
+                continue;
+            }
+            apic_ids.push(processor.local_apic_id);
+        }
+
         // Vectors to store IRQ overrides and Non-maskable interrupts
         let mut irq_overrides = Vec::<InterruptSourceOverride>::new();
         let mut nmi_sources = Vec::<NmiSource>::new();
@@ -89,8 +128,12 @@ impl Apic {
         // Vector to store initialized IO APICs with their base interrupt number
         let mut io_apics = Vec::<(Mutex<IoApic>, u32)>::new();
 
-        // Create Local APIC instance
-        let local_apic = Mutex::new(Self::create_local_apic(&madt));
+        // Create Local APIC instances (all cores access their own Local APIC at the same address)
+        let lapic_registers_address = Self::map_local_apic(&madt);
+        let local_apics = apic_ids.iter()
+            .map(|_| Mutex::new(Self::create_local_apic(lapic_registers_address)))
+            .collect::<Vec<Mutex<LocalApic>>>();
+        let local_apic = &local_apics[0];
 
         match int_model.0 {
             InterruptModel::Apic(apic_desc) => {
@@ -259,8 +302,24 @@ impl Apic {
         let timer_ticks_per_ms = Apic::calibrate_timer(&mut local_apic.lock());
         info!("   APIC Timer ticks per millisecond: [{timer_ticks_per_ms}]");
 
+        // The timer is started on each core by 'start_timer()', rescheduling interrupts are sent by 'reschedule()'
+        // and TLB shootdown interrupts by 'shootdown_tlb()'
+        interrupt_dispatcher().assign(
+            InterruptVector::ApicTimer,
+            Box::new(ApicTimerInterruptHandler::default()),
+        );
+        interrupt_dispatcher().assign(
+            InterruptVector::Reschedule,
+            Box::new(RescheduleInterruptHandler::default()),
+        );
+        interrupt_dispatcher().assign(
+            InterruptVector::TlbShootdown,
+            Box::new(TlbShootdownInterruptHandler::default()),
+        );
+
         Self {
-            local_apic,
+            local_apics,
+            apic_ids: RwLock::new(apic_ids),
             io_apics,
             irq_overrides,
             nmi_sources,
@@ -268,7 +327,7 @@ impl Apic {
         }
     }
 
-    fn create_local_apic(madt: &Madt) -> LocalApic {
+    fn map_local_apic(madt: &Madt) -> u64 {
         let process = process_manager().read().kernel_process().unwrap();
 
         let lapic_registers_phys_addr = madt.local_apic_address as u64;
@@ -281,15 +340,81 @@ impl Apic {
             "lapic",
         );
 
+        lapic_registers_page.start_address().as_u64()
+    }
+
+    fn create_local_apic(lapic_registers_address: u64) -> LocalApic {
         LocalApicBuilder::new()
             .timer_vector(InterruptVector::ApicTimer as usize)
             .error_vector(InterruptVector::ApicError as usize)
             .spurious_vector(InterruptVector::Spurious as usize)
-            .set_xapic_base(lapic_registers_page.start_address().as_u64())
+            .set_xapic_base(lapic_registers_address)
             .build()
             .unwrap_or_else(|err| panic!("Failed to initialize Local APIC ({})!", err))
     }
 
+    /// Return the number of cores (the bootstrap processor and all usable application processors)
+    pub fn core_count(&self) -> usize {
+        self.apic_ids.read().len()
+    }
+
+    /// Return the Local APIC id of `core`
+    fn apic_id(&self, core: usize) -> u32 {
+        self.apic_ids.read()[core]
+    }
+
+    /// Return the Local APIC of the calling core
+    fn local_apic(&self) -> &Mutex<LocalApic> {
+        &self.local_apics[core_id()]
+    }
+
+    /// Start the application processor with the id `core` by sending an INIT and two startup IPIs.
+    /// The processor starts executing in real mode at `start_address` (page aligned and below 1 MiB).
+    /// The caller needs to check whether the processor is actually running.
+    pub fn start_application_processor(&self, core: usize, start_address: usize) {
+        let apic_id = self.apic_id(core);
+        let vector = (start_address / PAGE_SIZE) as u8;
+
+        unsafe { self.local_apic().lock().send_init_ipi(apic_id); }
+        timer().wait(10);
+
+        for _ in 0..2 {
+            unsafe { self.local_apic().lock().send_sipi(vector, apic_id); }
+            timer().wait(1);
+        }
+    }
+
+    /// Stop the application processor `core`, which has not initialized itself in time, by sending an INIT IPI.
+    /// It is removed, so that the following application processors get the next lower core ids.
+    pub fn remove_application_processor(&self, core: usize) {
+        let apic_id = self.apic_ids.write().remove(core);
+
+        unsafe { self.local_apic().lock().send_init_ipi(apic_id); }
+        timer().wait(10);
+    }
+
+    /// Enable the Local APIC of an application processor (called by the processor itself during its initialization)
+    pub fn init_application_processor(&self) {
+        let core = core_id();
+        info!("   Enabling Local APIC [{}] of core [{}]", self.apic_id(core), core);
+
+        unsafe { self.local_apics[core].lock().enable(); }
+    }
+
+    /// Send a rescheduling interrupt to `core`, which lets it switch to the next thread in its ready queue
+    pub fn reschedule(&self, core: usize) {
+        interrupts::without_interrupts(|| unsafe {
+            self.local_apic().lock().send_ipi(InterruptVector::Reschedule as u8, self.apic_id(core));
+        });
+    }
+
+    /// Send a TLB shootdown interrupt to `core`, which lets it invalidate the TLB entries requested by `tlb::shootdown()`
+    pub fn shootdown_tlb(&self, core: usize) {
+        interrupts::without_interrupts(|| unsafe {
+            self.local_apic().lock().send_ipi(InterruptVector::TlbShootdown as u8, self.apic_id(core));
+        });
+    }
+
     fn create_io_apic(io_apic_desc: &acpi::platform::interrupt::IoApic) -> IoApic {
         let process = process_manager().read().kernel_process().unwrap();
 
@@ -328,14 +453,16 @@ impl Apic {
     }
 
     pub fn end_of_interrupt(&self) {
-        let mut local_apic = self.local_apic.try_lock();
+        let lapic = self.local_apic();
+        let mut local_apic = lapic.try_lock();
         while local_apic.is_none() {
             // It its extremely unlikely, that the local APIC is locked during an interrupt,
             // but if it happens, the whole system would hang, trying to send an EOI.
+            // The Local APIC is only used by its own core, so no other core holds the lock.
             unsafe {
-                self.local_apic.force_unlock();
+                lapic.force_unlock();
             }
-            local_apic = self.local_apic.try_lock();
+            local_apic = lapic.try_lock();
         }
 
         unsafe {
@@ -343,20 +470,18 @@ impl Apic {
         }
     }
 
+    /// Start the periodic timer of the calling core (all cores use the calibration of the bootstrap processor)
     pub fn start_timer(&self, interval_ms: usize) {
-        let mut local_apic = self.local_apic.lock();
+        interrupts::without_interrupts(|| {
+            let mut local_apic = self.local_apic().lock();
 
-        unsafe {
-            local_apic.set_timer_divide(TimerDivide::Div1);
-            local_apic.set_timer_mode(TimerMode::Periodic);
-            local_apic.set_timer_initial((self.timer_ticks_per_ms * interval_ms) as u32);
-            local_apic.enable_timer();
-        }
-
-        interrupt_dispatcher().assign(
-            InterruptVector::ApicTimer,
-            Box::new(ApicTimerInterruptHandler::default()),
-        );
+            unsafe {
+                local_apic.set_timer_divide(TimerDivide::Div1);
+                local_apic.set_timer_mode(TimerMode::Periodic);
+                local_apic.set_timer_initial((self.timer_ticks_per_ms * interval_ms) as u32);
+                local_apic.enable_timer();
+            }
+        });
     }
 
     fn calibrate_timer(local_apic: &mut LocalApic) -> usize {
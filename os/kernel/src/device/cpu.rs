@@ -20,6 +20,7 @@ pub struct Cpu {
     physical_address_bits: u8,
     linear_address_bits: u8,
     supports_1gib_pages: bool,
+    supports_no_execute: bool,
 }
 
 impl Cpu {
@@ -27,6 +28,7 @@ impl Cpu {
         let physical_bits;
         let virtual_bits;
         let mut has_1gib_pages: bool = false;
+        let has_no_execute;
 
         let cpuid = CpuId::new();
 
@@ -46,15 +48,17 @@ impl Cpu {
                 if features.has_1gib_pages() {
                     has_1gib_pages = true;
                 }
+                has_no_execute = features.has_execute_disable();
             }
         }
 
-        info!("Cpu: Physical address bits {physical_bits}, Linear address bits {virtual_bits}, supports_1gib_pages = {has_1gib_pages}");
+        info!("Cpu: Physical address bits {physical_bits}, Linear address bits {virtual_bits}, supports_1gib_pages = {has_1gib_pages}, supports_no_execute = {has_no_execute}");
 
         Cpu {
             physical_address_bits: physical_bits,
             linear_address_bits: virtual_bits,
             supports_1gib_pages: has_1gib_pages,
+            supports_no_execute: has_no_execute,
         }
     }
 
@@ -70,6 +74,11 @@ impl Cpu {
         self.supports_1gib_pages
     }
 
+    /// Check if page table entries may forbid executing code (`PageTableFlags::NO_EXECUTE`)
+    pub fn supports_no_execute(&self) -> bool {
+        self.supports_no_execute
+    }
+
     /// Return the highest virtual address in canonical form
     pub fn highest_virtual_address(&self) -> u64 {
         let virtual_bits = self.linear_address_bits();
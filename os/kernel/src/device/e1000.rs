@@ -0,0 +1,412 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: e1000                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Driver for Intel 8254x (e1000) and 82574 (e1000e) network controllers,  ║
+   ║ implementing the smoltcp 'Device' trait. Packets are received and sent  ║
+   ║ via descriptor rings with a 2 KiB buffer for each descriptor (legacy    ║
+   ║ descriptor format). The registers are accessed via MMIO (BAR0).         ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::BitOr;
+use core::{ptr, slice};
+use log::info;
+use pci_types::{CommandRegister, EndpointHeader};
+use smoltcp::phy;
+use smoltcp::phy::{DeviceCapabilities, Medium};
+use smoltcp::time::Instant;
+use smoltcp::wire::EthernetAddress;
+use spin::{Mutex, RwLock};
+use x86_64::structures::paging::PageTableFlags;
+
+use crate::interrupt::interrupt_dispatcher::InterruptVector;
+use crate::interrupt::interrupt_handler::InterruptHandler;
+use crate::memory::vma::VmaType;
+use crate::memory::vmm::alloc_dma_frames;
+use crate::memory::PAGE_SIZE;
+use crate::{apic, interrupt_dispatcher, network, pci_bus, process_manager, scheduler};
+
+const INTEL_VENDOR_ID: u16 = 0x8086;
+
+/// PCI device ids of supported controllers (82540EM is emulated by QEMU's 'e1000', 82574L by 'e1000e')
+const DEVICE_IDS: [u16; 7] = [0x100e, 0x100f, 0x1004, 0x1015, 0x1019, 0x10d3, 0x10f5];
+
+const RING_SIZE: usize = 32;
+const BUFFER_SIZE: usize = 2048;
+const MTU: usize = 1514;
+const RESET_TIMEOUT_MS: usize = 10;
+
+/// Register offsets
+const REG_CTRL: usize = 0x0000;
+const REG_ICR: usize = 0x00c0;
+const REG_IMS: usize = 0x00d0;
+const REG_IMC: usize = 0x00d8;
+const REG_RCTL: usize = 0x0100;
+const REG_TCTL: usize = 0x0400;
+const REG_TIPG: usize = 0x0410;
+const REG_RDBAL: usize = 0x2800;
+const REG_RDBAH: usize = 0x2804;
+const REG_RDLEN: usize = 0x2808;
+const REG_RDH: usize = 0x2810;
+const REG_RDT: usize = 0x2818;
+const REG_TDBAL: usize = 0x3800;
+const REG_TDBAH: usize = 0x3804;
+const REG_TDLEN: usize = 0x3808;
+const REG_TDH: usize = 0x3810;
+const REG_TDT: usize = 0x3818;
+const REG_MTA: usize = 0x5200;
+const REG_RAL: usize = 0x5400;
+const REG_RAH: usize = 0x5404;
+
+const CTRL_ASDE: u32 = 1 << 5;
+const CTRL_SLU: u32 = 1 << 6;
+const CTRL_RST: u32 = 1 << 26;
+
+const RCTL_EN: u32 = 1 << 1;
+const RCTL_BAM: u32 = 1 << 15;
+const RCTL_SECRC: u32 = 1 << 26; // Strip CRC (BSIZE = 0 selects 2048 byte buffers)
+
+const TCTL_EN: u32 = 1 << 1;
+const TCTL_PSP: u32 = 1 << 3;
+const TCTL_CT: u32 = 0x0f << 4;
+const TCTL_COLD: u32 = 0x40 << 12;
+const TIPG_DEFAULT: u32 = 0x0060_200a;
+
+const INT_TXDW: u32 = 1 << 0;
+const INT_LSC: u32 = 1 << 2;
+const INT_RXDMT0: u32 = 1 << 4;
+const INT_RXO: u32 = 1 << 6;
+const INT_RXT0: u32 = 1 << 7;
+
+const RAH_AV: u32 = 1 << 31;
+
+const STATUS_DD: u8 = 1 << 0;
+const STATUS_EOP: u8 = 1 << 1;
+const CMD_EOP: u8 = 1 << 0;
+const CMD_IFCS: u8 = 1 << 1;
+const CMD_RS: u8 = 1 << 3;
+
+/// Search the PCI bus for all supported controllers
+pub fn find_devices() -> Vec<&'static RwLock<EndpointHeader>> {
+    DEVICE_IDS.iter().flat_map(|device_id| pci_bus().search_by_ids(INTEL_VENDOR_ID, *device_id)).collect()
+}
+
+#[repr(C)]
+struct ReceiveDescriptor {
+    address: u64,
+    length: u16,
+    checksum: u16,
+    status: u8,
+    errors: u8,
+    special: u16,
+}
+
+#[repr(C)]
+struct TransmitDescriptor {
+    address: u64,
+    length: u16,
+    cso: u8,
+    cmd: u8,
+    status: u8,
+    css: u8,
+    special: u16,
+}
+
+/// Descriptor ring with a buffer for each descriptor
+struct Ring {
+    descriptors: u64,
+    buffers: u64,
+    next: usize, // Next descriptor to be checked (receive) or used (transmit)
+}
+
+impl Ring {
+    fn new() -> Self {
+        let descriptors = alloc_dma_frames((RING_SIZE * 16).div_ceil(PAGE_SIZE));
+        let buffers = alloc_dma_frames((RING_SIZE * BUFFER_SIZE).div_ceil(PAGE_SIZE));
+        Self {
+            descriptors: descriptors.start.start_address().as_u64(),
+            buffers: buffers.start.start_address().as_u64(),
+            next: 0,
+        }
+    }
+
+    fn buffer(&self, index: usize) -> u64 {
+        self.buffers + (index * BUFFER_SIZE) as u64
+    }
+}
+
+pub struct E1000 {
+    registers: u64,
+    interrupt: Option<InterruptVector>,
+    mac: EthernetAddress,
+    receive: Mutex<Ring>,
+    transmit: Mutex<Ring>,
+}
+
+impl E1000 {
+    pub fn new(pci_device: &RwLock<EndpointHeader>) -> Self {
+        let pci_config_space = pci_bus().config_space();
+        let mut pci_device = pci_device.write();
+        let (vendor_id, device_id) = pci_device.header().id(pci_config_space);
+        info!("Found Intel e1000 network controller [{}:{}]", vendor_id, device_id);
+
+        pci_device.update_command(pci_config_space, |command| {
+            command.bitor(CommandRegister::BUS_MASTER_ENABLE | CommandRegister::MEMORY_ENABLE).difference(CommandRegister::INTERRUPT_DISABLE)
+        });
+
+        let bar0 = pci_device.bar(0, pci_config_space).expect("Failed to read e1000 base address!");
+        let (base_address, size) = bar0.unwrap_mem();
+        let kernel_process = process_manager().read().kernel_process().unwrap();
+        let registers = kernel_process.virtual_address_space.kernel_map_devm_identity(
+            base_address as u64,
+            (base_address + size.max(PAGE_SIZE)) as u64,
+            PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_CACHE,
+            VmaType::DeviceMemory,
+            "e1000",
+        );
+
+        let mut e1000 = Self {
+            registers: registers.start_address().as_u64(),
+            interrupt: InterruptVector::try_from(pci_device.interrupt(pci_config_space).1 + 32).ok(),
+            mac: EthernetAddress::default(),
+            receive: Mutex::new(Ring::new()),
+            transmit: Mutex::new(Ring::new()),
+        };
+
+        e1000.reset();
+        e1000.mac = e1000.read_mac_from_registers();
+        info!("e1000 MAC address: [{}]", e1000.mac);
+        e1000.init_receive();
+        e1000.init_transmit();
+
+        e1000
+    }
+
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { ptr::read_volatile((self.registers + offset as u64) as *const u32) }
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        unsafe { ptr::write_volatile((self.registers + offset as u64) as *mut u32, value) }
+    }
+
+    fn reset(&self) {
+        self.write(REG_IMC, u32::MAX);
+        self.write(REG_CTRL, self.read(REG_CTRL) | CTRL_RST);
+        scheduler().sleep(RESET_TIMEOUT_MS);
+        while self.read(REG_CTRL) & CTRL_RST != 0 {
+            scheduler().sleep(1);
+        }
+
+        // Interrupts are enabled again by the reset
+        self.write(REG_IMC, u32::MAX);
+        self.read(REG_ICR);
+        self.write(REG_CTRL, self.read(REG_CTRL) | CTRL_SLU | CTRL_ASDE);
+
+        // Clear the multicast table
+        for i in 0..128 {
+            self.write(REG_MTA + i * 4, 0);
+        }
+    }
+
+    /// The receive address registers are loaded from the EEPROM after reset
+    fn read_mac_from_registers(&self) -> EthernetAddress {
+        let low = self.read(REG_RAL).to_le_bytes();
+        let high = self.read(REG_RAH);
+        if high & RAH_AV == 0 {
+            panic!("e1000 has no valid MAC address!");
+        }
+
+        let high = high.to_le_bytes();
+        EthernetAddress::from_bytes(&[low[0], low[1], low[2], low[3], high[0], high[1]])
+    }
+
+    fn init_receive(&self) {
+        let ring = self.receive.lock();
+        for i in 0..RING_SIZE {
+            let descriptor = (ring.descriptors as *mut ReceiveDescriptor).wrapping_add(i);
+            unsafe { ptr::write_volatile(descriptor, ReceiveDescriptor { address: ring.buffer(i), length: 0, checksum: 0, status: 0, errors: 0, special: 0 }) };
+        }
+
+        self.write(REG_RDBAL, ring.descriptors as u32);
+        self.write(REG_RDBAH, (ring.descriptors >> 32) as u32);
+        self.write(REG_RDLEN, (RING_SIZE * size_of::<ReceiveDescriptor>()) as u32);
+        self.write(REG_RDH, 0);
+        self.write(REG_RDT, (RING_SIZE - 1) as u32);
+        self.write(REG_RCTL, RCTL_EN | RCTL_BAM | RCTL_SECRC);
+    }
+
+    fn init_transmit(&self) {
+        let ring = self.transmit.lock();
+        for i in 0..RING_SIZE {
+            // All descriptors are initially marked as done, so they can be used
+            let descriptor = (ring.descriptors as *mut TransmitDescriptor).wrapping_add(i);
+            unsafe { ptr::write_volatile(descriptor, TransmitDescriptor { address: ring.buffer(i), length: 0, cso: 0, cmd: 0, status: STATUS_DD, css: 0, special: 0 }) };
+        }
+
+        self.write(REG_TDBAL, ring.descriptors as u32);
+        self.write(REG_TDBAH, (ring.descriptors >> 32) as u32);
+        self.write(REG_TDLEN, (RING_SIZE * size_of::<TransmitDescriptor>()) as u32);
+        self.write(REG_TDH, 0);
+        self.write(REG_TDT, 0);
+        self.write(REG_TCTL, TCTL_EN | TCTL_PSP | TCTL_CT | TCTL_COLD);
+        self.write(REG_TIPG, TIPG_DEFAULT);
+    }
+
+    pub fn plugin(device: Arc<E1000>) {
+        let Some(interrupt) = device.interrupt else {
+            return;
+        };
+
+        device.write(REG_IMS, INT_TXDW | INT_LSC | INT_RXDMT0 | INT_RXO | INT_RXT0);
+        interrupt_dispatcher().assign(interrupt, Box::new(E1000InterruptHandler::new(device)));
+        apic().allow(interrupt);
+    }
+
+    pub fn read_mac_address(&self) -> EthernetAddress {
+        self.mac
+    }
+
+    fn receive_descriptor(ring: &Ring, index: usize) -> *mut ReceiveDescriptor {
+        (ring.descriptors as *mut ReceiveDescriptor).wrapping_add(index)
+    }
+
+    fn transmit_descriptor(ring: &Ring, index: usize) -> *mut TransmitDescriptor {
+        (ring.descriptors as *mut TransmitDescriptor).wrapping_add(index)
+    }
+}
+
+pub struct E1000TxToken<'a> {
+    device: &'a E1000,
+}
+
+pub struct E1000RxToken<'a> {
+    index: usize,
+    device: &'a E1000,
+}
+
+impl<'a> phy::TxToken for E1000TxToken<'a> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where F: FnOnce(&mut [u8]) -> R {
+        if len > BUFFER_SIZE {
+            panic!("Packet length may not exceed buffer size!");
+        }
+
+        // Wait for the next descriptor to be sent
+        let mut ring = self.device.transmit.lock();
+        let index = ring.next;
+        let descriptor = E1000::transmit_descriptor(&ring, index);
+        while unsafe { ptr::read_volatile(&raw const (*descriptor).status) } & STATUS_DD == 0 {
+            scheduler().switch_thread_no_interrupt();
+        }
+
+        let buffer = unsafe { slice::from_raw_parts_mut(ring.buffer(index) as *mut u8, len) };
+        let result = f(buffer);
+
+        unsafe {
+            ptr::write_volatile(descriptor, TransmitDescriptor {
+                address: ring.buffer(index),
+                length: len as u16,
+                cso: 0,
+                cmd: CMD_EOP | CMD_IFCS | CMD_RS,
+                status: 0,
+                css: 0,
+                special: 0,
+            });
+        }
+
+        ring.next = (index + 1) % RING_SIZE;
+        self.device.write(REG_TDT, ring.next as u32);
+
+        result
+    }
+}
+
+impl<'a> phy::RxToken for E1000RxToken<'a> {
+    fn consume<R, F>(self, f: F) -> R
+    where F: FnOnce(&[u8]) -> R {
+        let ring = self.device.receive.lock();
+        let descriptor = E1000::receive_descriptor(&ring, self.index);
+        let length = unsafe { ptr::read_volatile(&raw const (*descriptor).length) } as usize;
+        let packet = unsafe { slice::from_raw_parts(ring.buffer(self.index) as *const u8, length.min(BUFFER_SIZE)) };
+        let result = f(packet);
+
+        // Give the descriptor back to the controller
+        unsafe { ptr::write_volatile(&raw mut (*descriptor).status, 0) };
+        self.device.write(REG_RDT, self.index as u32);
+
+        result
+    }
+}
+
+impl phy::Device for E1000 {
+    type RxToken<'a> = E1000RxToken<'a> where Self: 'a;
+    type TxToken<'a> = E1000TxToken<'a> where Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let device = unsafe { ptr::from_ref(self).as_ref()? };
+        let mut ring = self.receive.lock();
+        loop {
+            let index = ring.next;
+            let descriptor = E1000::receive_descriptor(&ring, index);
+            let status = unsafe { ptr::read_volatile(&raw const (*descriptor).status) };
+            if status & STATUS_DD == 0 {
+                return None;
+            }
+
+            ring.next = (index + 1) % RING_SIZE;
+            if status & STATUS_EOP != 0 && unsafe { ptr::read_volatile(&raw const (*descriptor).errors) } == 0 {
+                return Some((E1000RxToken { index, device }, E1000TxToken { device }));
+            }
+
+            // Drop packets spanning multiple descriptors (larger than the MTU) and erroneous packets
+            unsafe { ptr::write_volatile(&raw mut (*descriptor).status, 0) };
+            self.write(REG_RDT, index as u32);
+        }
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        let device = unsafe { ptr::from_ref(self).as_ref()? };
+        let ring = self.transmit.lock();
+        let descriptor = E1000::transmit_descriptor(&ring, ring.next);
+        if unsafe { ptr::read_volatile(&raw const (*descriptor).status) } & STATUS_DD == 0 {
+            return None;
+        }
+
+        Some(E1000TxToken { device })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.max_transmission_unit = MTU;
+        caps.max_burst_size = Some(RING_SIZE);
+        caps.medium = Medium::Ethernet;
+
+        caps
+    }
+}
+
+/// Reading the interrupt cause register acknowledges all pending interrupts.
+/// Received packets are fetched from the receive ring by the network poll thread, which is woken up here.
+pub struct E1000InterruptHandler {
+    device: Arc<E1000>,
+}
+
+impl E1000InterruptHandler {
+    fn new(device: Arc<E1000>) -> Self {
+        Self { device }
+    }
+}
+
+impl InterruptHandler for E1000InterruptHandler {
+    fn trigger(&self) {
+        if self.device.read(REG_ICR) != 0 {
+            network::wakeup();
+        }
+    }
+}
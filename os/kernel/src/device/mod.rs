@@ -7,6 +7,9 @@ pub mod tty;
 #[macro_use]
 pub mod serial;
 pub mod ide;
+pub mod ahci;
 pub mod pci;
 pub mod rtl8139;
+pub mod e1000;
+pub mod virtio;
 pub mod cpu;
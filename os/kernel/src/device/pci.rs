@@ -100,6 +100,10 @@ impl PciBus {
         &self.config_space
     }
 
+    pub fn devices(&self) -> Vec<&RwLock<EndpointHeader>> {
+        self.devices.iter().collect()
+    }
+
     pub fn search_by_ids(&self, vendor_id: u16, device_id: u16) -> Vec<&RwLock<EndpointHeader>> {
         self.devices
             .iter()
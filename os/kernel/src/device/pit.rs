@@ -2,7 +2,7 @@ use alloc::boxed::Box;
 use alloc::sync::Arc;
 use crate::interrupt::interrupt_dispatcher::InterruptVector;
 use crate::interrupt::interrupt_handler::InterruptHandler;
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use spin::Mutex;
 use x86_64::instructions::port::{Port, PortWriteOnly};
 use crate::{apic, interrupt_dispatcher};
@@ -54,6 +54,7 @@ pub struct Timer {
     registers: Mutex<Registers>,
     interval_ns: usize,
     systime_ns: AtomicUsize,
+    boot_date_ms: AtomicU64, // date at system time 0 in milliseconds since the Unix epoch (0 = unknown)
 }
 
 struct Registers {
@@ -116,7 +117,8 @@ impl Timer {
         let mut timer = Self {
             registers: Mutex::new(Registers::new()),
             interval_ns: 0,
-            systime_ns: AtomicUsize::new(0)
+            systime_ns: AtomicUsize::new(0),
+            boot_date_ms: AtomicU64::new(0),
         };
 
         timer.interrupt_rate(1);
@@ -164,6 +166,25 @@ impl Timer {
         self.systime_ns.load(Ordering::Relaxed) / 1000000
     }
 
+    pub fn systime_ns(&self) -> usize {
+        self.systime_ns.load(Ordering::Relaxed)
+    }
+
+    /// Return the current date (wall clock) in milliseconds since the Unix epoch or 0, if it has not been set.
+    /// The date is advanced together with the system time.
+    pub fn date_ms(&self) -> u64 {
+        match self.boot_date_ms.load(Ordering::Relaxed) {
+            0 => 0,
+            boot_date_ms => boot_date_ms + self.systime_ms() as u64,
+        }
+    }
+
+    /// Set the current date to `date_ms` milliseconds since the Unix epoch
+    /// (read from the RTC once during boot and changed by `sys_set_date`).
+    pub fn set_date_ms(&self, date_ms: u64) {
+        self.boot_date_ms.store(date_ms.saturating_sub(self.systime_ms() as u64).max(1), Ordering::Relaxed);
+    }
+
     pub fn wait(&self, wait_time_ms: usize) {
         let wait_time_ns = wait_time_ms * 1000000;
         let mut elapsed_time_ns = 0;
@@ -21,7 +21,7 @@ use x86_64::structures::paging::frame::PhysFrameRange;
 use x86_64::structures::paging::page::PageRange;
 use x86_64::{PhysAddr, VirtAddr};
  
-use crate::{apic, interrupt_dispatcher, pci_bus, process_manager, scheduler};
+use crate::{apic, interrupt_dispatcher, network, pci_bus, process_manager, scheduler};
 use crate::interrupt::interrupt_dispatcher::InterruptVector;
 use crate::interrupt::interrupt_handler::InterruptHandler;
 use crate::memory::{vmm, PAGE_SIZE};
@@ -350,6 +350,9 @@ impl InterruptHandler for Rtl8139InterruptHandler {
         if status.contains(Interrupt::RECEIVE_OK) {
             self.device.process_received_packet();
         }
+
+        // Received packets and freed buffers are handled by the network poll thread
+        network::wakeup();
     }
 }
 
@@ -11,6 +11,8 @@ use crate::scheduler;
 /// TTY-Input device (Workaround for missing pipes).
 /// Buffers input from the terminal when an application is reading.
 /// Depending on the chosen TerminalMode, the application will block until terminal has written some input.
+/// The mode is passed with each read and stored together with the state, so that a reader
+/// never gets input requested by a concurrent reader in another mode.
 ///
 /// NOTE: Just a Workaround. Parallel reads where not considered and might cause problems.
 ///
@@ -18,8 +20,7 @@ use crate::scheduler;
 #[derive(Debug)]
 pub struct TtyInput {
     buffer: Mutex<VecDeque<u8>>,
-    state: AtomicUsize,
-    mode: AtomicUsize,
+    state: AtomicUsize, // `TtyInputState` in the low byte, `TerminalMode` above
 }
 
 /// TTY-Output device (Workaround for missing pipes).
@@ -46,64 +47,81 @@ pub enum TtyInputState {
     Ready = 2,
 }
 
+/// Combine `state` and `mode` into the value stored in `TtyInput::state`
+const fn input_state(state: TtyInputState, mode: TerminalMode) -> usize {
+    (state as usize) | ((mode as usize) << 8)
+}
+
+const IDLE: usize = input_state(TtyInputState::Idle, TerminalMode::Canonical);
+
 impl TtyInput {
     pub const fn new() -> Self {
         TtyInput {
             buffer: Mutex::new(VecDeque::new()),
-            state: AtomicUsize::new(TtyInputState::Idle as usize),
-            mode: AtomicUsize::new(TerminalMode::Canonical as usize),
+            state: AtomicUsize::new(IDLE),
         }
     }
 
+    /// Read input in `mode` into `buffer`. \
+    /// If another reader is waiting for input in a different mode, this waits until that reader is done.
     pub fn read(&self, buffer: &mut [u8], mode: TerminalMode) -> usize {
-        self.state.store(TtyInputState::Waiting as usize, Ordering::SeqCst);
-        self.mode.store(mode.into(), Ordering::SeqCst);
+        let waiting = input_state(TtyInputState::Waiting, mode);
+        let ready = input_state(TtyInputState::Ready, mode);
+
+        loop {
+            // Input may already be there, if it has been requested by `poll` or another reader in the same mode
+            let _ = self.state.compare_exchange(IDLE, waiting, Ordering::SeqCst, Ordering::SeqCst);
+
+            if self.state.load(Ordering::SeqCst) == ready {
+                let mut input_buffer = self.buffer.lock();
+                if self.state.compare_exchange(ready, IDLE, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                    let mut count = 0;
+                    for byte in buffer {
+                        *byte = match input_buffer.pop_front() {
+                            Some(byte) => byte,
+                            None => break,
+                        };
+                        count += 1;
+                    }
+
+                    return count;
+                }
+            }
 
-        while self.state.load(Ordering::SeqCst) != (TtyInputState::Ready as usize) {
             scheduler().switch_thread_no_interrupt();
         }
+    }
 
-        let mut input_buffer = self.buffer.lock();
-        let mut count = 0;
-        for byte in buffer {
-            *byte = match input_buffer.pop_front() {
-                Some(byte) => byte,
-                None => break,
-            };
-            count += 1;
-        }
-
-        self.state.store(TtyInputState::Idle as usize, Ordering::SeqCst);
-
-        count
+    /// Check if input is available, so that `read` in canonical mode does not block.
+    /// Like `read`, this signals the terminal that an application is waiting for input.
+    pub fn poll(&self) -> bool {
+        let _ = self.state.compare_exchange(IDLE, input_state(TtyInputState::Waiting, TerminalMode::Canonical), Ordering::SeqCst, Ordering::SeqCst);
+        self.state.load(Ordering::SeqCst) == input_state(TtyInputState::Ready, TerminalMode::Canonical)
     }
 
     pub fn write(&self, bytes: &[u8], mode: TerminalMode) -> usize {
-        if self.state.load(Ordering::SeqCst) != (TtyInputState::Waiting as usize) {
-            return 0; // Abort, no more readers
-        }
-        if self.mode.load(Ordering::SeqCst) != mode as usize {
-            return 0; //Abort, mismatched mode
+        let mut input_buffer = self.buffer.lock();
+        if self.state.load(Ordering::SeqCst) != input_state(TtyInputState::Waiting, mode) {
+            return 0; // Abort, no more readers or mismatched mode
         }
 
-        let mut input_buffer = self.buffer.lock();
         let mut count = 0;
         for byte in bytes {
             input_buffer.push_back(*byte);
             count += 1;
         }
 
-        self.state.store(TtyInputState::Ready as usize, Ordering::SeqCst);
+        self.state.store(input_state(TtyInputState::Ready, mode), Ordering::SeqCst);
 
         count
     }
 
     pub fn state(&self) -> TtyInputState {
-        TtyInputState::from(self.state.load(Ordering::SeqCst))
+        TtyInputState::from(self.state.load(Ordering::SeqCst) & 0xff)
     }
 
     pub fn mode(&self) -> TerminalMode {
-        TerminalMode::from(self.mode.load(Ordering::SeqCst))
+        TerminalMode::from(self.state.load(Ordering::SeqCst) >> 8)
     }
 }
 
@@ -0,0 +1,253 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: blk                                                             ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Driver for virtio block devices. Requests are split into chunks of up   ║
+   ║ to 64 KiB, which are transferred via a bounce buffer. Each request is   ║
+   ║ a chain of header, data and status buffer in the request queue. The     ║
+   ║ buffers of a timed out request are only reused after the device has     ║
+   ║ returned it.                                                            ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ptr;
+use log::{error, info};
+use spin::Mutex;
+use x86_64::structures::paging::frame::PhysFrameRange;
+
+use crate::memory::vmm::alloc_dma_frames;
+use crate::memory::PAGE_SIZE;
+use crate::storage::add_block_device;
+use crate::storage::block::BlockDevice;
+use crate::{pci_bus, scheduler, timer};
+
+use super::queue::{Buffer, Virtqueue};
+use super::{log_device, VirtioPci, VIRTIO_VENDOR_ID};
+
+/// PCI device ids (transitional and modern)
+const DEVICE_IDS: [u16; 2] = [0x1001, 0x1042];
+
+const FEATURE_FLUSH: u64 = 1 << 9;
+
+const REQUEST_IN: u32 = 0;
+const REQUEST_OUT: u32 = 1;
+const REQUEST_FLUSH: u32 = 4;
+const STATUS_OK: u8 = 0;
+
+/// Sector size used in requests (independent of the block size of the device)
+const SECTOR_SIZE: usize = 512;
+const BUFFER_PAGES: usize = 16;
+const QUEUE_SIZE: u16 = 16;
+const REQUEST_TIMEOUT: usize = 30000;
+const CONFIG_CAPACITY: u64 = 0x00;
+
+/// Initialize all virtio block devices found on the PCI bus and register them in the storage module
+pub fn init() {
+    for device_id in DEVICE_IDS {
+        for pci_device in pci_bus().search_by_ids(VIRTIO_VENDOR_ID, device_id) {
+            log_device(pci_device, "block");
+            match VirtioBlk::new(VirtioPci::new(pci_device)) {
+                Some(blk) => {
+                    info!("Virtio block device has [{}] sectors (flush supported: {})", blk.sector_count, blk.flush_supported);
+                    add_block_device("vd", Arc::new(blk));
+                }
+                None => error!("Failed to initialize virtio block device"),
+            }
+        }
+    }
+}
+
+/// Header of a request (followed by the data and a status byte written by the device)
+#[repr(C)]
+struct RequestHeader {
+    typ: u32,
+    reserved: u32,
+    sector: u64,
+}
+
+/// Memory for the header, status and data of a request, owned by the device while the request is pending
+struct RequestBuffers {
+    header: PhysFrameRange, // Header at offset 0, status at offset 16
+    data: PhysFrameRange,
+}
+
+impl RequestBuffers {
+    fn new() -> Self {
+        Self { header: alloc_dma_frames(1), data: alloc_dma_frames(BUFFER_PAGES) }
+    }
+}
+
+/// Request queue with the buffers for requests
+struct RequestQueue {
+    queue: Virtqueue,
+    free: Vec<RequestBuffers>,
+    abandoned: BTreeMap<u16, RequestBuffers>, // buffers of timed out requests by chain id, until the device returns them
+}
+
+impl RequestQueue {
+    /// Take buffers for a new request (allocated, if all buffers are in use by the device)
+    fn take_buffers(&mut self) -> RequestBuffers {
+        self.free.pop().unwrap_or_else(RequestBuffers::new)
+    }
+
+    /// Handle the result of a request using `buffers`: `Err(id)` means, that the request with the chain `id` has timed out.
+    /// Then the buffers are kept, until the device returns the chain, otherwise they can be used again.
+    fn release_buffers(&mut self, buffers: RequestBuffers, result: Result<bool, u16>) -> bool {
+        match result {
+            Ok(success) => {
+                self.free.push(buffers);
+                success
+            }
+            Err(id) => {
+                self.abandoned.insert(id, buffers);
+                false
+            }
+        }
+    }
+}
+
+pub struct VirtioBlk {
+    sector_count: u64,
+    flush_supported: bool,
+    requests: Mutex<RequestQueue>,
+}
+
+impl VirtioBlk {
+    fn new(transport: Option<VirtioPci>) -> Option<Self> {
+        let transport = Arc::new(transport?);
+        let features = transport.negotiate_features(FEATURE_FLUSH)?;
+        let queue = transport.setup_queue(0, QUEUE_SIZE)?;
+        transport.driver_ok();
+        VirtioPci::plugin(Arc::clone(&transport), None);
+
+        Some(Self {
+            sector_count: transport.read_config(CONFIG_CAPACITY),
+            flush_supported: features & FEATURE_FLUSH != 0,
+            requests: Mutex::new(RequestQueue {
+                queue,
+                free: vec![RequestBuffers::new()],
+                abandoned: BTreeMap::new(),
+            }),
+        })
+    }
+
+    /// Helper function limiting `count` to the sectors of the device and the size of a buffer with `buffer_len` bytes
+    fn limit_count(&self, sector: u64, count: usize, buffer_len: usize) -> usize {
+        let available = self.sector_count.saturating_sub(sector);
+        count.min(available.min(usize::MAX as u64) as usize).min(buffer_len / SECTOR_SIZE)
+    }
+
+    /// Execute a single request with `bytes` of data in the bounce buffer of `buffers` and wait for its completion. \
+    /// Returns whether the request has been successful or `Err(id)`, if it has timed out
+    /// and the device still owns the chain `id` (and thus `buffers`).
+    fn request(&self, requests: &mut RequestQueue, buffers: &RequestBuffers, typ: u32, sector: u64, bytes: usize) -> Result<bool, u16> {
+        let header = buffers.header.start.start_address().as_u64();
+        let status = header + size_of::<RequestHeader>() as u64;
+        let buffer = buffers.data.start.start_address().as_u64();
+        unsafe {
+            ptr::write_volatile(header as *mut RequestHeader, RequestHeader { typ, reserved: 0, sector });
+            ptr::write_volatile(status as *mut u8, u8::MAX);
+        }
+
+        let data = match typ {
+            REQUEST_IN => Some(Buffer::writable(buffer, bytes)),
+            REQUEST_OUT => Some(Buffer::readable(buffer, bytes)),
+            _ => None,
+        };
+        let chain = [Some(Buffer::readable(header, size_of::<RequestHeader>())), data, Some(Buffer::writable(status, 1))];
+        let chain: Vec<Buffer> = chain.into_iter().flatten().collect();
+
+        let Some(id) = requests.queue.add(&chain) else {
+            return Ok(false);
+        };
+        requests.queue.notify();
+
+        // Wait until the device has returned the request (the interrupt is acknowledged by the transport)
+        let end_time = timer().systime_ms() + REQUEST_TIMEOUT;
+        loop {
+            match requests.queue.pop_used() {
+                Some((used_id, _)) if used_id == id => break,
+                // a timed out request has been returned late, its buffers can be used again
+                Some((used_id, _)) => {
+                    if let Some(buffers) = requests.abandoned.remove(&used_id) {
+                        requests.free.push(buffers);
+                    }
+                }
+                None if timer().systime_ms() >= end_time => {
+                    error!("Virtio block request timed out");
+                    return Err(id);
+                }
+                None => scheduler().switch_thread_no_interrupt(),
+            }
+        }
+
+        Ok(unsafe { ptr::read_volatile(status as *const u8) == STATUS_OK })
+    }
+
+    /// Transfer `count` sectors starting at `sector` in chunks fitting into the bounce buffer
+    fn transfer(&self, typ: u32, sector: u64, count: usize, buffer: *mut u8) -> usize {
+        let sectors_per_request = BUFFER_PAGES * PAGE_SIZE / SECTOR_SIZE;
+        let mut requests = self.requests.lock();
+
+        let mut transferred = 0;
+        while transferred < count {
+            let sectors = sectors_per_request.min(count - transferred);
+            let bytes = sectors * SECTOR_SIZE;
+            let data = unsafe { buffer.add(transferred * SECTOR_SIZE) };
+            let buffers = requests.take_buffers();
+            let bounce = buffers.data.start.start_address().as_u64() as *mut u8;
+            if typ == REQUEST_OUT {
+                unsafe { ptr::copy_nonoverlapping(data, bounce, bytes) };
+            }
+
+            let result = self.request(&mut requests, &buffers, typ, sector + transferred as u64, bytes);
+            if result == Ok(true) && typ == REQUEST_IN {
+                unsafe { ptr::copy_nonoverlapping(bounce, data, bytes) };
+            }
+            if !requests.release_buffers(buffers, result) {
+                error!("Failed to {} sectors on virtio block device", if typ == REQUEST_OUT { "write" } else { "read" });
+                break;
+            }
+            transferred += sectors;
+        }
+
+        transferred
+    }
+}
+
+impl BlockDevice for VirtioBlk {
+    fn read(&self, sector: u64, count: usize, buffer: &mut [u8]) -> usize {
+        let count = self.limit_count(sector, count, buffer.len());
+        self.transfer(REQUEST_IN, sector, count, buffer.as_mut_ptr())
+    }
+
+    fn write(&self, sector: u64, count: usize, buffer: &[u8]) -> usize {
+        // The buffer is only read by VirtioBlk::transfer() for write requests
+        let count = self.limit_count(sector, count, buffer.len());
+        self.transfer(REQUEST_OUT, sector, count, buffer.as_ptr().cast_mut())
+    }
+
+    fn sector_count(&self) -> u64 {
+        self.sector_count
+    }
+
+    fn sector_size(&self) -> u16 {
+        SECTOR_SIZE as u16
+    }
+
+    fn flush(&self) -> bool {
+        if !self.flush_supported {
+            return true;
+        }
+
+        let mut requests = self.requests.lock();
+        let buffers = requests.take_buffers();
+        let result = self.request(&mut requests, &buffers, REQUEST_FLUSH, 0, 0);
+        requests.release_buffers(buffers, result)
+    }
+}
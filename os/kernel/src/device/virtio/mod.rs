@@ -0,0 +1,277 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: virtio                                                          ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Transport for virtio devices on the PCI bus (virtio 1.x, 'modern'       ║
+   ║ interface). The configuration structures are located via the vendor     ║
+   ║ specific PCI capabilities and accessed via MMIO. Drivers:               ║
+   ║   - blk   block device (registered as 'vd<n>')                          ║
+   ║   - net   network device (used by the network module)                   ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+pub mod blk;
+pub mod net;
+pub mod queue;
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::ops::BitOr;
+use core::ptr;
+use log::{info, warn};
+use pci_types::capability::PciCapability;
+use pci_types::{CommandRegister, ConfigRegionAccess, EndpointHeader};
+use spin::RwLock;
+use x86_64::structures::paging::PageTableFlags;
+
+use crate::interrupt::interrupt_dispatcher::InterruptVector;
+use crate::interrupt::interrupt_handler::InterruptHandler;
+use crate::memory::vma::VmaType;
+use crate::memory::PAGE_SIZE;
+use crate::{apic, interrupt_dispatcher, pci_bus, process_manager};
+
+use queue::Virtqueue;
+
+pub const VIRTIO_VENDOR_ID: u16 = 0x1af4;
+
+/// Feature bit, which must be accepted by drivers of the modern interface
+const FEATURE_VERSION_1: u64 = 1 << 32;
+
+/// Vendor specific capability types
+const CAP_COMMON_CFG: u8 = 1;
+const CAP_NOTIFY_CFG: u8 = 2;
+const CAP_ISR_CFG: u8 = 3;
+const CAP_DEVICE_CFG: u8 = 4;
+
+/// Offsets in the common configuration structure
+const COMMON_DEVICE_FEATURE_SELECT: u64 = 0x00;
+const COMMON_DEVICE_FEATURE: u64 = 0x04;
+const COMMON_DRIVER_FEATURE_SELECT: u64 = 0x08;
+const COMMON_DRIVER_FEATURE: u64 = 0x0c;
+const COMMON_NUM_QUEUES: u64 = 0x12;
+const COMMON_DEVICE_STATUS: u64 = 0x14;
+const COMMON_QUEUE_SELECT: u64 = 0x16;
+const COMMON_QUEUE_SIZE: u64 = 0x18;
+const COMMON_QUEUE_ENABLE: u64 = 0x1c;
+const COMMON_QUEUE_NOTIFY_OFF: u64 = 0x1e;
+const COMMON_QUEUE_DESC: u64 = 0x20;
+const COMMON_QUEUE_DRIVER: u64 = 0x28;
+const COMMON_QUEUE_DEVICE: u64 = 0x30;
+
+/// Bits of the device status
+const STATUS_ACKNOWLEDGE: u8 = 1;
+const STATUS_DRIVER: u8 = 2;
+const STATUS_DRIVER_OK: u8 = 4;
+const STATUS_FEATURES_OK: u8 = 8;
+const STATUS_FAILED: u8 = 128;
+
+/// PCI transport of a virtio device (addresses of the mapped configuration structures)
+pub struct VirtioPci {
+    common: u64,
+    notify: u64,
+    notify_multiplier: u32,
+    isr: u64,
+    device: u64,
+    interrupt: Option<InterruptVector>,
+}
+
+impl VirtioPci {
+    /// Locate and map the configuration structures of `pci_device` and reset the device.
+    /// Returns None, if the device does not provide the modern interface.
+    pub fn new(pci_device: &RwLock<EndpointHeader>) -> Option<Self> {
+        let pci_config_space = pci_bus().config_space();
+        let mut pci_device = pci_device.write();
+
+        pci_device.update_command(pci_config_space, |command| {
+            command.bitor(CommandRegister::BUS_MASTER_ENABLE | CommandRegister::MEMORY_ENABLE).difference(CommandRegister::INTERRUPT_DISABLE)
+        });
+
+        let mut bars: [Option<u64>; 6] = [None; 6];
+        let (mut common, mut notify, mut notify_multiplier, mut isr, mut device) = (None, None, 0, None, None);
+        for capability in pci_device.capabilities(pci_config_space) {
+            let PciCapability::Vendor(address) = capability else {
+                continue;
+            };
+
+            let read = |offset: u16| unsafe { pci_config_space.read(address.address, address.offset + offset) };
+            let typ = (read(0) >> 24) as u8;
+            let bar = (read(4) & 0xff) as u8;
+            let offset = read(8) as u64;
+            if bar > 5 || !(CAP_COMMON_CFG..=CAP_DEVICE_CFG).contains(&typ) {
+                continue;
+            }
+
+            // Map each referenced BAR once (it usually contains all structures)
+            let base = match bars[bar as usize] {
+                Some(base) => base,
+                None => {
+                    let (start, size) = pci_device.bar(bar, pci_config_space)?.unwrap_mem();
+                    let kernel_process = process_manager().read().kernel_process().unwrap();
+                    let page = kernel_process.virtual_address_space.kernel_map_devm_identity(
+                        start as u64,
+                        (start + size.max(PAGE_SIZE)) as u64,
+                        PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_CACHE,
+                        VmaType::DeviceMemory,
+                        "virtio",
+                    );
+                    bars[bar as usize] = Some(page.start_address().as_u64());
+                    page.start_address().as_u64()
+                }
+            };
+
+            match typ {
+                CAP_COMMON_CFG => common = common.or(Some(base + offset)),
+                CAP_NOTIFY_CFG if notify.is_none() => {
+                    notify = Some(base + offset);
+                    notify_multiplier = read(16);
+                }
+                CAP_ISR_CFG => isr = isr.or(Some(base + offset)),
+                CAP_DEVICE_CFG => device = device.or(Some(base + offset)),
+                _ => {}
+            }
+        }
+
+        let interrupt = InterruptVector::try_from(pci_device.interrupt(pci_config_space).1 + 32).ok();
+        let transport = Self {
+            common: common?,
+            notify: notify?,
+            notify_multiplier,
+            isr: isr?,
+            device: device.unwrap_or(0),
+            interrupt,
+        };
+
+        transport.set_status(0);
+        transport.set_status(STATUS_ACKNOWLEDGE | STATUS_DRIVER);
+        Some(transport)
+    }
+
+    fn read_common<T>(&self, offset: u64) -> T {
+        unsafe { ptr::read_volatile((self.common + offset) as *const T) }
+    }
+
+    fn write_common<T>(&self, offset: u64, value: T) {
+        unsafe { ptr::write_volatile((self.common + offset) as *mut T, value) }
+    }
+
+    /// 64-bit fields are written as two 32-bit values (low part first)
+    fn write_common_u64(&self, offset: u64, value: u64) {
+        self.write_common(offset, value as u32);
+        self.write_common(offset + 4, (value >> 32) as u32);
+    }
+
+    fn set_status(&self, status: u8) {
+        self.write_common(COMMON_DEVICE_STATUS, status);
+    }
+
+    fn status(&self) -> u8 {
+        self.read_common(COMMON_DEVICE_STATUS)
+    }
+
+    /// Accept the features in `wanted`, which are offered by the device (plus `VERSION_1`).
+    /// Returns the accepted features, or None if the device does not accept them.
+    pub fn negotiate_features(&self, wanted: u64) -> Option<u64> {
+        let mut offered = 0;
+        for select in 0..2u32 {
+            self.write_common(COMMON_DEVICE_FEATURE_SELECT, select);
+            offered |= (self.read_common::<u32>(COMMON_DEVICE_FEATURE) as u64) << (32 * select);
+        }
+
+        if offered & FEATURE_VERSION_1 == 0 {
+            warn!("Virtio device does not support the modern interface");
+            self.set_status(STATUS_FAILED);
+            return None;
+        }
+
+        let accepted = offered & (wanted | FEATURE_VERSION_1);
+        for select in 0..2u32 {
+            self.write_common(COMMON_DRIVER_FEATURE_SELECT, select);
+            self.write_common(COMMON_DRIVER_FEATURE, (accepted >> (32 * select)) as u32);
+        }
+
+        self.set_status(self.status() | STATUS_FEATURES_OK);
+        if self.status() & STATUS_FEATURES_OK == 0 {
+            warn!("Virtio device did not accept features [0x{accepted:x}]");
+            self.set_status(STATUS_FAILED);
+            return None;
+        }
+
+        Some(accepted)
+    }
+
+    /// Create and enable the queue `index` with up to `max_size` entries
+    pub fn setup_queue(&self, index: u16, max_size: u16) -> Option<Virtqueue> {
+        if index >= self.read_common::<u16>(COMMON_NUM_QUEUES) {
+            return None;
+        }
+
+        self.write_common(COMMON_QUEUE_SELECT, index);
+        let device_size = self.read_common::<u16>(COMMON_QUEUE_SIZE);
+        if device_size == 0 {
+            return None;
+        }
+
+        // The queue size must be a power of 2
+        let size = 1 << device_size.min(max_size).ilog2();
+        let mut queue = Virtqueue::new(index, size);
+        self.write_common(COMMON_QUEUE_SIZE, size);
+        self.write_common_u64(COMMON_QUEUE_DESC, queue.descriptor_address());
+        self.write_common_u64(COMMON_QUEUE_DRIVER, queue.avail_address());
+        self.write_common_u64(COMMON_QUEUE_DEVICE, queue.used_address());
+
+        let notify_offset = self.read_common::<u16>(COMMON_QUEUE_NOTIFY_OFF);
+        queue.set_notify_address(self.notify + notify_offset as u64 * self.notify_multiplier as u64);
+        self.write_common(COMMON_QUEUE_ENABLE, 1u16);
+
+        Some(queue)
+    }
+
+    /// Finish initialization (queues must be set up before)
+    pub fn driver_ok(&self) {
+        self.set_status(self.status() | STATUS_DRIVER_OK);
+    }
+
+    /// Read from the device specific configuration structure
+    pub fn read_config<T>(&self, offset: u64) -> T {
+        unsafe { ptr::read_volatile((self.device + offset) as *const T) }
+    }
+
+    /// Register an interrupt handler, which acknowledges the interrupts of this device and calls `on_interrupt`
+    pub fn plugin(transport: Arc<VirtioPci>, on_interrupt: Option<fn()>) {
+        let Some(interrupt) = transport.interrupt else {
+            return;
+        };
+
+        interrupt_dispatcher().assign(interrupt, Box::new(VirtioInterruptHandler::new(transport, on_interrupt)));
+        apic().allow(interrupt);
+    }
+}
+
+/// Reading the ISR status acknowledges the (legacy) interrupt of the device.
+/// Completed requests are fetched from the used rings by the drivers (`on_interrupt` may wake them up).
+pub struct VirtioInterruptHandler {
+    transport: Arc<VirtioPci>,
+    on_interrupt: Option<fn()>,
+}
+
+impl VirtioInterruptHandler {
+    fn new(transport: Arc<VirtioPci>, on_interrupt: Option<fn()>) -> Self {
+        Self { transport, on_interrupt }
+    }
+}
+
+impl InterruptHandler for VirtioInterruptHandler {
+    fn trigger(&self) {
+        let status = unsafe { ptr::read_volatile(self.transport.isr as *const u8) };
+        if status != 0 && let Some(on_interrupt) = self.on_interrupt {
+            on_interrupt();
+        }
+    }
+}
+
+/// Log a found device
+fn log_device(pci_device: &RwLock<EndpointHeader>, name: &str) {
+    let device_id = pci_device.read().header().id(pci_bus().config_space());
+    info!("Found virtio {} device [{}:{}]", name, device_id.0, device_id.1);
+}
@@ -0,0 +1,212 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: net                                                             ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Driver for virtio network devices, implementing the smoltcp 'Device'    ║
+   ║ trait. Each packet uses a page, starting with the virtio net header.    ║
+   ║ All receive buffers are posted to the receive queue at initialization   ║
+   ║ and given back to the device after smoltcp has consumed the packet.     ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{ptr, slice};
+use log::info;
+use pci_types::EndpointHeader;
+use smoltcp::phy;
+use smoltcp::phy::{DeviceCapabilities, Medium};
+use smoltcp::time::Instant;
+use smoltcp::wire::EthernetAddress;
+use spin::{Mutex, RwLock};
+
+use crate::memory::vmm::alloc_dma_frames;
+use crate::memory::PAGE_SIZE;
+use crate::{network, pci_bus, scheduler};
+
+use super::queue::{Buffer, Virtqueue};
+use super::{log_device, VirtioPci, VIRTIO_VENDOR_ID};
+
+/// PCI device ids (transitional and modern)
+const DEVICE_IDS: [u16; 2] = [0x1000, 0x1041];
+
+const FEATURE_MAC: u64 = 1 << 5;
+
+const RECEIVE_QUEUE: u16 = 0;
+const TRANSMIT_QUEUE: u16 = 1;
+const QUEUE_SIZE: u16 = 64;
+
+/// Size of the header preceding each packet (with VERSION_1, it always includes the number of merged buffers)
+const HEADER_SIZE: usize = 12;
+const MTU: usize = 1514;
+const CONFIG_MAC: u64 = 0x00;
+
+/// Search the PCI bus for all virtio network devices
+pub fn find_devices() -> Vec<&'static RwLock<EndpointHeader>> {
+    DEVICE_IDS.iter().flat_map(|device_id| pci_bus().search_by_ids(VIRTIO_VENDOR_ID, *device_id)).collect()
+}
+
+/// Queue with a page for each descriptor, holding the packet of the chain starting at this descriptor
+struct PacketQueue {
+    queue: Virtqueue,
+    pages: Vec<u64>,      // Unused pages
+    in_flight: Vec<u64>,  // Page given to the device, indexed by descriptor id
+}
+
+impl PacketQueue {
+    fn new(queue: Virtqueue) -> Self {
+        let size = queue.size() as usize;
+        let frames = alloc_dma_frames(size);
+        let pages = (0..size).map(|i| frames.start.start_address().as_u64() + (i * PAGE_SIZE) as u64).collect();
+        Self { queue, pages, in_flight: vec![0; size] }
+    }
+
+    /// Give `page` with the buffer `buffer` to the device
+    fn add(&mut self, page: u64, buffer: Buffer) {
+        let id = self.queue.add(&[buffer]).expect("Virtio packet queue is full!");
+        self.in_flight[id as usize] = page;
+    }
+
+    /// Get a page returned by the device and the number of bytes written by the device
+    fn pop_used(&mut self) -> Option<(u64, usize)> {
+        self.queue.pop_used().map(|(id, length)| (self.in_flight[id as usize], length as usize))
+    }
+}
+
+pub struct VirtioNet {
+    mac: EthernetAddress,
+    receive: Mutex<PacketQueue>,
+    transmit: Mutex<PacketQueue>,
+}
+
+impl VirtioNet {
+    pub fn new(pci_device: &RwLock<EndpointHeader>) -> Self {
+        log_device(pci_device, "network");
+        let transport = Arc::new(VirtioPci::new(pci_device).expect("Virtio network device does not support the modern interface!"));
+        let features = transport.negotiate_features(FEATURE_MAC).expect("Failed to negotiate virtio network features!");
+
+        let mut receive = PacketQueue::new(transport.setup_queue(RECEIVE_QUEUE, QUEUE_SIZE).expect("Failed to set up receive queue!"));
+        let transmit = PacketQueue::new(transport.setup_queue(TRANSMIT_QUEUE, QUEUE_SIZE).expect("Failed to set up transmit queue!"));
+
+        // Post all receive buffers before the device is started
+        while let Some(page) = receive.pages.pop() {
+            receive.add(page, Buffer::writable(page, PAGE_SIZE));
+        }
+
+        transport.driver_ok();
+        receive.queue.notify();
+        VirtioPci::plugin(Arc::clone(&transport), Some(network::wakeup));
+
+        let mac = if features & FEATURE_MAC != 0 {
+            let bytes: [u8; 6] = core::array::from_fn(|i| transport.read_config(CONFIG_MAC + i as u64));
+            EthernetAddress::from_bytes(&bytes)
+        } else {
+            EthernetAddress::from_bytes(&[0x52, 0x54, 0x00, 0x12, 0x34, 0x56])
+        };
+        info!("Virtio network device MAC address: [{}]", mac);
+
+        Self {
+            mac,
+            receive: Mutex::new(receive),
+            transmit: Mutex::new(transmit),
+        }
+    }
+
+    pub fn read_mac_address(&self) -> EthernetAddress {
+        self.mac
+    }
+
+    /// Reclaim the pages of sent packets
+    fn reclaim_transmitted(transmit: &mut PacketQueue) {
+        while let Some((page, _)) = transmit.pop_used() {
+            transmit.pages.push(page);
+        }
+    }
+}
+
+pub struct VirtioNetTxToken<'a> {
+    device: &'a VirtioNet,
+}
+
+pub struct VirtioNetRxToken<'a> {
+    page: u64,
+    length: usize,
+    device: &'a VirtioNet,
+}
+
+impl<'a> phy::TxToken for VirtioNetTxToken<'a> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where F: FnOnce(&mut [u8]) -> R {
+        if len > PAGE_SIZE - HEADER_SIZE {
+            panic!("Packet length may not exceed page size!");
+        }
+
+        // Wait for a free page (the device returns sent packets quickly)
+        let mut transmit = self.device.transmit.lock();
+        let page = loop {
+            VirtioNet::reclaim_transmitted(&mut transmit);
+            if let Some(page) = transmit.pages.pop() {
+                break page;
+            }
+            scheduler().switch_thread_no_interrupt();
+        };
+
+        // The header is all zero (no checksum offloading and segmentation)
+        unsafe { ptr::write_bytes(page as *mut u8, 0, HEADER_SIZE) };
+        let buffer = unsafe { slice::from_raw_parts_mut((page as usize + HEADER_SIZE) as *mut u8, len) };
+        let result = f(buffer);
+
+        transmit.add(page, Buffer::readable(page, HEADER_SIZE + len));
+        transmit.queue.notify();
+
+        result
+    }
+}
+
+impl<'a> phy::RxToken for VirtioNetRxToken<'a> {
+    fn consume<R, F>(self, f: F) -> R
+    where F: FnOnce(&[u8]) -> R {
+        let packet = unsafe { slice::from_raw_parts((self.page as usize + HEADER_SIZE) as *const u8, self.length.saturating_sub(HEADER_SIZE)) };
+        let result = f(packet);
+
+        // Give the page back to the device for the next packet
+        let mut receive = self.device.receive.lock();
+        receive.add(self.page, Buffer::writable(self.page, PAGE_SIZE));
+        receive.queue.notify();
+
+        result
+    }
+}
+
+impl phy::Device for VirtioNet {
+    type RxToken<'a> = VirtioNetRxToken<'a> where Self: 'a;
+    type TxToken<'a> = VirtioNetTxToken<'a> where Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let device = unsafe { ptr::from_ref(self).as_ref()? };
+        let (page, length) = self.receive.lock().pop_used()?;
+        Some((VirtioNetRxToken { page, length, device }, VirtioNetTxToken { device }))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        let device = unsafe { ptr::from_ref(self).as_ref()? };
+        let mut transmit = self.transmit.lock();
+        VirtioNet::reclaim_transmitted(&mut transmit);
+        if transmit.pages.is_empty() {
+            return None;
+        }
+
+        Some(VirtioNetTxToken { device })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.max_transmission_unit = MTU;
+        caps.max_burst_size = Some(QUEUE_SIZE as usize);
+        caps.medium = Medium::Ethernet;
+
+        caps
+    }
+}
@@ -0,0 +1,207 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: queue                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Split virtqueue as defined in the virtio 1.x specification. Buffers are ║
+   ║ given to the device as descriptor chains via the available ring and     ║
+   ║ returned by the device via the used ring. All memory is physically      ║
+   ║ contiguous, uncached and identity mapped, so addresses are physical.    ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use core::ptr;
+use core::sync::atomic::{fence, Ordering};
+use x86_64::structures::paging::frame::PhysFrameRange;
+
+use crate::memory::vmm::alloc_dma_frames;
+use crate::memory::PAGE_SIZE;
+
+const DESC_F_NEXT: u16 = 1;
+const DESC_F_WRITE: u16 = 2;
+
+/// Entry of the descriptor table
+#[repr(C)]
+struct Descriptor {
+    address: u64,
+    length: u32,
+    flags: u16,
+    next: u16,
+}
+
+/// A buffer passed to the device (`writable` buffers are filled by the device)
+#[derive(Debug, Clone, Copy)]
+pub struct Buffer {
+    pub address: u64,
+    pub length: u32,
+    pub writable: bool,
+}
+
+impl Buffer {
+    pub fn readable(address: u64, length: usize) -> Self {
+        Self { address, length: length as u32, writable: false }
+    }
+
+    pub fn writable(address: u64, length: usize) -> Self {
+        Self { address, length: length as u32, writable: true }
+    }
+}
+
+pub struct Virtqueue {
+    index: u16,
+    size: u16,
+    memory: PhysFrameRange,
+    free_head: u16,     // First descriptor of the free list (linked via `next`)
+    free_count: u16,
+    avail_index: u16,   // Next index to be written in the available ring
+    last_used: u16,     // Next index to be read from the used ring
+    notify_address: u64,
+}
+
+impl Virtqueue {
+    /// Allocate the descriptor table, the available ring and the used ring (on its own page) for `size` entries
+    pub(super) fn new(index: u16, size: u16) -> Self {
+        let memory = alloc_dma_frames(Self::used_offset(size) / PAGE_SIZE + Self::used_size(size).div_ceil(PAGE_SIZE));
+
+        let queue = Self {
+            index,
+            size,
+            memory,
+            free_head: 0,
+            free_count: size,
+            avail_index: 0,
+            last_used: 0,
+            notify_address: 0,
+        };
+
+        // Link all descriptors into the free list
+        for i in 0..size {
+            unsafe { (*queue.descriptor(i)).next = (i + 1) % size };
+        }
+
+        queue
+    }
+
+    fn avail_offset(size: u16) -> usize {
+        size as usize * size_of::<Descriptor>()
+    }
+
+    fn used_offset(size: u16) -> usize {
+        (Self::avail_offset(size) + 6 + 2 * size as usize).next_multiple_of(PAGE_SIZE)
+    }
+
+    fn used_size(size: u16) -> usize {
+        6 + 8 * size as usize
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    pub fn free_count(&self) -> u16 {
+        self.free_count
+    }
+
+    pub(super) fn descriptor_address(&self) -> u64 {
+        self.memory.start.start_address().as_u64()
+    }
+
+    pub(super) fn avail_address(&self) -> u64 {
+        self.descriptor_address() + Self::avail_offset(self.size) as u64
+    }
+
+    pub(super) fn used_address(&self) -> u64 {
+        self.descriptor_address() + Self::used_offset(self.size) as u64
+    }
+
+    pub(super) fn set_notify_address(&mut self, address: u64) {
+        self.notify_address = address;
+    }
+
+    fn descriptor(&self, index: u16) -> *mut Descriptor {
+        (self.descriptor_address() as *mut Descriptor).wrapping_add(index as usize)
+    }
+
+    /// Put a chain of `buffers` into the available ring.
+    /// Returns the id of the chain (its first descriptor), or None if there are not enough free descriptors.
+    pub fn add(&mut self, buffers: &[Buffer]) -> Option<u16> {
+        if buffers.is_empty() || buffers.len() > self.free_count as usize {
+            return None;
+        }
+
+        let head = self.free_head;
+        let mut current = head;
+        for (i, buffer) in buffers.iter().enumerate() {
+            let descriptor = self.descriptor(current);
+            let next = unsafe { (*descriptor).next };
+            let mut flags = if buffer.writable { DESC_F_WRITE } else { 0 };
+            if i + 1 < buffers.len() {
+                flags |= DESC_F_NEXT;
+            }
+
+            unsafe { ptr::write_volatile(descriptor, Descriptor { address: buffer.address, length: buffer.length, flags, next }) };
+            if i + 1 < buffers.len() {
+                current = next;
+            } else {
+                self.free_head = next;
+            }
+        }
+        self.free_count -= buffers.len() as u16;
+
+        // Publish the chain (the ring entry must be visible before the index is updated)
+        let avail = self.avail_address();
+        unsafe {
+            let slot = (avail + 4 + 2 * (self.avail_index % self.size) as u64) as *mut u16;
+            ptr::write_volatile(slot, head);
+            fence(Ordering::SeqCst);
+            self.avail_index = self.avail_index.wrapping_add(1);
+            ptr::write_volatile((avail + 2) as *mut u16, self.avail_index);
+            fence(Ordering::SeqCst);
+        }
+
+        Some(head)
+    }
+
+    /// Check if the device has returned chains, which have not been fetched with `pop_used` yet
+    pub fn has_used(&self) -> bool {
+        let used_index = unsafe { ptr::read_volatile((self.used_address() + 2) as *const u16) };
+        used_index != self.last_used
+    }
+
+    /// Fetch the next chain returned by the device and free its descriptors.
+    /// Returns the id of the chain and the number of bytes written by the device.
+    pub fn pop_used(&mut self) -> Option<(u16, u32)> {
+        if !self.has_used() {
+            return None;
+        }
+        fence(Ordering::SeqCst);
+
+        let element = self.used_address() + 4 + 8 * (self.last_used % self.size) as u64;
+        let (id, length) = unsafe { (ptr::read_volatile(element as *const u32) as u16, ptr::read_volatile((element + 4) as *const u32)) };
+        self.last_used = self.last_used.wrapping_add(1);
+
+        // Return the chain to the free list
+        let mut current = id;
+        loop {
+            self.free_count += 1;
+            let descriptor = unsafe { &*self.descriptor(current) };
+            if descriptor.flags & DESC_F_NEXT == 0 {
+                break;
+            }
+            current = descriptor.next;
+        }
+        unsafe { (*self.descriptor(current)).next = self.free_head };
+        self.free_head = id;
+
+        Some((id, length))
+    }
+
+    /// Tell the device, that new chains are available
+    pub fn notify(&self) {
+        unsafe { ptr::write_volatile(self.notify_address as *mut u16, self.index) };
+    }
+}
@@ -7,12 +7,13 @@ use alloc::vec::Vec;
 use core::ops::Deref;
 use core::ptr;
 use log::{error, info, trace};
-use spin::Mutex;
+use spin::RwLock;
+use x86_64::instructions::interrupts;
 use x86_64::registers::control::Cr2;
-use x86_64::set_general_handler;
 use x86_64::structures::idt::InterruptStackFrame;
 use x86_64::structures::paging::page::PageRange;
 use x86_64::structures::paging::{Page, PageTableFlags};
+use x86_64::{set_general_handler, PrivilegeLevel, VirtAddr};
 
 #[repr(u8)]
 #[derive(PartialEq, PartialOrd, Copy, Clone, Debug)]
@@ -63,7 +64,9 @@ pub enum InterruptVector {
     SecondaryAta = 0x2f,
     // Possibly some other interrupts supported by IO APICs
 
-    // Local APIC interrupts (247 - 254)
+    // Local APIC interrupts (246 - 254)
+    TlbShootdown = 0xf6,
+    Reschedule = 0xf7,
     Cmci = 0xf8,
     ApicTimer = 0xf9,
     Thermal = 0xfa,
@@ -122,6 +125,8 @@ impl TryFrom<u8> for InterruptVector {
             value if value == InterruptVector::PrimaryAta as u8 => Ok(InterruptVector::PrimaryAta),
             value if value == InterruptVector::SecondaryAta as u8 => Ok(InterruptVector::SecondaryAta),
 
+            value if value == InterruptVector::TlbShootdown as u8 => Ok(InterruptVector::TlbShootdown),
+            value if value == InterruptVector::Reschedule as u8 => Ok(InterruptVector::Reschedule),
             value if value == InterruptVector::Cmci as u8 => Ok(InterruptVector::Cmci),
             value if value == InterruptVector::ApicTimer as u8 => Ok(InterruptVector::ApicTimer),
             value if value == InterruptVector::Thermal as u8 => Ok(InterruptVector::Thermal),
@@ -137,8 +142,13 @@ impl TryFrom<u8> for InterruptVector {
 
 const MAX_VECTORS: usize = 256;
 
+/// Exit codes of processes terminated because of a CPU exception (128 + number of the corresponding POSIX signal)
+const SIGILL_EXIT_CODE: usize = 132;
+const SIGFPE_EXIT_CODE: usize = 136;
+const SIGSEGV_EXIT_CODE: usize = 139;
+
 pub struct InterruptDispatcher {
-    int_vectors: Vec<Mutex<Vec<Box<dyn InterruptHandler>>>>,
+    int_vectors: Vec<RwLock<Vec<Box<dyn InterruptHandler>>>>,
 }
 
 unsafe impl Send for InterruptDispatcher {}
@@ -152,6 +162,14 @@ pub fn setup_idt() {
     set_general_handler!(&mut idt, handle_exception, 0..31);
     set_general_handler!(&mut idt, handle_interrupt, 32..255);
     set_general_handler!(&mut idt, handle_page_fault, 14);
+    drop(idt);
+
+    load_idt();
+}
+
+/// Load the IDT on the calling core (all cores share the IDT, which is set up by `setup_idt()`)
+pub fn load_idt() {
+    let idt = idt().lock();
 
     unsafe {
         // We need to obtain a static reference to the IDT for the following operation.
@@ -163,6 +181,10 @@ pub fn setup_idt() {
 }
 
 fn handle_exception(frame: InterruptStackFrame, index: u8, error: Option<u64>) {
+    if caused_by_application(&frame, index) {
+        kill_faulting_process(&frame, index, error, frame.instruction_pointer);
+    }
+
     panic!(
         "CPU Exception: [{} - {:?}]\nError code: [{:?}]\n{:?}",
         index,
@@ -211,19 +233,64 @@ fn handle_page_fault(frame: InterruptStackFrame, _index: u8, error: Option<u64>)
         }
     }
 
+    // Page fault caused by an application and not resolved, terminate its process
+    if caused_by_application(&frame, InterruptVector::PageFault as u8) {
+        kill_faulting_process(&frame, InterruptVector::PageFault as u8, error, fault_addr);
+    }
+
     // Page fault not resolved, panic
     panic!("Page Fault!\nError code: [{:?}]\nAddress: [0x{:0>16x}]\n{:?}", error, fault_addr, frame);
 }
 
+/// Check if the exception `index` has been raised by an application running in ring 3
+/// (non-maskable interrupts, double faults and machine checks are never caused by an application)
+fn caused_by_application(frame: &InterruptStackFrame, index: u8) -> bool {
+    let vector = InterruptVector::try_from(index);
+    frame.code_segment.rpl() == PrivilegeLevel::Ring3
+        && !matches!(vector, Ok(InterruptVector::NonMaskableInterrupt | InterruptVector::DoubleFault | InterruptVector::MachineCheck))
+}
+
+/// Terminate the process of the current thread, which has caused the exception `index` in ring 3. \
+/// A crash report is logged and the exit code of the process is set like in other systems on a fatal signal.
+/// The rest of the system keeps running.
+fn kill_faulting_process(frame: &InterruptStackFrame, index: u8, error: Option<u64>, fault_addr: VirtAddr) -> ! {
+    let thread = scheduler().current_thread();
+    let process = thread.process();
+    let vector = InterruptVector::try_from(index);
+
+    error!("Process [{}] crashed in thread [{}]: CPU Exception [{} - {:?}]", process.id(), thread.id(), index, vector.unwrap());
+    error!("Error code: [{:?}], RIP: [0x{:0>16x}], RSP: [0x{:0>16x}]", error, frame.instruction_pointer, frame.stack_pointer);
+    match process.virtual_address_space.vmas().iter().find(|vma| vma.start() <= fault_addr && fault_addr < vma.end()) {
+        Some(vma) => error!("Address: [0x{:0>16x}] in {:?}", fault_addr, vma),
+        None => error!("Address: [0x{:0>16x}] (outside of all VMAs)", fault_addr),
+    }
+
+    let exit_code = match vector {
+        Ok(InterruptVector::DivisionByZero | InterruptVector::X87FloatingPointException | InterruptVector::SimdFloatingPointException) => SIGFPE_EXIT_CODE,
+        Ok(InterruptVector::InvalidOpcode) => SIGILL_EXIT_CODE,
+        _ => SIGSEGV_EXIT_CODE,
+    };
+
+    // Other threads may hold locks needed for terminating the process (e.g. of the process manager),
+    // which they can only release, if the scheduler is able to switch to them
+    interrupts::enable();
+    process.exit(exit_code);
+
+    // Decrease Rc manually, because exit() does not return
+    drop(process);
+    drop(thread);
+    scheduler().exit();
+}
+
 fn handle_interrupt(_frame: InterruptStackFrame, index: u8, _error: Option<u64>) {
     interrupt_dispatcher().dispatch(index);
 }
 
 impl InterruptDispatcher {
     pub fn new() -> Self {
-        let mut int_vectors = Vec::<Mutex<Vec<Box<dyn InterruptHandler>>>>::new();
+        let mut int_vectors = Vec::<RwLock<Vec<Box<dyn InterruptHandler>>>>::new();
         for _ in 0..MAX_VECTORS {
-            int_vectors.push(Mutex::new(Vec::new()));
+            int_vectors.push(RwLock::new(Vec::new()));
         }
 
         Self { int_vectors }
@@ -231,7 +298,8 @@ impl InterruptDispatcher {
 
     pub fn assign(&self, vector: InterruptVector, handler: Box<dyn InterruptHandler>) {
         match self.int_vectors.get(vector as usize) {
-            Some(vec) => vec.lock().push(handler),
+            // Interrupts are disabled, since a handler for the same vector on this core would wait for the lock forever
+            Some(vec) => interrupts::without_interrupts(|| vec.write().push(handler)),
             None => panic!("Assigning interrupt handler to illegal vector number {}!", vector as u8),
         }
     }
@@ -241,29 +309,23 @@ impl InterruptDispatcher {
         if interrupt != 32 {
             trace!("handling interrupt {interrupt}");
         }
-        let handler_vec_mutex = self
+        // Multiple cores may handle the same vector at the same time (e.g. the APIC timer), so the handlers are only read.
+        // If a handler switches threads, the lock is held until the thread is resumed,
+        // so handlers must be assigned before the interrupt is enabled.
+        let handler_vec = self
             .int_vectors
             .get(interrupt as usize)
-            .unwrap_or_else(|| panic!("Interrupt Dispatcher: No handler vec assigned for interrupt [{}]!", interrupt));
-        let mut handler_vec = handler_vec_mutex.try_lock();
-        while handler_vec.is_none() {
-            // We have to force unlock inside the interrupt handler, or else the system will hang forever.
-            // While this might be unsafe, it is extremely unlikely that we destroy something here, since we only need read access to the vectors.
-            // The only scenario, in which something might break, is when two or more drivers are trying to assign an interrupt handler to the same vector,
-            // while an interrupt for that vector occurs.
-            unsafe {
-                handler_vec_mutex.force_unlock();
-            }
-            handler_vec = handler_vec_mutex.try_lock();
-        }
+            .unwrap_or_else(|| panic!("Interrupt Dispatcher: No handler vec assigned for interrupt [{}]!", interrupt))
+            .read();
 
-        if handler_vec.as_ref().unwrap().is_empty() {
+        if handler_vec.is_empty() {
             error!("Interrupt Dispatcher: No handler registered for interrupt [{interrupt}]!");
         }
 
-        for handler in handler_vec.unwrap().iter_mut() {
+        for handler in handler_vec.iter() {
             handler.trigger();
         }
+        drop(handler_vec);
 
         apic().end_of_interrupt();
     }
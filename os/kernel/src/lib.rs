@@ -33,6 +33,7 @@ use crate::process::process_manager::ProcessManager;
 use crate::process::scheduler::Scheduler;
 use crate::syscall::sys_graphic::LfbInfo;
 use crate::syscall::syscall_dispatcher::CoreLocalStorage;
+use crate::consts::MAX_CORES;
 use alloc::format;
 use graphic::color::{BLUE, WHITE};
 use ::log::{Level, Log, Record, error};
@@ -41,6 +42,8 @@ use alloc::string::String;
 use alloc::sync::Arc;
 use x86_64::instructions::interrupts;
 use core::fmt::Arguments;
+use core::ops::Deref;
+use core::ptr;
 use core::hint::spin_loop;
 use core::panic::PanicInfo;
 use device::tty::{TtyInput, TtyOutput};
@@ -49,7 +52,8 @@ use graphic::lfb::LFB;
 use multiboot2::ModuleTag;
 use spin::{Mutex, Once, RwLock};
 use tar_no_std::TarArchiveRef;
-use x86_64::PhysAddr;
+use x86_64::{PhysAddr, VirtAddr};
+use x86_64::registers::model_specific::KernelGsBase;
 use x86_64::structures::gdt::GlobalDescriptorTable;
 use x86_64::structures::idt::InterruptDescriptorTable;
 use x86_64::structures::paging::PhysFrame;
@@ -150,40 +154,61 @@ pub fn efi_services_available() -> bool {
     uefi::table::system_table_raw().is_some()
 }
 
-/// Global Descriptor Table.
+/// Global Descriptor Tables (one per core).
 /// Needed to set up basic segmentation (flat model) and the TSS.
-static GDT: Mutex<GlobalDescriptorTable> = Mutex::new(GlobalDescriptorTable::new());
+static GDT: [Mutex<GlobalDescriptorTable>; MAX_CORES] = [const { Mutex::new(GlobalDescriptorTable::new()) }; MAX_CORES];
 
+/// Returns the GDT of the calling core.
 pub fn gdt() -> &'static Mutex<GlobalDescriptorTable> {
-    &GDT
+    &GDT[core_id()]
 }
 
-/// Task State Segment.
+/// Task State Segments (one per core).
 /// Needed to set up kernel/user mode switching.
-/// Once multicore is implemented, we need one TSS per core.
-static TSS: Mutex<TaskStateSegment> = Mutex::new(TaskStateSegment::new());
+static TSS: [Mutex<TaskStateSegment>; MAX_CORES] = [const { Mutex::new(TaskStateSegment::new()) }; MAX_CORES];
 
+/// Returns the TSS of the calling core.
 pub fn tss() -> &'static Mutex<TaskStateSegment> {
-    &TSS
+    &TSS[core_id()]
 }
 
 /// Interrupt Descriptor Table.
 /// Tells the CPU which interrupt handler to call for each interrupt.
+/// All cores share the same IDT.
 static IDT: Mutex<InterruptDescriptorTable> = Mutex::new(InterruptDescriptorTable::new());
 
 pub fn idt() -> &'static Mutex<InterruptDescriptorTable> {
     &IDT
 }
 
-/// Core Local Storage.
+/// Core Local Storage (one per core).
 /// Contains information that is needed by the syscall handler.
-/// It is never accessed directly, but via the swapgs instruction.
-/// 'boot.rs' sets up the gs base register with a pointer to this struct.
-/// Once multicore is implemented, we need one of these per core.
-static CORE_LOCAL_STORAGE: Mutex<CoreLocalStorage> = Mutex::new(CoreLocalStorage::new());
+/// It is accessed by the syscall handler via the swapgs instruction.
+/// 'boot.rs' calls 'init_core_local_storage()' on each core, which sets up the kernel gs base register
+/// with a pointer to the struct of the core. This pointer is also used to get the id of the calling core.
+static CORE_LOCAL_STORAGE: [Mutex<CoreLocalStorage>; MAX_CORES] = [const { Mutex::new(CoreLocalStorage::new()) }; MAX_CORES];
 
+/// Initialize the core local storage of the calling core with `core_id`.
+/// Must be called first during the initialization of a core, since `core_id()` depends on it.
+pub fn init_core_local_storage(core_id: usize) {
+    let mut core_local_storage = CORE_LOCAL_STORAGE[core_id].lock();
+    core_local_storage.set_core_id(core_id);
+    KernelGsBase::write(VirtAddr::new(ptr::from_ref(core_local_storage.deref()) as u64));
+}
+
+/// Returns the core local storage of the calling core.
 pub fn core_local_storage() -> &'static Mutex<CoreLocalStorage> {
-    &CORE_LOCAL_STORAGE
+    &CORE_LOCAL_STORAGE[core_id()]
+}
+
+/// Returns the id of the calling core (the bootstrap processor has id 0).
+/// The kernel gs base register always points to the core local storage of the core,
+/// except during the short sections in the syscall handler and thread switching code, where interrupts are disabled.
+/// If interrupts are enabled, the calling thread may be moved to another core at any time,
+/// so the result is only reliable, if interrupts are disabled or the scheduler of the core is locked.
+pub fn core_id() -> usize {
+    let core_local_storage = KernelGsBase::read().as_u64() as *const CoreLocalStorage;
+    unsafe { (*core_local_storage).core_id() }
 }
 
 /// ACPI Tables.
@@ -5,6 +5,7 @@
    ║   - alloc              allooc a range of frames                         ║
    ║   - allocator_locked   check if allocator is locked                     ║
    ║   - dump               get a dump of the current free list              ║
+   ║   - free_frame_count   get the number of free frames                    ║
    ║   - free               free a range of frames                           ║
    ║   - phys_limit         get the highest phys. addr. managed by the alloc.║
    ║   - boot_avail         insert free frame region detected during boot    ║
@@ -127,6 +128,11 @@ pub fn dump() -> String {
     format!("{:?}", PAGE_FRAME_ALLOCATOR.lock())
 }
 
+/// Get the number of free page frames.
+pub fn free_frame_count() -> usize {
+    PAGE_FRAME_ALLOCATOR.lock().free_frame_count()
+}
+
 /// Entry in the free list.
 /// Represents a block of available physical memory.
 struct PageFrameNode {
@@ -201,6 +207,17 @@ impl PageFrameListAllocator {
         }
     }
 
+    /// Count the page frames of all blocks in the free list.
+    fn free_frame_count(&self) -> usize {
+        let mut count = 0;
+        let mut current = &self.head;
+        while let Some(block) = &current.next {
+            count += block.frame_count;
+            current = block;
+        }
+        count
+    }
+
     /// Insert a new range of `frames`, sorted ascending by its memory address.
     unsafe fn insert(&mut self, frames: PhysFrameRange) {
         let mut new_block = PageFrameNode::new((frames.end - frames.start) as usize);
@@ -3,6 +3,7 @@ pub mod vma;
 pub mod pages;
 pub mod frames;
 pub mod frames_lf;
+pub mod tlb;
 
 pub mod nvmem;
 pub mod dram;
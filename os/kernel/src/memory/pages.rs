@@ -7,6 +7,7 @@
    ║                   in the given memory space                             ║
    ║   - set_flags     set flags of page table entries for a range of pages  ║
    ║   - translate     translate a virtual address to a physical address     ║
+   ║   - flags         get flags of the page table entry for an address      ║
    ║   - unmap         unmap a range of pages                                ║
    ║   - page_from_u64 convert a u64 address to a Page                       ║
    ╟─────────────────────────────────────────────────────────────────────────╢
@@ -16,6 +17,7 @@
 
 
 use core::cmp::min;
+use alloc::vec::Vec;
 use core::{ptr, fmt};
 use spin::RwLock;
 use x86_64::structures::paging::{PageTable, PageTableFlags, PageTableIndex, PhysFrame};
@@ -145,14 +147,27 @@ impl Paging {
         Paging::translate_in_table(root_table, addr, depth)
     }
 
+    /// Return the flags of the page table entry mapping the given virtual address `addr` (`None`, if there is none)
+    pub(super) fn flags(&self, addr: VirtAddr) -> Option<PageTableFlags> {
+        let depth = self.depth;
+        let root_table_guard = self.root_table.read();
+        let root_table = unsafe { root_table_guard.as_mut().unwrap() };
+
+        Paging::flags_in_table(root_table, addr, depth)
+    }
+
     /// Unmap a range of `pages` from the address space. 
-    /// `free_physical` indicates if the physical frames should be freed.
-    pub(super) fn unmap(&self, pages: PageRange, free_physical: bool) {
+    /// `free_physical` indicates if the physical frames should be freed. \
+    /// Returns the frames to be freed (the mapped frames, if `free_physical` is true, and the frames of emptied page tables).
+    /// The caller must free them, after the TLB entries of `pages` have been invalidated on all cores.
+    pub(super) fn unmap(&self, pages: PageRange, free_physical: bool) -> Vec<PhysFrame> {
         let depth = self.depth;
         let root_table_guard = self.root_table.read();
         let root_table = unsafe { root_table_guard.as_mut().unwrap() };
 
-        Paging::unmap_in_table(root_table, pages, depth, free_physical);
+        let mut unused_frames = Vec::new();
+        Paging::unmap_in_table(root_table, pages, depth, free_physical, &mut unused_frames);
+        unused_frames
     }
 
     /// Set `flags` of page table entries for the give range of `pages`` 
@@ -277,7 +292,8 @@ impl Paging {
     }
 
     /// Internal recursive function to unmap a range of `pages` where `free_phyisical` defines if frame should be freed.
-    fn unmap_in_table(table: &mut PageTable, mut pages: PageRange, level: usize, free_physical: bool) -> usize {
+    /// Frames to be freed are added to `unused_frames`.
+    fn unmap_in_table(table: &mut PageTable, mut pages: PageRange, level: usize, free_physical: bool, unused_frames: &mut Vec<PhysFrame>) -> usize {
         let mut total_freed_pages: usize = 0;
         let start_index = usize::from(page_table_index(pages.start.start_address(), level));
 
@@ -288,13 +304,12 @@ impl Paging {
                 }
 
                 let next_level_table = unsafe { (entry.addr().as_u64() as *mut PageTable).as_mut().unwrap() };
-                let freed_pages = Paging::unmap_in_table(next_level_table, pages, level - 1, free_physical);
+                let freed_pages = Paging::unmap_in_table(next_level_table, pages, level - 1, free_physical, unused_frames);
                 pages = PageRange { start: pages.start + freed_pages as u64, end: pages.end };
                 total_freed_pages += freed_pages;
 
                 if Paging::is_table_empty(next_level_table) {
-                    let table_frame = PhysFrame::from_start_address(entry.addr()).unwrap();
-                    unsafe { frames::free(PhysFrameRange { start: table_frame, end: table_frame + 1 }); }
+                    unused_frames.push(PhysFrame::from_start_address(entry.addr()).unwrap());
                     entry.set_unused();
                 }
 
@@ -313,8 +328,7 @@ impl Paging {
 
                 if !entry.is_unused() {
                     if free_physical {
-                        let frame = PhysFrame::from_start_address(entry.addr()).unwrap();
-                        unsafe { frames::free(PhysFrameRange { start: frame, end: frame + 1 }); }
+                        unused_frames.push(PhysFrame::from_start_address(entry.addr()).unwrap());
                     }
 
                     entry.set_unused();
@@ -377,7 +391,10 @@ impl Paging {
                     break;
                 }
 
-                entry.set_flags(flags);
+                // Unused entries must stay unused (they have no frame)
+                if !entry.is_unused() {
+                    entry.set_flags(flags);
+                }
             }
 
             return edit_count;
@@ -386,6 +403,22 @@ impl Paging {
         total_edited_pages
     }
 
+    /// Internal recursive function returning the flags of the level 1 entry for the given virtual address `addr` or None.
+    fn flags_in_table(table: &mut PageTable, addr: VirtAddr, level: usize) -> Option<PageTableFlags> {
+        let index = usize::from(page_table_index(addr, level));
+        let entry = &table[index];
+        if entry.is_unused() {
+            return None;
+        }
+
+        if level > 1 { // Calculate next level page table until level == 1
+            let next_level_table = unsafe { (entry.addr().as_u64() as *mut PageTable).as_mut().unwrap() };
+            Paging::flags_in_table(next_level_table, addr, level - 1)
+        } else { // Reached level 1 page table
+            Some(entry.flags())
+        }
+    }
+
     /// Internal recursive function returning physical address for the given virtual address `addr` or None.
     fn translate_in_table(table: &mut PageTable, addr: VirtAddr, level: usize) -> Option<PhysAddr> {
         let aligned_addr = addr.align_down(PAGE_SIZE as u64);
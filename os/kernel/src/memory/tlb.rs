@@ -0,0 +1,86 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: tlb                                                             ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: TLB shootdown. After page table entries of an address space     ║
+   ║         have been changed or removed, the TLB entries of the pages are  ║
+   ║         invalidated on all cores by sending them an IPI.                ║
+   ║   - shootdown         invalidate TLB entries of pages on all cores      ║
+   ║   - handle_shootdown  called by the IPI handler on the other cores      ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use spin::Mutex;
+use x86_64::PhysAddr;
+use x86_64::instructions::{interrupts, tlb};
+use x86_64::registers::control::Cr3;
+use x86_64::structures::paging::page::PageRange;
+use x86_64::structures::paging::Page;
+use x86_64::VirtAddr;
+use crate::{apic, core_id, scheduler};
+
+/// Above this number of pages, the whole TLB is flushed instead of single entries
+const MAX_SINGLE_FLUSHES: u64 = 64;
+
+/// Only one shootdown is in progress at a time, its request is stored in `ROOT`, `START` and `END`
+static SHOOTDOWN_LOCK: Mutex<()> = Mutex::new(());
+static ROOT: AtomicU64 = AtomicU64::new(0); // physical address of the root page table
+static START: AtomicU64 = AtomicU64::new(0); // start address of the first page
+static END: AtomicU64 = AtomicU64::new(0); // start address of the page after the last one
+static PENDING: AtomicUsize = AtomicUsize::new(0); // number of cores, which have not handled the request yet
+
+/// Invalidate the TLB entries of `pages` of the address space with the root page table `root` on all cores,
+/// which use this address space, and wait until they are done. \
+/// Must be called with interrupts enabled and without holding locks, which other cores may
+/// try to get with interrupts disabled (e.g. the VMA lock, taken by the page fault handler).
+pub fn shootdown(root: PhysAddr, pages: PageRange) {
+    let _guard = SHOOTDOWN_LOCK.lock();
+
+    // The calling thread must not be moved to another core, until all other cores have been notified and are done
+    interrupts::without_interrupts(|| {
+        let core = core_id();
+        let others = scheduler().online_cores().into_iter().filter(|&other| other != core).collect::<Vec<usize>>();
+
+        ROOT.store(root.as_u64(), Ordering::SeqCst);
+        START.store(pages.start.start_address().as_u64(), Ordering::SeqCst);
+        END.store(pages.end.start_address().as_u64(), Ordering::SeqCst);
+        PENDING.store(others.len(), Ordering::SeqCst);
+
+        for &other in &others {
+            apic().shootdown_tlb(other);
+        }
+
+        flush(root, pages);
+        while PENDING.load(Ordering::SeqCst) > 0 {
+            core::hint::spin_loop();
+        }
+    });
+}
+
+/// Handle the request of the shootdown in progress (called by the interrupt handler of the TLB shootdown IPI)
+pub fn handle_shootdown() {
+    let root = PhysAddr::new(ROOT.load(Ordering::SeqCst));
+    let start = Page::containing_address(VirtAddr::new(START.load(Ordering::SeqCst)));
+    let end = Page::containing_address(VirtAddr::new(END.load(Ordering::SeqCst)));
+
+    flush(root, PageRange { start, end });
+    PENDING.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Invalidate the TLB entries of `pages` on the calling core, if it uses the address space with the root page table `root`.
+/// Other address spaces have no entries in the TLB, as it is flushed when switching address spaces.
+fn flush(root: PhysAddr, pages: PageRange) {
+    if Cr3::read().0.start_address() != root {
+        return;
+    }
+
+    if pages.end - pages.start > MAX_SINGLE_FLUSHES {
+        tlb::flush_all();
+    } else {
+        for page in pages {
+            tlb::flush(page.start_address());
+        }
+    }
+}
@@ -86,6 +86,11 @@ impl VirtualMemoryArea {
         self.typ
     }
 
+    /// Return the tag as string (unused bytes are filled with '-')
+    pub fn tag_str(&self) -> &str {
+        core::str::from_utf8(&self.tag).unwrap_or("<invalid>")
+    }
+
     pub fn overlaps_with(&self, other: &VirtualMemoryArea) -> bool {
         self.range.end > other.range.start && self.range.start < other.range.end
     }
@@ -27,9 +27,13 @@
    ║   - clone_address_space       used for process creation                 ║
    ║   - create_kernel_address_space   used for process creation             ║
    ║   - dump                      dump all VMAs of an address space         ║
+   ║   - vmas                      get all VMAs of an address space          ║
    ║   - page_table_address        get root page table address               ║
    ║   - set_flags                 set page table flags                      ║
    ║   - is_address_within_vma     check if address is within any vma        ║
+   ║   - access_user_memory        access user memory, if it is accessible   ║
+   ║   - set_vma_range_flags       set page table flags for pages of vmas    ║
+   ║   - free_vma_range            unmap pages of vmas and free frames       ║
    ║   - copy_to_addr_space        copy data to a given address space        ║
    ║   - get_phys                  get physical address of a page            ║
    ║   - pfr_from_pr_identity      get pfr range from page range identity    ║
@@ -41,7 +45,9 @@
 
 use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use core::ops::Range;
+use core::ptr;
 use log::{warn, info};
 use spin::RwLock;
 
@@ -52,11 +58,12 @@ use x86_64::structures::paging::frame::PhysFrameRange;
 use x86_64::structures::paging::page::PageRange;
 use x86_64::structures::paging::{Page, PageTableFlags};
 
-use crate::cpu;
+use crate::{cpu, process_manager};
 use crate::memory::frames;
 use crate::memory::frames::phys_limit;
 use crate::memory::pages;
 use crate::memory::pages::Paging;
+use crate::memory::tlb;
 use crate::memory::vma::{VirtualMemoryArea, VmaType};
 use crate::memory::{MemorySpace, PAGE_SIZE};
 
@@ -104,6 +111,23 @@ pub fn frame_allocator_locked() -> bool {
     frames::allocator_locked()
 }
 
+/// Allocate `frame_count` zeroed, contiguous page frames for structures shared with a device (DMA). \
+/// The frames are identity mapped in kernel space, so their physical addresses can be used directly; caching is disabled.
+pub fn alloc_dma_frames(frame_count: usize) -> PhysFrameRange {
+    let frames = unsafe { alloc_frames(frame_count) };
+    let start = frames.start.start_address().as_u64();
+    let pages = PageRange {
+        start: Page::from_start_address(VirtAddr::new(start)).unwrap(),
+        end: Page::from_start_address(VirtAddr::new(frames.end.start_address().as_u64())).unwrap(),
+    };
+
+    let kernel_process = process_manager().read().kernel_process().unwrap();
+    kernel_process.virtual_address_space.set_flags(pages, PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::NO_CACHE);
+    unsafe { ptr::write_bytes(start as *mut u8, 0, frame_count * PAGE_SIZE) };
+
+    frames
+}
+
 /// Convert a [`PageRange`] to a [`PhysFrameRange`] assuming the pages are identity mapped.
 pub fn pfr_from_pr_identity(pr: PageRange) -> PhysFrameRange {
     let virt_start_addr = pr.start.start_address().as_u64();
@@ -315,6 +339,74 @@ impl VirtualAddressSpace {
         self.page_tables.map(page_range, space, flags);
     }
 
+    /// Set page table `flags` for `pages`, which must be covered by VMAs of type `vma_type`,
+    /// and invalidate their TLB entries on all cores. \
+    /// Returns `false` without changing anything, if `pages` is not fully covered.
+    pub fn set_vma_range_flags(&self, pages: PageRange, vma_type: VmaType, flags: PageTableFlags) -> bool {
+        // Holding the lock prevents the pages from being freed concurrently
+        let areas = self.virtual_memory_areas.read();
+        if !Self::is_covered_by(&areas, pages, vma_type) {
+            return false;
+        }
+
+        self.page_tables.set_flags(pages, flags);
+        drop(areas);
+
+        tlb::shootdown(self.page_table_address(), pages);
+        true
+    }
+
+    /// Unmap `pages`, invalidate their TLB entries on all cores and free their frames.
+    /// The pages must be covered by VMAs of type `vma_type`. \
+    /// VMAs overlapping `pages` only partially are shrunk or split, all others are removed. \
+    /// Returns `false` without unmapping anything, if `pages` is not fully covered.
+    pub fn free_vma_range(&self, pages: PageRange, vma_type: VmaType) -> bool {
+        let mut areas = self.virtual_memory_areas.write();
+        if !Self::is_covered_by(&areas, pages, vma_type) {
+            return false;
+        }
+
+        let overlapping: Vec<Arc<VirtualMemoryArea>> = areas.values()
+            .filter(|vma| vma.range.end > pages.start && vma.range.start < pages.end)
+            .cloned().collect();
+        for vma in overlapping {
+            areas.remove(&vma.start());
+
+            // Keep the parts of the vma before and after `pages`
+            if vma.range.start < pages.start {
+                let head = VirtualMemoryArea { range: PageRange { start: vma.range.start, end: pages.start }, ..*vma };
+                areas.insert(head.start(), Arc::new(head));
+            }
+            if vma.range.end > pages.end {
+                let tail = VirtualMemoryArea { range: PageRange { start: pages.end, end: vma.range.end }, ..*vma };
+                areas.insert(tail.start(), Arc::new(tail));
+            }
+        }
+
+        let unused_frames = self.page_tables.unmap(pages, true);
+        drop(areas);
+
+        // Other cores may still access the frames through stale TLB entries, until they have been invalidated
+        tlb::shootdown(self.page_table_address(), pages);
+        for frame in unused_frames {
+            unsafe { frames::free(PhysFrameRange { start: frame, end: frame + 1 }); }
+        }
+        true
+    }
+
+    /// Helper function checking if `pages` are covered by `areas` of type `vma_type` (without gaps).
+    fn is_covered_by(areas: &BTreeMap<VirtAddr, Arc<VirtualMemoryArea>>, pages: PageRange, vma_type: VmaType) -> bool {
+        let mut covered_until = pages.start;
+        for vma in areas.values().filter(|vma| vma.range.end > pages.start && vma.range.start < pages.end) {
+            if vma.typ != vma_type || vma.range.start > covered_until {
+                return false;
+            }
+            covered_until = vma.range.end;
+        }
+
+        covered_until >= pages.end
+    }
+
     /// Set page table `flags` for the give page range `pages`  
     pub fn set_flags(&self, pages: PageRange, flags: PageTableFlags) {
         self.page_tables.set_flags(pages, flags);
@@ -325,6 +417,11 @@ impl VirtualAddressSpace {
         self.page_tables.page_table_address()
     }
 
+    /// Return copies of all virtual memory areas of this address space, sorted by start address
+    pub fn vmas(&self) -> Vec<VirtualMemoryArea> {
+        self.virtual_memory_areas.read().values().map(|area| **area).collect()
+    }
+
     /// Dump all virtual memory areas of this address space
     pub fn dump(&self, pid: usize) {
         info!("VMAs of process [{pid}]");
@@ -525,13 +622,45 @@ impl VirtualAddressSpace {
         }
         None
     }
+
+    /// Call `f`, if the `size` bytes at `address` are readable user memory, i.e. located in user VMAs and
+    /// either present or mapped on demand by the page fault handler (heap and user stack). \
+    /// The VMAs are locked while `f` runs, so that the memory cannot be unmapped concurrently. \
+    /// Returns `None` without calling `f`, if the memory is not accessible.
+    pub fn access_user_memory<R>(&self, address: u64, size: u64, f: impl FnOnce() -> R) -> Option<R> {
+        if size == 0 || address < self.first_usable_user_addr.as_u64() || address.checked_add(size)? > self.last_usable_user_addr.as_u64() {
+            return None;
+        }
+
+        let areas = self.virtual_memory_areas.read();
+        let pages = Page::range_inclusive(Page::containing_address(VirtAddr::new(address)), Page::containing_address(VirtAddr::new(address + size - 1)));
+        for page in pages {
+            let vma = areas.range(..=page.start_address()).next_back().map(|(_, vma)| vma)?;
+            if page >= vma.range.end || vma.space != MemorySpace::User {
+                return None;
+            }
+
+            let accessible = match self.page_tables.flags(page.start_address()) {
+                Some(flags) => flags.contains(PageTableFlags::PRESENT),
+                None => matches!(vma.typ, VmaType::Heap | VmaType::UserStack),
+            };
+            if !accessible {
+                return None;
+            }
+        }
+
+        Some(f())
+    }
 }
 
 impl Drop for VirtualAddressSpace {
     fn drop(&mut self) {
         for vma in self.virtual_memory_areas.read().iter() {
             if vma.1.typ != VmaType::DeviceMemory {
-                self.page_tables.unmap(vma.1.range, true);
+                // No thread uses this address space anymore, so there are no TLB entries to invalidate
+                for frame in self.page_tables.unmap(vma.1.range, true) {
+                    unsafe { frames::free(PhysFrameRange { start: frame, end: frame + 1 }); }
+                }
             }
         }
     }
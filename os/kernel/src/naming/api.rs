@@ -5,11 +5,26 @@
    ║   - init   init ns, called once                                         ║
    ║   - open   open a named object                                          ║
    ║   - read   read bytes from an open object                               ║
+   ║   - read_terminal read bytes, using a mode, if the object is the tty    ║
    ║   - write  write bytes into an open object                              ║
    ║   - seek   set file pointer (for files)                                 ║
+   ║   - close  close an object handle                                       ║
+   ║   - dup    duplicate an object handle                                   ║
+   ║   - dup2   duplicate an object handle into a given handle               ║
    ║   - mkdir  create a directory                                           ║
    ║   - touch  create a file                                                ║
    ║   - mkfifo create a named pipe                                          ║
+   ║   - unlink remove a file or named pipe                                  ║
+   ║   - rmdir  remove an empty directory                                    ║
+   ║   - rename rename or move a named object                                ║
+   ║   - symlink create a symbolic link                                      ║
+   ║   - readlink read the target of a symbolic link                         ║
+   ║   - stat   get meta data of a named object referenced by a path         ║
+   ║   - fstat  get meta data of an open object                              ║
+   ║   - poll   wait for events on open objects and sockets                  ║
+   ║   - sync   write cached data to the block devices                       ║
+   ║   - mount  attach a file system at a directory                          ║
+   ║   - umount detach a mounted file system                                 ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Michael Schoettner, Univ. Duesseldorf, 25.8.2025                ║
    ╚═════════════════════════════════════════════════════════════════════════╝
@@ -19,23 +34,34 @@ use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use log::{info, warn};
-use spin::{Mutex, Once};
+use spin::Once;
 
+use super::devfs;
+use super::fat32;
 use super::lookup;
+use super::mounts;
 use super::open_objects;
-use super::stat::Mode;
+use super::poll;
+use super::procfs;
+use super::stat::{Mode, MODE_LINK};
 use super::tmpfs;
-use super::traits::FileSystem;
+use super::traits::{FileSystem, NamedObject};
 
-use crate::initrd;
-use naming::shared_types::{OpenOptions, RawDirent, SeekOrigin};
+use crate::{initrd, process_manager, storage};
+use naming::shared_types::{OpenOptions, PollFd, RawDirent, RawStat, SeekOrigin};
 use syscall::return_vals::Errno;
+use terminal::TerminalMode;
 
 // root of naming service
 pub(super) static ROOT: Once<Arc<dyn FileSystem>> = Once::new();
 
-// current working directory
-static CWD: Mutex<String> = Mutex::new(String::new());
+// block device mounted during initialization (first partition of the first hard disk)
+const BOOT_MOUNT_DEVICE: &str = "ata0p0";
+const BOOT_MOUNT_PATH: &str = "/mnt";
+
+// directories at which devfs and procfs are mounted during initialization
+const DEV_MOUNT_PATH: &str = "/dev";
+const PROC_MOUNT_PATH: &str = "/proc";
 
 /// Initialize the naming service (must be called once before using it).
 pub fn init() {
@@ -53,31 +79,50 @@ pub fn init() {
         Arc::new(tmpfs)
     });
     open_objects::open_object_table_init();
-    let mut cwd = CWD.lock();
-    *cwd = "/".to_string();
+
+    // Make the devices and the state of the kernel accessible as files
+    for (path, fs_type) in [(DEV_MOUNT_PATH, "devfs"), (PROC_MOUNT_PATH, "procfs")] {
+        let res = mkdir(path).and_then(|_| mount(fs_type, path, fs_type));
+        if let Err(e) = res {
+            warn!("Failed to mount {} at [{}]: {:?}", fs_type, path, e);
+        }
+    }
+
+    // Mount the hard disk (if available), so that its files survive reboots
+    if storage::block_device(BOOT_MOUNT_DEVICE).is_some() {
+        let res = mkdir(BOOT_MOUNT_PATH).and_then(|_| mount(BOOT_MOUNT_DEVICE, BOOT_MOUNT_PATH, "fat32"));
+        if let Err(e) = res {
+            warn!("Failed to mount [{}] at [{}]: {:?}", BOOT_MOUNT_DEVICE, BOOT_MOUNT_PATH, e);
+        }
+    }
     info!("naming service initialized");
     //    test::running_tests();
 }
 
 /// Open/create a named object referenced by `path` using the given `flags`. \
+/// With `CREATE` a missing file is created, with `CREATE | EXCLUSIVE` opening an existing object fails with `EEXIST`. \
 /// Returns `Ok(object_handle)` or `Err`.
 pub fn open(path: &str, flags: OpenOptions) -> Result<usize, Errno> {
-    // avoid creating a file twice
+    let path = &absolute_path(path);
+
+    // at most one access mode is allowed
+    let access_modes = flags & (OpenOptions::READONLY | OpenOptions::WRITEONLY | OpenOptions::READWRITE);
+    if access_modes.bits().count_ones() > 1 {
+        return Err(Errno::EINVAL);
+    }
+
     if flags.contains(OpenOptions::CREATE) {
-        let result = lookup::lookup_named_object(path);
-        if result.is_ok() {
-            return Err(Errno::EEXIST);
+        match lookup::lookup_named_object(path) {
+            Ok(_) if flags.contains(OpenOptions::EXCLUSIVE) => return Err(Errno::EEXIST),
+            Ok(_) => {}
+            Err(Errno::ENOENT) => {
+                touch(path)?;
+            }
+            Err(e) => return Err(e),
         }
     }
 
-    // Try to open the object
-    open_objects::open(path, flags).or_else(|e| {
-        if flags.contains(OpenOptions::CREATE) && e != Errno::EEXIST {
-            touch(path).and_then(|_| open_objects::open(path, flags))
-        } else {
-            Err(e)
-        }
-    })
+    open_objects::open(path, flags)
 }
 
 /// Write all bytes from the given `buffer` into the named object referenced by `object_handle`. \
@@ -89,7 +134,14 @@ pub fn write(object_handle: usize, buffer: &[u8]) -> Result<usize, Errno> {
 /// Read from the named object referenced by `object_handle` into the given `buffer`. \
 /// Returns `Ok(number of bytes read)` or `Err`.
 pub fn read(object_handle: usize, buffer: &mut [u8]) -> Result<usize, Errno> {
-    open_objects::read(object_handle, buffer)
+    open_objects::read(object_handle, buffer, TerminalMode::Canonical)
+}
+
+/// Read like `read`, but if `object_handle` refers to the terminal, it is read in `mode`
+/// (the mode is passed with every read, so that concurrent readers do not change each other's mode). \
+/// Returns `Ok(number of bytes read)` or `Err`.
+pub fn read_terminal(object_handle: usize, buffer: &mut [u8], mode: TerminalMode) -> Result<usize, Errno> {
+    open_objects::read(object_handle, buffer, mode)
 }
 
 /// Move the object pointer for the named object referenced by `object_handle` to the specified `offset` from the `origin`. \
@@ -104,9 +156,25 @@ pub fn close(object_handle: usize) -> Result<usize, Errno> {
     open_objects::close(object_handle)
 }
 
+/// Duplicate `object_handle` using the lowest free handle of the current process. \
+/// Both handles share the position within the object. \
+/// Returns `Ok(new_handle)` or `Err`.
+pub fn dup(object_handle: usize) -> Result<usize, Errno> {
+    open_objects::dup(object_handle)
+}
+
+/// Duplicate `object_handle` into `new_handle`. If `new_handle` is open, it is closed first. \
+/// Both handles share the position within the object. \
+/// Returns `Ok(new_handle)` or `Err`.
+pub fn dup2(object_handle: usize, new_handle: usize) -> Result<usize, Errno> {
+    open_objects::dup2(object_handle, new_handle)
+}
+
 /// Create a directory for the given `path`. \
 /// Returns `Ok(0)` or `Err(errno)`
 pub fn mkdir(path: &str) -> Result<usize, Errno> {
+    let path = &absolute_path(path);
+
     // Split the path into components
     let mut components: Vec<&str> = path.split("/").collect();
 
@@ -141,6 +209,8 @@ pub fn mkdir(path: &str) -> Result<usize, Errno> {
 /// Create an empty file defined by `path`. \
 /// Returns `Ok(0)` or `Err(errno)`
 pub fn touch(path: &str) -> Result<usize, Errno> {
+    let path = &absolute_path(path);
+
     // Split the path into components
     let mut components: Vec<&str> = path.split("/").collect();
 
@@ -208,8 +278,8 @@ pub fn readdir(dir_handle: usize, dentry: Option<&mut RawDirent>) -> Result<usiz
 /// Get the current working directory and return path in `buffer`. \
 /// Return: `Ok(len of string)` or `Err(errno)`
 pub fn cwd(buffer: &mut [u8]) -> Result<usize, Errno> {
-    // Lock the CWD mutex to access its value
-    let cwd = CWD.lock();
+    // Get the working directory of the current process
+    let cwd = current_process_cwd();
 
     // Get the string as bytes
     let cwd_bytes = cwd.as_bytes();
@@ -229,17 +299,24 @@ pub fn cwd(buffer: &mut [u8]) -> Result<usize, Errno> {
     Ok(len_to_copy + 1)
 }
 
+/// Return `path` as canonical absolute path, a relative `path` is resolved against the working directory of the current process. \
+/// Repeated slashes and `.` components are removed and `..` removes the preceding component (symbolic links are not resolved).
+pub fn absolute_path(path: &str) -> String {
+    let cwd = if path.starts_with('/') { String::new() } else { current_process_cwd() };
+    naming::path::normalize(&cwd, path)
+}
+
 ///
-/// Description: Change working directory \
-/// Parameters: `path` absolute path \
+/// Description: Change working directory of the current process \
+/// Parameters: `path` absolute path or path relative to the working directory \
 /// Return: `Ok(0)` or `Err(errno)`
 ///
 pub fn cd(path: &String) -> Result<usize, Errno> {
-    let result = lookup::lookup_dir(path);
+    let path = absolute_path(path);
+    let result = lookup::lookup_dir(&path);
     match result {
         Ok(_) => {
-            let mut cwd = CWD.lock();
-            *cwd = path.clone();
+            process_manager().read().current_process().set_cwd(path);
             Ok(0)
         }
         Err(_) => {
@@ -252,6 +329,8 @@ pub fn cd(path: &String) -> Result<usize, Errno> {
 /// Create a named pipe using `path`. \
 /// Returns `Ok(0)` or `Err(errno)`
 pub fn mkfifo(path: &str) -> Result<usize, Errno> {
+    let path = &absolute_path(path);
+
     // Split the path into components
     let mut components: Vec<&str> = path.split("/").collect();
 
@@ -282,3 +361,191 @@ pub fn mkfifo(path: &str) -> Result<usize, Errno> {
         }
     }
 }
+
+/// Remove the file or named pipe `path`. \
+/// Fails with `Err(Errno::EBUSY)` as long as the object is open. \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn unlink(path: &str) -> Result<usize, Errno> {
+    let path = &absolute_path(path);
+    let (parent_dir, name) = split_path(path)?;
+    let named_object = lookup::lookup_named_object_nofollow(path)?;
+    if open_objects::is_open(&named_object) || open_objects::is_path_in_use(path) {
+        return Err(Errno::EBUSY);
+    }
+
+    lookup::lookup_dir(&parent_dir)?.unlink(name)?;
+    Ok(0)
+}
+
+/// Remove the empty directory `path`. \
+/// Fails with `Err(Errno::EBUSY)` if the directory is open, a mount point or the working directory of a process. \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn rmdir(path: &str) -> Result<usize, Errno> {
+    let path = &absolute_path(path);
+    let (parent_dir, name) = split_path(path)?;
+    let named_object = lookup::lookup_named_object(path)?;
+    if open_objects::is_open(&named_object)
+        || open_objects::is_path_in_use(path)
+        || mounts::has_mount_within(path)
+        || is_working_directory_within(path)
+    {
+        return Err(Errno::EBUSY);
+    }
+
+    lookup::lookup_dir(&parent_dir)?.rmdir(name)?;
+    Ok(0)
+}
+
+/// Rename or move the named object `old_path` to `new_path` (which must not exist). \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn rename(old_path: &str, new_path: &str) -> Result<usize, Errno> {
+    let old_path = &absolute_path(old_path);
+    let new_path = &absolute_path(new_path);
+    let (old_parent_dir, old_name) = split_path(old_path)?;
+    let (new_parent_dir, new_name) = split_path(new_path)?;
+
+    // a directory cannot be moved into itself (compared without symbolic links, which may lead into it)
+    let (named_object, resolved_old_path) = lookup::resolve(old_path, false)?;
+    let (_, resolved_new_parent_dir) = lookup::resolve(&new_parent_dir, true)?;
+    if mounts::is_within(&resolved_new_parent_dir, &resolved_old_path) {
+        return Err(Errno::EINVAL);
+    }
+
+    if open_objects::is_open(&named_object)
+        || open_objects::is_path_in_use(old_path)
+        || mounts::has_mount_within(old_path)
+        || is_working_directory_within(old_path)
+    {
+        return Err(Errno::EBUSY);
+    }
+
+    let new_dir = lookup::lookup_dir(&new_parent_dir)?;
+    lookup::lookup_dir(&old_parent_dir)?.rename(old_name, &new_dir, new_name)?;
+    Ok(0)
+}
+
+/// Create a symbolic link `link_path` pointing to `target`. \
+/// `target` is stored as given and need not exist, a relative `target` is resolved against the directory of the link. \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn symlink(target: &str, link_path: &str) -> Result<usize, Errno> {
+    if target.is_empty() {
+        return Err(Errno::ENOENT);
+    }
+
+    let link_path = &absolute_path(link_path);
+    let (parent_dir, name) = split_path(link_path)?;
+    lookup::lookup_dir(&parent_dir)?.create_symlink(name, target, Mode::new(MODE_LINK))?;
+    Ok(0)
+}
+
+/// Read the target of the symbolic link `path` into `buffer` (truncated, if `buffer` is too small; not null-terminated). \
+/// Returns `Ok(number of bytes)` or `Err(errno)`
+pub fn readlink(path: &str, buffer: &mut [u8]) -> Result<usize, Errno> {
+    let target = lookup::lookup_named_object_nofollow(path)?.as_link()?.target()?;
+    let len = buffer.len().min(target.len());
+    buffer[..len].copy_from_slice(&target.as_bytes()[..len]);
+    Ok(len)
+}
+
+/// Get the meta data of the named object referenced by `path` (symbolic links are followed) into `raw_stat`. \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn stat(path: &str, raw_stat: &mut RawStat) -> Result<usize, Errno> {
+    fill_raw_stat(&lookup::lookup_named_object(path)?, raw_stat)
+}
+
+/// Get the meta data of the named object referenced by `object_handle` into `raw_stat`. \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn fstat(object_handle: usize, raw_stat: &mut RawStat) -> Result<usize, Errno> {
+    fill_raw_stat(&open_objects::named_object(object_handle)?, raw_stat)
+}
+
+/// Wait until at least one entry of `fds` has a pending event, or `timeout_ms` milliseconds have passed (`None` waits forever). \
+/// Returns `Ok(number of entries with pending events)`, which is 0 on timeout
+pub fn poll(fds: &mut [PollFd], timeout_ms: Option<usize>) -> Result<usize, Errno> {
+    Ok(poll::poll(fds, timeout_ms))
+}
+
+/// Helper function converting the `Stat` of `named_object` into `raw_stat`
+fn fill_raw_stat(named_object: &NamedObject, raw_stat: &mut RawStat) -> Result<usize, Errno> {
+    let stat = named_object.stat()?;
+    raw_stat.st_type = named_object.file_type() as usize;
+    raw_stat.st_size = stat.size;
+    raw_stat.st_ctime = stat.created_time;
+    raw_stat.st_mtime = stat.modified_time;
+    raw_stat.st_atime = stat.accessed_time;
+    Ok(0)
+}
+
+/// Helper function splitting the absolute `path` into its parent directory and the last component
+fn split_path(path: &str) -> Result<(String, &str), Errno> {
+    match path.trim_end_matches('/').rsplit_once('/') {
+        Some((_, "")) | None => Err(Errno::EINVAL),
+        Some(("", name)) => Ok(("/".to_string(), name)),
+        Some((parent_dir, name)) => Ok((parent_dir.to_string(), name)),
+    }
+}
+
+/// Mount the file system `fs_type` stored on the block device `source` at the directory `target`. \
+/// Supported types are `fat32`, `tmpfs`, `devfs` and `procfs` (`source` is ignored for all but `fat32`). \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn mount(source: &str, target: &str, fs_type: &str) -> Result<usize, Errno> {
+    // the root file system cannot be replaced
+    let target = absolute_path(target);
+    let target = target.trim_end_matches('/');
+    if target.is_empty() {
+        return Err(Errno::EBUSY);
+    }
+    lookup::lookup_dir(&target.to_string())?;
+
+    let fs: Arc<dyn FileSystem> = match fs_type {
+        "fat32" => {
+            let device = storage::block_device(source).ok_or(Errno::ENOENT)?;
+            Arc::new(fat32::Fat32::new(device)?)
+        }
+        "tmpfs" => Arc::new(tmpfs::TmpFs::new()),
+        "devfs" => Arc::new(devfs::DevFs::new()),
+        "procfs" => Arc::new(procfs::ProcFs::new()),
+        _ => return Err(Errno::ENOTSUP),
+    };
+
+    mounts::add(target, fs)?;
+    info!("Mounted [{}] ({}) at [{}]", source, fs_type, target);
+    Ok(0)
+}
+
+/// Unmount the file system mounted at the directory `target`. \
+/// Fails with `Err(Errno::EBUSY)` as long as objects of the file system are open or it contains a working directory. \
+/// Returns `Ok(0)` or `Err(errno)`
+pub fn umount(target: &str) -> Result<usize, Errno> {
+    let target = absolute_path(target);
+    let target = target.trim_end_matches('/');
+    if open_objects::is_path_in_use(target) || is_working_directory_within(target) {
+        return Err(Errno::EBUSY);
+    }
+
+    mounts::remove(target)?;
+    info!("Unmounted [{}]", target);
+
+    // the device may be removed after unmounting
+    sync()
+}
+
+/// Write the data cached for all block devices to the devices. \
+/// Returns `Ok(0)` or `Err(Errno::EIO)`, if not all data could be written
+pub fn sync() -> Result<usize, Errno> {
+    match storage::sync() {
+        true => Ok(0),
+        false => Err(Errno::EIO),
+    }
+}
+
+/// Helper function returning the working directory of the current process
+fn current_process_cwd() -> String {
+    process_manager().read().current_process().cwd()
+}
+
+/// Helper function checking if the working directory of any process is `path` or below it
+fn is_working_directory_within(path: &str) -> bool {
+    let processes = process_manager().read().active_processes();
+    processes.iter().any(|process| mounts::is_within(&process.cwd(), path))
+}
@@ -0,0 +1,383 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: devfs                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Device file system exposing kernel devices as file objects (mounted at  ║
+   ║ /dev). It contains the block devices and their partitions (e.g. ata0,   ║
+   ║ ata0p0), the serial port (ttyS0), the terminal (tty), the framebuffer   ║
+   ║ (fb0) and the virtual devices null, zero, and random. The directory is  ║
+   ║ generated on each access, so devices registered later show up, too.     ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::result::Result;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::slice;
+use naming::shared_types::{DirEntry, FileType, OpenOptions};
+use stream::{DecodedInputStream, OutputStream};
+use syscall::return_vals::Errno;
+use x86_64::instructions::random::RdRand;
+
+use super::stat::{Mode, Stat, MODE_DIR, MODE_FILE};
+use super::traits::{DirectoryObject, FileObject, FileSystem, NamedObject};
+use super::tty::TtyFile;
+use crate::storage::block::BlockDevice;
+use crate::{buffered_lfb, scheduler, serial_port, storage};
+
+pub struct DevFs {
+    root_dir: Arc<DevDir>,
+}
+
+impl DevFs {
+    pub fn new() -> DevFs {
+        DevFs {
+            root_dir: Arc::new(DevDir {
+                stat: Stat::created_now(Mode::new(MODE_DIR), 0),
+            }),
+        }
+    }
+}
+
+impl FileSystem for DevFs {
+    fn root_dir(&self) -> Arc<dyn DirectoryObject> {
+        self.root_dir.clone()
+    }
+}
+
+/// The only directory of devfs, its entries cannot be changed from user space
+struct DevDir {
+    stat: Stat,
+}
+
+impl DevDir {
+    /// Helper function returning all devices currently available, with their names
+    fn devices(&self) -> Vec<(String, Arc<dyn FileObject>)> {
+        let mut devices: Vec<(String, Arc<dyn FileObject>)> = Vec::new();
+        for name in storage::block_device_names() {
+            if let Some(device) = storage::block_device(&name) {
+                devices.push((name, Arc::new(BlockDeviceFile { device })));
+            }
+        }
+        if serial_port().is_some() {
+            devices.push(("ttyS0".to_string(), Arc::new(SerialFile)));
+        }
+        devices.push(("tty".to_string(), Arc::new(TtyFile)));
+        devices.push(("null".to_string(), Arc::new(NullFile)));
+        devices.push(("zero".to_string(), Arc::new(ZeroFile)));
+        devices.push(("random".to_string(), Arc::new(RandomFile)));
+        devices.push(("fb0".to_string(), Arc::new(FramebufferFile)));
+        devices
+    }
+}
+
+impl DirectoryObject for DevDir {
+    fn lookup(&self, name: &str) -> Result<NamedObject, Errno> {
+        self.devices()
+            .into_iter()
+            .find(|(device_name, _)| device_name == name)
+            .map(|(_, device)| NamedObject::FileObject(device))
+            .ok_or(Errno::ENOENT)
+    }
+
+    fn create_file(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_dir(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_pipe(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_symlink(&self, _name: &str, _target: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn unlink(&self, _name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn rmdir(&self, _name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn rename(&self, _old_name: &str, _new_dir: &Arc<dyn DirectoryObject>, _new_name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(self.stat)
+    }
+
+    fn readdir(&self, index: usize) -> Result<Option<DirEntry>, Errno> {
+        Ok(self.devices().into_iter().nth(index).map(|(name, _)| DirEntry {
+            file_type: FileType::Regular,
+            name,
+        }))
+    }
+}
+
+impl Debug for DevDir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DevDir").finish()
+    }
+}
+
+/// Byte-wise access to a block device (or partition). \
+/// Requests not aligned to sectors are handled by reading (and writing back) the affected sectors.
+struct BlockDeviceFile {
+    device: Arc<dyn BlockDevice + Send + Sync>,
+}
+
+impl BlockDeviceFile {
+    fn size(&self) -> usize {
+        self.device.sector_count() as usize * self.device.sector_size() as usize
+    }
+
+    /// Helper function reading the sectors containing the bytes `offset..offset + len`. \
+    /// Returns the first sector and a buffer holding all affected sectors.
+    fn read_sectors(&self, offset: usize, len: usize) -> Result<(u64, Vec<u8>), Errno> {
+        let sector_size = self.device.sector_size() as usize;
+        let first_sector = offset / sector_size;
+        let count = (offset + len).div_ceil(sector_size) - first_sector;
+
+        let mut buffer = vec![0u8; count * sector_size];
+        if self.device.read(first_sector as u64, count, &mut buffer) != count {
+            return Err(Errno::EIO);
+        }
+        Ok((first_sector as u64, buffer))
+    }
+}
+
+impl FileObject for BlockDeviceFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), self.size()))
+    }
+
+    fn read(&self, buf: &mut [u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        let len = buf.len().min(self.size().saturating_sub(offset));
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let (_, sectors) = self.read_sectors(offset, len)?;
+        let start = offset % self.device.sector_size() as usize;
+        buf[..len].copy_from_slice(&sectors[start..start + len]);
+        Ok(len)
+    }
+
+    fn write(&self, buf: &[u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        let len = buf.len().min(self.size().saturating_sub(offset));
+        if len == 0 {
+            return Err(Errno::ENOSPC);
+        }
+
+        let (first_sector, mut sectors) = self.read_sectors(offset, len)?;
+        let start = offset % self.device.sector_size() as usize;
+        sectors[start..start + len].copy_from_slice(&buf[..len]);
+
+        let count = sectors.len() / self.device.sector_size() as usize;
+        if self.device.write(first_sector, count, &sectors) != count {
+            return Err(Errno::EIO);
+        }
+        Ok(len)
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(()) // the size of a device is fixed
+    }
+}
+
+impl Debug for BlockDeviceFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockDeviceFile").field("size", &self.size()).finish()
+    }
+}
+
+/// The serial port used for kernel log messages. \
+/// Reading blocks until at least one byte has been received (unless opened with `NONBLOCK`).
+#[derive(Debug)]
+struct SerialFile;
+
+impl FileObject for SerialFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, buf: &mut [u8], _offset: usize, options: OpenOptions) -> Result<usize, Errno> {
+        let serial = serial_port().ok_or(Errno::EIO)?;
+        let mut len = 0;
+        while len < buf.len() {
+            match serial.decoded_try_read_byte() {
+                Some(-1) => break, // port has been closed
+                Some(byte) => {
+                    buf[len] = byte as u8;
+                    len += 1;
+                }
+                None if len > 0 => break,
+                None if options.contains(OpenOptions::NONBLOCK) => return Err(Errno::EAGAIN),
+                None => scheduler().switch_thread_no_interrupt(),
+            }
+        }
+        Ok(len)
+    }
+
+    fn write(&self, buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        let serial = serial_port().ok_or(Errno::EIO)?;
+        for byte in buf {
+            serial.write_byte(*byte);
+        }
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(()) // a serial port has no size
+    }
+}
+
+/// Discards everything written, reading returns end of file
+#[derive(Debug)]
+struct NullFile;
+
+impl FileObject for NullFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, _buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Ok(0)
+    }
+
+    fn write(&self, buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(())
+    }
+}
+
+/// Discards everything written, reading returns zeros
+#[derive(Debug)]
+struct ZeroFile;
+
+impl FileObject for ZeroFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        buf.fill(0);
+        Ok(buf.len())
+    }
+
+    fn write(&self, buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(())
+    }
+}
+
+/// State of the pseudo random number generator used, if the CPU does not support `rdrand`
+static RANDOM_STATE: AtomicU64 = AtomicU64::new(0);
+
+/// Reading returns random bytes (from `rdrand` if available), writing is ignored
+#[derive(Debug)]
+struct RandomFile;
+
+impl RandomFile {
+    /// Helper function returning the next random number
+    fn next_random(&self) -> u64 {
+        if let Some(random) = RdRand::new().and_then(RdRand::get_u64) {
+            return random;
+        }
+
+        // xorshift64*, seeded with the time stamp counter
+        let mut state = RANDOM_STATE.load(Ordering::Relaxed);
+        if state == 0 {
+            state = unsafe { core::arch::x86_64::_rdtsc() } | 1;
+        }
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        RANDOM_STATE.store(state, Ordering::Relaxed);
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl FileObject for RandomFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        for chunk in buf.chunks_mut(8) {
+            let random = self.next_random().to_ne_bytes();
+            chunk.copy_from_slice(&random[..chunk.len()]);
+        }
+        Ok(buf.len())
+    }
+
+    fn write(&self, buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Ok(buf.len())
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(())
+    }
+}
+
+/// The raw memory of the linear framebuffer (`pitch * height` bytes, in the pixel format of the framebuffer)
+#[derive(Debug)]
+struct FramebufferFile;
+
+impl FramebufferFile {
+    /// Helper function running `f` on the memory of the framebuffer (while holding the lock of the framebuffer)
+    fn with_memory<R>(&self, f: impl FnOnce(&mut [u8]) -> R) -> R {
+        let mut lfb = buffered_lfb().lock();
+        let lfb = lfb.direct_lfb();
+        let size = lfb.pitch() as usize * lfb.height() as usize;
+        f(unsafe { slice::from_raw_parts_mut(lfb.buffer(), size) })
+    }
+}
+
+impl FileObject for FramebufferFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), self.with_memory(|memory| memory.len())))
+    }
+
+    fn read(&self, buf: &mut [u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        self.with_memory(|memory| {
+            let len = buf.len().min(memory.len().saturating_sub(offset));
+            if len > 0 {
+                buf[..len].copy_from_slice(&memory[offset..offset + len]);
+            }
+            Ok(len)
+        })
+    }
+
+    fn write(&self, buf: &[u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        self.with_memory(|memory| {
+            let len = buf.len().min(memory.len().saturating_sub(offset));
+            if len == 0 {
+                return Err(Errno::ENOSPC);
+            }
+            memory[offset..offset + len].copy_from_slice(&buf[..len]);
+            Ok(len)
+        })
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(()) // the size of the framebuffer is fixed
+    }
+}
@@ -0,0 +1,1294 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: fat32                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ FAT32 file system on top of a block device (e.g. a disk partition).     ║
+   ║ It supports reading and writing files and directories, including long   ║
+   ║ file names (VFAT). Named pipes are not supported.                       ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use super::stat::{MODE_DIR, MODE_FILE, Mode, Stat};
+use super::traits::{DirectoryObject, FileObject, FileSystem, NamedObject};
+use crate::timer;
+use crate::storage::block::BlockDevice;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::{Arc, Weak};
+use alloc::vec;
+use alloc::vec::Vec;
+use chrono::{DateTime, Datelike, NaiveDate, Timelike};
+use core::any::Any;
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::result::Result;
+use naming::shared_types::{DirEntry, FileType, OpenOptions};
+use spin::Mutex;
+use syscall::return_vals::Errno;
+
+/// Size of a directory entry in bytes
+const DIR_ENTRY_SIZE: usize = 32;
+
+/// Maximum number of entries in a directory (limits a directory to 2 MiB)
+const MAX_DIR_ENTRIES: usize = 0x10000;
+
+/// Attributes of a directory entry
+const ATTR_VOLUME_ID: u8 = 0x08;
+const ATTR_DIRECTORY: u8 = 0x10;
+const ATTR_ARCHIVE: u8 = 0x20;
+const ATTR_LONG_NAME: u8 = 0x0f;
+const ATTR_LONG_NAME_MASK: u8 = 0x3f;
+
+/// Markers in the first byte of a directory entry
+const ENTRY_END: u8 = 0x00;
+const ENTRY_FREE: u8 = 0xe5;
+const ENTRY_KANJI_E5: u8 = 0x05;
+
+/// Flags in the reserved byte of a short entry, marking a lower case base name or extension
+const NTRES_LOWER_BASE: u8 = 0x08;
+const NTRES_LOWER_EXT: u8 = 0x10;
+
+/// Long file names (VFAT) are stored in UTF-16 across several directory entries
+const LFN_LAST_ENTRY: u8 = 0x40;
+const LFN_ORDER_MASK: u8 = 0x1f;
+const LFN_CHARS_PER_ENTRY: usize = 13;
+const LFN_CHAR_OFFSETS: [usize; LFN_CHARS_PER_ENTRY] = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+const MAX_NAME_LEN: usize = 255;
+
+/// Special characters allowed in short (8.3) names
+const SHORT_NAME_SPECIAL_CHARS: &str = "$%'-_@~`!(){}^#&";
+
+/// Special values in the file allocation table (only the lower 28 bits of an entry are used)
+const CLUSTER_FREE: u32 = 0;
+const CLUSTER_MASK: u32 = 0x0fff_ffff;
+const CLUSTER_END: u32 = 0x0fff_ffff;
+const CLUSTER_END_MIN: u32 = 0x0fff_fff8;
+
+/// Signatures and special values of the FSInfo sector
+const FS_INFO_LEAD_SIG: u32 = 0x4161_5252;
+const FS_INFO_STRUCT_SIG: u32 = 0x6141_7272;
+const FS_INFO_UNKNOWN: u32 = 0xffff_ffff;
+
+/// 01.01.1980, the earliest date representable in a directory entry
+const FAT_EPOCH_DATE: u16 = (1 << 5) | 1;
+
+pub struct Fat32 {
+    root_dir: Arc<Dir>,
+}
+
+impl Fat32 {
+    /// Open the FAT32 file system stored on `device`. \
+    /// Returns `Err(Errno::EINVAL)` if the device does not contain a FAT32 file system.
+    pub fn new(device: Arc<dyn BlockDevice + Send + Sync>) -> Result<Fat32, Errno> {
+        let volume = Arc::new(Volume::new(device)?);
+
+        Ok(Fat32 {
+            root_dir: Arc::new(Dir {
+                cluster: volume.root_cluster,
+                stat: Stat::new(Mode::new(MODE_DIR), 0),
+                removed: AtomicBool::new(false),
+                volume,
+            }),
+        })
+    }
+}
+
+impl FileSystem for Fat32 {
+    fn root_dir(&self) -> Arc<dyn DirectoryObject> {
+        self.root_dir.clone()
+    }
+}
+
+/// Position of a short directory entry on the device
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct EntryLocation {
+    sector: u64,
+    offset: usize,
+}
+
+/// A directory entry (without its long file name entries) together with its decoded name. \
+/// `first_slot` is the index of its first long name entry (or of the entry itself, if it has no long name).
+struct Entry {
+    name: String,
+    raw: [u8; DIR_ENTRY_SIZE],
+    location: EntryLocation,
+    first_slot: usize,
+    slot: usize,
+}
+
+/// The contents of a directory, read from all clusters of its cluster chain
+struct DirData {
+    clusters: Vec<u32>,
+    data: Vec<u8>,
+}
+
+/// Allocation hints, as stored in the FSInfo sector
+struct AllocState {
+    free_count: u32,
+    next_free: u32,
+}
+
+/// Layout of a FAT32 volume, read from the BIOS parameter block (BPB) in the boot sector.
+struct Volume {
+    device: Arc<dyn BlockDevice + Send + Sync>,
+    sector_size: usize,
+    sectors_per_cluster: usize,
+    fat_start: u64,
+    fat_sectors: u64,
+    fat_count: u64,
+    active_fat: Option<u64>,
+    data_start: u64,
+    cluster_count: u32,
+    root_cluster: u32,
+    fs_info_sector: Option<u64>,
+    // All operations on the volume are serialized by this lock
+    state: Mutex<AllocState>,
+    // Nodes handed out to the naming service, so that each entry is represented by a single object (e.g. for `is_open()`).
+    // Files are identified by the position of their directory entry, directories by their first cluster.
+    files: Mutex<BTreeMap<EntryLocation, Weak<File>>>,
+    dirs: Mutex<BTreeMap<u32, Weak<Dir>>>,
+}
+
+impl Volume {
+    fn new(device: Arc<dyn BlockDevice + Send + Sync>) -> Result<Volume, Errno> {
+        let sector_size = device.sector_size() as usize;
+        if sector_size < 512 {
+            return Err(Errno::EINVAL);
+        }
+
+        let mut boot_sector = vec![0u8; sector_size];
+        if device.read(0, 1, &mut boot_sector) != 1 {
+            return Err(Errno::EIO);
+        }
+        if boot_sector[510] != 0x55 || boot_sector[511] != 0xaa {
+            return Err(Errno::EINVAL);
+        }
+
+        let bytes_per_sector = read_u16(&boot_sector, 11) as usize;
+        let sectors_per_cluster = boot_sector[13] as usize;
+        let reserved_sectors = read_u16(&boot_sector, 14) as u64;
+        let fat_count = boot_sector[16] as u64;
+        let root_entry_count = read_u16(&boot_sector, 17);
+        let total_sectors = match read_u16(&boot_sector, 19) {
+            0 => read_u32(&boot_sector, 32) as u64,
+            count => count as u64,
+        };
+        let fat_size_16 = read_u16(&boot_sector, 22);
+        let fat_sectors = read_u32(&boot_sector, 36) as u64;
+        let ext_flags = read_u16(&boot_sector, 40);
+        let root_cluster = read_u32(&boot_sector, 44);
+        let fs_info_sector = read_u16(&boot_sector, 48) as u64;
+
+        // FAT12 and FAT16 have a fixed size root directory and store the FAT size in 16 bits
+        if bytes_per_sector != sector_size
+            || !sectors_per_cluster.is_power_of_two()
+            || reserved_sectors == 0
+            || fat_count == 0
+            || root_entry_count != 0
+            || fat_size_16 != 0
+            || fat_sectors == 0
+        {
+            return Err(Errno::EINVAL);
+        }
+
+        let data_start = reserved_sectors + fat_count * fat_sectors;
+        if total_sectors <= data_start || total_sectors > device.sector_count() {
+            return Err(Errno::EINVAL);
+        }
+
+        // The number of clusters is limited by the size of the data region and by the number of FAT entries
+        let fat_entries = fat_sectors * sector_size as u64 / 4;
+        let cluster_count = ((total_sectors - data_start) / sectors_per_cluster as u64).min(fat_entries - 2) as u32;
+        if root_cluster < 2 || root_cluster >= cluster_count + 2 {
+            return Err(Errno::EINVAL);
+        }
+
+        // Bit 7 of the extended flags disables FAT mirroring, bits 0-3 select the only active FAT
+        let active_fat = match ext_flags & 0x80 {
+            0 => None,
+            _ => Some((ext_flags & 0x0f) as u64),
+        };
+        if active_fat.is_some_and(|fat| fat >= fat_count) {
+            return Err(Errno::EINVAL);
+        }
+
+        let mut volume = Volume {
+            device,
+            sector_size,
+            sectors_per_cluster,
+            fat_start: reserved_sectors,
+            fat_sectors,
+            fat_count,
+            active_fat,
+            data_start,
+            cluster_count,
+            root_cluster,
+            fs_info_sector: None,
+            state: Mutex::new(AllocState {
+                free_count: FS_INFO_UNKNOWN,
+                next_free: 2,
+            }),
+            files: Mutex::new(BTreeMap::new()),
+            dirs: Mutex::new(BTreeMap::new()),
+        };
+
+        // The FSInfo sector only contains hints, which are used to speed up cluster allocation
+        if fs_info_sector != 0 && fs_info_sector < reserved_sectors {
+            let mut fs_info = vec![0u8; sector_size];
+            if volume.device.read(fs_info_sector, 1, &mut fs_info) == 1
+                && read_u32(&fs_info, 0) == FS_INFO_LEAD_SIG
+                && read_u32(&fs_info, 484) == FS_INFO_STRUCT_SIG
+            {
+                volume.fs_info_sector = Some(fs_info_sector);
+
+                let next_free = read_u32(&fs_info, 492);
+                let next_free_valid = volume.is_valid_cluster(next_free);
+                let state = volume.state.get_mut();
+                state.free_count = read_u32(&fs_info, 488);
+                if next_free_valid {
+                    state.next_free = next_free;
+                }
+            }
+        }
+
+        Ok(volume)
+    }
+
+    fn cluster_size(&self) -> usize {
+        self.sector_size * self.sectors_per_cluster
+    }
+
+    fn is_valid_cluster(&self, cluster: u32) -> bool {
+        cluster >= 2 && cluster < self.cluster_count + 2
+    }
+
+    fn cluster_sector(&self, cluster: u32) -> u64 {
+        self.data_start + (cluster as u64 - 2) * self.sectors_per_cluster as u64
+    }
+
+    fn read_sectors(&self, sector: u64, buffer: &mut [u8]) -> Result<(), Errno> {
+        let count = buffer.len() / self.sector_size;
+        match self.device.read(sector, count, buffer) == count {
+            true => Ok(()),
+            false => Err(Errno::EIO),
+        }
+    }
+
+    fn write_sectors(&self, sector: u64, buffer: &[u8]) -> Result<(), Errno> {
+        let count = buffer.len() / self.sector_size;
+        match self.device.write(sector, count, buffer) == count {
+            true => Ok(()),
+            false => Err(Errno::EIO),
+        }
+    }
+
+    fn read_cluster(&self, cluster: u32, buffer: &mut [u8]) -> Result<(), Errno> {
+        self.read_sectors(self.cluster_sector(cluster), buffer)
+    }
+
+    fn write_cluster(&self, cluster: u32, buffer: &[u8]) -> Result<(), Errno> {
+        self.write_sectors(self.cluster_sector(cluster), buffer)
+    }
+
+    /// Returns the sector (relative to the start of a FAT) and the byte offset of the FAT entry for `cluster`.
+    fn fat_position(&self, cluster: u32) -> (u64, usize) {
+        let offset = cluster as usize * 4;
+        ((offset / self.sector_size) as u64, offset % self.sector_size)
+    }
+
+    /// First sector of the FAT used for reading
+    fn read_fat_start(&self) -> u64 {
+        self.fat_start + self.active_fat.unwrap_or(0) * self.fat_sectors
+    }
+
+    /// Set the FAT entry for `cluster` to `value` in all FATs (or only the active one, if mirroring is disabled).
+    fn set_fat_entry(&self, cluster: u32, value: u32) -> Result<(), Errno> {
+        let (sector, offset) = self.fat_position(cluster);
+        let mut buffer = vec![0u8; self.sector_size];
+
+        for fat in 0..self.fat_count {
+            if self.active_fat.is_some_and(|active| active != fat) {
+                continue;
+            }
+
+            // The upper 4 bits of an entry are reserved and must be preserved
+            let sector = self.fat_start + fat * self.fat_sectors + sector;
+            self.read_sectors(sector, &mut buffer)?;
+            let entry = (read_u32(&buffer, offset) & !CLUSTER_MASK) | (value & CLUSTER_MASK);
+            write_u32(&mut buffer, offset, entry);
+            self.write_sectors(sector, &buffer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Follow the cluster chain starting at `first_cluster`. \
+    /// An empty file has no clusters and is represented by `first_cluster == 0`.
+    fn cluster_chain(&self, first_cluster: u32) -> Result<Vec<u32>, Errno> {
+        let mut chain = Vec::new();
+        let mut buffer = vec![0u8; self.sector_size];
+        let mut loaded_sector = None;
+
+        let mut cluster = first_cluster;
+        while self.is_valid_cluster(cluster) {
+            // A chain longer than the volume can only be caused by a loop in a corrupted FAT
+            if chain.len() >= self.cluster_count as usize {
+                return Err(Errno::EIO);
+            }
+            chain.push(cluster);
+
+            let (sector, offset) = self.fat_position(cluster);
+            if loaded_sector != Some(sector) {
+                self.read_sectors(self.read_fat_start() + sector, &mut buffer)?;
+                loaded_sector = Some(sector);
+            }
+            cluster = read_u32(&buffer, offset) & CLUSTER_MASK;
+        }
+
+        if cluster < CLUSTER_END_MIN && !(chain.is_empty() && cluster == CLUSTER_FREE) {
+            return Err(Errno::EIO);
+        }
+
+        Ok(chain)
+    }
+
+    /// Allocate a zeroed cluster and append it to the chain ending with `last_cluster` (if any). \
+    /// Returns `Ok(cluster)` or `Err(Errno::ENOSPC)` if the volume is full.
+    fn allocate_cluster(&self, state: &mut AllocState, last_cluster: Option<u32>) -> Result<u32, Errno> {
+        let mut buffer = vec![0u8; self.sector_size];
+        let mut loaded_sector = None;
+
+        let mut cluster = state.next_free;
+        for _ in 0..self.cluster_count {
+            if !self.is_valid_cluster(cluster) {
+                cluster = 2;
+            }
+
+            let (sector, offset) = self.fat_position(cluster);
+            if loaded_sector != Some(sector) {
+                self.read_sectors(self.read_fat_start() + sector, &mut buffer)?;
+                loaded_sector = Some(sector);
+            }
+
+            if read_u32(&buffer, offset) & CLUSTER_MASK == CLUSTER_FREE {
+                // Clear the cluster first, so that new directory clusters contain no stale entries
+                // and new file clusters read as zeros
+                self.write_cluster(cluster, &vec![0u8; self.cluster_size()])?;
+                self.set_fat_entry(cluster, CLUSTER_END)?;
+                if let Some(last_cluster) = last_cluster {
+                    self.set_fat_entry(last_cluster, cluster)?;
+                }
+
+                state.next_free = cluster + 1;
+                if state.free_count != FS_INFO_UNKNOWN {
+                    state.free_count = state.free_count.saturating_sub(1);
+                }
+                self.write_fs_info(state)?;
+
+                return Ok(cluster);
+            }
+
+            cluster += 1;
+        }
+
+        Err(Errno::ENOSPC)
+    }
+
+    /// Mark all clusters of the chain starting at `first_cluster` as free.
+    fn free_chain(&self, state: &mut AllocState, first_cluster: u32) -> Result<(), Errno> {
+        let chain = self.cluster_chain(first_cluster)?;
+        for cluster in &chain {
+            self.set_fat_entry(*cluster, CLUSTER_FREE)?;
+        }
+
+        if state.free_count != FS_INFO_UNKNOWN {
+            state.free_count += chain.len() as u32;
+        }
+        self.write_fs_info(state)
+    }
+
+    /// Write the allocation hints back to the FSInfo sector (if the volume has one).
+    fn write_fs_info(&self, state: &AllocState) -> Result<(), Errno> {
+        if let Some(sector) = self.fs_info_sector {
+            let mut buffer = vec![0u8; self.sector_size];
+            self.read_sectors(sector, &mut buffer)?;
+            write_u32(&mut buffer, 488, state.free_count);
+            write_u32(&mut buffer, 492, state.next_free);
+            self.write_sectors(sector, &buffer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the contents of the directory starting at `cluster`.
+    fn read_dir(&self, cluster: u32) -> Result<DirData, Errno> {
+        let clusters = self.cluster_chain(cluster)?;
+        let cluster_size = self.cluster_size();
+
+        let mut data = vec![0u8; clusters.len() * cluster_size];
+        for (index, cluster) in clusters.iter().enumerate() {
+            self.read_cluster(*cluster, &mut data[index * cluster_size..(index + 1) * cluster_size])?;
+        }
+
+        Ok(DirData { clusters, data })
+    }
+
+    /// Position of the directory entry with index `slot` on the device
+    fn slot_location(&self, dir: &DirData, slot: usize) -> EntryLocation {
+        let offset = slot * DIR_ENTRY_SIZE;
+        let sector = offset / self.sector_size;
+
+        EntryLocation {
+            sector: self.cluster_sector(dir.clusters[sector / self.sectors_per_cluster]) + (sector % self.sectors_per_cluster) as u64,
+            offset: offset % self.sector_size,
+        }
+    }
+
+    /// Write `count` directory entries starting at index `first_slot` back to the device.
+    fn write_dir_slots(&self, dir: &DirData, first_slot: usize, count: usize) -> Result<(), Errno> {
+        let first_sector = first_slot * DIR_ENTRY_SIZE / self.sector_size;
+        let last_sector = ((first_slot + count) * DIR_ENTRY_SIZE - 1) / self.sector_size;
+
+        for sector in first_sector..=last_sector {
+            let device_sector = self.cluster_sector(dir.clusters[sector / self.sectors_per_cluster]) + (sector % self.sectors_per_cluster) as u64;
+            self.write_sectors(device_sector, &dir.data[sector * self.sector_size..(sector + 1) * self.sector_size])?;
+        }
+
+        Ok(())
+    }
+
+    /// Decode all entries of a directory, except for the volume label and the entries "." and "..".
+    fn parse_entries(&self, dir: &DirData) -> Vec<Entry> {
+        let mut entries = Vec::new();
+
+        // Long name entries precede their short entry in reverse order (last part first)
+        let mut long_name: Vec<u16> = Vec::new();
+        let mut long_name_order = 0;
+        let mut long_name_checksum = 0;
+        let mut long_name_start = 0;
+
+        for (slot, raw) in dir.data.chunks_exact(DIR_ENTRY_SIZE).enumerate() {
+            match raw[0] {
+                ENTRY_END => break,
+                ENTRY_FREE => {
+                    long_name_order = 0;
+                    continue;
+                }
+                _ => {}
+            }
+
+            if raw[11] & ATTR_LONG_NAME_MASK == ATTR_LONG_NAME {
+                let order = raw[0] & LFN_ORDER_MASK;
+                if raw[0] & LFN_LAST_ENTRY != 0 {
+                    long_name = long_name_part(raw);
+                    long_name_order = order;
+                    long_name_checksum = raw[13];
+                    long_name_start = slot;
+                } else if long_name_order > 1 && order == long_name_order - 1 && raw[13] == long_name_checksum {
+                    let mut part = long_name_part(raw);
+                    part.append(&mut long_name);
+                    long_name = part;
+                    long_name_order = order;
+                } else {
+                    // Orphaned long name entry
+                    long_name_order = 0;
+                }
+                continue;
+            }
+
+            let has_long_name = long_name_order == 1 && long_name_checksum == short_name_checksum(&raw[0..11]);
+            long_name_order = 0;
+
+            if raw[11] & ATTR_VOLUME_ID != 0 || raw[0] == b'.' {
+                continue;
+            }
+
+            let name = match has_long_name {
+                true => String::from_utf16_lossy(&long_name),
+                false => short_name_to_string(raw),
+            };
+
+            entries.push(Entry {
+                name,
+                raw: raw.try_into().unwrap(),
+                location: self.slot_location(dir, slot),
+                first_slot: if has_long_name { long_name_start } else { slot },
+                slot,
+            });
+        }
+
+        entries
+    }
+
+    /// Look up the entry `name` (case-insensitive) in the directory starting at `cluster`.
+    fn find_entry(&self, cluster: u32, name: &str) -> Result<Entry, Errno> {
+        let dir = self.read_dir(cluster)?;
+        self.parse_entries(&dir)
+            .into_iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+            .ok_or(Errno::ENOENT)
+    }
+
+    /// Create a new entry `name` in the directory starting at `dir_cluster`. \
+    /// If `name` is not a valid short (8.3) name, long name entries and a unique short name are generated.
+    fn create_entry(&self, state: &mut AllocState, dir_cluster: u32, name: &str, attr: u8, first_cluster: u32) -> Result<Entry, Errno> {
+        validate_name(name)?;
+
+        let mut dir = self.read_dir(dir_cluster)?;
+        let entries = self.parse_entries(&dir);
+        if entries.iter().any(|entry| entry.name.eq_ignore_ascii_case(name)) {
+            return Err(Errno::EEXIST);
+        }
+
+        let short_names: Vec<[u8; 11]> = entries.iter().map(|entry| entry.raw[0..11].try_into().unwrap()).collect();
+        let (short_name, ntres, long_name) = match to_short_name(name) {
+            Some((short_name, ntres)) if !short_names.contains(&short_name) => (short_name, ntres, Vec::new()),
+            _ => (generate_short_name(name, &short_names)?, 0, name.encode_utf16().collect()),
+        };
+
+        let long_entries = long_name.len().div_ceil(LFN_CHARS_PER_ENTRY);
+        let needed = long_entries + 1;
+
+        // Find enough consecutive free entries or grow the directory
+        let (first_slot, past_end) = loop {
+            if let Some(found) = find_free_slots(&dir.data, needed) {
+                break found;
+            }
+            if dir.data.len() / DIR_ENTRY_SIZE >= MAX_DIR_ENTRIES {
+                return Err(Errno::ENOSPC);
+            }
+
+            let cluster = self.allocate_cluster(state, dir.clusters.last().copied())?;
+            dir.clusters.push(cluster);
+            dir.data.resize(dir.data.len() + self.cluster_size(), 0);
+        };
+
+        let checksum = short_name_checksum(&short_name);
+        for index in 0..long_entries {
+            let order = long_entries - index;
+            let raw = &mut dir.data[(first_slot + index) * DIR_ENTRY_SIZE..][..DIR_ENTRY_SIZE];
+            raw.fill(0);
+            raw[0] = order as u8 | if index == 0 { LFN_LAST_ENTRY } else { 0 };
+            raw[11] = ATTR_LONG_NAME;
+            raw[13] = checksum;
+
+            // The name is terminated by 0x0000 and padded with 0xffff
+            for (char_index, offset) in LFN_CHAR_OFFSETS.iter().enumerate() {
+                let position = (order - 1) * LFN_CHARS_PER_ENTRY + char_index;
+                let c = match position.cmp(&long_name.len()) {
+                    core::cmp::Ordering::Less => long_name[position],
+                    core::cmp::Ordering::Equal => 0x0000,
+                    core::cmp::Ordering::Greater => 0xffff,
+                };
+                write_u16(raw, *offset, c);
+            }
+        }
+
+        let slot = first_slot + long_entries;
+        let raw = new_short_entry(&short_name, ntres, attr, first_cluster);
+        dir.data[slot * DIR_ENTRY_SIZE..][..DIR_ENTRY_SIZE].copy_from_slice(&raw);
+
+        // When the new entries replaced the end marker, the entry behind them becomes the new end marker
+        let mut count = needed;
+        if past_end && (slot + 1) * DIR_ENTRY_SIZE < dir.data.len() {
+            dir.data[(slot + 1) * DIR_ENTRY_SIZE] = ENTRY_END;
+            count += 1;
+        }
+        self.write_dir_slots(&dir, first_slot, count)?;
+
+        Ok(Entry {
+            name: name.to_string(),
+            raw,
+            location: self.slot_location(&dir, slot),
+            first_slot,
+            slot,
+        })
+    }
+
+    /// Mark `entry` (including its long name entries) in the directory starting at `dir_cluster` as free. \
+    /// The clusters of the entry are not freed.
+    fn remove_entry(&self, dir_cluster: u32, entry: &Entry) -> Result<(), Errno> {
+        let mut dir = self.read_dir(dir_cluster)?;
+        for slot in entry.first_slot..=entry.slot {
+            dir.data[slot * DIR_ENTRY_SIZE] = ENTRY_FREE;
+        }
+
+        self.write_dir_slots(&dir, entry.first_slot, entry.slot - entry.first_slot + 1)
+    }
+
+    /// Remove the entry `name` from the directory starting at `dir_cluster` and free its clusters. \
+    /// Directories must be empty and are only removed if `is_dir` is set, files only if it is not set.
+    fn delete_entry(&self, state: &mut AllocState, dir_cluster: u32, name: &str, is_dir: bool) -> Result<(), Errno> {
+        let entry = self.find_entry(dir_cluster, name)?;
+        let first_cluster = entry_first_cluster(&entry.raw);
+
+        match (entry.raw[11] & ATTR_DIRECTORY != 0, is_dir) {
+            (true, false) => return Err(Errno::EISDIR),
+            (false, true) => return Err(Errno::ENOTDIR),
+            (true, true) => {
+                if !self.parse_entries(&self.read_dir(first_cluster)?).is_empty() {
+                    return Err(Errno::ENOTEMPTY);
+                }
+            }
+            (false, false) => {}
+        }
+
+        self.remove_entry(dir_cluster, &entry)?;
+        if is_dir {
+            self.remove_dir_node(first_cluster);
+        } else {
+            self.move_file_node(entry.location, None);
+        }
+        if first_cluster != 0 {
+            self.free_chain(state, first_cluster)?;
+        }
+
+        Ok(())
+    }
+
+    /// Let the node of the file, whose directory entry has moved from `old` to `new`, refer to `new`. \
+    /// If the entry has been removed (`new` is `None`), all further operations on the node fail with `ENOENT`.
+    fn move_file_node(&self, old: EntryLocation, new: Option<EntryLocation>) {
+        let node = self.files.lock().remove(&old).and_then(|node| node.upgrade());
+        if let Some(node) = node {
+            *node.location.lock() = new;
+            if let Some(new) = new {
+                self.files.lock().insert(new, Arc::downgrade(&node));
+            }
+        }
+    }
+
+    /// Mark the node of the removed directory starting at `cluster`, so that all further operations on it fail with `ENOENT`.
+    fn remove_dir_node(&self, cluster: u32) {
+        let node = self.dirs.lock().remove(&cluster).and_then(|node| node.upgrade());
+        if let Some(node) = node {
+            node.removed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Move the entry `old_name` from the directory starting at `old_dir_cluster`
+    /// to `new_name` in the directory starting at `new_dir_cluster`.
+    fn move_entry(&self, state: &mut AllocState, old_dir_cluster: u32, old_name: &str, new_dir_cluster: u32, new_name: &str) -> Result<(), Errno> {
+        let entry = self.find_entry(old_dir_cluster, old_name)?;
+        let attr = entry.raw[11];
+        let first_cluster = entry_first_cluster(&entry.raw);
+
+        // Changing only the case of a name requires removing the old entry first, as names are case-insensitive
+        let new_entry = if old_dir_cluster == new_dir_cluster && old_name.eq_ignore_ascii_case(new_name) {
+            let old_dir = self.read_dir(old_dir_cluster)?;
+            self.remove_entry(old_dir_cluster, &entry)?;
+            match self.create_entry(state, new_dir_cluster, new_name, attr, first_cluster) {
+                Ok(new_entry) => new_entry,
+                Err(error) => {
+                    // Restore the old entry
+                    self.write_dir_slots(&old_dir, entry.first_slot, entry.slot - entry.first_slot + 1)?;
+                    return Err(error);
+                }
+            }
+        } else {
+            let new_entry = self.create_entry(state, new_dir_cluster, new_name, attr, first_cluster)?;
+            self.remove_entry(old_dir_cluster, &entry)?;
+            new_entry
+        };
+
+        // Keep size and timestamps, only the name changes
+        let mut raw = entry.raw;
+        raw[0..11].copy_from_slice(&new_entry.raw[0..11]);
+        raw[12] = new_entry.raw[12];
+        self.write_entry(new_entry.location, &raw)?;
+        if attr & ATTR_DIRECTORY == 0 {
+            self.move_file_node(entry.location, Some(new_entry.location));
+        }
+
+        // A moved directory needs to point to its new parent
+        if attr & ATTR_DIRECTORY != 0 && old_dir_cluster != new_dir_cluster {
+            let parent_cluster = if new_dir_cluster == self.root_cluster { 0 } else { new_dir_cluster };
+            let mut dir = self.read_dir(first_cluster)?;
+            set_entry_first_cluster(&mut dir.data[DIR_ENTRY_SIZE..2 * DIR_ENTRY_SIZE], parent_cluster);
+            self.write_dir_slots(&dir, 1, 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the subdirectory `name` in the directory starting at `dir_cluster`.
+    fn create_dir(&self, state: &mut AllocState, dir_cluster: u32, name: &str) -> Result<Entry, Errno> {
+        let cluster = self.allocate_cluster(state, None)?;
+
+        // Every directory, except for the root directory, starts with the entries "." and ".."
+        let parent_cluster = if dir_cluster == self.root_cluster { 0 } else { dir_cluster };
+        let mut data = vec![0u8; self.cluster_size()];
+        data[0..DIR_ENTRY_SIZE].copy_from_slice(&new_short_entry(b".          ", 0, ATTR_DIRECTORY, cluster));
+        data[DIR_ENTRY_SIZE..2 * DIR_ENTRY_SIZE].copy_from_slice(&new_short_entry(b"..         ", 0, ATTR_DIRECTORY, parent_cluster));
+
+        let result = self
+            .write_cluster(cluster, &data)
+            .and_then(|_| self.create_entry(state, dir_cluster, name, ATTR_DIRECTORY, cluster));
+        if result.is_err() {
+            self.free_chain(state, cluster)?;
+        }
+
+        result
+    }
+
+    fn read_entry(&self, location: EntryLocation) -> Result<[u8; DIR_ENTRY_SIZE], Errno> {
+        let mut buffer = vec![0u8; self.sector_size];
+        self.read_sectors(location.sector, &mut buffer)?;
+        Ok(buffer[location.offset..location.offset + DIR_ENTRY_SIZE].try_into().unwrap())
+    }
+
+    fn write_entry(&self, location: EntryLocation, raw: &[u8; DIR_ENTRY_SIZE]) -> Result<(), Errno> {
+        let mut buffer = vec![0u8; self.sector_size];
+        self.read_sectors(location.sector, &mut buffer)?;
+        buffer[location.offset..location.offset + DIR_ENTRY_SIZE].copy_from_slice(raw);
+        self.write_sectors(location.sector, &buffer)
+    }
+
+    /// Read `buffer.len()` bytes at `offset` from the data stored in the cluster `chain`.
+    fn read_data(&self, chain: &[u32], offset: usize, buffer: &mut [u8]) -> Result<(), Errno> {
+        let cluster_size = self.cluster_size();
+        let mut cluster_buffer = vec![0u8; cluster_size];
+
+        let mut done = 0;
+        while done < buffer.len() {
+            let position = offset + done;
+            let cluster = *chain.get(position / cluster_size).ok_or(Errno::EIO)?;
+            let start = position % cluster_size;
+            let count = (cluster_size - start).min(buffer.len() - done);
+
+            if count == cluster_size {
+                self.read_cluster(cluster, &mut buffer[done..done + count])?;
+            } else {
+                self.read_cluster(cluster, &mut cluster_buffer)?;
+                buffer[done..done + count].copy_from_slice(&cluster_buffer[start..start + count]);
+            }
+
+            done += count;
+        }
+
+        Ok(())
+    }
+
+    /// Write `buffer` at `offset` into the data stored in the cluster `chain`.
+    fn write_data(&self, chain: &[u32], offset: usize, buffer: &[u8]) -> Result<(), Errno> {
+        let cluster_size = self.cluster_size();
+        let mut cluster_buffer = vec![0u8; cluster_size];
+
+        let mut done = 0;
+        while done < buffer.len() {
+            let position = offset + done;
+            let cluster = *chain.get(position / cluster_size).ok_or(Errno::EIO)?;
+            let start = position % cluster_size;
+            let count = (cluster_size - start).min(buffer.len() - done);
+
+            if count == cluster_size {
+                self.write_cluster(cluster, &buffer[done..done + count])?;
+            } else {
+                self.read_cluster(cluster, &mut cluster_buffer)?;
+                cluster_buffer[start..start + count].copy_from_slice(&buffer[done..done + count]);
+                self.write_cluster(cluster, &cluster_buffer)?;
+            }
+
+            done += count;
+        }
+
+        Ok(())
+    }
+
+    fn read_file(&self, location: EntryLocation, buffer: &mut [u8], offset: usize) -> Result<usize, Errno> {
+        let raw = self.read_entry(location)?;
+        let size = read_u32(&raw, 28) as usize;
+        if offset >= size {
+            return Ok(0);
+        }
+
+        let len = buffer.len().min(size - offset);
+        let chain = self.cluster_chain(entry_first_cluster(&raw))?;
+        self.read_data(&chain, offset, &mut buffer[..len])?;
+
+        Ok(len)
+    }
+
+    fn write_file(&self, state: &mut AllocState, location: EntryLocation, buffer: &[u8], offset: usize) -> Result<usize, Errno> {
+        let mut raw = self.read_entry(location)?;
+        let size = read_u32(&raw, 28) as usize;
+        let end = offset + buffer.len();
+        if end > u32::MAX as usize {
+            return Err(Errno::EINVAL);
+        }
+
+        // Grow the cluster chain if the write extends the file
+        let mut chain = self.cluster_chain(entry_first_cluster(&raw))?;
+        let allocated = chain.len() * self.cluster_size();
+        while chain.len() < end.div_ceil(self.cluster_size()) {
+            match self.allocate_cluster(state, chain.last().copied()) {
+                Ok(cluster) => {
+                    if chain.is_empty() {
+                        set_entry_first_cluster(&mut raw, cluster);
+                    }
+                    chain.push(cluster);
+                }
+                Err(error) => {
+                    // Keep already allocated clusters reachable
+                    self.write_entry(location, &raw)?;
+                    return Err(error);
+                }
+            }
+        }
+
+        // New clusters are zeroed, but the space behind the old end of the file may contain stale data
+        if offset > size && size < allocated {
+            self.write_data(&chain, size, &vec![0u8; offset.min(allocated) - size])?;
+        }
+        self.write_data(&chain, offset, buffer)?;
+
+        if end > size {
+            write_u32(&mut raw, 28, end as u32);
+        }
+        let (date, time) = current_timestamp();
+        write_u16(&mut raw, 18, date);
+        write_u16(&mut raw, 22, time);
+        write_u16(&mut raw, 24, date);
+        self.write_entry(location, &raw)?;
+
+        Ok(buffer.len())
+    }
+
+    fn truncate_file(&self, state: &mut AllocState, location: EntryLocation, size: usize) -> Result<(), Errno> {
+        let mut raw = self.read_entry(location)?;
+        if size >= read_u32(&raw, 28) as usize {
+            // Growing is an empty write behind the end of the file, which fills the gap with zeros
+            return self.write_file(state, location, &[], size).map(|_| ());
+        }
+
+        // Free all clusters behind the new end of the file
+        let chain = self.cluster_chain(entry_first_cluster(&raw))?;
+        let needed = size.div_ceil(self.cluster_size());
+        if needed < chain.len() {
+            if needed == 0 {
+                set_entry_first_cluster(&mut raw, CLUSTER_FREE);
+            } else {
+                self.set_fat_entry(chain[needed - 1], CLUSTER_END)?;
+            }
+            self.free_chain(state, chain[needed])?;
+        }
+
+        write_u32(&mut raw, 28, size as u32);
+        let (date, time) = current_timestamp();
+        write_u16(&mut raw, 18, date);
+        write_u16(&mut raw, 22, time);
+        write_u16(&mut raw, 24, date);
+        self.write_entry(location, &raw)
+    }
+}
+
+struct Dir {
+    volume: Arc<Volume>,
+    cluster: u32,
+    stat: Stat,
+    removed: AtomicBool,
+}
+
+impl Dir {
+    /// Return the node of `entry`, which is created, if there is none yet.
+    fn to_named_object(&self, entry: Entry) -> NamedObject {
+        if entry.raw[11] & ATTR_DIRECTORY != 0 {
+            let cluster = entry_first_cluster(&entry.raw);
+            let mut dirs = self.volume.dirs.lock();
+            let dir = dirs.get(&cluster).and_then(Weak::upgrade).unwrap_or_else(|| {
+                let dir = Arc::new(Dir {
+                    volume: self.volume.clone(),
+                    cluster,
+                    stat: entry_stat(&entry.raw),
+                    removed: AtomicBool::new(false),
+                });
+                dirs.insert(cluster, Arc::downgrade(&dir));
+                dir
+            });
+            (dir as Arc<dyn DirectoryObject>).into()
+        } else {
+            let mut files = self.volume.files.lock();
+            let file = files.get(&entry.location).and_then(Weak::upgrade).unwrap_or_else(|| {
+                let file = Arc::new(File {
+                    volume: self.volume.clone(),
+                    location: Mutex::new(Some(entry.location)),
+                });
+                files.insert(entry.location, Arc::downgrade(&file));
+                file
+            });
+            (file as Arc<dyn FileObject>).into()
+        }
+    }
+
+    /// Return the first cluster of the directory or `ENOENT`, if it has been removed
+    fn cluster(&self) -> Result<u32, Errno> {
+        if self.removed.load(Ordering::Relaxed) {
+            return Err(Errno::ENOENT);
+        }
+
+        Ok(self.cluster)
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        let mut dirs = self.volume.dirs.lock();
+        if dirs.get(&self.cluster).is_some_and(|dir| dir.strong_count() == 0) {
+            dirs.remove(&self.cluster);
+        }
+    }
+}
+
+impl DirectoryObject for Dir {
+    fn lookup(&self, name: &str) -> Result<NamedObject, Errno> {
+        let _state = self.volume.state.lock();
+        let entry = self.volume.find_entry(self.cluster()?, name)?;
+        Ok(self.to_named_object(entry))
+    }
+
+    fn create_file(&self, name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        let mut state = self.volume.state.lock();
+        let entry = self.volume.create_entry(&mut state, self.cluster()?, name, ATTR_ARCHIVE, 0)?;
+        Ok(self.to_named_object(entry))
+    }
+
+    fn create_dir(&self, name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        let mut state = self.volume.state.lock();
+        let entry = self.volume.create_dir(&mut state, self.cluster()?, name)?;
+        Ok(self.to_named_object(entry))
+    }
+
+    fn create_pipe(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_symlink(&self, _name: &str, _target: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn unlink(&self, name: &str) -> Result<(), Errno> {
+        let mut state = self.volume.state.lock();
+        self.volume.delete_entry(&mut state, self.cluster()?, name, false)
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), Errno> {
+        let mut state = self.volume.state.lock();
+        self.volume.delete_entry(&mut state, self.cluster()?, name, true)
+    }
+
+    fn rename(&self, old_name: &str, new_dir: &Arc<dyn DirectoryObject>, new_name: &str) -> Result<(), Errno> {
+        // Entries can only be moved within the same volume
+        let new_dir = (new_dir.as_ref() as &dyn Any).downcast_ref::<Dir>().ok_or(Errno::EXDEV)?;
+        if !Arc::ptr_eq(&self.volume, &new_dir.volume) {
+            return Err(Errno::EXDEV);
+        }
+
+        let mut state = self.volume.state.lock();
+        self.volume.move_entry(&mut state, self.cluster()?, old_name, new_dir.cluster()?, new_name)
+    }
+
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(self.stat)
+    }
+
+    fn readdir(&self, index: usize) -> Result<Option<DirEntry>, Errno> {
+        let _state = self.volume.state.lock();
+        let dir = self.volume.read_dir(self.cluster()?)?;
+
+        Ok(self.volume.parse_entries(&dir).into_iter().nth(index).map(|entry| DirEntry {
+            file_type: if entry.raw[11] & ATTR_DIRECTORY != 0 {
+                FileType::Directory
+            } else {
+                FileType::Regular
+            },
+            name: entry.name,
+        }))
+    }
+}
+
+impl Debug for Dir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fat32Dir").field("cluster", &self.cluster).finish()
+    }
+}
+
+/// A file is identified by the position of its directory entry, which holds its size and first cluster.
+/// The position is updated, if the entry is moved, and `None` after the entry has been removed.
+struct File {
+    volume: Arc<Volume>,
+    location: Mutex<Option<EntryLocation>>,
+}
+
+impl File {
+    /// Return the position of the directory entry or `ENOENT`, if it has been removed
+    fn location(&self) -> Result<EntryLocation, Errno> {
+        self.location.lock().ok_or(Errno::ENOENT)
+    }
+}
+
+impl FileObject for File {
+    fn stat(&self) -> Result<Stat, Errno> {
+        let _state = self.volume.state.lock();
+        Ok(entry_stat(&self.volume.read_entry(self.location()?)?))
+    }
+
+    fn read(&self, buf: &mut [u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        let _state = self.volume.state.lock();
+        self.volume.read_file(self.location()?, buf, offset)
+    }
+
+    fn write(&self, buf: &[u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        let mut state = self.volume.state.lock();
+        self.volume.write_file(&mut state, self.location()?, buf, offset)
+    }
+
+    fn truncate(&self, size: usize) -> Result<(), Errno> {
+        let mut state = self.volume.state.lock();
+        self.volume.truncate_file(&mut state, self.location()?, size)
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        let location = *self.location.lock();
+        if let Some(location) = location {
+            let mut files = self.volume.files.lock();
+            if files.get(&location).is_some_and(|file| file.strong_count() == 0) {
+                files.remove(&location);
+            }
+        }
+    }
+}
+
+impl Debug for File {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self.location.lock() {
+            Some(location) => f.debug_struct("Fat32File")
+                .field("sector", &location.sector)
+                .field("offset", &location.offset)
+                .finish(),
+            None => f.debug_struct("Fat32File").field("removed", &true).finish(),
+        }
+    }
+}
+
+fn read_u16(buffer: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(buffer[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(buffer: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap())
+}
+
+fn write_u16(buffer: &mut [u8], offset: usize, value: u16) {
+    buffer[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u32(buffer: &mut [u8], offset: usize, value: u32) {
+    buffer[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn entry_first_cluster(raw: &[u8]) -> u32 {
+    ((read_u16(raw, 20) as u32) << 16) | read_u16(raw, 26) as u32
+}
+
+fn set_entry_first_cluster(raw: &mut [u8], cluster: u32) {
+    write_u16(raw, 20, (cluster >> 16) as u16);
+    write_u16(raw, 26, cluster as u16);
+}
+
+/// Create a short directory entry with the current time as creation and modification time.
+fn new_short_entry(short_name: &[u8; 11], ntres: u8, attr: u8, first_cluster: u32) -> [u8; DIR_ENTRY_SIZE] {
+    let (date, time) = current_timestamp();
+
+    let mut raw = [0u8; DIR_ENTRY_SIZE];
+    raw[0..11].copy_from_slice(short_name);
+    raw[11] = attr;
+    raw[12] = ntres;
+    write_u16(&mut raw, 14, time);
+    write_u16(&mut raw, 16, date);
+    write_u16(&mut raw, 18, date);
+    write_u16(&mut raw, 22, time);
+    write_u16(&mut raw, 24, date);
+    set_entry_first_cluster(&mut raw, first_cluster);
+
+    raw
+}
+
+fn entry_stat(raw: &[u8]) -> Stat {
+    let is_dir = raw[11] & ATTR_DIRECTORY != 0;
+
+    Stat {
+        mode: Mode::new(if is_dir { MODE_DIR } else { MODE_FILE }),
+        size: if is_dir { 0 } else { read_u32(raw, 28) as usize },
+        // The creation time has an additional field with a resolution of 10 ms
+        created_time: fat_time_to_millis(read_u16(raw, 16), read_u16(raw, 14)) + (raw[13] as u64 * 10),
+        modified_time: fat_time_to_millis(read_u16(raw, 24), read_u16(raw, 22)),
+        accessed_time: fat_time_to_millis(read_u16(raw, 18), 0),
+    }
+}
+
+/// Convert a FAT date and time into milliseconds since the unix epoch (0 if invalid).
+fn fat_time_to_millis(date: u16, time: u16) -> u64 {
+    NaiveDate::from_ymd_opt(1980 + (date >> 9) as i32, ((date >> 5) & 0x0f) as u32, (date & 0x1f) as u32)
+        .and_then(|date| date.and_hms_opt((time >> 11) as u32, ((time >> 5) & 0x3f) as u32, (time & 0x1f) as u32 * 2))
+        .map(|date_time| date_time.and_utc().timestamp_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns the current date and time in FAT format (or 01.01.1980 00:00 if no clock is available).
+fn current_timestamp() -> (u16, u16) {
+    if let Some(now) = DateTime::from_timestamp_millis(timer().date_ms() as i64) && (1980..2108).contains(&now.year()) {
+        let date = (((now.year() - 1980) as u16) << 9) | ((now.month() as u16) << 5) | now.day() as u16;
+        let time = ((now.hour() as u16) << 11) | ((now.minute() as u16) << 5) | (now.second() as u16 / 2);
+        return (date, time);
+    }
+
+    (FAT_EPOCH_DATE, 0)
+}
+
+/// Extract the characters of a long name entry (up to the terminating 0x0000).
+fn long_name_part(raw: &[u8]) -> Vec<u16> {
+    LFN_CHAR_OFFSETS
+        .iter()
+        .map(|offset| read_u16(raw, *offset))
+        .take_while(|c| *c != 0x0000)
+        .collect()
+}
+
+/// Checksum of a short name, stored in its long name entries
+fn short_name_checksum(short_name: &[u8]) -> u8 {
+    short_name.iter().fold(0u8, |sum, c| ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(*c))
+}
+
+fn short_name_to_string(raw: &[u8]) -> String {
+    let mut name = String::new();
+
+    let base = raw[0..8].trim_ascii_end();
+    for (index, c) in base.iter().enumerate() {
+        let c = if index == 0 && *c == ENTRY_KANJI_E5 { ENTRY_FREE } else { *c };
+        let c = if raw[12] & NTRES_LOWER_BASE != 0 { c.to_ascii_lowercase() } else { c };
+        name.push(c as char);
+    }
+
+    let extension = raw[8..11].trim_ascii_end();
+    if !extension.is_empty() {
+        name.push('.');
+        for c in extension {
+            let c = if raw[12] & NTRES_LOWER_EXT != 0 { c.to_ascii_lowercase() } else { *c };
+            name.push(c as char);
+        }
+    }
+
+    name
+}
+
+fn is_short_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || SHORT_NAME_SPECIAL_CHARS.contains(c)
+}
+
+/// Check if `name` can be stored in a directory entry.
+fn validate_name(name: &str) -> Result<(), Errno> {
+    if name.is_empty() || name.encode_utf16().count() > MAX_NAME_LEN || name.ends_with(['.', ' ']) || name.chars().any(|c| c < ' ' || "\"*/:<>?\\|".contains(c))
+    {
+        return Err(Errno::EINVAL);
+    }
+
+    Ok(())
+}
+
+/// Convert `name` into a short (8.3) name, if possible without a long name. \
+/// Base name and extension may each be either lower or upper case, which is stored in the NT reserved byte.
+fn to_short_name(name: &str) -> Option<([u8; 11], u8)> {
+    let (base, extension) = name.split_once('.').unwrap_or((name, ""));
+    if base.is_empty() || base.len() > 8 || extension.len() > 3 || extension.contains('.') {
+        return None;
+    }
+
+    let mut short_name = [b' '; 11];
+    let mut ntres = 0;
+    for (part, start, lower_flag) in [(base, 0, NTRES_LOWER_BASE), (extension, 8, NTRES_LOWER_EXT)] {
+        if !part.chars().all(is_short_name_char) {
+            return None;
+        }
+
+        let has_lower = part.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = part.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper {
+            return None;
+        }
+        if has_lower {
+            ntres |= lower_flag;
+        }
+
+        for (index, c) in part.bytes().enumerate() {
+            short_name[start + index] = c.to_ascii_uppercase();
+        }
+    }
+
+    Some((short_name, ntres))
+}
+
+/// Generate a unique short name (e.g. "LONGNA~1.TXT") for a name that requires a long name entry.
+fn generate_short_name(name: &str, existing: &[[u8; 11]]) -> Result<[u8; 11], Errno> {
+    let (base, extension) = match name.rfind('.') {
+        Some(position) if position > 0 => (&name[..position], &name[position + 1..]),
+        _ => (name, ""),
+    };
+
+    let convert = |part: &str| -> Vec<u8> {
+        part.chars()
+            .filter(|c| *c != ' ' && *c != '.')
+            .map(|c| if is_short_name_char(c) { c.to_ascii_uppercase() as u8 } else { b'_' })
+            .collect()
+    };
+    let base = convert(base);
+    let extension = convert(extension);
+
+    for number in 1..1_000_000 {
+        let tail = format!("~{number}");
+        let base_len = base.len().min(8 - tail.len());
+
+        let mut short_name = [b' '; 11];
+        short_name[..base_len].copy_from_slice(&base[..base_len]);
+        short_name[base_len..base_len + tail.len()].copy_from_slice(tail.as_bytes());
+        for (index, c) in extension.iter().take(3).enumerate() {
+            short_name[8 + index] = *c;
+        }
+
+        if !existing.contains(&short_name) {
+            return Ok(short_name);
+        }
+    }
+
+    Err(Errno::EEXIST)
+}
+
+/// Find `count` consecutive free directory entries. \
+/// Returns the index of the first entry and whether the entries are located behind the end marker.
+fn find_free_slots(data: &[u8], count: usize) -> Option<(usize, bool)> {
+    let mut run_start = 0;
+    let mut run_len = 0;
+    let mut past_end = false;
+
+    for (slot, raw) in data.chunks_exact(DIR_ENTRY_SIZE).enumerate() {
+        // All entries behind the end marker are free
+        past_end |= raw[0] == ENTRY_END;
+
+        if past_end || raw[0] == ENTRY_FREE {
+            if run_len == 0 {
+                run_start = slot;
+            }
+            run_len += 1;
+            if run_len == count {
+                return Some((run_start, past_end));
+            }
+        } else {
+            run_len = 0;
+        }
+    }
+
+    None
+}
@@ -6,69 +6,110 @@
    ║ Author: Michael Schoettner, Univ. Duesseldorf, 25.8.2025                ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
-use alloc::string::String;
+use alloc::string::{String, ToString};
+use alloc::vec;
 use alloc::vec::Vec;
 use alloc::sync::Arc;
+use super::api;
 use super::api::ROOT;
+use super::mounts;
 use super::traits;
 use super::traits::{NamedObject, DirectoryObject};
 use syscall::return_vals::Errno;
 
-/// Resolves an absolute path into an `DirectoryLike`
+/// Max. number of symbolic links followed while resolving a single path (more are considered a loop)
+const MAX_SYMLINKS: usize = 40;
+
+/// Resolves a path into an `DirectoryLike`
 pub(super) fn lookup_dir(path: &String) -> Result<Arc<dyn DirectoryObject>, Errno> {
     match lookup_named_object(path)? {
         NamedObject::DirectoryObject(dir) => Ok(dir),
         NamedObject::FileObject(_) => Err(Errno::ENOTDIR),
         NamedObject::PipeObject(_) => Err(Errno::ENOTDIR),
+        NamedObject::LinkObject(_) => Err(Errno::ENOTDIR),
     }
 }
 
-/// Resolves absolute `path` into a named object. \
+/// Resolves `path` into a named object, following all symbolic links. \
+/// A relative `path` is resolved against the working directory of the current process. \
 /// Returns `Ok(NamedObject)` or `Err`
 pub(super) fn lookup_named_object(path: &str) -> Result<NamedObject, Errno> {
-    let mut found_named_object;
+    resolve(path, true).map(|(named_object, _)| named_object)
+}
+
+/// Resolves `path` like `lookup_named_object`, but returns a symbolic link in the last component itself. \
+/// Returns `Ok(NamedObject)` or `Err`
+pub(super) fn lookup_named_object_nofollow(path: &str) -> Result<NamedObject, Errno> {
+    resolve(path, false).map(|(named_object, _)| named_object)
+}
 
-    if check_absolute_path(path) {
-        if path == "/" {
-            found_named_object = traits::as_named_object(ROOT.get().unwrap().root_dir());
-            return Ok(found_named_object);
+/// Resolves `path` component by component, starting at the root directory. \
+/// `..` moves to the directory the current one has been entered from, which is also correct after following links. \
+/// Returns the named object and its absolute path without `.`, `..` and symbolic links
+/// (except for a link in the last component, if `follow_last_link` is `false`). \
+/// Fails with `Err(Errno::ELOOP)`, if more than `MAX_SYMLINKS` links have to be followed.
+pub(super) fn resolve(path: &str, follow_last_link: bool) -> Result<(NamedObject, String), Errno> {
+    let root_dir = ROOT.get().unwrap().root_dir();
+
+    // directories from the root to the current directory and the components of their paths
+    let mut dirs = vec![root_dir.clone()];
+    let mut current_path: Vec<String> = Vec::new();
+
+    // components still to be resolved, in reverse order
+    let mut pending: Vec<String> = api::absolute_path(path).split('/').rev().map(|c| c.to_string()).collect();
+    let mut found_named_object = traits::as_named_object(root_dir);
+    let mut links = 0;
+
+    while let Some(component) = pending.pop() {
+        // every component except the last one must be a directory
+        let current_dir = found_named_object.as_dir().map_err(|_| Errno::ENOTDIR)?.clone();
+        match component.as_str() {
+            "" | "." => continue,
+            ".." => {
+                if dirs.len() > 1 {
+                    dirs.pop();
+                    current_path.pop();
+                }
+                found_named_object = traits::as_named_object(dirs.last().unwrap().clone());
+                continue;
+            }
+            _ => {}
         }
-        let mut components: Vec<&str> = path.split("/").collect();
-        components.remove(0); // remove empty string at position 0
 
-        // get root directory and open the desired file
-        let mut current_dir = ROOT.get().unwrap().root_dir();
-        let mut len = components.len();
-        let mut found;
-        for component in &components {
-            found = current_dir.lookup(component);
-            if found.is_err() {
-                return Err(Errno::ENOENT);
+        found_named_object = current_dir.lookup(&component)?;
+
+        // follow symbolic links, unless it is the last component and the link itself is requested
+        if let NamedObject::LinkObject(link) = &found_named_object {
+            if pending.iter().all(|c| c.is_empty()) && !follow_last_link {
+                current_path.push(component);
+                return Ok((found_named_object, ["/", &current_path.join("/")].concat()));
             }
-            found_named_object = found.unwrap();
 
-            // if not last component, this must be a directory
-            if len > 1 {
-                if !found_named_object.is_dir() {
-                    return Err(Errno::ENOENT);
-                }
-                current_dir = found_named_object.as_dir().unwrap().clone();
-            // if this is the last component, this must be a file or directory (see flags)
-            } else {
-                return Ok(found_named_object.clone());
+            links += 1;
+            if links > MAX_SYMLINKS {
+                return Err(Errno::ELOOP);
+            }
+
+            // a relative target is resolved against the directory containing the link
+            let target = link.target()?;
+            if target.starts_with('/') {
+                dirs.truncate(1);
+                current_path.clear();
             }
-            len -= 1;
+            pending.extend(target.split('/').rev().map(|c| c.to_string()));
+            found_named_object = traits::as_named_object(dirs.last().unwrap().clone());
+            continue;
         }
-    }
-    Err(Errno::ENOENT)
-}
 
-/// Helper function for checking if `path` is an abolute path
-fn check_absolute_path(path: &str) -> bool {
-    if let Some(pos) = path.find('/') {
-        if pos == 0 {
-            return true;
+        // a file system mounted on this directory hides its contents
+        current_path.push(component);
+        if let NamedObject::DirectoryObject(dir) = &found_named_object {
+            let path = ["/", &current_path.join("/")].concat();
+            let dir = mounts::mounted_root(&path).unwrap_or_else(|| dir.clone());
+            found_named_object = traits::as_named_object(dir.clone());
+            dirs.push(dir);
         }
     }
-    false
+
+    Ok((found_named_object, ["/", &current_path.join("/")].concat()))
 }
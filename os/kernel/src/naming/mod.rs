@@ -1,7 +1,13 @@
 pub mod api;
+pub mod open_objects;
+pub mod poll;
 pub mod stat;
 
-mod open_objects;
+mod devfs;
+mod fat32;
+mod mounts;
+mod procfs;
 mod tmpfs;
 mod lookup;
-mod traits;
\ No newline at end of file
+mod traits;
+mod tty;
\ No newline at end of file
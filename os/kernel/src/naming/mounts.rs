@@ -0,0 +1,70 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: mounts                                                          ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Mount table of the naming service. Additional file systems (e.g. disk   ║
+   ║ partitions) can be attached at any directory, hiding its contents until ║
+   ║ they are unmounted.                                                     ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use spin::rwlock::RwLock;
+
+use super::traits::{DirectoryObject, FileSystem};
+use syscall::return_vals::Errno;
+
+/// A file system attached at the absolute directory `path`
+struct MountPoint {
+    path: String,
+    fs: Arc<dyn FileSystem>,
+}
+
+static MOUNTS: RwLock<Vec<MountPoint>> = RwLock::new(Vec::new());
+
+/// Attach `fs` at the absolute directory `path`. \
+/// Returns `Err(Errno::EBUSY)` if another file system is already mounted there.
+pub(super) fn add(path: &str, fs: Arc<dyn FileSystem>) -> Result<(), Errno> {
+    let mut mounts = MOUNTS.write();
+    if mounts.iter().any(|mount| mount.path == path) {
+        return Err(Errno::EBUSY);
+    }
+
+    mounts.push(MountPoint { path: path.to_string(), fs });
+    Ok(())
+}
+
+/// Detach the file system mounted at `path`. \
+/// Returns `Err(Errno::EINVAL)` if nothing is mounted there and `Err(Errno::EBUSY)`
+/// if other file systems are mounted below `path`.
+pub(super) fn remove(path: &str) -> Result<(), Errno> {
+    let mut mounts = MOUNTS.write();
+    let index = mounts.iter().position(|mount| mount.path == path).ok_or(Errno::EINVAL)?;
+    if mounts.iter().any(|mount| mount.path != path && is_within(&mount.path, path)) {
+        return Err(Errno::EBUSY);
+    }
+
+    mounts.remove(index);
+    Ok(())
+}
+
+/// Returns the root directory of the file system mounted at `path` (if any).
+pub(super) fn mounted_root(path: &str) -> Option<Arc<dyn DirectoryObject>> {
+    MOUNTS.read().iter().find(|mount| mount.path == path).map(|mount| mount.fs.root_dir())
+}
+
+/// Check if a file system is mounted at `path` or below it.
+pub(super) fn has_mount_within(path: &str) -> bool {
+    MOUNTS.read().iter().any(|mount| is_within(&mount.path, path))
+}
+
+/// Check if `path` is equal to `dir` or located below it.
+pub(super) fn is_within(path: &str, dir: &str) -> bool {
+    match path.strip_prefix(dir) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || dir == "/",
+        None => false,
+    }
+}
@@ -1,39 +1,46 @@
 /* ╔═════════════════════════════════════════════════════════════════════════╗
    ║ Module: open_objects                                                    ║
    ╟─────────────────────────────────────────────────────────────────────────╢
-   ║ Managing opened objects in a global table (OPEN_OBJECTS). And providing ║
-   ║ all major functions for the naming service.                             ║
+   ║ Managing opened objects in a table per process (OpenObjectTable). The   ║
+   ║ handles 0, 1 and 2 are the standard streams, which are bound to the     ║
+   ║ terminal for the kernel process and inherited by all other processes.   ║
+   ║ And providing all major functions for the naming service.               ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Michael Schoettner, Univ. Duesseldorf, 03.09.2025               ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 
-use alloc::boxed::Box;
+use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::result::Result;
 use core::sync::atomic::{AtomicUsize, Ordering};
 use spin::rwlock::RwLock;
-use spin::Once;
+use terminal::TerminalMode;
 
 use super::lookup;
-use super::traits::NamedObject;
-use naming::shared_types::{DirEntry, OpenOptions, SeekOrigin};
+use super::mounts;
+use super::traits::{FileObject, NamedObject};
+use super::tty::{TtyInputFile, TtyOutputFile};
+use crate::process::process::Process;
+use crate::process_manager;
+use naming::shared_types::{DirEntry, OpenOptions, PollEvents, SeekOrigin, STDERR, STDIN, STDOUT};
 use syscall::return_vals::{Errno, SyscallResult};
 
-/// Max. number of open objetcs
-const MAX_OPEN_OBJECTS: usize = 0x1000;
-
-//static OPEN_OBJECTS: Once<Arc<Mutex<Box<OpenObjectTable>>>> = Once::new();
-static OPEN_OBJECTS: Once<Arc<OpenObjectTable>> = Once::new();
-
-struct OpenObjectTable {
-    open_handles: RwLock<Vec<(usize, Option<Arc<OpenedObject>>)>>,
-    free_handles: RwLock<Box<[usize; MAX_OPEN_OBJECTS]>>,
-}
+/// Max. number of open objetcs per process
+const MAX_OPEN_OBJECTS: usize = 0x400;
 
+/// Bind the standard streams of the kernel process to the terminal.
+/// All other processes inherit them (see `OpenObjectTable::inherit`).
 pub(super) fn open_object_table_init() {
-    OPEN_OBJECTS.call_once(|| Arc::new(OpenObjectTable::new()));
+    let kernel_process = process_manager().read().kernel_process().expect("No kernel process found!");
+    let table = kernel_process.open_objects();
+
+    let stdin: Arc<dyn FileObject> = Arc::new(TtyInputFile);
+    let stdout: Arc<dyn FileObject> = Arc::new(TtyOutputFile);
+    table.install_handle(STDIN, Arc::new(OpenedObject::new("", Arc::new(stdin.into()), AtomicUsize::new(0), OpenOptions::READONLY)));
+    table.install_handle(STDOUT, Arc::new(OpenedObject::new("", Arc::new(stdout.clone().into()), AtomicUsize::new(0), OpenOptions::WRITEONLY)));
+    table.install_handle(STDERR, Arc::new(OpenedObject::new("", Arc::new(stdout.into()), AtomicUsize::new(0), OpenOptions::WRITEONLY)));
 }
 
 pub(super) fn open(path: &str, flags: OpenOptions) -> Result<usize, Errno> {
@@ -51,21 +58,73 @@ pub(super) fn open(path: &str, flags: OpenOptions) -> Result<usize, Errno> {
         }
     }
 
+    // a file opened for writing with `TRUNCATE` starts empty
+    if flags.contains(OpenOptions::TRUNCATE) && flags.is_writable() {
+        if let Ok(file) = found_named_object.as_file() {
+            file.truncate(0)?;
+        }
+    }
+
     // call the 'open' for pipes specific behavior
     if found_named_object.is_pipe() {
         found_named_object.as_pipe()?.open(flags)?; // ignore return value
     }
 
     // try to allocate an new handle
-    get_open_object_table().allocate_handle(Arc::new(OpenedObject::new(Arc::new(found_named_object), AtomicUsize::new(0), flags)))
+    current_process()
+        .open_objects()
+        .allocate_handle(Arc::new(OpenedObject::new(path, Arc::new(found_named_object), AtomicUsize::new(0), flags)))
+}
+
+/// Check if `named_object` is currently open in any process (regardless of the path used for opening it).
+pub(super) fn is_open(named_object: &NamedObject) -> bool {
+    let object_ptr = named_object_ptr(named_object);
+    any_opened_object(|opened_object| named_object_ptr(&opened_object.named_object) == object_ptr)
+}
+
+/// Helper function returning the address of the object wrapped by `named_object`
+fn named_object_ptr(named_object: &NamedObject) -> *const () {
+    match named_object {
+        NamedObject::FileObject(file) => Arc::as_ptr(file) as *const (),
+        NamedObject::PipeObject(pipe) => Arc::as_ptr(pipe) as *const (),
+        NamedObject::DirectoryObject(dir) => Arc::as_ptr(dir) as *const (),
+        NamedObject::LinkObject(link) => Arc::as_ptr(link) as *const (),
+    }
+}
+
+/// Check if any process has opened an object using `path` or a path below it.
+pub(super) fn is_path_in_use(path: &str) -> bool {
+    any_opened_object(|opened_object| mounts::is_within(&opened_object.path, path))
+}
+
+/// Helper function checking `predicate` for the opened objects of all active processes
+fn any_opened_object(predicate: impl Fn(&OpenedObject) -> bool) -> bool {
+    let processes = process_manager().read().active_processes();
+    processes.iter().any(|process| {
+        process
+            .open_objects()
+            .handles
+            .read()
+            .iter()
+            .filter_map(|obj| obj.as_ref())
+            .any(|opened_object| predicate(opened_object))
+    })
 }
 
 pub(super) fn write(fh: usize, buf: &[u8]) -> Result<usize, Errno> {
-    get_open_object_table().lookup_opened_object(fh).and_then(|opened_object| {
+    current_process().open_objects().lookup_opened_object(fh).and_then(|opened_object| {
+        if !opened_object.options.is_writable() {
+            return Err(Errno::EBADF);
+        }
         if opened_object.named_object.is_file() {
             // Make `opened_object` mutable here
             return opened_object.named_object.as_file().and_then(|file| {
-                let pos = opened_object.pos.load(Ordering::SeqCst);
+                // with `APPEND` every write goes to the end of the file
+                let pos = if opened_object.options.contains(OpenOptions::APPEND) {
+                    file.stat()?.size
+                } else {
+                    opened_object.pos.load(Ordering::SeqCst)
+                };
                 let bytes_written = file.write(buf, pos, opened_object.options)?;
                 opened_object.pos.store(pos + bytes_written, Ordering::SeqCst);
                 Ok(bytes_written) // Return the bytes written
@@ -82,13 +141,17 @@ pub(super) fn write(fh: usize, buf: &[u8]) -> Result<usize, Errno> {
     })
 }
 
-pub(super) fn read(fh: usize, buf: &mut [u8]) -> Result<usize, Errno> {
-    get_open_object_table().lookup_opened_object(fh).and_then(|opened_object| {
+/// Read from the object `fh` into `buf`, using `mode`, if the object is the terminal
+pub(super) fn read(fh: usize, buf: &mut [u8], mode: TerminalMode) -> Result<usize, Errno> {
+    current_process().open_objects().lookup_opened_object(fh).and_then(|opened_object| {
+        if !opened_object.options.is_readable() {
+            return Err(Errno::EBADF);
+        }
         if opened_object.named_object.is_file() {
             // Make `opened_object` mutable here
             return opened_object.named_object.as_file().and_then(|file| {
                 let pos = opened_object.pos.load(Ordering::SeqCst);
-                let bytes_read = file.read(buf, pos, opened_object.options)?;
+                let bytes_read = file.read_terminal(buf, pos, opened_object.options, mode)?;
                 opened_object.pos.store(pos + bytes_read, Ordering::SeqCst);
                 Ok(bytes_read) // Return the bytes read
             });
@@ -105,7 +168,7 @@ pub(super) fn read(fh: usize, buf: &mut [u8]) -> Result<usize, Errno> {
 }
 
 pub fn seek(fh: usize, offset: usize, origin: SeekOrigin) -> Result<usize, Errno> {
-    get_open_object_table().lookup_opened_object(fh).and_then(|opened_object| {
+    current_process().open_objects().lookup_opened_object(fh).and_then(|opened_object| {
         if opened_object.named_object.is_file() {
             // Make `opened_object` mutable here
             return opened_object.named_object.as_file().and_then(|file| {
@@ -123,7 +186,7 @@ pub fn seek(fh: usize, offset: usize, origin: SeekOrigin) -> Result<usize, Errno
 }
 
 pub(super) fn readdir(fh: usize) -> Result<Option<DirEntry>, Errno> {
-    get_open_object_table().lookup_opened_object(fh).and_then(|opened_object| {
+    current_process().open_objects().lookup_opened_object(fh).and_then(|opened_object| {
         if opened_object.named_object.is_dir() {
             // Make `opened_object` mutable here
             return opened_object.named_object.as_dir().and_then(|dir| {
@@ -137,104 +200,160 @@ pub(super) fn readdir(fh: usize) -> Result<Option<DirEntry>, Errno> {
     })
 }
 
+/// Return the events pending for the object opened with handle `fh` (`NVAL`, if the handle is invalid)
+pub(super) fn poll(fh: usize) -> PollEvents {
+    match current_process().open_objects().lookup_opened_object(fh) {
+        Ok(opened_object) => opened_object.named_object.poll(opened_object.options),
+        Err(_) => PollEvents::NVAL,
+    }
+}
+
+/// Return the named object opened with handle `fh`
+pub(super) fn named_object(fh: usize) -> Result<NamedObject, Errno> {
+    Ok((*current_process().open_objects().lookup_opened_object(fh)?.named_object).clone())
+}
+
 pub(super) fn close(handle: usize) -> Result<usize, Errno> {
-    get_open_object_table().free_handle(handle)
+    current_process().open_objects().free_handle(handle)
+}
+
+pub(super) fn dup(handle: usize) -> Result<usize, Errno> {
+    let process = current_process();
+    let table = process.open_objects();
+    table.allocate_handle(table.lookup_opened_object(handle)?)
 }
 
-/*pub(super) fn dump() {
-    get_open_object_table().lock().dump();
-}*/
+pub(super) fn dup2(handle: usize, new_handle: usize) -> Result<usize, Errno> {
+    if new_handle >= MAX_OPEN_OBJECTS {
+        return Err(Errno::EINVALH);
+    }
 
-/// ************************ OpenedObject ************************
+    let process = current_process();
+    let table = process.open_objects();
+    let opened_object = table.lookup_opened_object(handle)?;
+    if handle != new_handle {
+        table.install_handle(new_handle, opened_object); // closes `new_handle`, if it was open
+    }
+    Ok(new_handle)
+}
+
+/// Helper function returning the process of the calling thread, owning the open object table to be used
+fn current_process() -> Arc<Process> {
+    process_manager().read().current_process()
+}
+
+/// ************************ OpenObjectTable ************************
+
+/// Table of the objects opened by one process, indexed by handle. \
+/// Handles may share an 'OpenedObject' (and thus the position within the object), after `dup` or when inherited.
+pub struct OpenObjectTable {
+    handles: RwLock<Vec<Option<Arc<OpenedObject>>>>,
+}
 
 impl OpenObjectTable {
-    /// Create a new OpenObjectTable
-    fn new() -> OpenObjectTable {
+    /// Create a new, empty OpenObjectTable
+    pub fn new() -> OpenObjectTable {
+        OpenObjectTable {
+            handles: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Create a copy of this table for a new process. \
+    /// Both tables use the same handles for the same opened objects.
+    pub fn inherit(&self) -> OpenObjectTable {
         OpenObjectTable {
-            open_handles: RwLock::new(Vec::with_capacity(MAX_OPEN_OBJECTS)),
-            free_handles: RwLock::new(Box::new([0; MAX_OPEN_OBJECTS])),
+            handles: RwLock::new(self.handles.read().clone()),
         }
     }
 
+    /// Close all handles (called when the owning process exits)
+    pub fn close_all(&self) {
+        self.handles.write().clear();
+    }
+
+    /// Return all open handles with the paths used for opening the objects, sorted by handle
+    pub fn handles(&self) -> Vec<(usize, String)> {
+        let guard = self.handles.read();
+        guard.iter().enumerate()
+            .filter_map(|(handle, obj)| obj.as_ref().map(|obj| (handle, obj.path.clone())))
+            .collect()
+    }
+
     /// Lookup an 'OpenedObject' for a given handle
     fn lookup_opened_object(&self, handle: usize) -> Result<Arc<OpenedObject>, Errno> {
-        let guard = self.open_handles.read();
-        guard
-            .iter()
-            .find(|(h, _)| *h == handle)
-            .and_then(|(_, obj)| obj.as_ref())
-            .cloned()
-            .ok_or(Errno::EINVALH)
+        let guard = self.handles.read();
+        guard.get(handle).and_then(|obj| obj.as_ref()).cloned().ok_or(Errno::EINVALH)
     }
 
-    /// Allocate a new handle for a given 'OpenObject'
+    /// Allocate the lowest free handle for a given 'OpenObject'
     fn allocate_handle(&self, opened_object: Arc<OpenedObject>) -> Result<usize, Errno> {
-        // We modify both vectors, so take a write lock
-        let mut guard = self.open_handles.write();
+        let mut guard = self.handles.write();
 
         // find a free slot
-        let handle = self.find_free_handle().ok_or(Errno::ENOHANDLES)?;
-        guard.push((handle, Some(opened_object)));
-        Ok(handle)
-    }
-
-    /// Free handle
-    fn free_handle(&self, handle: usize) -> SyscallResult {
-        let mut guard = self.open_handles.write();
-
-        if let Some(idx) = guard.iter().position(|(h, _)| *h == handle) {
-            guard.swap_remove(idx);
-            self.free_handles.write()[handle] = 0;
-            Ok(0)
-        } else {
-            Err(Errno::EINVALH)
+        if let Some(handle) = guard.iter().position(|obj| obj.is_none()) {
+            guard[handle] = Some(opened_object);
+            return Ok(handle);
         }
+        if guard.len() >= MAX_OPEN_OBJECTS {
+            return Err(Errno::ENOHANDLES);
+        }
+        guard.push(Some(opened_object));
+        Ok(guard.len() - 1)
     }
 
-    /// Helper function of 'allocate' to find a free handle
-    fn find_free_handle(&self) -> Option<usize> {
-        let mut free = self.free_handles.write(); // hold guard for the whole loop
-        for (i, v) in free.iter_mut().enumerate() {
-            if *v == 0 {
-                *v = 1;
-                return Some(i);
-            }
+    /// Use `handle` for a given 'OpenObject'. An object already using this handle is closed.
+    fn install_handle(&self, handle: usize, opened_object: Arc<OpenedObject>) {
+        let mut guard = self.handles.write();
+        if guard.len() <= handle {
+            guard.resize(handle + 1, None);
         }
-        None
+        guard[handle] = Some(opened_object);
     }
 
-    /*
-    fn dump(&self) {
-        info!("OpenObjectTable: dumping used handles");
-        for (handle, opened_object) in &self.open_handles {
-            if let Some(opened_object) = opened_object {
-                info!(
-                    "    handle = {:?}, named object = {:?}",
-                    handle, opened_object.named_object
-                );
+    /// Free handle
+    fn free_handle(&self, handle: usize) -> SyscallResult {
+        let mut guard = self.handles.write();
+
+        match guard.get_mut(handle).and_then(|obj| obj.take()) {
+            Some(_) => {
+                // shrink the table, if the last handles are free
+                while guard.last().is_some_and(|obj| obj.is_none()) {
+                    guard.pop();
+                }
+                Ok(0)
             }
+            None => Err(Errno::EINVALH),
         }
     }
-    */
-}
-
-/// Helper function returning safe access to the open object table (oot)
-fn get_open_object_table() -> Arc<OpenObjectTable> {
-    OPEN_OBJECTS.get().unwrap().clone()
 }
 
 /// ************************ OpenedObject ************************
 
 // Opened object stored in the 'OpenObjectTable'
-// (includes NamedObject, current position within object, and options)
+// (includes path used for opening, NamedObject, current position within object, and options)
 pub struct OpenedObject {
+    path: String,
     named_object: Arc<NamedObject>,
     pos: AtomicUsize, // current position within file or number of next DirEntry
     options: OpenOptions,
 }
 
 impl OpenedObject {
-    pub fn new(named_object: Arc<NamedObject>, pos: AtomicUsize, options: OpenOptions) -> OpenedObject {
-        OpenedObject { named_object, pos, options }
+    pub fn new(path: &str, named_object: Arc<NamedObject>, pos: AtomicUsize, options: OpenOptions) -> OpenedObject {
+        OpenedObject {
+            path: path.to_string(),
+            named_object,
+            pos,
+            options,
+        }
+    }
+}
+
+impl Drop for OpenedObject {
+    fn drop(&mut self) {
+        // pipes keep track of their readers and writers
+        if let Ok(pipe) = self.named_object.as_pipe() {
+            pipe.close(self.options);
+        }
     }
 }
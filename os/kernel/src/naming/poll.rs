@@ -0,0 +1,62 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: poll                                                            ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Waiting for events on several opened objects and sockets at once.       ║
+   ║ Objects call `notify`, whenever their readiness may have changed (e.g.  ║
+   ║ data written into a pipe). Waiting threads then check all entries.      ║
+   ║                                                                         ║
+   ║ Public functions:                                                       ║
+   ║   - poll:   wait until at least one entry is ready or a timeout occurs  ║
+   ║   - notify: wake up all threads waiting in `poll`                       ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use naming::shared_types::{PollEvents, PollFd, PollKind};
+
+use super::open_objects;
+use crate::network;
+use crate::sync::wait_queue::WaitQueue;
+
+/// Threads waiting in `poll`
+static POLL_WQ: WaitQueue = WaitQueue::new();
+
+/// Wait until at least one entry of `fds` has a pending event or `timeout_ms` milliseconds have passed
+/// (`None` waits forever, `Some(0)` does not block). \
+/// Sets `revents` of all entries and returns the number of entries with pending events (0 on timeout).
+pub fn poll(fds: &mut [PollFd], timeout_ms: Option<usize>) -> usize {
+    let mut check = || check_all(fds) > 0;
+    match timeout_ms {
+        Some(timeout_ms) => {
+            POLL_WQ.wait_timeout(&mut check, timeout_ms);
+        }
+        None => POLL_WQ.wait(&mut check),
+    }
+    fds.iter().filter(|fd| fd.revents != 0).count()
+}
+
+/// Wake up all threads waiting in `poll`, because an object may have become ready.
+pub fn notify() {
+    POLL_WQ.notify_all();
+}
+
+/// Helper function setting `revents` of all entries, returns the number of entries with pending events
+fn check_all(fds: &mut [PollFd]) -> usize {
+    let mut ready = 0;
+    for fd in fds.iter_mut() {
+        let events = match PollKind::from(fd.kind) {
+            PollKind::Object => open_objects::poll(fd.handle),
+            // a socket is passed to user space as its id (see `sys_sock_open`)
+            PollKind::Socket => network::socket_id(fd.handle).map_or(PollEvents::NVAL, network::socket_readiness),
+        };
+
+        // errors are reported, even if they have not been requested
+        let requested = PollEvents::from_bits_truncate(fd.events) | PollEvents::ERR | PollEvents::HUP | PollEvents::NVAL;
+        fd.revents = (events & requested).bits();
+        if fd.revents != 0 {
+            ready += 1;
+        }
+    }
+    ready
+}
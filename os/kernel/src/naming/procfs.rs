@@ -0,0 +1,341 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: procfs                                                          ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Process file system exposing kernel state as read-only text files       ║
+   ║ (mounted at /proc):                                                     ║
+   ║   - <pid>/status   id, working directory, number of threads and handles ║
+   ║   - <pid>/threads  ids of all threads of the process                    ║
+   ║   - <pid>/maps     virtual memory areas with type and tag               ║
+   ║   - <pid>/handles  open handles with the paths used for opening them    ║
+   ║   - self           link to the directory of the current process         ║
+   ║   - meminfo        free memory as reported by the page frame allocator  ║
+   ║   - pci            devices found on the PCI bus                         ║
+   ║   - devices        registered block devices                             ║
+   ║   - cache          hits and misses of the block device caches           ║
+   ║   - net            network interfaces with driver, MAC and IP addresses ║
+   ║ The content of a file is generated when it is opened.                   ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::fmt::{self, Debug, Formatter};
+use core::result::Result;
+use naming::shared_types::{DirEntry, FileType, OpenOptions};
+use syscall::return_vals::Errno;
+
+use super::stat::{Mode, Stat, MODE_DIR, MODE_FILE, MODE_LINK};
+use super::traits::{DirectoryObject, FileObject, FileSystem, LinkObject, NamedObject};
+use crate::memory::{frames, PAGE_SIZE};
+use crate::process::process::Process;
+use crate::{network, pci_bus, process_manager, storage};
+
+/// Files in the directory of each process
+const PROCESS_FILES: &[&str] = &["status", "threads", "maps", "handles"];
+
+/// Files in the root directory (besides the process directories and `self`)
+const GLOBAL_FILES: &[&str] = &["meminfo", "pci", "devices", "cache", "net"];
+
+pub struct ProcFs {
+    root_dir: Arc<ProcDir>,
+}
+
+impl ProcFs {
+    pub fn new() -> ProcFs {
+        ProcFs {
+            root_dir: Arc::new(ProcDir::new(None)),
+        }
+    }
+}
+
+impl FileSystem for ProcFs {
+    fn root_dir(&self) -> Arc<dyn DirectoryObject> {
+        self.root_dir.clone()
+    }
+}
+
+/// The root directory (`pid` is None) or the directory of the process `pid`
+struct ProcDir {
+    pid: Option<usize>,
+    stat: Stat,
+}
+
+impl ProcDir {
+    fn new(pid: Option<usize>) -> ProcDir {
+        ProcDir {
+            pid,
+            stat: Stat::created_now(Mode::new(MODE_DIR), 0),
+        }
+    }
+
+    /// Helper function returning the process of this directory (fails, if it has terminated in the meantime)
+    fn process(pid: usize) -> Result<Arc<Process>, Errno> {
+        process_manager().read().active_process(pid).ok_or(Errno::ENOENT)
+    }
+
+    /// Helper function returning the names and types of all entries
+    fn entries(&self) -> Vec<(String, FileType)> {
+        match self.pid {
+            Some(_) => PROCESS_FILES.iter().map(|name| (name.to_string(), FileType::Regular)).collect(),
+            None => {
+                let mut entries: Vec<(String, FileType)> = GLOBAL_FILES.iter().map(|name| (name.to_string(), FileType::Regular)).collect();
+                entries.push(("self".to_string(), FileType::Link));
+                let pids = process_manager().read().active_process_ids();
+                entries.extend(pids.into_iter().map(|pid| (pid.to_string(), FileType::Directory)));
+                entries
+            }
+        }
+    }
+}
+
+impl DirectoryObject for ProcDir {
+    fn lookup(&self, name: &str) -> Result<NamedObject, Errno> {
+        let content = match (self.pid, name) {
+            (Some(pid), _) => {
+                let process = Self::process(pid)?;
+                match name {
+                    "status" => status(&process),
+                    "threads" => threads(&process),
+                    "maps" => maps(&process),
+                    "handles" => handles(&process),
+                    _ => return Err(Errno::ENOENT),
+                }
+            }
+            (None, "meminfo") => meminfo(),
+            (None, "pci") => pci(),
+            (None, "devices") => devices(),
+            (None, "cache") => cache(),
+            (None, "net") => net(),
+            (None, "self") => return Ok(NamedObject::LinkObject(Arc::new(SelfLink))),
+            (None, _) => {
+                let pid = name.parse::<usize>().map_err(|_| Errno::ENOENT)?;
+                Self::process(pid)?;
+                return Ok(NamedObject::DirectoryObject(Arc::new(ProcDir::new(Some(pid)))));
+            }
+        };
+        Ok(NamedObject::FileObject(Arc::new(ProcFile::new(content))))
+    }
+
+    fn create_file(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_dir(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_pipe(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn create_symlink(&self, _name: &str, _target: &str, _mode: Mode) -> Result<NamedObject, Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn unlink(&self, _name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn rmdir(&self, _name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn rename(&self, _old_name: &str, _new_dir: &Arc<dyn DirectoryObject>, _new_name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOTSUP)
+    }
+
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(self.stat)
+    }
+
+    fn readdir(&self, index: usize) -> Result<Option<DirEntry>, Errno> {
+        Ok(self.entries().into_iter().nth(index).map(|(name, file_type)| DirEntry { file_type, name }))
+    }
+}
+
+impl Debug for ProcDir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcDir").field("pid", &self.pid).finish()
+    }
+}
+
+/// A read-only text file, holding the content generated when it has been looked up
+struct ProcFile {
+    content: String,
+    stat: Stat,
+}
+
+impl ProcFile {
+    fn new(content: String) -> ProcFile {
+        let stat = Stat::created_now(Mode::new(MODE_FILE), content.len());
+        ProcFile { content, stat }
+    }
+}
+
+impl FileObject for ProcFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(self.stat)
+    }
+
+    fn read(&self, buf: &mut [u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        let data = self.content.as_bytes();
+        let len = buf.len().min(data.len().saturating_sub(offset));
+        if len > 0 {
+            buf[..len].copy_from_slice(&data[offset..offset + len]);
+        }
+        Ok(len)
+    }
+
+    fn write(&self, _buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Err(Errno::ERDONLY)
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Err(Errno::ERDONLY)
+    }
+}
+
+impl Debug for ProcFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcFile").field("size", &self.content.len()).finish()
+    }
+}
+
+/// `self` points to the directory of the process resolving it
+#[derive(Debug)]
+struct SelfLink;
+
+impl LinkObject for SelfLink {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_LINK), 0))
+    }
+
+    fn target(&self) -> Result<String, Errno> {
+        Ok(process_manager().read().current_process().id().to_string())
+    }
+}
+
+/// Helper function generating `<pid>/status`
+fn status(process: &Process) -> String {
+    format!(
+        "Pid:     {}\nCwd:     {}\nThreads: {}\nHandles: {}\n",
+        process.id(),
+        process.cwd(),
+        process.thread_ids().len(),
+        process.open_objects().handles().len()
+    )
+}
+
+/// Helper function generating `<pid>/threads` (one thread id per line)
+fn threads(process: &Process) -> String {
+    process.thread_ids().iter().map(|thread_id| format!("{}\n", thread_id)).collect()
+}
+
+/// Helper function generating `<pid>/maps` (one virtual memory area per line)
+fn maps(process: &Process) -> String {
+    let mut content = String::new();
+    for vma in process.virtual_address_space.vmas() {
+        let _ = writeln!(
+            content,
+            "{:#018x}-{:#018x} {:<6} {:<12} {}",
+            vma.start().as_u64(),
+            vma.end().as_u64(),
+            format!("{:?}", vma.space),
+            format!("{:?}", vma.typ()),
+            vma.tag_str()
+        );
+    }
+    content
+}
+
+/// Helper function generating `<pid>/handles` (one handle per line)
+fn handles(process: &Process) -> String {
+    let mut content = String::new();
+    for (handle, path) in process.open_objects().handles() {
+        let _ = writeln!(content, "{:>3} {}", handle, path);
+    }
+    content
+}
+
+/// Helper function generating `meminfo`
+fn meminfo() -> String {
+    let free_frames = frames::free_frame_count();
+    format!(
+        "FreeFrames: {}\nFreeMemory: {} KiB\nPhysLimit:  {:#x}\n",
+        free_frames,
+        free_frames * PAGE_SIZE / 1024,
+        frames::phys_limit().start_address().as_u64()
+    )
+}
+
+/// Helper function generating `pci` (one device per line: address, vendor and device id, class and subclass)
+fn pci() -> String {
+    let pci = pci_bus();
+    let mut content = String::new();
+    for device in pci.devices() {
+        let header = device.read().header();
+        let address = header.address();
+        let (vendor_id, device_id) = header.id(pci.config_space());
+        let (_, base_class, sub_class, _) = header.revision_and_class(pci.config_space());
+        let _ = writeln!(
+            content,
+            "{:02x}:{:02x}.{} {:04x}:{:04x} class {:02x}:{:02x}",
+            address.bus(),
+            address.device(),
+            address.function(),
+            vendor_id,
+            device_id,
+            base_class,
+            sub_class
+        );
+    }
+    content
+}
+
+/// Helper function generating `devices` (one block device per line: name, number and size of sectors)
+fn devices() -> String {
+    let mut content = String::new();
+    for name in storage::block_device_names() {
+        if let Some(device) = storage::block_device(&name) {
+            let _ = writeln!(content, "{:<8} {:>10} sectors of {} bytes", name, device.sector_count(), device.sector_size());
+        }
+    }
+    content
+}
+
+/// Helper function generating `cache` (one block device per line: hits, misses, write-backs, cached and dirty sectors)
+fn cache() -> String {
+    let mut content = String::new();
+    let _ = writeln!(content, "{:<8} {:>10} {:>10} {:>10} {:>8} {:>8}", "Device", "Hits", "Misses", "WriteBacks", "Cached", "Dirty");
+    for (name, stats) in storage::cache_stats() {
+        let _ = writeln!(
+            content,
+            "{:<8} {:>10} {:>10} {:>10} {:>8} {:>8}",
+            name, stats.hits, stats.misses, stats.write_backs, stats.cached, stats.dirty
+        );
+    }
+    content
+}
+
+/// Helper function generating `net` (one network interface per line: name, driver, MAC and IP addresses)
+fn net() -> String {
+    let mut content = String::new();
+    for interface in network::interfaces() {
+        let addresses: Vec<String> = interface.addresses.iter().map(|cidr| cidr.to_string()).collect();
+        let _ = writeln!(
+            content,
+            "{:<6} {:<10} {} {}{}",
+            interface.name,
+            interface.driver,
+            interface.mac,
+            addresses.join(" "),
+            if interface.default_route { " (default route)" } else { "" }
+        );
+    }
+    content
+}
@@ -7,6 +7,8 @@
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 
+use crate::timer;
+
 pub const MODE_FILE: u32 = 0x1;
 pub const MODE_DIR: u32  = 0x2;
 pub const MODE_LINK: u32 = 0x3;
@@ -16,9 +18,9 @@ pub const MODE_LINK: u32 = 0x3;
 pub struct Stat {
     pub mode: Mode,
     pub size: usize,
-    pub created_time: u64,
-    pub modified_time: u64,
-    pub accessed_time: u64,
+    pub created_time: u64,  // milliseconds since the Unix epoch (0 = unknown)
+    pub modified_time: u64, // milliseconds since the Unix epoch (0 = unknown)
+    pub accessed_time: u64, // milliseconds since the Unix epoch (0 = unknown)
 }
 
 impl Stat {
@@ -31,6 +33,23 @@ impl Stat {
             accessed_time: 0,
         }
     }
+    /// Meta data of an object created right now (all timestamps are set to the current date)
+    pub fn created_now(mode: Mode, size: usize) -> Stat {
+        let now = timer().date_ms();
+        Stat {
+            mode,
+            size,
+            created_time: now,
+            modified_time: now,
+            accessed_time: now,
+        }
+    }
+
+    /// Set the modification time to the current date
+    pub fn touch(&mut self) {
+        self.modified_time = timer().date_ms();
+    }
+
     pub fn zeroed() -> Stat {
         Stat {
             mode: Mode::new(MODE_FILE),
@@ -2,24 +2,25 @@
    ║ Module: tmpfs                                                           ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Temporary file system running storing everything in main memory. It     ║
-   ║ supports directories, files, and named pipes.                           ║
+   ║ supports directories, files, named pipes, and symbolic links.           ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Michael Schoettner, Univ. Duesseldorf, 01.09.2025               ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
-use super::stat::Mode;
-use super::stat::Stat;
-use super::traits::{DirectoryObject, FileObject, FileSystem, NamedObject, PipeObject};
+use super::stat::{Mode, Stat, MODE_FILE};
+use super::poll;
+use super::traits::{DirectoryObject, FileObject, FileSystem, LinkObject, NamedObject, PipeObject};
 use crate::sync::wait_queue::WaitQueue;
 use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::any::Any;
 use core::fmt::{Debug, Formatter};
 use core::result::Result;
-use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::{AtomicBool, AtomicUsize};
 use core::sync::atomic::Ordering;
 use core::{fmt, ptr};
-use naming::shared_types::{DirEntry, FileType, OpenOptions};
+use naming::shared_types::{DirEntry, FileType, OpenOptions, PollEvents};
 use nolock::queues::mpmc;
 use spin::rwlock::RwLock;
 use syscall::return_vals::Errno;
@@ -68,6 +69,7 @@ enum TmpFsINode {
     File(Arc<dyn FileObject>),
     Pipe(Arc<dyn PipeObject>),
     Directory(Arc<Dir>),
+    Link(Arc<dyn LinkObject>),
 }
 
 struct DirInner {
@@ -81,10 +83,7 @@ impl Dir {
     pub fn new() -> Dir {
         Dir(RwLock::new(DirInner {
             files: Vec::new(),
-            stat: Stat {
-                mode: Mode::new(0),
-                ..Stat::zeroed()
-            },
+            stat: Stat::created_now(Mode::new(0), 0),
         }))
     }
 
@@ -99,6 +98,7 @@ impl Dir {
         // Create a new file and add it to the directory
         let inode = Arc::new(StaticFile::new(buffer));
         dir_lock.files.push((name.to_string(), TmpFsINode::File(inode.clone())));
+        dir_lock.stat.touch();
 
         // Return the created file as a NamedObject
         Ok((inode as Arc<dyn FileObject>).into())
@@ -115,6 +115,7 @@ impl DirectoryObject for Dir {
                 TmpFsINode::File(file) => Ok(file.clone().into()), // Clone and convert to NamedObject
                 TmpFsINode::Pipe(pipe) => Ok(pipe.clone().into()), // Clone and convert to NamedObject
                 TmpFsINode::Directory(dir) => Ok((dir.clone() as Arc<dyn DirectoryObject>).into()), // Clone and cast directory
+                TmpFsINode::Link(link) => Ok(link.clone().into()),
             }
         } else {
             Err(Errno::ENOENT) // Return error if the file is not found
@@ -132,11 +133,28 @@ impl DirectoryObject for Dir {
         // Create a new pipe and add it to the directory
         let inode = Arc::new(Pipe::new());
         dir_lock.files.push((name.to_string(), TmpFsINode::Pipe(inode.clone())));
+        dir_lock.stat.touch();
 
         // Return the created file as a NamedObject
         Ok((inode as Arc<dyn PipeObject>).into())
     }
 
+    fn create_symlink(&self, name: &str, target: &str, mode: Mode) -> Result<NamedObject, Errno> {
+        let mut dir_lock = self.0.write();
+
+        // Check if an object with the same name already exists
+        if dir_lock.files.iter().any(|(file_name, _)| file_name == name) {
+            return Err(Errno::EEXIST);
+        }
+
+        // Create a new link and add it to the directory
+        let inode = Arc::new(Link::new(target, mode));
+        dir_lock.files.push((name.to_string(), TmpFsINode::Link(inode.clone())));
+        dir_lock.stat.touch();
+
+        Ok((inode as Arc<dyn LinkObject>).into())
+    }
+
     fn create_file(&self, name: &str, _mode: Mode) -> Result<NamedObject, Errno> {
         let mut dir_lock = self.0.write();
 
@@ -148,6 +166,7 @@ impl DirectoryObject for Dir {
         // Create a new file and add it to the directory
         let inode = Arc::new(File::new());
         dir_lock.files.push((name.to_string(), TmpFsINode::File(inode.clone())));
+        dir_lock.stat.touch();
 
         // Return the created file as a NamedObject
         Ok((inode as Arc<dyn FileObject>).into())
@@ -164,11 +183,80 @@ impl DirectoryObject for Dir {
         // Create a new directory and add it to the directory's entries
         let inode = Arc::new(Dir::new());
         dir_lock.files.push((name.to_string(), TmpFsINode::Directory(inode.clone())));
+        dir_lock.stat.touch();
 
         // Return the created directory as a NamedObject
         Ok((inode as Arc<dyn DirectoryObject>).into())
     }
 
+    fn unlink(&self, name: &str) -> Result<(), Errno> {
+        let mut dir_lock = self.0.write();
+
+        let index = dir_lock.files.iter().position(|(file_name, _)| file_name == name).ok_or(Errno::ENOENT)?;
+        if let TmpFsINode::Directory(_) = dir_lock.files[index].1 {
+            return Err(Errno::EISDIR); // Directories are removed with 'rmdir'
+        }
+
+        dir_lock.files.remove(index);
+        dir_lock.stat.touch();
+        Ok(())
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), Errno> {
+        let mut dir_lock = self.0.write();
+
+        let index = dir_lock.files.iter().position(|(file_name, _)| file_name == name).ok_or(Errno::ENOENT)?;
+        match &dir_lock.files[index].1 {
+            TmpFsINode::Directory(dir) => {
+                if !dir.0.read().files.is_empty() {
+                    return Err(Errno::ENOTEMPTY); // Only empty directories can be removed
+                }
+            }
+            _ => return Err(Errno::ENOTDIR),
+        }
+
+        dir_lock.files.remove(index);
+        dir_lock.stat.touch();
+        Ok(())
+    }
+
+    fn rename(&self, old_name: &str, new_dir: &Arc<dyn DirectoryObject>, new_name: &str) -> Result<(), Errno> {
+        // Entries can only be moved between tmpfs directories
+        let new_dir = (new_dir.as_ref() as &dyn Any).downcast_ref::<Dir>().ok_or(Errno::EXDEV)?;
+
+        if ptr::eq(self, new_dir) {
+            let mut dir_lock = self.0.write();
+            if dir_lock.files.iter().any(|(file_name, _)| file_name == new_name) {
+                return Err(Errno::EEXIST);
+            }
+
+            let entry = dir_lock.files.iter_mut().find(|(file_name, _)| file_name == old_name).ok_or(Errno::ENOENT)?;
+            entry.0 = new_name.to_string();
+            dir_lock.stat.touch();
+            return Ok(());
+        }
+
+        // Always lock the directory with the lower address first to avoid deadlocks
+        let (mut old_lock, mut new_lock) = if ptr::from_ref(self) < ptr::from_ref(new_dir) {
+            let old_lock = self.0.write();
+            (old_lock, new_dir.0.write())
+        } else {
+            let new_lock = new_dir.0.write();
+            (self.0.write(), new_lock)
+        };
+
+        if new_lock.files.iter().any(|(file_name, _)| file_name == new_name) {
+            return Err(Errno::EEXIST);
+        }
+
+        let index = old_lock.files.iter().position(|(file_name, _)| file_name == old_name).ok_or(Errno::ENOENT)?;
+        let (_, inode) = old_lock.files.remove(index);
+        new_lock.files.push((new_name.to_string(), inode));
+        old_lock.stat.touch();
+        new_lock.stat.touch();
+        Ok(())
+    }
+
     fn stat(&self) -> Result<Stat, Errno> {
         Ok(self.0.read().stat)
     }
@@ -195,6 +283,10 @@ impl DirectoryObject for Dir {
                 file_type: FileType::NamedPipe,
                 name: name.clone(),
             },
+            TmpFsINode::Link(_link) => DirEntry {
+                file_type: FileType::Link,
+                name: name.clone(),
+            },
         };
         Ok(Some(entry))
     }
@@ -215,10 +307,7 @@ impl File {
     pub fn new() -> File {
         File {
             data: RwLock::new(Vec::new()),
-            stat: RwLock::new(Stat {
-                mode: Mode::new(0),
-                ..Stat::zeroed()
-            }),
+            stat: RwLock::new(Stat::created_now(Mode::new(0), 0)),
         }
     }
 }
@@ -240,19 +329,31 @@ impl FileObject for File {
         Ok(len)
     }
 
-    fn write(&self, buf: &[u8], offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+    /// Write `buf` at `offset`, or at the end of the file if opened with `APPEND`
+    fn write(&self, buf: &[u8], offset: usize, options: OpenOptions) -> Result<usize, Errno> {
         let mut data = self.data.write();
+        let offset = if options.contains(OpenOptions::APPEND) { data.len() } else { offset };
 
+        let mut stat = self.stat.write();
         if offset + buf.len() > data.len() {
-            let mut stat = self.stat.write();
             stat.size = offset + buf.len();
-
             data.resize(stat.size, 0);
         }
 
         data[offset..offset + buf.len()].clone_from_slice(buf);
+        stat.touch();
         Ok(buf.len())
     }
+
+    fn truncate(&self, size: usize) -> Result<(), Errno> {
+        let mut data = self.data.write();
+        data.resize(size, 0);
+
+        let mut stat = self.stat.write();
+        stat.size = size;
+        stat.touch();
+        Ok(())
+    }
 }
 
 impl Debug for File {
@@ -270,10 +371,7 @@ impl StaticFile {
     pub fn new(data: &'static [u8]) -> StaticFile {
         StaticFile {
             data,
-            stat: Stat {
-                size: data.len(),
-                ..Stat::zeroed()
-            },
+            stat: Stat::created_now(Mode::new(MODE_FILE), data.len()),
         }
     }
 }
@@ -301,6 +399,10 @@ impl FileObject for StaticFile {
     fn write(&self, _buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
         Err(Errno::ERDONLY)
     }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Err(Errno::ERDONLY)
+    }
 }
 
 impl Debug for StaticFile {
@@ -309,6 +411,36 @@ impl Debug for StaticFile {
     }
 }
 
+struct Link {
+    target: String,
+    stat: Stat,
+}
+
+impl Link {
+    pub fn new(target: &str, mode: Mode) -> Link {
+        Link {
+            target: target.to_string(),
+            stat: Stat::created_now(mode, target.len()),
+        }
+    }
+}
+
+impl LinkObject for Link {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(self.stat)
+    }
+
+    fn target(&self) -> Result<String, Errno> {
+        Ok(self.target.clone())
+    }
+}
+
+impl Debug for Link {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TmpFsLink").field("target", &self.target).finish()
+    }
+}
+
 const PIPE_SIZE: usize = 0x1000;
 
 struct Pipe {
@@ -318,6 +450,10 @@ struct Pipe {
     wx_wq: WaitQueue,                     // writers block when pipe is full
     stat: RwLock<Stat>,
     count: AtomicUsize,
+    readers: AtomicUsize,   // number of opened objects reading from the pipe
+    writers: AtomicUsize,   // number of opened objects writing into the pipe
+    had_reader: AtomicBool, // pipe has been opened for reading at least once
+    had_writer: AtomicBool, // pipe has been opened for writing at least once
 }
 
 impl Pipe {
@@ -328,11 +464,12 @@ impl Pipe {
             wx,
             rx_wq: WaitQueue::new(),
             wx_wq: WaitQueue::new(),
-            stat: RwLock::new(Stat {
-                mode: Mode::new(0),
-                ..Stat::zeroed()
-            }),
+            stat: RwLock::new(Stat::created_now(Mode::new(0), 0)),
             count: AtomicUsize::new(0),
+            readers: AtomicUsize::new(0),
+            writers: AtomicUsize::new(0),
+            had_reader: AtomicBool::new(false),
+            had_writer: AtomicBool::new(false),
         }
     }
 
@@ -345,19 +482,52 @@ impl Pipe {
     fn has_space(&self) -> bool {
         self.count.load(Ordering::Acquire) < PIPE_SIZE
     }
+
+    /// All writers have closed the pipe -> readers get end of file
+    #[inline]
+    fn writers_gone(&self) -> bool {
+        self.had_writer.load(Ordering::Acquire) && self.writers.load(Ordering::Acquire) == 0
+    }
+
+    /// All readers have closed the pipe -> writers get `EPIPE`
+    #[inline]
+    fn readers_gone(&self) -> bool {
+        self.had_reader.load(Ordering::Acquire) && self.readers.load(Ordering::Acquire) == 0
+    }
 }
 
 impl PipeObject for Pipe {
-    fn open(&self, _flags: OpenOptions) -> Result<usize, Errno> {
-        Err(Errno::EBADF)
+    fn open(&self, flags: OpenOptions) -> Result<usize, Errno> {
+        if flags.is_readable() {
+            self.readers.fetch_add(1, Ordering::AcqRel);
+            self.had_reader.store(true, Ordering::Release);
+        }
+        if flags.is_writable() {
+            self.writers.fetch_add(1, Ordering::AcqRel);
+            self.had_writer.store(true, Ordering::Release);
+        }
+        Ok(0)
+    }
+
+    fn close(&self, flags: OpenOptions) {
+        // wake up blocked threads on the other side, if the last reader or writer is gone
+        if flags.is_readable() && self.readers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.wx_wq.notify_all();
+        }
+        if flags.is_writable() && self.writers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.rx_wq.notify_all();
+        }
+        poll::notify();
     }
 
     fn stat(&self) -> Result<Stat, Errno> {
         Ok(*self.stat.read())
     }
 
-    /// Read from pipe buffer, `offset` is ignored
-    fn read(&self, buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+    /// Read from pipe buffer, `offset` is ignored. \
+    /// Blocks until at least one byte is available (or fails with `EAGAIN` if opened with `NONBLOCK`).
+    /// Returns 0 (end of file), if all writers have closed the pipe.
+    fn read(&self, buf: &mut [u8], _offset: usize, options: OpenOptions) -> Result<usize, Errno> {
         let total_to_read = buf.len();
 
         // Nothing to do?
@@ -380,22 +550,33 @@ impl PipeObject for Pipe {
 
                     // We freed space -> wake potentially blocked writer
                     self.wx_wq.notify_one();
+                    poll::notify();
 
                     // Copy byte
                     buf[total_read] = byte;
                     total_read += 1;
                 }
                 Err(_) => {
-                    // no data available -> block until data appears
-                    self.rx_wq.wait(|| self.has_data());
+                    // return what we have got so far or end of file
+                    if total_read > 0 || self.writers_gone() {
+                        break;
+                    }
+                    if options.contains(OpenOptions::NONBLOCK) {
+                        return Err(Errno::EAGAIN);
+                    }
+
+                    // no data available -> block until data appears or the last writer is gone
+                    self.rx_wq.wait(|| self.has_data() || self.writers_gone());
                 }
             }
         }
         Ok(total_read)
     }
 
-    /// Write to pipe buffer, `offset` is ignored
-    fn write(&self, buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+    /// Write to pipe buffer, `offset` is ignored. \
+    /// Blocks until all bytes are written. Returns `EPIPE`, if all readers have closed the pipe. \
+    /// If opened with `NONBLOCK`, only the bytes fitting into the buffer are written (`EAGAIN`, if it is full).
+    fn write(&self, buf: &[u8], _offset: usize, options: OpenOptions) -> Result<usize, Errno> {
         let total_to_write: usize = buf.len();
 
         // Nothing to do?
@@ -404,25 +585,55 @@ impl PipeObject for Pipe {
         }
 
         let mut total_written = 0;
-        for byte in buf {
-//            info!("    pipe write loop");
-            match self.wx.try_enqueue(*byte) {
+        while total_written < total_to_write {
+            if self.readers_gone() {
+                return if total_written > 0 { Ok(total_written) } else { Err(Errno::EPIPE) };
+            }
+
+            match self.wx.try_enqueue(buf[total_written]) {
                 Ok(()) => {
                     self.count.fetch_add(1, Ordering::Release);
 
                     // We have new data -> wake potentially blocked reader
                     self.rx_wq.notify_one();
-                   
+                    poll::notify();
+
                     total_written += 1;
                 }
                 Err(_) => {
-                    // no space in buffer available -> block until data is consumed
-                    self.wx_wq.wait(|| self.has_space());
+                    if options.contains(OpenOptions::NONBLOCK) {
+                        return if total_written > 0 { Ok(total_written) } else { Err(Errno::EAGAIN) };
+                    }
+
+                    // no space in buffer available -> block until data is consumed or the last reader is gone
+                    self.wx_wq.wait(|| self.has_space() || self.readers_gone());
                 }
             }
         }
         Ok(total_written)
     }
+
+    /// The read end is readable, if data is available or all writers are gone (`HUP`). \
+    /// The write end is writable, if there is space in the buffer, and reports `ERR`, if all readers are gone.
+    fn poll(&self, options: OpenOptions) -> PollEvents {
+        let mut events = PollEvents::empty();
+        if options.is_readable() {
+            if self.has_data() {
+                events |= PollEvents::IN;
+            }
+            if self.writers_gone() {
+                events |= PollEvents::IN | PollEvents::HUP;
+            }
+        }
+        if options.is_writable() {
+            if self.readers_gone() {
+                events |= PollEvents::ERR;
+            } else if self.has_space() {
+                events |= PollEvents::OUT;
+            }
+        }
+        events
+    }
 }
 
 impl Debug for Pipe {
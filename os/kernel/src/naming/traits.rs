@@ -7,18 +7,22 @@
    ║   - DirectoryObject: specifies all operations on a directory object     ║
    ║   - FileObject:      specifies all operations on a file object          ║
    ║   - PipeObject:      specifies all operations on a pipe object          ║
+   ║   - LinkObject:      specifies all operations on a symbolic link        ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Michael Schoettner, Univ. Duesseldorf, 05.09.2025               ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 
 
+use alloc::string::String;
 use alloc::sync::Arc;
+use core::any::Any;
 use core::fmt::{self, Debug};
 use core::result::Result;
 
 use super::stat::{Mode, Stat};
-use naming::shared_types::{OpenOptions, DirEntry};
+use naming::shared_types::{OpenOptions, DirEntry, FileType, PollEvents};
+use terminal::TerminalMode;
 use syscall::return_vals::Errno;
 
 /// FileSystem operations
@@ -30,25 +34,50 @@ pub trait FileSystem: Send + Sync {
 pub trait FileObject: Debug + Send + Sync {
     fn stat(&self) -> Result<Stat, Errno>;
     fn read(&self, _buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno>;
+    /// Read like `read`, using `mode`, if the file is the terminal (by default `mode` is ignored)
+    fn read_terminal(&self, buf: &mut [u8], offset: usize, options: OpenOptions, _mode: TerminalMode) -> Result<usize, Errno> {
+        self.read(buf, offset, options)
+    }
     fn write(&self, _buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno>;
+    /// Set the size of the file to `size` bytes (cut off or filled with zeros)
+    fn truncate(&self, _size: usize) -> Result<(), Errno>;
+    /// Events currently pending for an object opened with `options` (by default reading and writing never block)
+    fn poll(&self, _options: OpenOptions) -> PollEvents {
+        PollEvents::IN | PollEvents::OUT
+    }
 }
 
 /// Pipe object operations
 pub trait PipeObject: Debug + Send + Sync {
     fn open(&self, flags: OpenOptions) -> Result<usize, Errno>;
+    /// Called once for each successful `open`, when the opened object is dropped
+    fn close(&self, flags: OpenOptions);
     fn stat(&self) -> Result<Stat, Errno>;
     fn read(&self, _buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno>;
     fn write(&self, _buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno>;
+    /// Events currently pending for an end of the pipe opened with `options`
+    fn poll(&self, options: OpenOptions) -> PollEvents;
  }
 
+/// Symbolic link operations
+pub trait LinkObject: Debug + Send + Sync {
+    fn stat(&self) -> Result<Stat, Errno>;
+    /// Path the link points to (as given when creating the link, may be relative to the link's directory)
+    fn target(&self) -> Result<String, Errno>;
+}
 
-/// Directory object operations
-pub trait DirectoryObject: Debug + Send + Sync {
+
+/// Directory object operations \
+/// `Any` allows `rename` to access the concrete type of the target directory.
+pub trait DirectoryObject: Any + Debug + Send + Sync {
     fn lookup(&self, name: &str) -> Result<NamedObject, Errno>;
     fn create_file(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno>;
     fn create_dir(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno>;
     fn create_pipe(&self, _name: &str, _mode: Mode) -> Result<NamedObject, Errno>;
-    #[allow(dead_code)]
+    fn create_symlink(&self, _name: &str, _target: &str, _mode: Mode) -> Result<NamedObject, Errno>;
+    fn unlink(&self, name: &str) -> Result<(), Errno>;
+    fn rmdir(&self, name: &str) -> Result<(), Errno>;
+    fn rename(&self, old_name: &str, new_dir: &Arc<dyn DirectoryObject>, new_name: &str) -> Result<(), Errno>;
     fn stat(&self) -> Result<Stat, Errno>;
     fn readdir(&self, index: usize) -> Result<Option<DirEntry>, Errno>;
 }
@@ -59,6 +88,7 @@ pub enum NamedObject {
     FileObject(Arc<dyn FileObject>),
     PipeObject(Arc<dyn PipeObject>),
     DirectoryObject(Arc<dyn DirectoryObject>),
+    LinkObject(Arc<dyn LinkObject>),
 }
 
 impl NamedObject {
@@ -86,6 +116,44 @@ impl NamedObject {
         }
     }
     
+    /// Unwraps as a symbolic link. If it's not, returns `Errno::EINVAL`.
+    pub fn as_link(&self) -> Result<&Arc<dyn LinkObject>, Errno> {
+        match self {
+            NamedObject::LinkObject(link) => Ok(link),
+            _ => Err(Errno::EINVAL),
+        }
+    }
+
+    /// Returns the meta data of the object.
+    pub fn stat(&self) -> Result<Stat, Errno> {
+        match self {
+            NamedObject::FileObject(file) => file.stat(),
+            NamedObject::PipeObject(pipe) => pipe.stat(),
+            NamedObject::DirectoryObject(dir) => dir.stat(),
+            NamedObject::LinkObject(link) => link.stat(),
+        }
+    }
+
+    /// Returns the events currently pending for the object opened with `options`. \
+    /// Directories and links are always readable.
+    pub fn poll(&self, options: OpenOptions) -> PollEvents {
+        match self {
+            NamedObject::FileObject(file) => file.poll(options),
+            NamedObject::PipeObject(pipe) => pipe.poll(options),
+            NamedObject::DirectoryObject(_) | NamedObject::LinkObject(_) => PollEvents::IN,
+        }
+    }
+
+    /// Returns the type of the object (as used in directory entries).
+    pub fn file_type(&self) -> FileType {
+        match self {
+            NamedObject::FileObject(_) => FileType::Regular,
+            NamedObject::PipeObject(_) => FileType::NamedPipe,
+            NamedObject::DirectoryObject(_) => FileType::Directory,
+            NamedObject::LinkObject(_) => FileType::Link,
+        }
+    }
+
     /// Returns `true` if it's a file.
     #[allow(dead_code)]
     pub fn is_file(&self) -> bool {
@@ -110,6 +178,7 @@ impl fmt::Debug for NamedObject {
             NamedObject::FileObject(file) => fmt::Debug::fmt(file, f),
             NamedObject::PipeObject(pipe) => fmt::Debug::fmt(pipe, f),
             NamedObject::DirectoryObject(dir) => fmt::Debug::fmt(dir, f),
+            NamedObject::LinkObject(link) => fmt::Debug::fmt(link, f),
         }
     }
 }
@@ -132,6 +201,12 @@ impl From<Arc<dyn DirectoryObject>> for NamedObject {
     }
 }
 
+impl From<Arc<dyn LinkObject>> for NamedObject {
+    fn from(link: Arc<dyn LinkObject>) -> Self {
+        NamedObject::LinkObject(link)
+    }
+}
+
 pub fn as_named_object(dir: Arc<dyn DirectoryObject>) -> NamedObject {
     NamedObject::DirectoryObject(dir)
 }
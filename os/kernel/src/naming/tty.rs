@@ -0,0 +1,101 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: tty                                                             ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ File objects for the terminal, used for the standard streams (stdin,    ║
+   ║ stdout, stderr) of every process and for /dev/tty.                      ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+
+use core::result::Result;
+
+use super::stat::{Mode, Stat, MODE_FILE};
+use super::traits::FileObject;
+use crate::{tty_input, tty_output};
+use naming::shared_types::{OpenOptions, PollEvents};
+use syscall::return_vals::Errno;
+use terminal::TerminalMode;
+
+/// Input of the terminal (read only). \
+/// The terminal mode is passed with every read (`SystemCall::TerminalRead`), plain reads use the canonical mode.
+#[derive(Debug)]
+pub(super) struct TtyInputFile;
+
+/// Output of the terminal (write only)
+#[derive(Debug)]
+pub(super) struct TtyOutputFile;
+
+/// Input and output of the terminal, as exposed by devfs (`/dev/tty`)
+#[derive(Debug)]
+pub(super) struct TtyFile;
+
+impl FileObject for TtyInputFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, buf: &mut [u8], offset: usize, options: OpenOptions) -> Result<usize, Errno> {
+        self.read_terminal(buf, offset, options, TerminalMode::Canonical)
+    }
+
+    fn read_terminal(&self, buf: &mut [u8], _offset: usize, _options: OpenOptions, mode: TerminalMode) -> Result<usize, Errno> {
+        Ok(tty_input().read(buf, mode))
+    }
+
+    fn write(&self, _buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Err(Errno::EBADF)
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(()) // a terminal has no size
+    }
+
+    fn poll(&self, _options: OpenOptions) -> PollEvents {
+        if tty_input().poll() { PollEvents::IN } else { PollEvents::empty() }
+    }
+}
+
+impl FileObject for TtyOutputFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, _buf: &mut [u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Err(Errno::EBADF)
+    }
+
+    fn write(&self, buf: &[u8], _offset: usize, _options: OpenOptions) -> Result<usize, Errno> {
+        Ok(tty_output().write(buf))
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(()) // a terminal has no size
+    }
+}
+
+impl FileObject for TtyFile {
+    fn stat(&self) -> Result<Stat, Errno> {
+        Ok(Stat::new(Mode::new(MODE_FILE), 0))
+    }
+
+    fn read(&self, buf: &mut [u8], offset: usize, options: OpenOptions) -> Result<usize, Errno> {
+        TtyInputFile.read(buf, offset, options)
+    }
+
+    fn read_terminal(&self, buf: &mut [u8], offset: usize, options: OpenOptions, mode: TerminalMode) -> Result<usize, Errno> {
+        TtyInputFile.read_terminal(buf, offset, options, mode)
+    }
+
+    fn write(&self, buf: &[u8], offset: usize, options: OpenOptions) -> Result<usize, Errno> {
+        TtyOutputFile.write(buf, offset, options)
+    }
+
+    fn truncate(&self, _size: usize) -> Result<(), Errno> {
+        Ok(()) // a terminal has no size
+    }
+
+    fn poll(&self, options: OpenOptions) -> PollEvents {
+        TtyInputFile.poll(options) | PollEvents::OUT
+    }
+}
@@ -1,33 +1,133 @@
 use alloc::collections::btree_map::BTreeMap;
+use alloc::format;
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 use smoltcp::socket::dns::GetQueryResultError;
 use core::net::{Ipv4Addr, Ipv6Addr};
-use core::ops::Deref;
+use core::ops::{Deref, DerefMut};
 use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use log::{info, warn};
 use smoltcp::iface::{self, Interface, SocketHandle, SocketSet};
-use smoltcp::socket::{dhcpv4, dns, icmp, tcp, udp};
+use smoltcp::socket::{dhcpv4, dns, icmp, tcp, udp, AnySocket, Socket};
 use smoltcp::time::Instant;
-use smoltcp::wire::{DnsQueryType, HardwareAddress, IpAddress, IpCidr, IpEndpoint};
-use spin::{Once, RwLock};
+use smoltcp::wire::{DnsQueryType, EthernetAddress, HardwareAddress, IpAddress, IpCidr, IpEndpoint};
+use spin::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use x86_64::instructions::interrupts;
+use crate::device::e1000;
+use crate::device::e1000::E1000;
 use crate::device::rtl8139::Rtl8139;
+use crate::device::virtio;
+use crate::device::virtio::net::VirtioNet;
 use crate::process::process::Process;
 use crate::{pci_bus, process_manager, scheduler, timer};
 use crate::process::thread::Thread;
+use crate::naming::poll;
+use crate::sync::wait_queue::WaitQueue;
+use naming::shared_types::PollEvents;
+
+/// The network device used by an interface
+enum NetworkDevice {
+    Rtl8139(Arc<Rtl8139>),
+    E1000(Arc<E1000>),
+    VirtioNet(Arc<VirtioNet>),
+}
+
+impl NetworkDevice {
+    fn name(&self) -> &'static str {
+        match self {
+            NetworkDevice::Rtl8139(_) => "RTL8139",
+            NetworkDevice::E1000(_) => "e1000",
+            NetworkDevice::VirtioNet(_) => "VirtioNet",
+        }
+    }
+
+    fn mac_address(&self) -> EthernetAddress {
+        match self {
+            NetworkDevice::Rtl8139(rtl8139) => rtl8139.read_mac_address(),
+            NetworkDevice::E1000(e1000) => e1000.read_mac_address(),
+            NetworkDevice::VirtioNet(virtio_net) => virtio_net.read_mac_address(),
+        }
+    }
 
-static RTL8139: Once<Arc<Rtl8139>> = Once::new();
+    // The Smoltcp interface struct wants a mutable reference to the device.
+    // However, the drivers are designed to work with shared references.
+    // Since smoltcp does not actually store the mutable reference anywhere,
+    // we can safely cast the shared reference to a mutable one.
+    // (Actually, I am not sure why the smoltcp interface wants a mutable reference to the device,
+    // since it does not modify the device itself.)
+    fn create_interface(&self, conf: iface::Config, now: Instant) -> Interface {
+        match self {
+            NetworkDevice::Rtl8139(rtl8139) => Interface::new(conf, unsafe { ptr::from_ref(rtl8139.deref()).cast_mut().as_mut().unwrap() }, now),
+            NetworkDevice::E1000(e1000) => Interface::new(conf, unsafe { ptr::from_ref(e1000.deref()).cast_mut().as_mut().unwrap() }, now),
+            NetworkDevice::VirtioNet(virtio_net) => Interface::new(conf, unsafe { ptr::from_ref(virtio_net.deref()).cast_mut().as_mut().unwrap() }, now),
+        }
+    }
+
+    fn poll(&self, interface: &mut Interface, time: Instant, sockets: &mut SocketSet) {
+        match self {
+            NetworkDevice::Rtl8139(rtl8139) => interface.poll(time, unsafe { ptr::from_ref(rtl8139.deref()).cast_mut().as_mut().unwrap() }, sockets),
+            NetworkDevice::E1000(e1000) => interface.poll(time, unsafe { ptr::from_ref(e1000.deref()).cast_mut().as_mut().unwrap() }, sockets),
+            NetworkDevice::VirtioNet(virtio_net) => interface.poll(time, unsafe { ptr::from_ref(virtio_net.deref()).cast_mut().as_mut().unwrap() }, sockets),
+        };
+    }
+}
 
-static INTERFACES: RwLock<Vec<Interface>> = RwLock::new(Vec::new());
-static SOCKETS: Once<RwLock<SocketSet>> = Once::new();
-/// This maps sockets to the respective process.
+/// A network interface (named eth0, eth1, ... in the order the devices are found)
+struct NetworkInterface {
+    name: String,
+    device: NetworkDevice,
+    interface: Interface,
+    /// Sockets sending and receiving via this interface (smoltcp drops packets for sockets, which are not in the set)
+    sockets: SocketSet<'static>,
+    dhcp_handle: SocketHandle,
+    /// Used for DNS queries, if this interface has the default route (the servers are set via DHCP)
+    dns_handle: SocketHandle,
+    /// Only one interface gets the default route
+    default_route: bool,
+}
+
+/// Id of a socket, as passed to user space
+pub type SocketId = usize;
+
+/// A socket opened by an application. It is kept in the socket set of the interface it uses:
+/// the default interface, until it is bound to an address or connected to a host of another interface.
+struct OpenSocket {
+    process: Arc<Process>,
+    interface: usize,
+    handle: SocketHandle,
+}
+
+/// Summary of a network interface (used by procfs)
+pub struct InterfaceInfo {
+    pub name: String,
+    pub driver: &'static str,
+    pub mac: EthernetAddress,
+    pub addresses: Vec<IpCidr>,
+    pub default_route: bool,
+}
+
+static INTERFACES: RwLock<Vec<NetworkInterface>> = RwLock::new(Vec::new());
+/// This maps socket ids to the sockets and their processes.
 /// We use this to check whether a process can access a particular socket.
 /// We can't just create a SocketSet per process because smoltcp drops all
 /// packets for non-existing sockets when polling.
-static SOCKET_PROCESS: RwLock<BTreeMap<SocketHandle, Arc<Process>>> = RwLock::new(BTreeMap::new());
-static DNS_SOCKET: Once<SocketHandle> = Once::new();
-static DHCP_SOCKET: Once<SocketHandle> = Once::new();
+/// If both locks are needed, `SOCKETS` is locked before `INTERFACES`.
+static SOCKETS: RwLock<BTreeMap<SocketId, OpenSocket>> = RwLock::new(BTreeMap::new());
+static NEXT_SOCKET_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// The thread polling all interfaces waits here, until a network device raises an interrupt,
+/// an application has changed a socket or smoltcp needs to handle a timeout.
+static POLL_WQ: WaitQueue = WaitQueue::new();
+/// Set by `wakeup`, if the interfaces need to be polled again
+static POLL_PENDING: AtomicBool = AtomicBool::new(false);
+/// Set by the poll thread, if it has found the interfaces locked.
+/// It is woken up, when the lock is released (see `PollLockGuard`).
+static POLL_DEFERRED: AtomicBool = AtomicBool::new(false);
+/// Threads waiting for a socket to change its state (e.g. in `accept_tcp`)
+static SOCKET_WQ: WaitQueue = WaitQueue::new();
 
 #[derive(Debug)]
 #[repr(u8)]
@@ -36,89 +136,210 @@ pub enum SocketType {
     Udp, Tcp, Icmp,
 }
 
-pub fn init() {
-    SOCKETS.call_once(|| RwLock::new(SocketSet::new(Vec::new())));
+/// Guard for the interfaces, which wakes up the poll thread after releasing the lock,
+/// if the poll thread has found it locked
+struct PollLockGuard<G>(Option<G>);
 
-    let devices = pci_bus().search_by_ids(0x10ec, 0x8139);
-    if !devices.is_empty() {
-        RTL8139.call_once(|| {
-            info!("Found Realtek RTL8139 network controller");
-            let rtl8139 = Arc::new(Rtl8139::new(devices[0]));
-            info!("RTL8139 MAC address: [{}]", rtl8139.read_mac_address());
+impl<G: Deref> Deref for PollLockGuard<G> {
+    type Target = G::Target;
 
-            Rtl8139::plugin(Arc::clone(&rtl8139));
-            rtl8139
-        });
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("Lock has been released")
     }
+}
 
-    if let Some(rtl8139) = RTL8139.get() {
-        extern "sysv64" fn poll() {
-            loop { poll_sockets(); scheduler().sleep(50); }
+impl<G: DerefMut> DerefMut for PollLockGuard<G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("Lock has been released")
+    }
+}
+
+impl<G> Drop for PollLockGuard<G> {
+    fn drop(&mut self) {
+        drop(self.0.take());
+        if POLL_DEFERRED.swap(false, Ordering::AcqRel) {
+            wakeup();
         }
-        scheduler().ready(Thread::new_kernel_thread(poll, "RTL8139"));
-        
-        // Set up network interface
+    }
+}
+
+fn read_interfaces() -> PollLockGuard<RwLockReadGuard<'static, Vec<NetworkInterface>>> {
+    PollLockGuard(Some(INTERFACES.read()))
+}
+
+fn lock_interfaces() -> PollLockGuard<RwLockWriteGuard<'static, Vec<NetworkInterface>>> {
+    PollLockGuard(Some(INTERFACES.write()))
+}
+
+pub fn init() {
+    let mut devices = Vec::new();
+    for pci_device in pci_bus().search_by_ids(0x10ec, 0x8139) {
+        info!("Found Realtek RTL8139 network controller");
+        let rtl8139 = Arc::new(Rtl8139::new(pci_device));
+        info!("RTL8139 MAC address: [{}]", rtl8139.read_mac_address());
+
+        Rtl8139::plugin(Arc::clone(&rtl8139));
+        devices.push(NetworkDevice::Rtl8139(rtl8139));
+    }
+    for pci_device in e1000::find_devices() {
+        let e1000 = Arc::new(E1000::new(pci_device));
+        E1000::plugin(Arc::clone(&e1000));
+        devices.push(NetworkDevice::E1000(e1000));
+    }
+    for pci_device in virtio::net::find_devices() {
+        devices.push(NetworkDevice::VirtioNet(Arc::new(VirtioNet::new(pci_device))));
+    }
+
+    if devices.is_empty() {
+        return;
+    }
+
+    // Set up a network interface for each device
+    for (index, device) in devices.into_iter().enumerate() {
         let time = timer().systime_ms();
-        let mut conf = iface::Config::new(HardwareAddress::from(rtl8139.read_mac_address()));
-        conf.random_seed = time as u64;
-
-        // The Smoltcp interface struct wants a mutable reference to the device.
-        // However, the RTL8139 driver is designed to work with shared references.
-        // Since smoltcp does not actually store the mutable reference anywhere,
-        // we can safely cast the shared reference to a mutable one.
-        // (Actually, I am not sure why the smoltcp interface wants a mutable reference to the device,
-        // since it does not modify the device itself.)
-        let device = unsafe { ptr::from_ref(rtl8139.deref()).cast_mut().as_mut().unwrap() };
-        add_interface(Interface::new(conf, device, Instant::from_millis(time as i64)));
-
-        let sockets = SOCKETS.get().expect("Socket set not initialized!");
-        let current_process = process_manager().read().current_process();
-        let mut process_map = SOCKET_PROCESS.write();
-        // setup DNS
-        DNS_SOCKET.call_once(|| {
-            let dns_socket = dns::Socket::new(&[], Vec::new());
-            let dns_handle = sockets.write().add(dns_socket);
-            process_map
-                .try_insert(dns_handle, current_process.clone())
-                .expect("failed to insert socket into socket-process map");
-            dns_handle
-        });
+        let mut conf = iface::Config::new(HardwareAddress::from(device.mac_address()));
+        conf.random_seed = time as u64 + index as u64;
+
+        let interface = device.create_interface(conf, Instant::from_millis(time as i64));
+        let name = format!("eth{index}");
+        info!("Network interface [{}] uses {} device with MAC address [{}]", name, device.name(), device.mac_address());
+
         // request an IP address via DHCP
-        DHCP_SOCKET.call_once(|| {
-            let dhcp_socket = dhcpv4::Socket::new();
-            let dhcp_handle = sockets
-                .write()
-                .add(dhcp_socket);
-            process_map
-                .try_insert(dhcp_handle, current_process)
-                .expect("failed to insert socket into socket-process map");
-            dhcp_handle
+        let mut sockets = SocketSet::new(Vec::new());
+        let dhcp_handle = sockets.add(dhcpv4::Socket::new());
+        let dns_handle = sockets.add(dns::Socket::new(&[], Vec::new()));
+        lock_interfaces().push(NetworkInterface {
+            name,
+            device,
+            interface,
+            sockets,
+            dhcp_handle,
+            dns_handle,
+            default_route: false,
         });
     }
+
+    extern "sysv64" fn poll() {
+        loop {
+            let pending = || POLL_PENDING.swap(false, Ordering::AcqRel);
+            match poll_sockets() {
+                Some(delay) => {
+                    // received packets, freed buffers and timeouts may have made sockets ready
+                    SOCKET_WQ.notify_all();
+                    poll::notify();
+                    match delay {
+                        Some(delay) => { POLL_WQ.wait_timeout(pending, delay); }
+                        None => POLL_WQ.wait(pending),
+                    }
+                }
+                // an application is using the interfaces or the sockets and wakes this thread up, when it releases them
+                None => POLL_WQ.wait(pending),
+            }
+        }
+    }
+    scheduler().ready(Thread::new_kernel_thread(poll, "network"));
 }
 
-fn check_ownership(handle: SocketHandle) {
+/// Let the poll thread poll all interfaces as soon as possible. \
+/// This is called by the interrupt handlers of the network devices and after a socket has been changed by an application.
+pub fn wakeup() {
+    POLL_PENDING.store(true, Ordering::Release);
+
+    // Interrupt handlers must not deblock threads directly, as this may allocate memory
+    if interrupts::are_enabled() {
+        POLL_WQ.notify_all();
+    } else {
+        POLL_WQ.notify_all_from_interrupt();
+    }
+}
+
+/// Return `id`, if the socket exists and belongs to the current process
+pub fn socket_id(id: usize) -> Option<SocketId> {
+    let current_process = process_manager().read().current_process();
+    SOCKETS.read().get(&id).filter(|socket| socket.process == current_process).map(|_| id)
+}
+
+fn owned_socket(sockets: &BTreeMap<SocketId, OpenSocket>, id: SocketId) -> &OpenSocket {
     // TODO: these panics should probably kill the process that made the call, not the kernel
-    let lock = SOCKET_PROCESS.read();
-    let owning_process = lock
-        .get(&handle)
+    let socket = sockets
+        .get(&id)
         .expect("process tried accessing non-existent socket");
-    if *owning_process != process_manager().read().current_process() {
+    if socket.process != process_manager().read().current_process() {
         panic!("process tried to access socket of a different process");
     }
+    socket
 }
 
 // for lifetime-reasons this must be a macro
 macro_rules! get_socket_for_current_process {
-    ($socket:ident, $handle:ident, $type:ty) => {
-        check_ownership($handle);
-        let mut sockets = SOCKETS.get().expect("Socket set not initialized!").write();
-        let $socket = sockets.get_mut::<$type>($handle);
+    ($socket:ident, $id:ident, $type:ty) => {
+        let sockets = SOCKETS.read();
+        let open_socket = owned_socket(&sockets, $id);
+        let mut interfaces = lock_interfaces();
+        let $socket = interfaces[open_socket.interface].sockets.get_mut::<$type>(open_socket.handle);
+    }
+}
+
+/// Move the socket `id` of the current process into the socket set of the interface chosen by `select`
+/// (it stays in its set, if `select` returns `None`). \
+/// Returns the locked interfaces, the index of the interface and the handle of the socket in its set.
+fn place_socket(id: SocketId, select: impl FnOnce(&[NetworkInterface]) -> Option<usize>)
+    -> (PollLockGuard<RwLockWriteGuard<'static, Vec<NetworkInterface>>>, usize, SocketHandle) {
+    let mut sockets = SOCKETS.write();
+    owned_socket(&sockets, id);
+    let open_socket = sockets.get_mut(&id).unwrap();
+
+    let mut interfaces = lock_interfaces();
+    if let Some(target) = select(&interfaces) && target != open_socket.interface {
+        let handle = match interfaces[open_socket.interface].sockets.remove(open_socket.handle) {
+            Socket::Udp(socket) => interfaces[target].sockets.add(socket),
+            Socket::Tcp(socket) => interfaces[target].sockets.add(socket),
+            Socket::Icmp(socket) => interfaces[target].sockets.add(socket),
+            _ => panic!("application sockets are UDP, TCP or ICMP sockets"),
+        };
+        open_socket.interface = target;
+        open_socket.handle = handle;
     }
+
+    (interfaces, open_socket.interface, open_socket.handle)
 }
 
-fn add_interface(interface: Interface) {
-    INTERFACES.write().push(interface);
+/// Select the interface for sending packets to `destination`: The first interface with `destination` in one of its subnets,
+/// otherwise the interface with the default route (or the first interface, if none has a default route).
+fn route(interfaces: &[NetworkInterface], destination: IpAddress) -> Option<usize> {
+    interfaces
+        .iter()
+        .position(|interface| interface.interface.ip_addrs().iter().any(|cidr| cidr.contains_addr(&destination)))
+        .or_else(|| default_interface(interfaces))
+}
+
+/// The interface with the default route (or the first interface, if none has a default route)
+fn default_interface(interfaces: &[NetworkInterface]) -> Option<usize> {
+    interfaces
+        .iter()
+        .position(|interface| interface.default_route)
+        .or((!interfaces.is_empty()).then_some(0))
+}
+
+/// The interface with the address `address` (none for unspecified addresses)
+fn interface_with_address(interfaces: &[NetworkInterface], address: IpAddress) -> Option<usize> {
+    interfaces
+        .iter()
+        .position(|interface| interface.interface.ip_addrs().iter().any(|cidr| cidr.address() == address))
+}
+
+/// Get a summary of all network interfaces.
+pub fn interfaces() -> Vec<InterfaceInfo> {
+    read_interfaces()
+        .iter()
+        .map(|interface| InterfaceInfo {
+            name: interface.name.clone(),
+            driver: interface.device.name(),
+            mac: interface.device.mac_address(),
+            addresses: interface.interface.ip_addrs().to_vec(),
+            default_route: interface.default_route,
+        })
+        .collect()
 }
 
 /// Get IP addresses for a host.
@@ -126,14 +347,13 @@ fn add_interface(interface: Interface) {
 /// If host is none, get the addresses of the current host.
 pub fn get_ip_addresses(host: Option<&str>) -> Vec<IpAddress> {
     if let Some(host) = host {
-        let handle = DNS_SOCKET.get().expect("DNS socket does not exist yet");
         // first, start the queries
-        let mut query_handles: Vec<_> = {
-            let mut interfaces = INTERFACES.write();
-            let interface = interfaces.get_mut(0).expect("network interface is missing");
-            let mut sockets = SOCKETS.get().expect("Socket set not initialized!").write();
-            let socket = sockets.get_mut::<dns::Socket>(*handle);
-            [DnsQueryType::Aaaa, DnsQueryType::A, DnsQueryType::Cname]
+        let (index, dns_handle, mut query_handles) = {
+            let mut interfaces = lock_interfaces();
+            let index = default_interface(&interfaces).expect("network interface is missing");
+            let NetworkInterface { interface, sockets, dns_handle, .. } = &mut interfaces[index];
+            let socket = sockets.get_mut::<dns::Socket>(*dns_handle);
+            let query_handles: Vec<_> = [DnsQueryType::Aaaa, DnsQueryType::A, DnsQueryType::Cname]
                 .into_iter()
                 .filter_map(|ty|
                         socket
@@ -144,14 +364,16 @@ pub fn get_ip_addresses(host: Option<&str>) -> Vec<IpAddress> {
                             })
                             .ok()
                 )
-                .collect()
+                .collect();
+            (index, *dns_handle, query_handles)
         };
-        // then, see if they've returned something
+        wakeup();
+        // then, wait until they've returned something
         let mut resulting_ips = Vec::new();
-        loop {
+        SOCKET_WQ.wait(|| {
             {
-                let mut sockets = SOCKETS.get().expect("Socket set not initialized!").write();
-                let socket = sockets.get_mut::<dns::Socket>(*handle);
+                let mut interfaces = lock_interfaces();
+                let socket = interfaces[index].sockets.get_mut::<dns::Socket>(dns_handle);
                 let mut remaining: Vec<_> = query_handles
                     .drain(..)
                     .filter(|query| match socket.get_query_result(*query) {
@@ -172,29 +394,40 @@ pub fn get_ip_addresses(host: Option<&str>) -> Vec<IpAddress> {
                     .collect();
                 if remaining.is_empty() {
                     // we're done!
-                    break;
+                    return true;
                 }
                 // else, check for the remaining ones
                 query_handles.clear();
                 query_handles.append(&mut remaining);
             }
-            // release the locks and sleep
-            scheduler().sleep(50);
-        }
+            // release the locks and wait for the next poll
+            false
+        });
         resulting_ips
     } else {
-        INTERFACES
-            .read()
+        read_interfaces()
             .iter()
-            .flat_map(Interface::ip_addrs)
+            .flat_map(|interface| interface.interface.ip_addrs())
             .map(IpCidr::address)
             .collect()
     }
 }
 
-pub fn open_udp() -> SocketHandle {
-    let sockets = SOCKETS.get().expect("Socket set not initialized!");
+/// Add `socket` to the socket set of the default interface and register it for the current process.
+/// Returns `None`, if there is no network interface.
+fn open_socket<T: AnySocket<'static>>(socket: T) -> Option<SocketId> {
+    let process = process_manager().read().current_process();
+    let mut sockets = SOCKETS.write();
+    let mut interfaces = lock_interfaces();
+    let interface = default_interface(&interfaces)?;
+
+    let handle = interfaces[interface].sockets.add(socket);
+    let id = NEXT_SOCKET_ID.fetch_add(1, Ordering::Relaxed);
+    sockets.insert(id, OpenSocket { process, interface, handle });
+    Some(id)
+}
 
+pub fn open_udp() -> Option<SocketId> {
     let rx_buffer = udp::PacketBuffer::new(
         vec![udp::PacketMetadata::EMPTY, udp::PacketMetadata::EMPTY],
         vec![0; 65535],
@@ -204,30 +437,17 @@ pub fn open_udp() -> SocketHandle {
         vec![0; 65535],
     );
 
-    let handle = sockets.write().add(udp::Socket::new(rx_buffer, tx_buffer));
-    SOCKET_PROCESS
-        .write()
-        .try_insert(handle, process_manager().read().current_process())
-        .expect("failed to insert socket into socket-process map");
-    handle
+    open_socket(udp::Socket::new(rx_buffer, tx_buffer))
 }
 
-pub fn open_tcp() -> SocketHandle {
-    let sockets = SOCKETS.get().expect("Socket set not initialized!");
+pub fn open_tcp() -> Option<SocketId> {
     let rx_buffer = tcp::SocketBuffer::new(vec![0; 65535]);
     let tx_buffer = tcp::SocketBuffer::new(vec![0; 65535]);
 
-    let handle = sockets.write().add(tcp::Socket::new(rx_buffer, tx_buffer));
-    SOCKET_PROCESS
-        .write()
-        .try_insert(handle, process_manager().read().current_process())
-        .expect("failed to insert socket into socket-process map");
-    handle
+    open_socket(tcp::Socket::new(rx_buffer, tx_buffer))
 }
 
-pub fn open_icmp() -> SocketHandle {
-    let sockets = SOCKETS.get().expect("Socket set not initialized!");
-    
+pub fn open_icmp() -> Option<SocketId> {
     let rx_buffer = icmp::PacketBuffer::new(
         vec![icmp::PacketMetadata::EMPTY, icmp::PacketMetadata::EMPTY],
         vec![0; 65535],
@@ -237,23 +457,20 @@ pub fn open_icmp() -> SocketHandle {
         vec![0; 65535],
     );
 
-    let handle = sockets.write().add(icmp::Socket::new(rx_buffer, tx_buffer));
-    SOCKET_PROCESS
-        .write()
-        .try_insert(handle, process_manager().read().current_process())
-        .expect("failed to insert socket into socket-process map");
-    handle
+    open_socket(icmp::Socket::new(rx_buffer, tx_buffer))
 }
 
-pub fn close_socket(handle: SocketHandle) {
-    let sockets = SOCKETS.get().expect("Socket set not initialized!");
-    check_ownership(handle);
-    SOCKET_PROCESS.write().remove(&handle).unwrap();
-    sockets.write().remove(handle);
+pub fn close_socket(id: SocketId) {
+    let mut sockets = SOCKETS.write();
+    owned_socket(&sockets, id);
+    let socket = sockets.remove(&id).unwrap();
+    lock_interfaces()[socket.interface].sockets.remove(socket.handle);
 }
 
-pub fn bind_udp(handle: SocketHandle, addr: IpAddress, port: u16) -> Result<(), udp::BindError> {
-    get_socket_for_current_process!(socket, handle, udp::Socket);
+pub fn bind_udp(id: SocketId, addr: IpAddress, port: u16) -> Result<(), udp::BindError> {
+    // the socket is moved to the interface with the address (binding to 0.0.0.0 or :: keeps it at its interface)
+    let (mut interfaces, index, handle) = place_socket(id, |interfaces| interface_with_address(interfaces, addr));
+    let socket = interfaces[index].sockets.get_mut::<udp::Socket>(handle);
     let port = pick_port(port);
     match addr {
         // binding to 0.0.0.0 or :: means listening to all requests
@@ -264,8 +481,9 @@ pub fn bind_udp(handle: SocketHandle, addr: IpAddress, port: u16) -> Result<(),
     }
 }
 
-pub fn bind_tcp(handle: SocketHandle, addr: IpAddress, port: u16) -> Result<(), tcp::ListenError> {
-    get_socket_for_current_process!(socket, handle, tcp::Socket);
+pub fn bind_tcp(id: SocketId, addr: IpAddress, port: u16) -> Result<(), tcp::ListenError> {
+    let (mut interfaces, index, handle) = place_socket(id, |interfaces| interface_with_address(interfaces, addr));
+    let socket = interfaces[index].sockets.get_mut::<tcp::Socket>(handle);
     let port = pick_port(port);
     match addr {
         // binding to 0.0.0.0 or :: means listening to all requests
@@ -276,140 +494,222 @@ pub fn bind_tcp(handle: SocketHandle, addr: IpAddress, port: u16) -> Result<(),
     }
 }
 
-pub fn bind_icmp(handle: SocketHandle, ident: u16) -> Result<(), icmp::BindError> {
-    get_socket_for_current_process!(socket, handle, icmp::Socket);
+pub fn bind_icmp(id: SocketId, ident: u16) -> Result<(), icmp::BindError> {
+    get_socket_for_current_process!(socket, id, icmp::Socket);
     socket.bind(icmp::Endpoint::Ident(ident))
 }
 
-pub fn accept_tcp(handle: SocketHandle) -> Result<IpEndpoint, tcp::ConnectError> {
+pub fn accept_tcp(id: SocketId) -> Result<IpEndpoint, tcp::ConnectError> {
     // TODO: smoltcp knows no backlog
     // all but the first connection will fail
-    loop {
-        // this extra block is needed so that we don't block all sockets
-        {
-            get_socket_for_current_process!(socket, handle, tcp::Socket);
-            if socket.is_active() {
-                break;
-            }
-        }
-        scheduler().sleep(100);
-    }
-    get_socket_for_current_process!(socket, handle, tcp::Socket);
+    SOCKET_WQ.wait(|| {
+        // the interfaces must not be locked while waiting, so that they can be polled
+        get_socket_for_current_process!(socket, id, tcp::Socket);
+        socket.is_active()
+    });
+    get_socket_for_current_process!(socket, id, tcp::Socket);
     Ok(socket.remote_endpoint().unwrap())
 }
 
-pub fn connect_tcp(handle: SocketHandle, host: IpAddress, port: u16) -> Result<IpEndpoint, tcp::ConnectError> {    get_socket_for_current_process!(socket, handle, tcp::Socket);
-    let mut interfaces = INTERFACES.write();
-    let interface = interfaces.get_mut(0).ok_or(tcp::ConnectError::InvalidState)?;
+pub fn connect_tcp(id: SocketId, host: IpAddress, port: u16) -> Result<IpEndpoint, tcp::ConnectError> {
+    // the socket is moved to the interface with the route to `host`
+    let (mut interfaces, index, handle) = place_socket(id, |interfaces| route(interfaces, host));
+    let NetworkInterface { interface, sockets, .. } = &mut interfaces[index];
+    let socket = sockets.get_mut::<tcp::Socket>(handle);
     let local_port = pick_port(0);
 
     socket.connect(interface.context(), (host, port), local_port)?;
+    wakeup();
     Ok(socket.local_endpoint().unwrap())
 }
 
-pub fn send_datagram(handle: SocketHandle, destination: IpAddress, port: u16, data: &[u8]) -> Result<(), udp::SendError> {
-    get_socket_for_current_process!(socket, handle, udp::Socket);
+pub fn send_datagram(id: SocketId, destination: IpAddress, port: u16, data: &[u8]) -> Result<(), udp::SendError> {
+    // sockets bound to an address stay at its interface, the others are moved to the interface with the route to `destination`
+    let has_address = {
+        get_socket_for_current_process!(socket, id, udp::Socket);
+        socket.endpoint().addr.is_some()
+    };
+    let (mut interfaces, index, handle) = place_socket(id, |interfaces| {
+        if has_address { None } else { route(interfaces, destination) }
+    });
+    let socket = interfaces[index].sockets.get_mut::<udp::Socket>(handle);
+    // the poll thread sends the data, as soon as the interfaces are released
+    wakeup();
     socket.send_slice(data, (destination, port))
 }
 
-pub fn send_tcp(handle: SocketHandle, data: &[u8]) -> Result<usize, tcp::SendError> {
-    get_socket_for_current_process!(socket, handle, tcp::Socket);
+pub fn send_tcp(id: SocketId, data: &[u8]) -> Result<usize, tcp::SendError> {
+    get_socket_for_current_process!(socket, id, tcp::Socket);
+    wakeup();
     socket.send_slice(data)
 }
 
-pub fn send_icmp(handle: SocketHandle, destination: IpAddress, data: &[u8]) -> Result<(), icmp::SendError> {
-    get_socket_for_current_process!(socket, handle, icmp::Socket);
+pub fn send_icmp(id: SocketId, destination: IpAddress, data: &[u8]) -> Result<(), icmp::SendError> {
+    // ICMP sockets are only bound to an identifier, so they are always moved to the interface with the route to `destination`
+    let (mut interfaces, index, handle) = place_socket(id, |interfaces| route(interfaces, destination));
+    let socket = interfaces[index].sockets.get_mut::<icmp::Socket>(handle);
+    wakeup();
     socket.send_slice(data, destination)
 }
 
-pub fn receive_datagram(handle: SocketHandle, data: &mut [u8]) -> Result<(usize, udp::UdpMetadata), udp::RecvError> {
-    get_socket_for_current_process!(socket, handle, udp::Socket);
+pub fn receive_datagram(id: SocketId, data: &mut [u8]) -> Result<(usize, udp::UdpMetadata), udp::RecvError> {
+    get_socket_for_current_process!(socket, id, udp::Socket);
     socket.recv_slice(data)
 }
 
-pub fn receive_tcp(handle: SocketHandle, data: &mut [u8]) -> Result<usize, tcp::RecvError> {
-    get_socket_for_current_process!(socket, handle, tcp::Socket);
+pub fn receive_tcp(id: SocketId, data: &mut [u8]) -> Result<usize, tcp::RecvError> {
+    get_socket_for_current_process!(socket, id, tcp::Socket);
+    // reading frees space in the receive window, which must be announced to the remote side
+    wakeup();
     socket.recv_slice(data)
 }
 
-pub fn receive_icmp(handle: SocketHandle, data: &mut [u8]) -> Result<(usize, IpAddress), icmp::RecvError> {
-    get_socket_for_current_process!(socket, handle, icmp::Socket);
+pub fn receive_icmp(id: SocketId, data: &mut [u8]) -> Result<(usize, IpAddress), icmp::RecvError> {
+    get_socket_for_current_process!(socket, id, icmp::Socket);
     socket.recv_slice(data)
 }
 
+/// Events currently pending for the socket `id` (used by the `Poll` system call, see `socket_id`). \
+/// Returns `NVAL`, if the socket does not exist. \
+/// A listening TCP socket becomes readable or writable, once a connection can be accepted.
+pub fn socket_readiness(id: SocketId) -> PollEvents {
+    let sockets = SOCKETS.read();
+    let Some(open_socket) = sockets.get(&id) else {
+        return PollEvents::NVAL;
+    };
+    let interfaces = read_interfaces();
+    let Some((_, socket)) = interfaces[open_socket.interface].sockets.iter().find(|(handle, _)| *handle == open_socket.handle) else {
+        return PollEvents::NVAL;
+    };
+
+    let mut events = PollEvents::empty();
+    match socket {
+        Socket::Udp(socket) => {
+            events.set(PollEvents::IN, socket.can_recv());
+            events.set(PollEvents::OUT, socket.can_send());
+        },
+        Socket::Icmp(socket) => {
+            events.set(PollEvents::IN, socket.can_recv());
+            events.set(PollEvents::OUT, socket.can_send());
+        },
+        Socket::Tcp(socket) => match socket.state() {
+            tcp::State::Listen | tcp::State::SynSent => {},
+            tcp::State::SynReceived => events |= PollEvents::IN,
+            tcp::State::Closed | tcp::State::TimeWait => events |= PollEvents::IN | PollEvents::HUP,
+            _ => {
+                // if the remote side has closed the connection, reading does not block (end of stream)
+                events.set(PollEvents::IN, socket.can_recv() || !socket.may_recv());
+                events.set(PollEvents::HUP, !socket.may_recv());
+                events.set(PollEvents::OUT, socket.can_send());
+            },
+        },
+        _ => {},
+    }
+    events
+}
+
 /// Try to poll all sockets.
 /// 
-/// This returns None, if it failed to get all needed locks.
-/// This is needed, because we otherwise might get a deadlock, because an
-/// application has the lock on `sockets` while we have the lock on `interfaces`.
-fn poll_sockets() -> Option<()> {
-    let rtl8139 = RTL8139.get().expect("RTL8139 not initialized");
+/// Returns the time in ms, until smoltcp needs to poll the interfaces again (e.g. for retransmissions),
+/// or `Some(None)`, if smoltcp does not need to poll them before the next packet or socket change.
+/// This returns None, if an application has locked the interfaces, so that the poll thread does not spin,
+/// while the application is preempted. The poll thread is woken up, when the application releases the lock.
+fn poll_sockets() -> Option<Option<usize>> {
+    // Set before trying to lock, so that a release right after a failed attempt is not missed
+    POLL_DEFERRED.store(true, Ordering::Release);
     let mut interfaces = INTERFACES.try_write()?;
-    let mut sockets = SOCKETS.get().expect("Socket set not initialized!").try_write()?;
+    POLL_DEFERRED.store(false, Ordering::Release);
     let time = Instant::from_millis(timer().systime_ms() as i64);
 
-    // Smoltcp expects a mutable reference to the device, but the RTL8139 driver is built
-    // to work with a shared reference. We can safely cast the shared reference to a mutable.
-    let device = unsafe { ptr::from_ref(rtl8139.deref()).cast_mut().as_mut().unwrap() };
-
-    let interface = interfaces.get_mut(0).expect("failed to get interface");
-    interface.poll(time, device, &mut sockets);
-    // DHCP handling is based on https://github.com/smoltcp-rs/smoltcp/blob/main/examples/dhcp_client.rs
-    let dhcp_handle = DHCP_SOCKET.get().expect("DHCP socket does not exist yet");
-    let dhcp_socket = sockets.get_mut::<dhcpv4::Socket>(*dhcp_handle);
-    if let Some(event) = dhcp_socket.poll() {
-        match event {
-            dhcpv4::Event::Deconfigured => {
-                info!("lost DHCP lease");
-                interface.update_ip_addrs(|addrs| addrs.clear());
-                interface.routes_mut().remove_default_ipv4_route();
-            },
-            dhcpv4::Event::Configured(config) => {
-                info!("acquired DHCP lease:");
-                info!("IP address: {}", config.address);
-                interface.update_ip_addrs(|addrs| {
-                    addrs.clear();
-                    addrs.push(IpCidr::Ipv4(config.address)).unwrap();
-                });
-
-                if let Some(router) = config.router {
+    // Each interface only polls its own sockets, so packets leave via the interface the socket has been placed at
+    let mut delay: Option<usize> = None;
+    for index in 0..interfaces.len() {
+        let NetworkInterface { device, interface, sockets, dhcp_handle, .. } = &mut interfaces[index];
+        device.poll(interface, time, sockets);
+        if let Some(interface_delay) = interface.poll_delay(time, sockets) {
+            let interface_delay = interface_delay.total_millis() as usize;
+            delay = Some(delay.map_or(interface_delay, |delay| delay.min(interface_delay)));
+        }
+
+        // The DHCP event borrows the DHCP socket, so the socket is taken out of the set, while the event is handled
+        let Socket::Dhcpv4(mut dhcp_socket) = sockets.remove(*dhcp_handle) else {
+            panic!("DHCP socket has been replaced");
+        };
+        if let Some(event) = dhcp_socket.poll() {
+            handle_dhcp_event(&mut interfaces, index, event);
+        }
+        interfaces[index].dhcp_handle = interfaces[index].sockets.add(dhcp_socket);
+    }
+    Some(delay)
+}
+
+// DHCP handling is based on https://github.com/smoltcp-rs/smoltcp/blob/main/examples/dhcp_client.rs
+fn handle_dhcp_event(interfaces: &mut [NetworkInterface], index: usize, event: dhcpv4::Event) {
+    let other_default_route = interfaces
+        .iter()
+        .enumerate()
+        .any(|(other, interface)| other != index && interface.default_route);
+    let NetworkInterface { name, interface, sockets, dns_handle, default_route, .. } = &mut interfaces[index];
+
+    match event {
+        dhcpv4::Event::Deconfigured => {
+            info!("[{name}] lost DHCP lease");
+            interface.update_ip_addrs(|addrs| addrs.clear());
+            interface.routes_mut().remove_default_ipv4_route();
+            *default_route = false;
+        },
+        dhcpv4::Event::Configured(config) => {
+            info!("[{name}] acquired DHCP lease:");
+            info!("IP address: {}", config.address);
+            interface.update_ip_addrs(|addrs| {
+                addrs.clear();
+                addrs.push(IpCidr::Ipv4(config.address)).unwrap();
+            });
+
+            match config.router {
+                Some(router) if !other_default_route => {
                     info!("default gateway: {router}");
                     interface
                         .routes_mut()
                         .add_default_ipv4_route(router)
                         .unwrap();
-                } else {
+                    *default_route = true;
+                },
+                Some(router) => {
+                    info!("ignoring default gateway {router} (another interface has the default route)");
+                },
+                None => {
                     info!("no default gateway");
                     interface
                         .routes_mut()
                         .remove_default_ipv4_route();
-                }
-                info!("DNS servers: {:?}", config.dns_servers);
-                let dns_servers: Vec<_> = config.dns_servers
-                    .iter()
-                    .map(|ip| IpAddress::Ipv4(*ip))
-                    .collect();
-                let dns_handle = DNS_SOCKET.get().expect("DNS socket does not exist yet");
-                let dns_socket = sockets.get_mut::<dns::Socket>(*dns_handle);
-                dns_socket.update_servers(&dns_servers);
-            },
-        }
+                    *default_route = false;
+                },
+            }
+
+            info!("DNS servers: {:?}", config.dns_servers);
+            let dns_servers: Vec<_> = config.dns_servers
+                .iter()
+                .map(|ip| IpAddress::Ipv4(*ip))
+                .collect();
+            let dns_socket = sockets.get_mut::<dns::Socket>(*dns_handle);
+            dns_socket.update_servers(&dns_servers);
+        },
     }
-    Some(())
 }
 
 pub(crate) fn close_sockets_for_process(process: &mut Process) {
-    let mut lock = SOCKET_PROCESS.write();
-    let mut sockets = SOCKETS.get().expect("Socket set not initialized!").write();
-    let handles: Vec<_> = lock
+    let mut sockets = SOCKETS.write();
+    let mut interfaces = lock_interfaces();
+    let ids: Vec<_> = sockets
         .iter()
-        .filter(|(_handle, proc)| ***proc == *process)
-        .map(|(handle, _proc)| handle)
+        .filter(|(_id, socket)| *socket.process == *process)
+        .map(|(id, _socket)| id)
         .copied()
         .collect();
-    for handle in handles {
-        lock.remove(&handle).unwrap();
-        sockets.remove(handle);
+    for id in ids {
+        let socket = sockets.remove(&id).unwrap();
+        interfaces[socket.interface].sockets.remove(socket.handle);
     }
 }
 
@@ -6,13 +6,18 @@
    ║ Author: Fabian Ruhland, HHU                                             ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
+use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::sync::atomic::AtomicUsize;
 use core::sync::atomic::Ordering::Relaxed;
+use spin::{Mutex, Once, RwLock};
 use crate::{ network, process_manager, scheduler};
 use crate::memory::pages::Paging;
 use crate::memory::vmm::VirtualAddressSpace;
+use crate::naming::open_objects::OpenObjectTable;
+use crate::sync::wait_queue::WaitQueue;
 
 static PROCESS_ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
@@ -23,13 +28,30 @@ fn next_process_id() -> usize {
 
 pub struct Process {
     pub id: usize,
+    parent_id: usize, // id of the process which has started this process (0 for the kernel process)
     pub virtual_address_space: VirtualAddressSpace,
+    open_objects: OpenObjectTable,
+    environment: RwLock<Vec<String>>, // environment variables (`KEY=VALUE`), inherited by new processes
+    cwd: RwLock<String>,              // absolute path of the working directory, inherited by new processes
+    exit_code: Once<usize>, // set, when the process terminates
+    exit_wq: WaitQueue,     // threads waiting for the process to terminate
+    futexes: Mutex<BTreeMap<usize, Arc<WaitQueue>>>, // wait queues of futexes, indexed by their user space address
 }
 
 
 impl Process {
-    pub fn new(page_tables: Arc<Paging>) -> Self {
-        Self { id: next_process_id(), virtual_address_space: VirtualAddressSpace::new(page_tables) }
+    pub fn new(page_tables: Arc<Paging>, parent_id: usize, open_objects: OpenObjectTable, environment: Vec<String>, cwd: String) -> Self {
+        Self {
+            id: next_process_id(),
+            parent_id,
+            virtual_address_space: VirtualAddressSpace::new(page_tables),
+            open_objects,
+            environment: RwLock::new(environment),
+            cwd: RwLock::new(cwd),
+            exit_code: Once::new(),
+            exit_wq: WaitQueue::new(),
+            futexes: Mutex::new(BTreeMap::new()),
+        }
     }
 
     /// Return the id of the process
@@ -37,8 +59,93 @@ impl Process {
         self.id
     }
 
-    pub fn exit(&self) {
-        process_manager().write().exit(self.id);
+    /// Return the id of the process which has started this process (0 for the kernel process)
+    pub fn parent_id(&self) -> usize {
+        self.parent_id
+    }
+
+    /// Return the table of objects opened by the process
+    pub fn open_objects(&self) -> &OpenObjectTable {
+        &self.open_objects
+    }
+
+    /// Return the environment variables of the process (`KEY=VALUE`)
+    pub fn environment(&self) -> Vec<String> {
+        self.environment.read().clone()
+    }
+
+    /// Set the environment variable `key` to `value`
+    pub fn set_env(&self, key: &str, value: &str) {
+        let mut environment = self.environment.write();
+        let entry = [key, "=", value].concat();
+        match environment.iter_mut().find(|var| var.split_once('=').is_some_and(|(name, _)| name == key)) {
+            Some(var) => *var = entry,
+            None => environment.push(entry),
+        }
+    }
+
+    /// Return the absolute path of the working directory
+    pub fn cwd(&self) -> String {
+        self.cwd.read().clone()
+    }
+
+    /// Set the working directory to the absolute path `cwd`
+    pub fn set_cwd(&self, cwd: String) {
+        *self.cwd.write() = cwd;
+    }
+
+    /// Terminate the process with `exit_code` (called by one of its threads)
+    pub fn exit(&self, exit_code: usize) {
+        // Opened objects are closed and waiting threads are woken up after releasing the lock of the process manager
+        let process = process_manager().write().exit(self.id, exit_code);
+        if let Some(process) = process {
+            process.release(exit_code);
+        }
+    }
+
+    /// Return the exit code, if the process has terminated
+    pub fn exit_code(&self) -> Option<usize> {
+        self.exit_code.get().copied()
+    }
+
+    /// Close all objects opened by the terminated process and wake up all threads waiting for it. \
+    /// Must be called without holding the lock of the process manager.
+    pub fn release(&self, exit_code: usize) {
+        self.open_objects.close_all();
+        self.set_exit_code(exit_code);
+    }
+
+    /// Record the exit code and wake up all threads waiting for the process (only the first call has an effect)
+    fn set_exit_code(&self, exit_code: usize) {
+        self.exit_code.call_once(|| exit_code);
+        self.exit_wq.notify_all();
+    }
+
+    /// Block calling thread until the process has terminated
+    pub fn wait_for_exit(&self) {
+        self.exit_wq.wait(|| self.exit_code.is_completed());
+    }
+
+    /// Return the wait queue of the futex at the user space address `address`.
+    /// If there is none yet, it is created, if `create` is true.
+    /// The caller must hand the queue back with `release_futex()`.
+    pub fn futex(&self, address: usize, create: bool) -> Option<Arc<WaitQueue>> {
+        let mut futexes = self.futexes.lock();
+        if create {
+            return Some(Arc::clone(futexes.entry(address).or_insert_with(|| Arc::new(WaitQueue::new()))));
+        }
+
+        futexes.get(&address).cloned()
+    }
+
+    /// Hand back the wait queue `futex` of the futex at `address` (returned by `futex()`).
+    /// The queue is removed, once it is not used by any thread anymore (then no thread is waiting in it).
+    pub fn release_futex(&self, address: usize, futex: Arc<WaitQueue>) {
+        let mut futexes = self.futexes.lock();
+        drop(futex);
+        if futexes.get(&address).is_some_and(|futex| Arc::strong_count(futex) == 1) {
+            futexes.remove(&address);
+        }
     }
 
     /// Return the ids of all threads of the process
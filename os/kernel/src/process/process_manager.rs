@@ -6,21 +6,35 @@
    ║ Author: Fabian Ruhland, Univ. Duesseldorf, 20.07.2025                   ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 use log::info;
 use x86_64::structures::paging::frame::PhysFrameRange;
 use x86_64::structures::paging::Page;
 use x86_64::VirtAddr;
+use syscall::return_vals::Errno;
 
 use crate::memory::{vmm, MemorySpace};
 use crate::memory::vma::VmaType;
+use crate::naming::open_objects::OpenObjectTable;
 use crate::process::process::Process;
 use crate::scheduler;
 
+/// Environment of the kernel process, inherited by the first applications
+const KERNEL_ENVIRONMENT: &[&str] = &["PATH=/bin"];
+
+/// Working directory of the kernel process, inherited by the first applications
+const KERNEL_CWD: &str = "/";
+
+/// Exit code of a killed process (128 + SIGKILL, as reported by Unix shells)
+pub const KILLED_EXIT_CODE: usize = 137;
+
 pub struct ProcessManager {
     active_processes: Vec<Arc<Process>>,
     exited_processes: Vec<Arc<Process>>, // processed by cleanup thread later
+    zombies: BTreeMap<usize, (usize, usize)>, // (parent id, exit code) of terminated processes, until reaped by `wait`
 }
 
 impl ProcessManager {
@@ -28,14 +42,16 @@ impl ProcessManager {
         Self {
             active_processes: Vec::new(),
             exited_processes: Vec::new(),
+            zombies: BTreeMap::new(),
         }
     }
 
-    /// Create a new process
-    pub fn create_process(&mut self) -> Arc<Process> {
+    /// Create a new child process of the process with `parent_id`, using `open_objects` as its table of opened objects,
+    /// `environment` as its environment variables and `cwd` as its working directory
+    pub fn create_process(&mut self, parent_id: usize, open_objects: OpenObjectTable, environment: Vec<String>, cwd: String) -> Arc<Process> {
         let kernel_process = self.kernel_process().expect("No kernel process found!");
         let paging = vmm::clone_address_space(&(kernel_process.virtual_address_space));
-        let process = Arc::new(Process::new(paging));
+        let process = Arc::new(Process::new(paging, parent_id, open_objects, environment, cwd));
         self.active_processes.push(Arc::clone(&process));
         process
     }
@@ -48,7 +64,8 @@ impl ProcessManager {
         }
 
         let paging = vmm::create_kernel_address_space();
-        let kernel_process = Arc::new(Process::new(paging));
+        let environment = KERNEL_ENVIRONMENT.iter().map(|var| var.to_string()).collect();
+        let kernel_process = Arc::new(Process::new(paging, 0, OpenObjectTable::new(), environment, KERNEL_CWD.to_string()));
         self.active_processes.push(Arc::clone(&kernel_process));
 
         // TODO: adjust this when removing 1:1 mapping
@@ -86,6 +103,16 @@ impl ProcessManager {
         self.active_processes.iter().map(|process| process.id()).collect()
     }
 
+    /// Return all active processes
+    pub fn active_processes(&self) -> Vec<Arc<Process>> {
+        self.active_processes.clone()
+    }
+
+    /// Get reference to the active process with `process_id`
+    pub fn active_process(&self, process_id: usize) -> Option<Arc<Process>> {
+        self.active_processes.iter().find(|process| process.id == process_id).map(Arc::clone)
+    }
+
     /// Get reference to kernel process
     pub fn kernel_process(&self) -> Option<Arc<Process>> {
         self.active_processes.first().map(Arc::clone)
@@ -100,28 +127,25 @@ impl ProcessManager {
         }
     }
 
-    /// Exit a process by its id
-    pub fn exit(&mut self, process_id: usize) {
-        let index = self
-            .active_processes
-            .iter()
-            .position(|process| process.id == process_id)
-            .expect("Process: Trying to exit a non-existent process!");
+    /// Exit a process by its id. \
+    /// Nothing happens and `None` is returned, if the process is not active anymore, e.g. because another of its threads
+    /// has terminated it concurrently (on another core) or it has been killed. \
+    /// Otherwise, the exited process is returned and `Process::release()` has to be called,
+    /// after the lock of the process manager has been released.
+    pub fn exit(&mut self, process_id: usize, exit_code: usize) -> Option<Arc<Process>> {
+        let index = self.active_processes.iter().position(|process| process.id == process_id)?;
 
         let process = Arc::clone(&self.active_processes[index]);
         process.kill_all_threads_but_current();
 
         self.active_processes.swap_remove(index);
-        self.exited_processes.push(process);
+        Some(self.terminate(process, exit_code))
     }
 
-    /// Kill a process by its id
-    pub fn kill(&mut self, process_id: usize) {
-        let index = self
-            .active_processes
-            .iter()
-            .position(|process| process.id == process_id)
-            .expect("Process: Trying to kill a non-existent process!");
+    /// Kill a process by its id (like `exit()`, nothing happens, if the process is not active anymore). \
+    /// The caller has to call `Process::release()` with `KILLED_EXIT_CODE` for the returned process.
+    pub fn kill(&mut self, process_id: usize) -> Option<Arc<Process>> {
+        let index = self.active_processes.iter().position(|process| process.id == process_id)?;
 
         let process = Arc::clone(&self.active_processes[index]);
         for thread_id in process.thread_ids() {
@@ -129,7 +153,29 @@ impl ProcessManager {
         }
 
         self.active_processes.swap_remove(index);
-        self.exited_processes.push(process);
+        Some(self.terminate(process, KILLED_EXIT_CODE))
+    }
+
+    /// Remove the exit code of the terminated process with `process_id`, which must be a child of the process with `parent_id`.
+    /// Returns `Err(ECHILD)`, if it is not a child, or `Err(ESRCH)`, if it has not terminated or has already been reaped.
+    pub fn reap(&mut self, process_id: usize, parent_id: usize) -> Result<usize, Errno> {
+        match self.zombies.get(&process_id) {
+            Some((parent, _)) if *parent != parent_id => Err(Errno::ECHILD),
+            Some(_) => Ok(self.zombies.remove(&process_id).unwrap().1),
+            None => Err(Errno::ESRCH),
+        }
+    }
+
+    /// Keep the exit code of a removed process until it is reaped, its resources are freed by the cleanup thread. \
+    /// Exit codes are only kept while the parent is active, so those of the children of `process` are dropped.
+    fn terminate(&mut self, process: Arc<Process>, exit_code: usize) -> Arc<Process> {
+        self.zombies.retain(|_, (parent_id, _)| *parent_id != process.id());
+        if self.active_process(process.parent_id()).is_some() {
+            self.zombies.insert(process.id(), (process.parent_id(), exit_code));
+        }
+
+        self.exited_processes.push(Arc::clone(&process));
+        process
     }
 
     /// 
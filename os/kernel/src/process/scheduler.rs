@@ -1,39 +1,53 @@
 /* ╔═════════════════════════════════════════════════════════════════════════╗
    ║ Module: scheduler                                                       ║
    ╟─────────────────────────────────────────────────────────────────────────╢
-   ║ Implementation of a basic round-robin scheduler.                        ║
+   ║ Implementation of a priority-based round-robin scheduler. Each core has ║
+   ║ one ready queue per priority level. A thread is only preempted by       ║
+   ║ threads with the same or a higher priority. New and woken up threads    ║
+   ║ are put into the ready queues of the core with the lowest load. A core  ║
+   ║ without a ready thread steals one from the core with the highest load   ║
+   ║ or runs its idle thread, which halts the core. Sleeping threads are     ║
+   ║ kept in a queue ordered by their wakeup time. The CPU time of each      ║
+   ║ thread is accounted at every thread switch.                             ║
    ║                                                                         ║
    ║ Public functions                                                        ║
    ║   - active_thread_ids      get a list of all active thread IDs          ║
    ║   - current_thread         get the currently running thread             ║
    ║   - current_ids            get the (pid, tid) of the current thread     ║
    ║   - exit                   exit the calling thread                      ║
+   ║   - init_idle_threads      create the idle thread of each core          ║
    ║   - join                   wait for a thread to finish                  ║
    ║   - kill                   kill a thread                                ║
    ║   - set_init               set the scheduler as initialized             ║
    ║   - thread                 get reference to a thread                    ║
-   ║   - ready                  insert a thread in the ready queue           ║
+   ║   - ready                  insert a thread in a ready queue             ║
+   ║   - set_priority           change the priority of a thread              ║
    ║   - sleep                  put the caller into sleeping mode            ║
-   ║   - start                  start the scheduler                          ║
+   ║   - start                  start the scheduler on the calling core      ║
    ║   - switch_thread_from_interrupt  switch thread, called from interrupt  ║
    ║   - switch_thread_no_interrupt    switch thread, not called from int.   ║
    ║   - current_ids            get the (pid, tid) of the current thread     ║
    ║   - block                   put the calling thread into blocked mode    ║
-   ║   - deblock                 wake up a blocked thread                    ║  
+   ║   - deblock                 wake up a blocked or sleeping thread        ║
+   ║   - wakeup                  wake up a sleeping thread, interrupt-safe   ║
+   ║   - notify                  wake up a waiting thread, interrupt-safe    ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Fabian Ruhland, 05.09.2025, HHU                                 ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
+use crate::consts::{MAX_CORES, THREAD_PRIORITY_LEVELS};
 use crate::process::thread::Thread;
-use crate::{allocator, apic, scheduler, timer, tss};
+use crate::{allocator, apic, core_id, scheduler, timer, tss};
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
-use core::{panic, ptr};
+use core::hint::spin_loop;
+use core::{iter, panic, ptr};
 use core::sync::atomic::AtomicUsize;
-use core::sync::atomic::Ordering::Relaxed;
+use core::sync::atomic::Ordering::{Relaxed, SeqCst};
 use smallmap::Map;
 use spin::{Mutex, MutexGuard};
+use x86_64::instructions::interrupts;
 
 // thread IDs
 static THREAD_ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
@@ -42,28 +56,111 @@ pub fn next_thread_id() -> usize {
     THREAD_ID_COUNTER.fetch_add(1, Relaxed)
 }
 
-/// Everything related to the threads in ready state in the scheduler
+/// Everything related to the threads in ready state on a single core
 struct ReadyState {
     initialized: bool,
+    online: bool, // the scheduler has been started on this core
     current_thread: Option<Arc<Thread>>,
-    ready_queue: VecDeque<Arc<Thread>>,
+    idle_thread: Option<Arc<Thread>>,
+    exited_thread: Option<Arc<Thread>>, // last thread, that has exited on this core (kept alive until it has been switched out)
+    ready_queues: [VecDeque<Arc<Thread>>; THREAD_PRIORITY_LEVELS], // one queue per priority (highest priority last)
+    switch_time: usize, // system time (in ns), at which the current thread has been switched in
 }
 
 impl ReadyState {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             initialized: false,
+            online: false,
             current_thread: None,
-            ready_queue: VecDeque::new(),
+            idle_thread: None,
+            exited_thread: None,
+            ready_queues: [const { VecDeque::new() }; THREAD_PRIORITY_LEVELS],
+            switch_time: 0,
+        }
+    }
+
+    /// Insert `thread` at the end of the ready queue for its priority
+    fn push(&mut self, thread: Arc<Thread>) {
+        self.ready_queues[thread.priority()].push_front(thread);
+    }
+
+    /// Check if `thread` can be inserted into its ready queue without allocating memory
+    fn has_capacity(&self, thread: &Thread) -> bool {
+        let queue = &self.ready_queues[thread.priority()];
+        queue.len() < queue.capacity()
+    }
+
+    /// Remove the next ready thread with a priority of at least `min_priority` (the highest priority first)
+    fn pop(&mut self, min_priority: usize) -> Option<Arc<Thread>> {
+        self.ready_queues[min_priority..].iter_mut().rev().find_map(|queue| queue.pop_back())
+    }
+
+    /// Check if there is a ready thread with a priority of at least `min_priority`
+    fn has_ready(&self, min_priority: usize) -> bool {
+        self.ready_queues[min_priority..].iter().any(|queue| !queue.is_empty())
+    }
+
+    /// Remove the ready thread with `thread_id`
+    fn remove(&mut self, thread_id: usize) -> Option<Arc<Thread>> {
+        self.ready_queues.iter_mut().find_map(|queue| {
+            let pos = queue.iter().position(|thread| thread.id() == thread_id)?;
+            queue.remove(pos)
+        })
+    }
+
+    /// Iterate over all ready threads
+    fn ready_threads(&self) -> impl Iterator<Item = &Arc<Thread>> {
+        self.ready_queues.iter().flatten()
+    }
+
+    /// Number of ready threads
+    fn ready_count(&self) -> usize {
+        self.ready_queues.iter().map(|queue| queue.len()).sum()
+    }
+
+    /// Lowest priority of a thread, that may replace the current thread
+    fn min_priority(&self) -> usize {
+        match &self.current_thread {
+            Some(current) if !self.is_idle() && !current.is_killed() => current.priority(),
+            _ => 0,
+        }
+    }
+
+    /// Charge the time since the last thread switch to the current thread
+    fn account(&mut self) {
+        let now = timer().systime_ns();
+        if let Some(current) = &self.current_thread {
+            current.add_cpu_time(now.saturating_sub(self.switch_time));
         }
+        self.switch_time = now;
+    }
+
+    /// Check if `thread` is the idle thread of this core
+    fn is_idle_thread(&self, thread: &Arc<Thread>) -> bool {
+        self.idle_thread.as_ref().is_some_and(|idle| Arc::ptr_eq(idle, thread))
+    }
+
+    /// Check if the idle thread is currently running on this core
+    fn is_idle(&self) -> bool {
+        self.current_thread.as_ref().is_some_and(|current| self.is_idle_thread(current))
+    }
+
+    /// Number of ready threads plus the current thread (unless the core is idle)
+    fn load(&self) -> usize {
+        let running = self.current_thread.is_some() && !self.is_idle();
+        self.ready_count() + running as usize
     }
 }
 
 /// Main struct of the scheduler
 pub struct Scheduler {
-    ready_state: Mutex<ReadyState>,
-    sleep_list: Mutex<Vec<(Arc<Thread>, usize)>>,
+    ready_states: [Mutex<ReadyState>; MAX_CORES],
+    core_count: AtomicUsize,
+    sleep_queue: Mutex<VecDeque<(Arc<Thread>, usize)>>, // ordered by wakeup time
     blocked_list: Mutex<Vec<Arc<Thread>>>,
+    pending_wakeups: Mutex<Vec<usize>>, // ids of threads, that have been deblocked before they have blocked themselves
+    notified_count: AtomicUsize, // number of threads, that have been notified by `notify()` and not been woken up yet
     join_map: Mutex<Map<usize, Vec<Arc<Thread>>>>, // manage which threads are waiting for a thread-id to terminate
 }
 
@@ -73,7 +170,15 @@ unsafe impl Sync for Scheduler {}
 /// Called from assembly code, after the thread has been switched
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn unlock_scheduler() {
-    unsafe { scheduler().ready_state.force_unlock(); }
+    unsafe { scheduler().ready_states[core_id()].force_unlock(); }
+}
+
+/// Entry function of the idle threads, which halt their core until the next interrupt
+extern "sysv64" fn idle() {
+    loop {
+        scheduler().switch_thread_no_interrupt();
+        interrupts::enable_and_hlt();
+    }
 }
 
 impl Scheduler {
@@ -81,29 +186,49 @@ impl Scheduler {
     /// Create and initialize the scheduler.
     pub fn new() -> Self {
         Self {
-            ready_state: Mutex::new(ReadyState::new()),
-            sleep_list: Mutex::new(Vec::new()),
+            ready_states: [const { Mutex::new(ReadyState::new()) }; MAX_CORES],
+            core_count: AtomicUsize::new(1),
+            sleep_queue: Mutex::new(VecDeque::new()),
             blocked_list: Mutex::new(Vec::new()),
+            pending_wakeups: Mutex::new(Vec::new()),
+            notified_count: AtomicUsize::new(0),
             join_map: Mutex::new(Map::new()),
         }
     }
 
-    /// Called after the scheduler has been fully initialized
+    /// Called after the scheduler has been fully initialized on the calling core
     pub fn set_init(&self) {
         self.get_ready_state().initialized = true;
     }
 
+    /// Create the idle thread of each core, which runs if no other thread is ready. \
+    /// Called once from `boot.rs`, before the application processors are started.
+    pub fn init_idle_threads(&self, core_count: usize) {
+        self.core_count.store(core_count, Relaxed);
+        for core in 0..core_count {
+            let thread = Thread::new_kernel_thread(idle, "idle");
+            self.with_ready_state(core, |state| state.idle_thread = Some(thread));
+        }
+    }
+
     /// Get all active thread IDs
     pub fn active_thread_ids(&self) -> Vec<usize> {
-        let state = self.get_ready_state();
-        let sleep_list = self.sleep_list.lock();
+        let current_id = self.current_thread().id();
+        let mut ids = Vec::new();
+
+        // Threads running on other cores are active too (the idle threads are not reported)
+        for core in 0..self.core_count() {
+            let state_ids = self.run_atomic(|| {
+                let state = self.ready_states[core].lock();
+                let running = state.current_thread.iter().filter(|thread| !state.is_idle_thread(thread) && thread.id() != current_id);
+                state.ready_threads().chain(running).map(|thread| thread.id()).collect::<Vec<usize>>()
+            });
+            ids.extend(state_ids);
+        }
 
-        state.ready_queue.iter()
-            .map(|thread| thread.id())
-            .collect::<Vec<usize>>()
-            .into_iter()
-            .chain(sleep_list.iter().map(|entry| entry.0.id()))
-            .collect()
+        let sleep_ids = self.run_atomic(|| self.sleep_queue.lock().iter().map(|entry| entry.0.id()).collect::<Vec<usize>>());
+        ids.extend(sleep_ids);
+        ids
     }
 
     /// Return reference to current thread
@@ -112,12 +237,19 @@ impl Scheduler {
         Scheduler::current(&state)
     }
 
-    /// Return reference to thread identified by `thread_id`
+    /// Return reference to thread identified by `thread_id` (running, ready, sleeping or blocked)
     pub fn thread(&self, thread_id: usize) -> Option<Arc<Thread>> {
-        self.ready_state.lock().ready_queue
-            .iter()
-            .find(|thread| thread.id() == thread_id)
-            .cloned()
+        let thread = (0..self.core_count()).find_map(|core| self.with_ready_state(core, |state| {
+            state.ready_threads()
+                .chain(state.current_thread.iter())
+                .find(|thread| thread.id() == thread_id)
+                .cloned()
+        }));
+
+        thread.or_else(|| interrupts::without_interrupts(|| {
+            let sleeping = self.sleep_queue.lock().iter().map(|entry| &entry.0).find(|thread| thread.id() == thread_id).cloned();
+            sleeping.or_else(|| self.blocked_list.lock().iter().find(|thread| thread.id() == thread_id).cloned())
+        }))
     }
 
     /// Return (pid, tid) of current thread
@@ -127,45 +259,52 @@ impl Scheduler {
         (pid, tid)
     }
 
+    /// Return the ids of all cores, on which the scheduler has been started
+    pub fn online_cores(&self) -> Vec<usize> {
+        (0..self.core_count()).filter(|&core| self.with_ready_state(core, |state| state.online)).collect()
+    }
+
 
-    /// Start the scheduler, called only once from `boot.rs` 
+    /// Start the scheduler on the calling core, called once per core from `boot.rs`
     pub fn start(&self) {
-        // TODO: make sure this is actually called just once
         let mut state = self.get_ready_state();
-        state.current_thread = state.ready_queue.pop_back();
+        let core = core_id();
+        state.online = true;
+
+        let first = state.pop(0)
+            .or_else(|| self.steal_thread(core, 0, 0))
+            .unwrap_or_else(|| Scheduler::idle(&state));
+        first.set_running();
 
-        unsafe { Thread::start_first(state.current_thread.as_ref().expect("Failed to dequeue first thread!").as_ref()); }
+        let first_ptr = ptr::from_ref(first.as_ref());
+        state.current_thread = Some(first);
+        state.switch_time = timer().systime_ns();
+        MutexGuard::leak(state); // unlocked by the first thread
+
+        unsafe { Thread::start_first(first_ptr); }
     }
 
-    /// Insert `thread` into the ready queue of the scheduler
+    /// Insert `thread` into a ready queue of the scheduler (for the priority of the thread)
     pub fn ready(&self, thread: Arc<Thread>) {
         let id = thread.id();
 
-        // If we get the lock on 'self.state' but not on 'self.join_map' the system hangs.
-        // The scheduler is not able to switch threads anymore, because of 'self.state' is locked,
-        // and we will never be able to get the lock on 'self.join_map'.
-        // To solve this, we need to release the lock on 'self.state' in case we do not get
-        // the lock on 'self.join_map' and let the scheduler switch threads until we get both locks.
-        let (mut state, mut join_map) = loop {
-            let state = self.get_ready_state();
-            if let Some(join_map) = self.join_map.try_lock() {
-                break (state, join_map);
-            }
-            self.switch_thread_no_interrupt();
-        };
-
-        state.ready_queue.push_front(thread);
-        join_map.insert(id, Vec::new());
+        // The thread must be registered in the join map, before it runs (and possibly exits) on another core
+        self.run_atomic(|| self.join_map.lock().insert(id, Vec::new()));
+        self.enqueue(thread);
     }
 
-    /// Put calling thread to sleep for `ms` milliseconds
-    pub fn sleep(&self, ms: usize) {
-        let mut state = self.get_ready_state();
+    /// Put calling thread to sleep for `ms` milliseconds. \
+    /// Returns `true`, if the thread has been woken up earlier by `deblock()` (also if it has been called before)
+    /// and `false` otherwise.
+    pub fn sleep(&self, ms: usize) -> bool {
+        let state = self.get_ready_state();
 
         if !state.initialized {
             // Scheduler is not initialized yet, so this function has been called during the boot process
             // So we do active waiting
+            drop(state);
             timer().wait(ms);
+            false
         } 
         else {
             // Scheduler is initialized, so we can block the calling thread
@@ -174,17 +313,24 @@ impl Scheduler {
             
             {
                 // Execute in own block, so that the lock is released automatically (block() does not return)
-                let mut sleep_list = self.sleep_list.lock();
-                sleep_list.push((thread, wakeup_time));
+                let mut sleep_queue = self.sleep_queue.lock();
+                if self.take_pending_wakeup(thread.id()) || self.take_notification(&thread) {
+                    return true;
+                }
+
+                // Keep the queue ordered by wakeup time (threads with the same wakeup time in insertion order)
+                let pos = sleep_queue.partition_point(|entry| entry.1 <= wakeup_time);
+                sleep_queue.insert(pos, (thread, wakeup_time));
             }
 
-            self.block_and_switch(&mut state);
+            self.block_and_switch(state);
+            self.current_thread().take_deblocked()
         }
     }
 
     /// Put calling thread to block
     pub fn block(&self) {
-        let mut state = self.get_ready_state();
+        let state = self.get_ready_state();
 
         if !state.initialized {
             // Scheduler is not initialized yet, so this function has been called during the boot process
@@ -197,33 +343,102 @@ impl Scheduler {
             {
                 // Execute in own block, so that the lock is released automatically (block() does not return)
                 let mut block_list = self.blocked_list.lock();
+                if self.take_pending_wakeup(thread.id()) || self.take_notification(&thread) {
+                    // Another core has already deblocked this thread
+                    return;
+                }
                 block_list.push(thread);
             } // drop lock for block_list
             //info!("Scheduler::block: switch to next thread");
-            self.block_and_switch(&mut state);
+            self.block_and_switch(state);
         }
     }
 
-    /// Put calling thread to block
+    /// Wake up a blocked thread. \
+    /// A sleeping thread is woken up before its wakeup time (used for waiting with a timeout, `sleep()` returns `true`).
+    /// If the thread has not blocked itself yet (e.g. it is still running on another core),
+    /// the wakeup is remembered and its next call of `block()` or `sleep()` returns immediately.
     pub fn deblock(&self, pid: usize, tid: usize) {
-        let mut block_list = self.blocked_list.lock();
+        let matches = |thread: &Arc<Thread>| thread.id() == tid && thread.process().id() == pid;
 
-        if let Some(pos) = block_list.iter().position(|thread| thread.id() == tid && thread.process().id() == pid) {
-            let thread = block_list.remove(pos);
-            self.ready(thread);
+        // same lock order as in `block` and `sleep`
+        let thread = self.run_atomic(|| {
+            let mut block_list = self.blocked_list.lock();
+            if let Some(pos) = block_list.iter().position(matches) {
+                return Some(block_list.remove(pos));
+            }
+
+            let mut sleep_queue = self.sleep_queue.lock();
+            if let Some(pos) = sleep_queue.iter().position(|entry| matches(&entry.0)) {
+                let thread = sleep_queue.remove(pos).map(|entry| entry.0);
+                thread.iter().for_each(|thread| thread.set_deblocked());
+                return thread;
+            }
+
+            self.pending_wakeups.lock().push(tid);
+            None
+        });
+
+        if let Some(thread) = thread {
+            self.enqueue(thread);
         }
     }
 
-    /// Switch from current to next thread (from ready queue). \
+    /// Let a sleeping thread wake up at the next thread switch (before its wakeup time). \
+    /// This can be called from an interrupt handler, since the sleep queue is not locked, if it is already in use.
+    /// In this case or if the thread is not sleeping, `false` is returned and the thread is not woken up.
+    pub fn wakeup(&self, thread_id: usize) -> bool {
+        let Some(mut sleep_queue) = self.sleep_queue.try_lock() else {
+            return false;
+        };
+
+        // Move the thread to the front of the queue (this does not allocate memory)
+        match sleep_queue.iter().position(|entry| entry.0.id() == thread_id).and_then(|pos| sleep_queue.remove(pos)) {
+            Some((thread, _)) => {
+                sleep_queue.push_front((thread, 0));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Wake up a thread, which is blocked or sleeping (e.g. waiting in a `WaitQueue`), from an interrupt handler. \
+    /// This neither locks nor allocates memory, the thread is woken up at the next thread switch on any core.
+    /// If the thread has not blocked itself yet, its next call of `block()` or `sleep()` returns immediately.
+    pub fn notify(&self, thread: &Thread) {
+        if !thread.set_notified() {
+            self.notified_count.fetch_add(1, SeqCst);
+        }
+    }
+
+    /// Switch from current to next thread (from the ready queue of the calling core or another core). \
     /// If `interrupt` is true, the function is called from an ISR and will send EOI to APIC otherwise not. 
     fn switch_thread(&self, interrupt: bool) {
-        if let Some(mut state) = self.ready_state.try_lock() {
+        // Interrupts are disabled, so that the calling thread cannot be moved to another core in between
+        let interrupts_enabled = interrupts::are_enabled();
+        interrupts::disable();
+
+        self.switch_thread_on_core(core_id(), interrupt);
+
+        if interrupts_enabled {
+            interrupts::enable();
+        }
+    }
+
+    /// Switch from current to next thread on `core` (the calling core), called with interrupts disabled
+    fn switch_thread_on_core(&self, core: usize, interrupt: bool) {
+        if let Some(mut state) = self.ready_states[core].try_lock() {
             if !state.initialized {
                 return;
             }
 
-            if let Some(mut sleep_list) = self.sleep_list.try_lock() {
-                Scheduler::check_sleep_list(&mut state, &mut sleep_list);
+            // Enqueuing threads may allocate memory, which deadlocks, if a preempted thread holds the allocator
+            let allocator_free = !allocator().is_locked();
+            if allocator_free && let Some(mut sleep_queue) = self.sleep_queue.try_lock() {
+                Scheduler::check_sleep_queue(&mut state, &mut sleep_queue);
+            }
+            if allocator_free {
+                self.check_notified(&mut state);
             }
 
             // Get clone of the current thread
@@ -234,22 +449,40 @@ impl Scheduler {
                 return;
             }
 
-            // Try to get the next thread from the ready queue
-            let next = match state.ready_queue.pop_back() {
+            // A killed thread is not enqueued again, but kept until it has been switched out
+            let retire = current.is_killed() && allocator_free;
+            let idle = state.is_idle();
+            if !retire && !idle && !allocator_free && !state.has_capacity(&current) {
+                return;
+            }
+
+            // Try to get the next thread with at least the priority of the current thread
+            // from the own ready queues, or from the core with the highest load
+            let min_priority = state.min_priority();
+            let load = if retire { state.ready_count() } else { state.load() };
+            let next = match state.pop(min_priority).or_else(|| allocator_free.then(|| self.steal_thread(core, load, min_priority)).flatten()) {
                 Some(thread) => thread,
+                None if retire => Scheduler::idle(&state),
                 None => return,
             };
 
             let current_ptr = ptr::from_ref(current.as_ref());
             let next_ptr = ptr::from_ref(next.as_ref());
 
+            next.set_running();
+            state.account();
             state.current_thread = Some(next);
-            state.ready_queue.push_front(current);
+            if retire {
+                state.exited_thread = Some(current);
+            } else if !idle {
+                state.push(current);
+            }
 
             if interrupt {
                 apic().end_of_interrupt();
             }
 
+            MutexGuard::leak(state); // unlocked by the next thread
             unsafe {
                 Thread::switch(current_ptr, next_ptr);
             }
@@ -268,7 +501,7 @@ impl Scheduler {
 
     /// Calling thread will block until thread with `thread_id` has terminated
     pub fn join(&self, thread_id: usize) {
-        let mut state = self.get_ready_state();
+        let state = self.get_ready_state();
         let thread = Scheduler::current(&state);
 
         {
@@ -282,7 +515,7 @@ impl Scheduler {
             }
         }
 
-        self.block_and_switch(&mut state);
+        self.block_and_switch(state);
     }
 
     /// Exit calling thread.
@@ -300,14 +533,18 @@ impl Scheduler {
             let join_list = join_map.get_mut(&current.id()).expect("Missing join_map entry!");
 
             for thread in join_list {
-                ready_state.ready_queue.push_front(Arc::clone(thread));
+                ready_state.push(Arc::clone(thread));
             }
 
             join_map.remove(&current.id());
         }
 
-        drop(current); // Decrease Rc manually, because block() does not return
-        self.block_and_switch(&mut ready_state);
+        self.pending_wakeups.lock().retain(|&id| id != current.id());
+        self.take_notification(&current);
+
+        // The thread is still running on its stack, so it is kept until the next thread has exited on this core
+        ready_state.exited_thread = Some(current);
+        self.block_and_switch(ready_state);
         unreachable!()
     }
 
@@ -323,80 +560,259 @@ impl Scheduler {
             }
         }
 
-        let state = self.get_ready_state_and_join_map();
-        let mut ready_state = state.0;
-        let mut join_map = state.1;
+        {
+            let state = self.get_ready_state_and_join_map();
+            let mut ready_state = state.0;
+            let mut join_map = state.1;
+
+            let join_list = join_map.get_mut(&thread_id).expect("Missing join map entry!");
 
-        let join_list = join_map.get_mut(&thread_id).expect("Missing join map entry!");
+            for thread in join_list {
+                ready_state.push(Arc::clone(thread));
+            }
 
-        for thread in join_list {
-            ready_state.ready_queue.push_front(Arc::clone(thread));
+            join_map.remove(&thread_id);
         }
 
-        join_map.remove(&thread_id);
-        ready_state.ready_queue.retain(|thread| thread.id() != thread_id);
-    }
-
-    /// Block calling thread and switch to next ready thread.
-    fn block_and_switch(&self, state: &mut ReadyState) {
-        let mut next_thread = state.ready_queue.pop_back();
+        // Remove the thread from the ready queues. A thread running on another core is marked as killed
+        // and removed by the scheduler of this core at the next thread switch.
+        for core in 0..self.core_count() {
+            let (queued, running) = self.with_ready_state(core, |state| {
+                let queued = state.remove(thread_id);
+                let running = state.current_thread.clone().filter(|thread| thread.id() == thread_id);
+                (queued, running)
+            });
+
+            if let Some(thread) = running {
+                thread.kill();
+                apic().reschedule(core);
+            }
 
-        {
-            // Execute in own block, so that the lock is released automatically (block() does not return)
-            let mut sleep_list = self.sleep_list.lock();
-            while next_thread.is_none() {
-                Scheduler::check_sleep_list(state, &mut sleep_list);
-                next_thread = state.ready_queue.pop_back();
+            if let Some(thread) = queued {
+                // The thread may have been woken up, while it was still switched out by another core
+                while thread.is_running() {
+                    spin_loop();
+                }
             }
         }
+    }
 
-        let current = Scheduler::current(state);
-        let next = next_thread.unwrap();
+    /// Block calling thread and switch to next ready thread (or the idle thread of the calling core). \
+    /// The ready state is unlocked by the next thread.
+    fn block_and_switch(&self, mut state: MutexGuard<'_, ReadyState>) {
+        if let Some(mut sleep_queue) = self.sleep_queue.try_lock() {
+            Scheduler::check_sleep_queue(&mut state, &mut sleep_queue);
+        }
+
+        let core = core_id();
+        let next = state.pop(0)
+            .or_else(|| self.steal_thread(core, 0, 0))
+            .unwrap_or_else(|| Scheduler::idle(&state));
+        let current = Scheduler::current(&state);
 
         // Thread has enqueued itself into sleep list and waited so long, that it dequeued itself in the meantime
-        if current.id() == next.id() {
+        if Arc::ptr_eq(&current, &next) {
             return;
         }
 
         let current_ptr = ptr::from_ref(current.as_ref());
         let next_ptr = ptr::from_ref(next.as_ref());
 
+        next.set_running();
+        state.account();
         state.current_thread = Some(next);
         drop(current); // Decrease Rc manually, because Thread::switch does not return
+        MutexGuard::leak(state); // unlocked by the next thread
 
         unsafe {
             Thread::switch(current_ptr, next_ptr);
         }
     }
 
+    /// Put `thread` into the ready queues of the online core with the lowest load. \
+    /// If this core is idle or runs a thread with a lower priority, it is interrupted to run the thread immediately.
+    fn enqueue(&self, thread: Arc<Thread>) {
+        let core = interrupts::without_interrupts(core_id);
+        let target = self.least_loaded_core(core);
+
+        let preempt = self.run_atomic(|| {
+            let mut state = self.ready_states[target].lock();
+            let preempt = state.online && (state.is_idle() || state.min_priority() < thread.priority());
+            state.push(thread);
+            preempt
+        });
+
+        if preempt {
+            apic().reschedule(target);
+        }
+    }
+
+    /// Set the priority of `thread`. A ready thread is moved to the ready queue for its new priority.
+    pub fn set_priority(&self, thread: &Arc<Thread>, priority: usize) {
+        thread.set_priority(priority);
+
+        for core in 0..self.core_count() {
+            let moved = self.run_atomic(|| {
+                let mut state = self.ready_states[core].lock();
+                let ready = state.remove(thread.id())?;
+                let preempt = state.online && !state.is_idle() && state.min_priority() < priority;
+                state.push(ready);
+                Some(preempt)
+            });
+
+            if let Some(preempt) = moved {
+                if preempt {
+                    apic().reschedule(core);
+                }
+                return;
+            }
+        }
+    }
+
+    /// Find the online core with the lowest load, preferring `core` (the calling core). \
+    /// Cores, whose ready state is currently locked, are skipped.
+    fn least_loaded_core(&self, core: usize) -> usize {
+        interrupts::without_interrupts(|| {
+            let mut target = (core, usize::MAX);
+            for other in iter::once(core).chain((0..self.core_count()).filter(|&other| other != core)) {
+                if let Some(state) = self.ready_states[other].try_lock() && state.online && state.load() < target.1 {
+                    target = (other, state.load());
+                }
+            }
+
+            target.0
+        })
+    }
+
+    /// Take the next ready thread with a priority of at least `min_priority` from the core with the highest load,
+    /// if its load exceeds `load` by more than one. \
+    /// Other cores are only locked with `try_lock()`, so this can be called with the ready state of `core` locked.
+    fn steal_thread(&self, core: usize, load: usize, min_priority: usize) -> Option<Arc<Thread>> {
+        let mut victim: Option<MutexGuard<'_, ReadyState>> = None;
+        for other in (0..self.core_count()).filter(|&other| other != core) {
+            if let Some(state) = self.ready_states[other].try_lock()
+                && state.has_ready(min_priority)
+                && state.load() > load + 1
+                && victim.as_ref().is_none_or(|victim| state.load() > victim.load()) {
+                victim = Some(state);
+            }
+        }
+
+        let mut victim = victim?;
+        victim.pop(min_priority)
+    }
+
+    /// Remove a pending wakeup for the thread with `thread_id` (set by `deblock`) and return if there was one
+    fn take_pending_wakeup(&self, thread_id: usize) -> bool {
+        let mut pending_wakeups = self.pending_wakeups.lock();
+        match pending_wakeups.iter().position(|&id| id == thread_id) {
+            Some(pos) => {
+                pending_wakeups.swap_remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Helper function checking and resetting if `thread` has been notified by `notify()`
+    fn take_notification(&self, thread: &Thread) -> bool {
+        let notified = thread.take_notified();
+        if notified {
+            self.notified_count.fetch_sub(1, SeqCst);
+        }
+        notified
+    }
+
+    /// Move notified threads from the blocked list and the sleep queue into the ready queues of `state`.
+    /// Called at each thread switch, the lists are not checked, if they are in use.
+    fn check_notified(&self, state: &mut ReadyState) {
+        if self.notified_count.load(SeqCst) == 0 {
+            return;
+        }
+        let (Some(mut block_list), Some(mut sleep_queue)) = (self.blocked_list.try_lock(), self.sleep_queue.try_lock()) else {
+            return;
+        };
+
+        let mut i = 0;
+        while i < block_list.len() {
+            if self.take_notification(&block_list[i]) {
+                state.push(block_list.swap_remove(i));
+            } else {
+                i += 1;
+            }
+        }
+
+        let mut i = 0;
+        while i < sleep_queue.len() {
+            if self.take_notification(&sleep_queue[i].0) && let Some((thread, _)) = sleep_queue.remove(i) {
+                thread.set_deblocked();
+                state.push(thread);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Return number of cores managed by the scheduler
+    fn core_count(&self) -> usize {
+        self.core_count.load(Relaxed)
+    }
+
     /// Return current running thread
     fn current(state: &ReadyState) -> Arc<Thread> {
         Arc::clone(state.current_thread.as_ref().expect("Trying to access current thread before initialization!"))
     }
 
-    /// Check sleep list for threads that need to be waken up
-    fn check_sleep_list(state: &mut ReadyState, sleep_list: &mut Vec<(Arc<Thread>, usize)>) {
+    /// Return idle thread of the core
+    fn idle(state: &ReadyState) -> Arc<Thread> {
+        Arc::clone(state.idle_thread.as_ref().expect("Trying to access idle thread before initialization!"))
+    }
+
+    /// Check sleep queue for threads that need to be waken up (the queue is ordered by wakeup time)
+    fn check_sleep_queue(state: &mut ReadyState, sleep_queue: &mut VecDeque<(Arc<Thread>, usize)>) {
         let time = timer().systime_ms();
 
-        sleep_list.retain(|entry| {
-            if time >= entry.1 {
-                state.ready_queue.push_front(Arc::clone(&entry.0));
-                false
-            } else {
-                true
+        while sleep_queue.front().is_some_and(|entry| time >= entry.1) {
+            if let Some((thread, _)) = sleep_queue.pop_front() {
+                state.push(thread);
             }
-        });
+        }
+    }
+
+    /// Helper function executing `f` with interrupts disabled, while the kernel memory manager is not locked. \
+    /// Used for locks, which may be acquired by an interrupted thread or by other cores.
+    fn run_atomic<R>(&self, f: impl FnOnce() -> R) -> R {
+        let mut f = Some(f);
+        loop {
+            let result = interrupts::without_interrupts(|| {
+                if allocator().is_locked() {
+                    return None;
+                }
+
+                f.take().map(|f| f())
+            });
+
+            if let Some(result) = result {
+                return result;
+            }
+        }
+    }
+
+    /// Helper function executing `f` with the ready state of `core` locked and interrupts disabled (`f` must not allocate memory)
+    fn with_ready_state<R>(&self, core: usize, f: impl FnOnce(&mut ReadyState) -> R) -> R {
+        interrupts::without_interrupts(|| f(&mut self.ready_states[core].lock()))
     }
 
-    /// Helper function returning `ReadyState` of scheduler in a MutexGuard
+    /// Helper function returning `ReadyState` of the calling core in a MutexGuard
     fn get_ready_state(&self) -> MutexGuard<'_, ReadyState> {
         let state;
 
         // We need to make sure, that both the kernel memory manager and the ready queue are currently not locked.
         // Otherwise, a deadlock may occur: Since we are holding the ready queue lock,
-        // the scheduler won't switch threads anymore, and none of the locks will ever be released
+        // the scheduler won't switch threads anymore, and none of the locks will ever be released.
+        // Interrupts are disabled while locking, so that the calling thread is not moved to another core in between.
         loop {
-            let state_tmp = self.ready_state.lock();
+            let state_tmp = interrupts::without_interrupts(|| self.ready_states[core_id()].lock());
             if allocator().is_locked() {
                 continue;
             }
@@ -11,6 +11,15 @@
    ║  - start_first        start a thread, called once by scheduler          ║
    ║  - switch             switch threads, called by scheduler               ║
    ║  - stacks_locked      check if stacks are locked, called by scheduler   ║
+   ║  - set_running        claim thread for a core, called by scheduler      ║
+   ║  - kill               mark running thread as killed, called by sched.   ║
+   ║  - set_deblocked      mark sleeping thread as woken up early, by sched. ║
+   ║  - take_deblocked     check and reset if woken up early from sleeping   ║
+   ║  - set_notified       mark thread as notified from an interrupt handler ║
+   ║  - take_notified      check and reset if notified                       ║
+   ║  - priority           return my priority                                ║
+   ║  - set_priority       set my priority, called by scheduler              ║
+   ║  - cpu_time           return the CPU time consumed so far (in ns)       ║
    ║  - grow_user_stack    grow stack as needed, called from page fault      ║
    ║  - user_stack_start   return last usable address of user stack          ║
    ║  - is_kernel_thread   check if self is a kernel only thread or not      ║
@@ -32,7 +41,7 @@
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 
-use crate::consts::MAIN_USER_STACK_START;
+use crate::consts::{DEFAULT_THREAD_PRIORITY, MAIN_USER_STACK_START, THREAD_PRIORITY_LEVELS};
 use crate::consts::MAX_USER_STACK_SIZE;
 use crate::consts::USER_SPACE_ENV_START;
 use crate::memory::stack;
@@ -43,16 +52,21 @@ use crate::process::process::Process;
 use crate::process::scheduler;
 use crate::syscall::syscall_dispatcher::CORE_LOCAL_STORAGE_TSS_RSP0_PTR_INDEX;
 use crate::{process_manager, scheduler, tss};
+use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::arch::naked_asm;
+use core::hint::spin_loop;
 use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use goblin::elf::Elf;
 use goblin::elf64;
 use log::info;
 use spin::Mutex;
 use x86_64::PrivilegeLevel::Ring3;
 use x86_64::VirtAddr;
+use x86_64::instructions::interrupts;
 use x86_64::structures::gdt::SegmentSelector;
 use x86_64::structures::paging::Page;
 
@@ -92,6 +106,18 @@ pub struct Thread {
     user_kickoff: VirtAddr,
     /// the actual entry point (eg. for user threads the single parameter to kickoff)
     entry: extern "sysv64" fn(),
+    /// set while the thread is running on a core (cleared by `thread_switch` after its registers have been saved)
+    running: AtomicBool,
+    /// set if the thread has been killed while running on another core
+    killed: AtomicBool,
+    /// set if the thread has been woken up by `Scheduler::deblock` before its wakeup time
+    deblocked: AtomicBool,
+    /// set by `Scheduler::notify` (from an interrupt handler), until the thread has been woken up
+    notified: AtomicBool,
+    /// scheduling priority (0 is the lowest priority)
+    priority: AtomicUsize,
+    /// CPU time consumed so far (in ns)
+    cpu_time: AtomicUsize,
 }
 
 impl Stacks {
@@ -128,6 +154,12 @@ impl Thread {
                 .expect("Trying to create a kernel thread before process initialization!"),
             user_kickoff: VirtAddr::zero(),
             entry,
+            running: AtomicBool::new(false),
+            killed: AtomicBool::new(false),
+            deblocked: AtomicBool::new(false),
+            notified: AtomicBool::new(false),
+            priority: AtomicUsize::new(DEFAULT_THREAD_PRIORITY),
+            cpu_time: AtomicUsize::new(0),
         };
 
         thread.prepare_kernel_stack();
@@ -135,11 +167,18 @@ impl Thread {
     }
 
     /// Load application code from `elf_buffer`, create a process with a main thread. \
+    /// The new process inherits all opened objects of the current process (including stdin, stdout, and stderr),
+    /// its environment variables and its working directory. \
     /// `name` is the name of the application, `args` are the arguments passed to the application. \
     /// Returns the main thread of the application which is not yet registered in the scheduler.
     pub fn load_application(elf_buffer: &[u8], name: &str, args: &Vec<&str>) -> Arc<Thread> {
         let current_process = process_manager().read().current_process();
-        let new_process = process_manager().write().create_process();
+        let new_process = process_manager().write().create_process(
+            current_process.id(),
+            current_process.open_objects().inherit(),
+            current_process.environment(),
+            current_process.cwd(),
+        );
         let pid = new_process.id();
         let tid = scheduler::next_thread_id();
 
@@ -148,8 +187,8 @@ impl Thread {
         // parse elf file headers and map and copy code if successful
         let entry = Thread::parse_and_map_elf_bin(&current_process, &new_process, elf_buffer, name);
 
-        // create environment for the application and copy arguments
-        Thread::copy_args(&new_process, name, args);
+        // create environment for the application and copy arguments and environment variables
+        Thread::copy_args(&current_process, &new_process, name, args);
 
         // create thread
         // this first thread is special in that there is not really a kickoff;
@@ -192,6 +231,12 @@ impl Thread {
             process: parent,
             user_kickoff: kickoff_addr,
             entry,
+            running: AtomicBool::new(false),
+            killed: AtomicBool::new(false),
+            deblocked: AtomicBool::new(false),
+            notified: AtomicBool::new(false),
+            priority: AtomicUsize::new(DEFAULT_THREAD_PRIORITY),
+            cpu_time: AtomicUsize::new(0),
         };
 
         thread.prepare_kernel_stack();
@@ -204,7 +249,9 @@ impl Thread {
         scheduler.set_init(); // scheduler initialized
 
         let thread = scheduler.current_thread();
-        tss().lock().privilege_stack_table[0] = thread.kernel_stack_addr(); // get stack pointer for kernel stack
+        interrupts::without_interrupts(|| {
+            tss().lock().privilege_stack_table[0] = thread.kernel_stack_addr(); // get stack pointer for kernel stack
+        });
 
         if thread.is_kernel_thread() {
             assert!(thread.user_kickoff.is_null());
@@ -240,9 +287,10 @@ impl Thread {
         let next_rsp0 = next.stacks.lock().old_rsp0.as_u64();
         let next_rsp0_end = next.kernel_stack_addr().as_u64();
         let next_address_space = next.process.virtual_address_space.page_table_address().as_u64();
+        let current_running = current.running.as_ptr();
 
         unsafe {
-            thread_switch(current_rsp0, next_rsp0, next_rsp0_end, next_address_space);
+            thread_switch(current_rsp0, next_rsp0, next_rsp0_end, next_address_space, current_running);
         }
     }
 
@@ -251,6 +299,72 @@ impl Thread {
         self.stacks.is_locked()
     }
 
+    /// Mark thread as running on the calling core. \
+    /// If the thread is still being switched out by another core, this waits until its registers have been saved.
+    pub fn set_running(&self) {
+        while self.running.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            spin_loop();
+        }
+    }
+
+    /// Check if thread is running on a core (or still being switched out)
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// Mark thread as killed, so that the scheduler does not run it again after its next thread switch
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if thread has been killed
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+
+    /// Mark sleeping thread as woken up before its wakeup time, called by scheduler
+    pub fn set_deblocked(&self) {
+        self.deblocked.store(true, Ordering::Release);
+    }
+
+    /// Check and reset if the thread has been woken up before its wakeup time
+    pub fn take_deblocked(&self) -> bool {
+        self.deblocked.swap(false, Ordering::Acquire)
+    }
+
+    /// Mark thread as notified, called by scheduler (from an interrupt handler). \
+    /// Returns `true`, if it has already been notified before.
+    pub fn set_notified(&self) -> bool {
+        self.notified.swap(true, Ordering::AcqRel)
+    }
+
+    /// Check and reset if the thread has been notified
+    pub fn take_notified(&self) -> bool {
+        self.notified.swap(false, Ordering::AcqRel)
+    }
+
+    /// Return my priority (0 is the lowest priority)
+    pub fn priority(&self) -> usize {
+        self.priority.load(Ordering::Relaxed)
+    }
+
+    /// Set my priority (must be lower than `THREAD_PRIORITY_LEVELS`). \
+    /// Use [`scheduler::Scheduler::set_priority`] for threads, which may be in a ready queue.
+    pub fn set_priority(&self, priority: usize) {
+        assert!(priority < THREAD_PRIORITY_LEVELS, "Invalid thread priority!");
+        self.priority.store(priority, Ordering::Relaxed);
+    }
+
+    /// Return the CPU time consumed so far (in ns, updated at each thread switch)
+    pub fn cpu_time(&self) -> usize {
+        self.cpu_time.load(Ordering::Relaxed)
+    }
+
+    /// Add `ns` to the consumed CPU time
+    pub fn add_cpu_time(&self, ns: usize) {
+        self.cpu_time.fetch_add(ns, Ordering::Relaxed);
+    }
+
     /// Check if self is a kernel only thread or not
     pub fn is_kernel_thread(&self) -> bool {
         self.stacks.lock().user_stack.capacity() == 0
@@ -419,60 +533,60 @@ impl Thread {
         elf.entry
     }
 
-    /// Helper function to provide arguments to a new application
-    /// Used only by `load_application()`
-    fn copy_args(new_process: &Arc<Process>, name: &str, args: &Vec<&str>) {
-        let args_size = args.iter().map(|arg| arg.len()).sum::<usize>();
-        let env_size = args_size;
-
+    /// Helper function to provide arguments and environment variables to a new application. \
+    /// Used only by `load_application()`. The layout (at `USER_SPACE_ENV_START`) is:
+    /// `argc`, `argv[0..argc]`, null, `envp[0..envc]`, null, followed by the null-terminated strings.
+    fn copy_args(current_process: &Arc<Process>, new_process: &Arc<Process>, name: &str, args: &Vec<&str>) {
+        let environment = new_process.environment();
+        let strings: Vec<&str> = core::iter::once(name)
+            .chain(args.iter().copied())
+            .chain(environment.iter().map(String::as_str))
+            .collect();
+        let argc = args.len() + 1;
+
+        // pointers (argc, argv + null, envp + null) are followed by the strings
+        let pointers_size = (1 + argc + 1 + environment.len() + 1) * size_of::<usize>();
+        let env_size = pointers_size + strings.iter().map(|string| string.len() + 1).sum::<usize>();
+
+        // build the environment in kernel memory, using the virtual addresses of user space
         let env_virt_start = Page::from_start_address(VirtAddr::new(USER_SPACE_ENV_START as u64)).unwrap();
-        let env_page_count = if env_size > 0 && env_size % PAGE_SIZE == 0 {
-            env_size / PAGE_SIZE
-        } else {
-            (env_size / PAGE_SIZE) + 1
-        };
+        let mut env = vec![0u8; env_size];
+        let mut pointers = Vec::with_capacity(pointers_size / size_of::<usize>());
+        let mut offset = pointers_size;
+
+        pointers.push(argc);
+        for (i, string) in strings.iter().enumerate() {
+            if i == argc {
+                pointers.push(0); // end of argv
+            }
+            pointers.push(USER_SPACE_ENV_START + offset);
+            env[offset..offset + string.len()].copy_from_slice(string.as_bytes()); // null-terminated for C compatibility
+            offset += string.len() + 1;
+        }
+        if strings.len() == argc {
+            pointers.push(0); // end of argv (no environment variables)
+        }
+        pointers.push(0); // end of envp
 
-        // create mapping for 'total_page_count'
-        let _vma = new_process
+        for (i, pointer) in pointers.iter().enumerate() {
+            env[i * size_of::<usize>()..(i + 1) * size_of::<usize>()].copy_from_slice(&pointer.to_ne_bytes());
+        }
+
+        // create mapping and copy the environment into the new address space
+        let env_page_count = env_size.div_ceil(PAGE_SIZE);
+        new_process
             .virtual_address_space
             .user_alloc_map_full(Some(env_virt_start), env_page_count as u64, VmaType::Environment, "env")
             .expect("user_alloc_map_full failed");
 
-        if env_page_count > 1 {
-            panic!("Environment size exceeds one page, which is not supported yet");
-        }
-
-        let env_frame = new_process.virtual_address_space.get_phys(env_virt_start.start_address().as_u64())
-            .expect("get_phys failed for environment");
-
-        // create argc and argv in the user space environment
-        let env_addr = VirtAddr::new(env_frame.as_u64()); // Start address of user space environment
-        let argc = env_addr.as_mut_ptr::<usize>(); // First entry in environment is argc (number of arguments)
-        let argv = (env_addr + size_of::<usize>() as u64).as_mut_ptr::<*const u8>(); // Second entry in environment is argv (array of pointers to arguments)
-
-        // copy arguments directly behind argv array and store pointers to them in argv
         unsafe {
-            argc.write(args.len() + 1);
-
-            let args_begin = argv.add(args.len() + 1) as *mut u8; // Physical start address of arguments (we use this address to copy them)
-            let args_begin_virt = env_virt_start.start_address() + size_of::<usize>() as u64 + ((args.len() + 1) * size_of::<usize>()) as u64; // Virtual start address of arguments (they will be visible here in user space)
-
-            // copy program name as first argument
-            args_begin.copy_from(name.as_bytes().as_ptr(), name.len());
-            args_begin.add(name.len()).write(0); // null-terminate the string for C compatibility
-            argv.write(args_begin_virt.as_ptr());
-
-            let mut offset = name.len() + 1;
-
-            // copy remaining arguments
-            for (i, arg) in args.iter().enumerate() {
-                let target = args_begin.add(offset);
-                target.copy_from(arg.as_bytes().as_ptr(), arg.len());
-                target.add(arg.len()).write(0); // null-terminate the string for C compatibility
-
-                argv.add(i + 1).write((args_begin_virt + offset as u64).as_ptr());
-                offset += arg.len() + 1;
-            }
+            current_process.virtual_address_space.copy_to_addr_space(
+                env.as_ptr(),
+                &new_process.virtual_address_space,
+                env_virt_start,
+                env_size as u64,
+                false,
+            );
         }
     }
 }
@@ -516,10 +630,11 @@ unsafe extern "C" fn thread_user_start(old_rsp0: u64, entry: extern "sysv64" fn(
 
 /// Low-level thread switching function
 #[unsafe(naked)]
-unsafe extern "C" fn thread_switch(current_rsp0: *mut u64, next_rsp0: u64, next_rsp0_end: u64, next_cr3: u64) {
+unsafe extern "C" fn thread_switch(current_rsp0: *mut u64, next_rsp0: u64, next_rsp0_end: u64, next_cr3: u64, current_running: *mut bool) {
     naked_asm!(
     // Save registers of current thread
     "pushf",
+    "cli", // The kernel gs base must not be accessed by interrupt handlers while it is swapped
     "push r8",
     "push r9",
     "push r10",
@@ -550,6 +665,10 @@ unsafe extern "C" fn thread_switch(current_rsp0: *mut u64, next_rsp0: u64, next_
 
     // Load registers of next thread by using 'next_rsp0' (second parameter)
     "mov rsp, rsi",
+
+    // The current thread may now be run by another core ('current_running' is the fifth parameter)
+    "mov byte ptr [r8], 0",
+
     "pop rbp",
     "pop rdi",
     "pop rsi",
@@ -17,6 +17,12 @@ pub trait BlockDevice {
 
     /// Get the size of a sector in bytes.
     fn sector_size(&self) -> u16;
+
+    /// Write all cached data to the device.
+    /// Returns false, if not all data could be written.
+    fn flush(&self) -> bool {
+        true
+    }
 }
 
 /// Convert a Logical Block Address (LBA) to Cylinder-Head-Sector (CHS) addressing.
@@ -69,8 +75,8 @@ impl BlockDevice for Partition {
             return 0;
         }
 
-        let sector = sector + self.start_sector;
         let count = count.min((self.sector_count - sector) as usize);
+        let sector = sector + self.start_sector;
         self.device.read(sector, count, buffer)
     }
 
@@ -79,8 +85,8 @@ impl BlockDevice for Partition {
             return 0;
         }
 
-        let sector = sector + self.start_sector;
         let count = count.min((self.sector_count - sector) as usize);
+        let sector = sector + self.start_sector;
         self.device.write(sector, count, buffer)
     }
 
@@ -91,4 +97,8 @@ impl BlockDevice for Partition {
     fn sector_size(&self) -> u16 {
         self.device.sector_size()
     }
+
+    fn flush(&self) -> bool {
+        self.device.flush()
+    }
 }
\ No newline at end of file
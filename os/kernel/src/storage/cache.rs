@@ -0,0 +1,351 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: cache                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Write-back cache for the sectors of a block device with LRU     ║
+   ║         eviction and hit/miss statistics. The cache lock is not held    ║
+   ║         during device accesses, sectors with pending I/O are marked     ║
+   ║         and other threads wait for the I/O to finish.                   ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::{Mutex, MutexGuard};
+use crate::storage::block::BlockDevice;
+use crate::sync::wait_queue::WaitQueue;
+
+/// Max. number of sectors cached per device (512 KiB for 512 byte sectors)
+const CACHE_SECTORS: usize = 1024;
+
+/// Statistics of a block cache, as shown in `/proc/cache`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub write_backs: usize, // dirty sectors written to the device (when evicted or flushed)
+    pub cached: usize,
+    pub dirty: usize,
+}
+
+struct CachedSector {
+    data: Box<[u8]>,
+    valid: bool, // false, while the sector is read from the device
+    dirty: bool,
+    io: bool,    // the sector is read from or written to the device (it must not be evicted)
+    last_use: u64,
+}
+
+struct CacheState {
+    sectors: BTreeMap<u64, CachedSector>,
+    lru: BTreeMap<u64, u64>, // last use -> sector number (least recently used first)
+    clock: u64,
+    stats: CacheStats,
+}
+
+/// Write-back cache for the sectors of a block device with LRU eviction.
+/// Written sectors are kept in memory until they are evicted or `flush` is called (periodically by the write-back thread).
+pub struct BlockCache {
+    device: Arc<dyn BlockDevice + Send + Sync>,
+    sector_size: usize,
+    state: Mutex<CacheState>,
+    io_count: AtomicUsize, // number of finished device accesses (changed while holding the lock)
+    io_done: WaitQueue,    // notified after each device access
+}
+
+impl BlockCache {
+    pub fn new(device: Arc<dyn BlockDevice + Send + Sync>) -> Self {
+        let sector_size = device.sector_size() as usize;
+        BlockCache {
+            device,
+            sector_size,
+            state: Mutex::new(CacheState {
+                sectors: BTreeMap::new(),
+                lru: BTreeMap::new(),
+                clock: 0,
+                stats: CacheStats::default(),
+            }),
+            io_count: AtomicUsize::new(0),
+            io_done: WaitQueue::new(),
+        }
+    }
+
+    /// Get the hit/miss statistics and the number of cached and dirty sectors
+    pub fn stats(&self) -> CacheStats {
+        let state = self.state.lock();
+        CacheStats {
+            cached: state.sectors.len(),
+            dirty: state.sectors.values().filter(|entry| entry.dirty).count(),
+            ..state.stats
+        }
+    }
+
+    /// Helper function limiting `count` to the sectors of the device and the size of a buffer with `buffer_len` bytes
+    fn limit_count(&self, sector: u64, count: usize, buffer_len: usize) -> usize {
+        let available = self.device.sector_count().saturating_sub(sector);
+        count.min(available.min(usize::MAX as u64) as usize).min(buffer_len / self.sector_size)
+    }
+
+    /// Helper function marking `sector` as most recently used
+    fn touch(state: &mut CacheState, sector: u64) {
+        state.clock += 1;
+        let clock = state.clock;
+        if let Some(entry) = state.sectors.get_mut(&sector) {
+            state.lru.remove(&entry.last_use);
+            entry.last_use = clock;
+            state.lru.insert(clock, sector);
+        }
+    }
+
+    /// Helper function inserting `data` for `sector` (which must not be cached).
+    /// Without `valid` data, the sector is marked as being read from the device.
+    fn insert(state: &mut CacheState, sector: u64, data: Box<[u8]>, valid: bool, dirty: bool) {
+        state.clock += 1;
+        let last_use = state.clock;
+        state.sectors.insert(sector, CachedSector { data, valid, dirty, io: !valid, last_use });
+        state.lru.insert(last_use, sector);
+    }
+
+    /// Helper function removing `sector` (which must be cached)
+    fn remove(state: &mut CacheState, sector: u64) {
+        if let Some(entry) = state.sectors.remove(&sector) {
+            state.lru.remove(&entry.last_use);
+        }
+    }
+
+    /// Helper function evicting least recently used sectors, until there is room for another sector.
+    /// If all sectors have pending I/O, it waits for the I/O to finish, but only if `may_wait` is `true`
+    /// (a thread must not wait for its own reservations). As dirty sectors are written back without holding the lock,
+    /// the cache may have changed, when this returns. \
+    /// Returns the locked state again and whether there is room (`false`, if it would have to wait or a write-back failed).
+    fn make_room<'a>(&'a self, mut state: MutexGuard<'a, CacheState>, may_wait: bool) -> (MutexGuard<'a, CacheState>, bool) {
+        while state.sectors.len() >= CACHE_SECTORS {
+            // Sectors with pending I/O cannot be evicted
+            let Some(victim) = state.lru.values().copied().find(|sector| !state.sectors[sector].io) else {
+                if !may_wait {
+                    return (state, false);
+                }
+                state = self.wait_for_io(state);
+                continue;
+            };
+
+            if state.sectors[&victim].dirty {
+                let success;
+                (state, success) = self.write_back(state, victim, 1);
+                if !success {
+                    return (state, false);
+                }
+            } else {
+                Self::remove(&mut state, victim);
+            }
+        }
+
+        (state, true)
+    }
+
+    /// Helper function writing `count` dirty sectors starting at `first` (all cached and without pending I/O) to the device.
+    /// The sectors may be changed again during the write, they stay dirty then. \
+    /// Returns the locked state again and whether the write has been successful.
+    fn write_back<'a>(&'a self, mut state: MutexGuard<'a, CacheState>, first: u64, count: usize) -> (MutexGuard<'a, CacheState>, bool) {
+        let mut run = Vec::with_capacity(count * self.sector_size);
+        for entry in state.sectors.range_mut(first..first + count as u64).map(|(_, entry)| entry) {
+            run.extend_from_slice(&entry.data);
+            entry.dirty = false;
+            entry.io = true;
+        }
+        drop(state);
+
+        let success = self.device.write(first, count, &run) == count;
+
+        let mut state = self.state.lock();
+        for entry in state.sectors.range_mut(first..first + count as u64).map(|(_, entry)| entry) {
+            entry.dirty |= !success;
+            entry.io = false;
+        }
+        if success {
+            state.stats.write_backs += count;
+        }
+
+        (self.finish_io(state), success)
+    }
+
+    /// Helper function waking up all threads waiting for pending I/O (without holding the lock).
+    /// Returns the locked state again.
+    fn finish_io<'a>(&'a self, state: MutexGuard<'a, CacheState>) -> MutexGuard<'a, CacheState> {
+        self.io_count.fetch_add(1, Ordering::Release);
+        drop(state);
+
+        self.io_done.notify_all();
+        self.state.lock()
+    }
+
+    /// Helper function waiting (without holding the lock) until a device access of another thread has finished.
+    /// Returns the locked state again.
+    fn wait_for_io<'a>(&'a self, state: MutexGuard<'a, CacheState>) -> MutexGuard<'a, CacheState> {
+        let io_count = self.io_count.load(Ordering::Acquire);
+        drop(state);
+
+        self.io_done.wait(|| self.io_count.load(Ordering::Acquire) != io_count);
+        self.state.lock()
+    }
+}
+
+impl BlockDevice for BlockCache {
+    /// Cached sectors are copied from the cache, consecutive missing sectors are read from the device with a single request.
+    fn read(&self, sector: u64, count: usize, buffer: &mut [u8]) -> usize {
+        let count = self.limit_count(sector, count, buffer.len());
+        let sector_size = self.sector_size;
+        let mut state = self.state.lock();
+
+        let mut index = 0;
+        while index < count {
+            let current = sector + index as u64;
+            match state.sectors.get(&current) {
+                Some(entry) if entry.valid => {
+                    buffer[index * sector_size..(index + 1) * sector_size].copy_from_slice(&entry.data);
+                    state.stats.hits += 1;
+                    Self::touch(&mut state, current);
+                    index += 1;
+                    continue;
+                }
+                // another thread is reading the sector from the device
+                Some(_) => {
+                    state = self.wait_for_io(state);
+                    continue;
+                }
+                None => {}
+            }
+
+            // Reserve all following sectors, which are not cached, so that other threads wait for this read.
+            // If there is no room for another sector, the sectors reserved so far are read.
+            let mut end = index;
+            while end < count && !state.sectors.contains_key(&(sector + end as u64)) {
+                let has_room;
+                (state, has_room) = self.make_room(state, end == index);
+                if !has_room {
+                    if end == index {
+                        return index;
+                    }
+                    break;
+                }
+                // the sector may have been inserted by another thread, while a dirty sector has been evicted
+                if state.sectors.contains_key(&(sector + end as u64)) {
+                    break;
+                }
+                Self::insert(&mut state, sector + end as u64, vec![0u8; sector_size].into_boxed_slice(), false, false);
+                end += 1;
+            }
+            if end == index {
+                continue;
+            }
+            drop(state);
+
+            let run = &mut buffer[index * sector_size..end * sector_size];
+            let read = self.device.read(current, end - index, run);
+
+            state = self.state.lock();
+            state.stats.misses += end - index;
+            for i in 0..end - index {
+                let reserved = current + i as u64;
+                if i < read {
+                    let entry = state.sectors.get_mut(&reserved).unwrap();
+                    entry.data.copy_from_slice(&run[i * sector_size..(i + 1) * sector_size]);
+                    entry.valid = true;
+                    entry.io = false;
+                } else {
+                    Self::remove(&mut state, reserved);
+                }
+            }
+            state = self.finish_io(state);
+
+            if read < end - index {
+                return index + read;
+            }
+            index = end;
+        }
+
+        count
+    }
+
+    /// Sectors are only written into the cache. They are written to the device, when they are evicted or flushed.
+    fn write(&self, sector: u64, count: usize, buffer: &[u8]) -> usize {
+        let count = self.limit_count(sector, count, buffer.len());
+        let sector_size = self.sector_size;
+        let mut state = self.state.lock();
+
+        let mut index = 0;
+        while index < count {
+            let current = sector + index as u64;
+            let data = &buffer[index * sector_size..(index + 1) * sector_size];
+            match state.sectors.get_mut(&current) {
+                Some(entry) if entry.valid => {
+                    entry.data.copy_from_slice(data);
+                    entry.dirty = true;
+                    Self::touch(&mut state, current);
+                    index += 1;
+                }
+                // another thread is reading the sector from the device
+                Some(_) => state = self.wait_for_io(state),
+                None => {
+                    let has_room;
+                    (state, has_room) = self.make_room(state, true);
+                    if !has_room {
+                        return index;
+                    }
+                    // the sector may have been inserted by another thread, while a dirty sector has been evicted
+                    if !state.sectors.contains_key(&current) {
+                        Self::insert(&mut state, current, Box::from(data), true, true);
+                        index += 1;
+                    }
+                }
+            }
+        }
+
+        count
+    }
+
+    fn sector_count(&self) -> u64 {
+        self.device.sector_count()
+    }
+
+    fn sector_size(&self) -> u16 {
+        self.device.sector_size()
+    }
+
+    /// Write all dirty sectors to the device (consecutive sectors with a single request).
+    fn flush(&self) -> bool {
+        let mut state = self.state.lock();
+        let mut success = true;
+        let mut next = 0;
+
+        loop {
+            let mut writable = state.sectors.range(next..).filter(|(_, entry)| entry.dirty && !entry.io).map(|(&sector, _)| sector);
+            let Some(first) = writable.next() else {
+                // Sectors, which have been written during their write-back by another thread, need to be written again
+                if state.sectors.values().any(|entry| entry.dirty && entry.io) {
+                    state = self.wait_for_io(state);
+                    next = 0;
+                    continue;
+                }
+                break;
+            };
+
+            let mut count = 1;
+            while writable.next() == Some(first + count as u64) {
+                count += 1;
+            }
+
+            let written;
+            (state, written) = self.write_back(state, first, count);
+            success &= written;
+            next = first + count as u64;
+        }
+        drop(state);
+
+        success && self.device.flush()
+    }
+}
@@ -1,24 +1,46 @@
 use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::sync::Arc;
-use log::info;
+use alloc::vec::Vec;
+use log::{info, warn};
 use smallmap::Map;
 use spin::{Mutex, Once, RwLock};
-use crate::device::ide;
+use crate::device::{ahci, ide, virtio};
+use crate::process::thread::Thread;
+use crate::scheduler;
 use crate::storage::block::BlockDevice;
+use crate::storage::cache::{BlockCache, CacheStats};
 
 pub mod block;
+pub mod cache;
 
 static BLOCK_DEVICES: Once<RwLock<Map<String, Arc<dyn BlockDevice + Send + Sync>>>> = Once::new();
+static BLOCK_CACHES: Once<RwLock<Map<String, Arc<BlockCache>>>> = Once::new();
 static DEVICE_TYPES: Once<Mutex<Map<String, usize>>> = Once::new();
 
-/// Initialize all storage drivers
+/// Interval, in which the write-back thread writes dirty sectors of all caches to the devices
+const WRITE_BACK_INTERVAL_MS: usize = 5000;
+
+/// Initialize all storage drivers and start the write-back thread
 pub fn init() {
     ide::init();
+    ahci::init();
+    virtio::blk::init();
+
+    extern "sysv64" fn write_back() {
+        loop {
+            scheduler().sleep(WRITE_BACK_INTERVAL_MS);
+            if !sync() {
+                warn!("Failed to write back cached sectors");
+            }
+        }
+    }
+    scheduler().ready(Thread::new_kernel_thread(write_back, "write-back"));
 }
 
 /// Register a block device with the given type
 /// The type is used to generate a unique name for the device (e.g. type "ata" will generate names "ata0", "ata1", etc.)
+/// All accesses to the device and its partitions go through a write-back cache (see `sync`), which is written back periodically.
 pub fn add_block_device(typ: &str, drive: Arc<dyn BlockDevice + Send + Sync>) {
     let typ = typ.to_string();
     let mut types = DEVICE_TYPES.call_once(|| Mutex::new(Map::new())).lock();
@@ -26,6 +48,10 @@ pub fn add_block_device(typ: &str, drive: Arc<dyn BlockDevice + Send + Sync>) {
     let name = format!("{typ}{index}");
     types.insert(typ, index + 1);
 
+    let cache = Arc::new(BlockCache::new(drive));
+    BLOCK_CACHES.call_once(|| RwLock::new(Map::new())).write().insert(name.clone(), Arc::clone(&cache));
+    let drive: Arc<dyn BlockDevice + Send + Sync> = cache;
+
     let partitions = block::scan_partitions(&drive);
 
     let mut drives = BLOCK_DEVICES.call_once(|| RwLock::new(Map::new())).write();
@@ -45,4 +71,30 @@ pub fn block_device(name: &str) -> Option<Arc<dyn BlockDevice + Send + Sync>> {
         None => None,
         Some(device) => Some(Arc::clone(device))
     }
-}
\ No newline at end of file
+}
+
+/// Get the names of all registered block devices (including partitions), sorted by name
+pub fn block_device_names() -> Vec<String> {
+    let mut names: Vec<String> = BLOCK_DEVICES.call_once(|| RwLock::new(Map::new())).read().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Write the cached data of all block devices to the devices.
+/// Returns false, if not all data could be written.
+pub fn sync() -> bool {
+    let caches: Vec<Arc<BlockCache>> = BLOCK_CACHES.call_once(|| RwLock::new(Map::new())).read().values().cloned().collect();
+    caches.iter().fold(true, |success, cache| cache.flush() && success)
+}
+
+/// Get the cache statistics of all block devices (without partitions), sorted by name
+pub fn cache_stats() -> Vec<(String, CacheStats)> {
+    let mut stats: Vec<(String, CacheStats)> = BLOCK_CACHES
+        .call_once(|| RwLock::new(Map::new()))
+        .read()
+        .iter()
+        .map(|(name, cache)| (name.clone(), cache.stats()))
+        .collect();
+    stats.sort_by(|a, b| a.0.cmp(&b.0));
+    stats
+}
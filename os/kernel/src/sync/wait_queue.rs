@@ -5,25 +5,39 @@
    ║                                                                         ║
    ║ Public functions:                                                       ║
    ║   - wait:       Blocks calling thread if the given predicate is true.   ║
+   ║   - wait_timeout: Like wait, but returns after the given time at most.  ║
+   ║   - wait_once:  Blocks calling thread until the next notification.      ║
    ║   - notify_one: Deblocks one waiting thread (if any).                   ║
+   ║   - notify_all: Deblocks all waiting threads.                           ║
+   ║   - notify_all_from_interrupt: Like notify_all, for interrupt handlers. ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Michael Schoettner, Univ. Duesseldorf, 01.09.2025               ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 
 use alloc::collections::VecDeque;
+use alloc::sync::Arc;
 
-use crate::scheduler;
+use crate::{scheduler, timer};
+use crate::process::thread::Thread;
 use crate::sync::irqsave_spinlock::IrqSaveSpinlock;
 
 pub struct WaitQueue {
-    queue: IrqSaveSpinlock<VecDeque<(usize, usize)>>,
+    queue: IrqSaveSpinlock<VecDeque<Arc<Thread>>>,
+}
+
+/// Reason for returning from `WaitQueue::wait_once`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wakeup {
+    Ready,    // the predicate has been true, the thread has not been blocked
+    Notified, // woken up by `notify_one` or `notify_all`
+    TimedOut, // the timeout has passed
 }
 
 impl WaitQueue {
-    pub fn new() -> WaitQueue {
+    pub const fn new() -> WaitQueue {
         WaitQueue {
-            queue: IrqSaveSpinlock::new(VecDeque::<(usize,usize)>::new()),
+            queue: IrqSaveSpinlock::new(VecDeque::<Arc<Thread>>::new()),
         }
     }
 
@@ -37,7 +51,7 @@ impl WaitQueue {
                 return;
             }
 
-            let (pid, tid) = scheduler().current_ids();
+            let thread = scheduler().current_thread();
 
             // Take the queue lock, and re-check the predicate while holding it to avoid racing with notify_*().
             {
@@ -49,7 +63,7 @@ impl WaitQueue {
                 }
 
                 // Enqueue ourselves as a waiter
-                quard.push_back((pid, tid));
+                quard.push_back(thread);
                 // lock is dropped here => IRQs restored
             }
 
@@ -60,19 +74,127 @@ impl WaitQueue {
         }
     }
 
+    /// Block until `pred()` becomes true or `timeout_ms` milliseconds have passed. \
+    /// Returns `true` if `pred()` became true and `false` on timeout.
+    pub fn wait_timeout<F>(&self, mut pred: F, timeout_ms: usize) -> bool
+    where
+        F: FnMut() -> bool,
+    {
+        let deadline = timer().systime_ms().saturating_add(timeout_ms);
+        loop {
+            if pred() {
+                return true;
+            }
+
+            let now = timer().systime_ms();
+            if now >= deadline {
+                return false;
+            }
+
+            let thread = scheduler().current_thread();
+            {
+                let mut quard = self.queue.lock();
+                if pred() {
+                    return true;
+                }
+                quard.push_back(Arc::clone(&thread));
+            }
+
+            // Sleep until the deadline, notify_one/notify_all wake us up earlier.
+            self.sleep(&thread, deadline - now);
+        }
+    }
+
+    /// Block once until the next notification or until `timeout_ms` milliseconds (`None` = no timeout) have passed,
+    /// unless `pred()` is true. Unlike `wait`, this returns after a notification, even if `pred()` is still false. \
+    /// `pred()` is checked after enqueuing, without holding the queue lock (so it may take other locks),
+    /// a notification after checking it is not missed.
+    pub fn wait_once<F>(&self, mut pred: F, timeout_ms: Option<usize>) -> Wakeup
+    where
+        F: FnMut() -> bool,
+    {
+        let thread = scheduler().current_thread();
+        self.queue.lock().push_back(Arc::clone(&thread));
+
+        if pred() {
+            return if self.cancel(&thread) { Wakeup::Ready } else { Wakeup::Notified };
+        }
+
+        match timeout_ms {
+            Some(timeout_ms) if !self.sleep(&thread, timeout_ms) => Wakeup::TimedOut,
+            Some(_) => Wakeup::Notified,
+            None => {
+                scheduler().block();
+                Wakeup::Notified
+            }
+        }
+    }
+
+    /// Helper function sleeping for `ms` milliseconds, after the calling `thread` has enqueued itself.
+    /// Returns `true`, if it has been woken up by `notify_one`/`notify_all` and `false` on timeout.
+    fn sleep(&self, thread: &Arc<Thread>, ms: usize) -> bool {
+        // A notification between enqueuing and sleeping is not missed, because the scheduler
+        // remembers it and lets `sleep()` return immediately.
+        scheduler().sleep(ms) || !self.cancel(thread)
+    }
+
+    /// Helper function removing the calling `thread` from the queue, if it stops waiting without a notification.
+    /// Returns `false`, if a notifier has already taken it out of the queue. Then its wakeup is consumed here,
+    /// so that it cannot cut short a later `block()` or `sleep()`.
+    fn cancel(&self, thread: &Arc<Thread>) -> bool {
+        let removed = {
+            let mut quard = self.queue.lock();
+            match quard.iter().position(|waiter| Arc::ptr_eq(waiter, thread)) {
+                Some(pos) => {
+                    quard.remove(pos);
+                    true
+                }
+                None => false,
+            }
+        };
+        if !removed {
+            scheduler().block();
+        }
+        removed
+    }
+
     /// Wake exactly one waiter (if any). Returns true if someone was woken.
     pub fn notify_one(&self) -> bool {
         let waiter = {  
             let mut quard = self.queue.lock();
             quard.pop_front()
         };
-        if let Some((pid, tid)) = waiter {
-            scheduler().deblock(pid, tid);
+        if let Some(thread) = waiter {
+            scheduler().deblock(thread.process().id(), thread.id());
             true
         } else {
             false
         }
     }
 
+    /// Wake all waiters. Returns the number of threads woken.
+    pub fn notify_all(&self) -> usize {
+        let waiters = {
+            let mut quard = self.queue.lock();
+            core::mem::take(&mut *quard)
+        };
+        for thread in &waiters {
+            scheduler().deblock(thread.process().id(), thread.id());
+        }
+        waiters.len()
+    }
+
+    /// Wake all waiters from an interrupt handler, which must neither block nor allocate memory.
+    /// The waiters are woken up at the next thread switch (see `Scheduler::notify`). Returns the number of threads woken.
+    pub fn notify_all_from_interrupt(&self) -> usize {
+        // The waiters are notified while holding the lock, so that `cancel` does not miss a notification
+        let mut quard = self.queue.lock();
+        let count = quard.len();
+        while let Some(thread) = quard.pop_front() {
+            scheduler().notify(&thread);
+        }
+        count
+    }
+
 
  }
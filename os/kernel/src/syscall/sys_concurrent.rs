@@ -6,26 +6,73 @@
    ║ Author: Fabian Ruhland, 30.8.2024, HHU                                  ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
-use alloc::format;
+use alloc::vec;
 use alloc::vec::Vec;
 use alloc::sync::Arc;
 use core::ptr::slice_from_raw_parts;
+use core::sync::atomic::{AtomicU32, Ordering};
 use core::str::from_utf8;
+use goblin::elf::Elf;
 use x86_64::VirtAddr;
-use syscall::return_vals::Errno;
-use crate::{initrd, process_manager, scheduler};
+use naming::shared_types::{FileType, OpenOptions, RawStat};
+use syscall::return_vals::{self, Errno};
+use crate::{process_manager, scheduler};
+use crate::consts::{MAX_USER_THREAD_PRIORITY, THREAD_PRIORITY_LEVELS};
+use crate::naming::api;
+use crate::process::process::Process;
 use crate::process::thread::Thread;
+use crate::sync::wait_queue::Wakeup;
 
 
 pub extern "sysv64" fn sys_process_id() -> isize {
     process_manager().read().current_process().id() as isize
 }
 
-pub extern "sysv64" fn sys_process_exit() -> ! {
-    scheduler().current_thread().process().exit();
+/// Terminate the calling process. Like on Unix, only the lowest 8 bits of `exit_code` are kept.
+pub extern "sysv64" fn sys_process_exit(exit_code: usize) -> ! {
+    scheduler().current_thread().process().exit(exit_code & 0xff);
     scheduler().exit();
 }
 
+/// Block until the process with `id` has terminated and return its exit code.
+/// If `nohang` is not 0, `EAGAIN` is returned instead of blocking.
+/// Only the parent of a process may wait for it, other processes get `ECHILD`.
+/// The exit code can only be fetched once, further calls fail with `ESRCH`.
+/// It is only kept while the parent of the process is active (the parent is expected to wait).
+pub extern "sysv64" fn sys_process_wait(id: usize, nohang: usize) -> isize {
+    let current_id = process_manager().read().current_process().id();
+    let process = process_manager().read().active_process(id);
+    if let Some(process) = process {
+        if process.parent_id() != current_id {
+            return Errno::ECHILD.into();
+        }
+        if nohang != 0 && process.exit_code().is_none() {
+            return Errno::EAGAIN.into();
+        }
+        process.wait_for_exit();
+    }
+
+    match process_manager().write().reap(id, current_id) {
+        Ok(exit_code) => exit_code as isize,
+        Err(errno) => errno.into(),
+    }
+}
+
+/// Set the environment variable `key` to `value` for the calling process (inherited by processes started afterwards).
+pub unsafe extern "sysv64" fn sys_process_set_env(key_buffer: *const u8, key_length: usize, value_buffer: *const u8, value_length: usize) -> isize {
+    let key = from_utf8(unsafe { slice_from_raw_parts(key_buffer, key_length).as_ref().unwrap() });
+    let value = from_utf8(unsafe { slice_from_raw_parts(value_buffer, value_length).as_ref().unwrap() });
+    let (Ok(key), Ok(value)) = (key, value) else {
+        return Errno::EBADSTR.into();
+    };
+    if key.is_empty() || key.contains(['=', '\0']) || value.contains('\0') {
+        return Errno::EINVAL.into();
+    }
+
+    process_manager().read().current_process().set_env(key, value);
+    0
+}
+
 pub fn sys_process_count() -> isize {
     process_manager().read().active_process_ids().len() as isize
 }
@@ -70,16 +117,152 @@ pub fn sys_thread_count() -> isize {
     scheduler().active_thread_ids().len() as isize
 }
 
-pub unsafe extern "sysv64" fn sys_process_execute_binary(name_buffer: *const u8, name_length: usize, args: *const Vec<&str>) -> isize {
-    let app_name = from_utf8(unsafe { slice_from_raw_parts(name_buffer, name_length).as_ref().unwrap() }).unwrap();
-    let path = format!("bin/{}", app_name);
+/// Return the thread with `id`, if it belongs to the calling process
+fn own_thread(id: usize) -> Result<Arc<Thread>, Errno> {
+    let pid = process_manager().read().current_process().id();
+    scheduler().thread(id).filter(|thread| thread.process().id() == pid).ok_or(Errno::ESRCH)
+}
+
+/// Return the priority of the thread with `id` (0 is the lowest priority).
+pub extern "sysv64" fn sys_thread_get_priority(id: usize) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(own_thread(id).map(|thread| thread.priority()))
+}
+
+/// Set the priority of the thread with `id` to `priority` (0 is the lowest priority).
+/// User threads may not exceed `MAX_USER_THREAD_PRIORITY`, otherwise they could starve kernel threads (`EACCES`).
+pub extern "sysv64" fn sys_thread_set_priority(id: usize, priority: usize) -> isize {
+    if priority >= THREAD_PRIORITY_LEVELS {
+        return Errno::EINVAL.into();
+    }
+    if priority > MAX_USER_THREAD_PRIORITY {
+        return Errno::EACCES.into();
+    }
+
+    match own_thread(id) {
+        Ok(thread) => {
+            scheduler().set_priority(&thread, priority);
+            0
+        }
+        Err(errno) => errno.into(),
+    }
+}
+
+/// Return the CPU time consumed by the thread with `id` (in ns).
+pub extern "sysv64" fn sys_thread_cpu_time(id: usize) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(own_thread(id).map(|thread| thread.cpu_time()))
+}
+
+/// Load the 32-bit futex word at `address` of `process`. \
+/// Returns `EINVAL`, if `address` is not aligned, and `EFAULT`, if it is not readable user memory (e.g. not mapped).
+fn load_futex_word(process: &Process, address: usize) -> Result<u32, Errno> {
+    if address % align_of::<AtomicU32>() != 0 {
+        return Err(Errno::EINVAL);
+    }
+
+    process.virtual_address_space
+        .access_user_memory(address as u64, size_of::<AtomicU32>() as u64, || {
+            unsafe { (*(address as *const AtomicU32)).load(Ordering::Acquire) }
+        })
+        .ok_or(Errno::EFAULT)
+}
+
+/// Block the calling thread, if the futex word at `address` contains `expected`,
+/// until it is woken up by `sys_futex_wake()` or `timeout_ms` (0 = no timeout) have passed. \
+/// The thread returns after being woken up, even if the futex word still contains `expected`. \
+/// Returns `EAGAIN`, if the futex word does not contain `expected`, `ETIMEDOUT` on timeout
+/// and `EFAULT`, if `address` is not accessible.
+pub extern "sysv64" fn sys_futex_wait(address: usize, expected: usize, timeout_ms: usize) -> isize {
+    let process = process_manager().read().current_process();
+    let expected = expected as u32;
+    match load_futex_word(&process, address) {
+        Ok(value) if value != expected => return Errno::EAGAIN.into(),
+        Ok(_) => {}
+        Err(errno) => return errno.into(),
+    }
+
+    // Wait queues are private to the process, hence futexes cannot be shared between processes.
+    // An inaccessible futex word (e.g. unmapped concurrently) counts as changed.
+    let futex = process.futex(address, true).unwrap();
+    let changed = || load_futex_word(&process, address) != Ok(expected);
+    let wakeup = futex.wait_once(changed, (timeout_ms != 0).then_some(timeout_ms));
+    process.release_futex(address, futex);
+
+    match wakeup {
+        Wakeup::Ready => Errno::EAGAIN.into(),
+        Wakeup::Notified => 0,
+        Wakeup::TimedOut => Errno::ETIMEDOUT.into(),
+    }
+}
+
+/// Wake up to `count` threads waiting on the futex word at `address`.
+/// Returns the number of woken threads.
+pub extern "sysv64" fn sys_futex_wake(address: usize, count: usize) -> isize {
+    let process = process_manager().read().current_process();
+    if let Err(errno) = load_futex_word(&process, address) {
+        return errno.into();
+    }
+
+    let Some(futex) = process.futex(address, false) else {
+        return 0; // nobody is waiting on this futex
+    };
+    let mut woken = 0;
+    while woken < count && futex.notify_one() {
+        woken += 1;
+    }
+    process.release_futex(address, futex);
 
-    match initrd().entries().find(|entry| entry.filename().as_str().unwrap() == path) {
-        Some(app) => {
-            let thread = Thread::load_application(app.data(), app_name, unsafe { args.as_ref().unwrap() });
-            scheduler().ready(Arc::clone(&thread));
-            thread.id() as isize
+    woken as isize
+}
+
+/// Start the executable at `path` (absolute or relative to the current working directory) in a new process.
+/// Returns the id of the new process.
+pub unsafe extern "sysv64" fn sys_process_execute_binary(path_buffer: *const u8, path_length: usize, args: *const Vec<&str>) -> isize {
+    let path = match from_utf8(unsafe { slice_from_raw_parts(path_buffer, path_length).as_ref().unwrap() }) {
+        Ok(path) => path,
+        Err(_) => return Errno::EBADSTR.into(),
+    };
+
+    let result = read_executable(path).map(|elf_buffer| {
+        let app_name = path.rsplit('/').next().unwrap();
+        let thread = Thread::load_application(&elf_buffer, app_name, unsafe { args.as_ref().unwrap() });
+        scheduler().ready(Arc::clone(&thread));
+        thread.process().id()
+    });
+    return_vals::convert_syscall_result_to_ret_code(result)
+}
+
+/// Read the whole file at `path` using the naming service. \
+/// Returns `Err(EACCES)`, if it is not a regular file, and `Err(ENOEXEC)`, if it is not an ELF file.
+fn read_executable(path: &str) -> Result<Vec<u8>, Errno> {
+    let handle = api::open(&api::absolute_path(path), OpenOptions::READONLY)?;
+    let result = read_regular_file(handle);
+    api::close(handle)?;
+    let elf_buffer = result?;
+
+    match Elf::parse(&elf_buffer) {
+        Ok(_) => Ok(elf_buffer),
+        Err(_) => Err(Errno::ENOEXEC),
+    }
+}
+
+/// Helper function reading as many bytes as the size of the regular file referenced by `handle` (character devices report a size of 0). \
+/// Returns `Err(EACCES)`, if it is not a regular file.
+fn read_regular_file(handle: usize) -> Result<Vec<u8>, Errno> {
+    let mut raw_stat = RawStat::new();
+    api::fstat(handle, &mut raw_stat)?;
+    if raw_stat.st_type != FileType::Regular as usize {
+        return Err(Errno::EACCES);
+    }
+
+    let mut buffer = vec![0u8; raw_stat.st_size];
+    let mut read = 0;
+    while read < buffer.len() {
+        match api::read(handle, &mut buffer[read..])? {
+            0 => break,
+            len => read += len,
         }
-        None => Errno::ENOENT.into(),
     }
+    buffer.truncate(read);
+
+    Ok(buffer)
 }
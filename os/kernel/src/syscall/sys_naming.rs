@@ -11,14 +11,16 @@ use alloc::string::{String, ToString};
 use core::ptr::slice_from_raw_parts;
 use core::str::from_utf8;
 use core::mem;
-use naming::shared_types::{OpenOptions, SeekOrigin, RawDirent};
+use naming::shared_types::{OpenOptions, SeekOrigin, RawDirent, RawStat, PollFd};
 use syscall::return_vals::{self, Errno};
 use num_enum::FromPrimitive;
 
 use crate::naming::api;
 
 pub unsafe extern "sysv64" fn sys_open(path: *const u8, flag_bits: usize) -> isize {
-    let flags = OpenOptions::from_bits(flag_bits).unwrap();
+    let Some(flags) = OpenOptions::from_bits(flag_bits) else {
+        return Errno::EINVAL as isize;
+    };
     return_vals::convert_syscall_result_to_ret_code(api::open(&unsafe { ptr_to_string(path).unwrap() }, flags))
 }
 
@@ -52,6 +54,14 @@ pub extern "sysv64" fn sys_close(fh: usize) -> isize {
     return_vals::convert_syscall_result_to_ret_code(api::close(fh))
 }
 
+pub extern "sysv64" fn sys_dup(fh: usize) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(api::dup(fh))
+}
+
+pub extern "sysv64" fn sys_dup2(fh: usize, new_fh: usize) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(api::dup2(fh, new_fh))
+}
+
 pub unsafe extern "sysv64" fn sys_mkdir(path: *const u8) -> isize {
     return_vals::convert_syscall_result_to_ret_code(api::mkdir(&unsafe { ptr_to_string(path).unwrap() }))
 }
@@ -109,3 +119,68 @@ pub unsafe extern "sysv64" fn sys_cwd(buffer: *mut u8, buffer_length: usize) ->
 pub unsafe extern "sysv64" fn sys_cd(path: *const u8) -> isize {
     return_vals::convert_syscall_result_to_ret_code(api::cd(&unsafe {ptr_to_string(path)}.unwrap()))
 }
+
+pub unsafe extern "sysv64" fn sys_mount(source: *const u8, target: *const u8, fs_type: *const u8) -> isize {
+    let args = unsafe { ptr_to_string(source).and_then(|source| Ok((source, ptr_to_string(target)?, ptr_to_string(fs_type)?))) };
+    return_vals::convert_syscall_result_to_ret_code(args.and_then(|(source, target, fs_type)| api::mount(&source, &target, &fs_type)))
+}
+
+pub unsafe extern "sysv64" fn sys_umount(target: *const u8) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(unsafe { ptr_to_string(target) }.and_then(|target| api::umount(&target)))
+}
+
+pub unsafe extern "sysv64" fn sys_unlink(path: *const u8) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(unsafe { ptr_to_string(path) }.and_then(|path| api::unlink(&path)))
+}
+
+pub unsafe extern "sysv64" fn sys_rmdir(path: *const u8) -> isize {
+    return_vals::convert_syscall_result_to_ret_code(unsafe { ptr_to_string(path) }.and_then(|path| api::rmdir(&path)))
+}
+
+pub unsafe extern "sysv64" fn sys_rename(old_path: *const u8, new_path: *const u8) -> isize {
+    let paths = unsafe { ptr_to_string(old_path).and_then(|old_path| Ok((old_path, ptr_to_string(new_path)?))) };
+    return_vals::convert_syscall_result_to_ret_code(paths.and_then(|(old_path, new_path)| api::rename(&old_path, &new_path)))
+}
+
+pub unsafe extern "sysv64" fn sys_symlink(target: *const u8, link_path: *const u8) -> isize {
+    let paths = unsafe { ptr_to_string(target).and_then(|target| Ok((target, ptr_to_string(link_path)?))) };
+    return_vals::convert_syscall_result_to_ret_code(paths.and_then(|(target, link_path)| api::symlink(&target, &link_path)))
+}
+
+pub unsafe extern "sysv64" fn sys_readlink(path: *const u8, buffer: *mut u8, buffer_length: usize) -> isize {
+    if buffer.is_null() || buffer_length == 0 {
+        return Errno::EINVAL as isize;
+    }
+    let buf = unsafe { slice::from_raw_parts_mut(buffer, buffer_length) };
+    return_vals::convert_syscall_result_to_ret_code(unsafe { ptr_to_string(path) }.and_then(|path| api::readlink(&path, buf)))
+}
+
+pub unsafe extern "sysv64" fn sys_stat(path: *const u8, buffer: *mut u8, buffer_length: usize) -> isize {
+    if buffer.is_null() || buffer_length < mem::size_of::<RawStat>() {
+        return Errno::EINVAL as isize;
+    }
+    let raw_stat = unsafe { (buffer as *mut RawStat).as_mut().unwrap() };
+    return_vals::convert_syscall_result_to_ret_code(unsafe { ptr_to_string(path) }.and_then(|path| api::stat(&path, raw_stat)))
+}
+
+pub unsafe extern "sysv64" fn sys_fstat(fh: usize, buffer: *mut u8, buffer_length: usize) -> isize {
+    if buffer.is_null() || buffer_length < mem::size_of::<RawStat>() {
+        return Errno::EINVAL as isize;
+    }
+    let raw_stat = unsafe { (buffer as *mut RawStat).as_mut().unwrap() };
+    return_vals::convert_syscall_result_to_ret_code(api::fstat(fh, raw_stat))
+}
+
+pub extern "sysv64" fn sys_sync() -> isize {
+    return_vals::convert_syscall_result_to_ret_code(api::sync())
+}
+
+/// `timeout_ms` is `usize::MAX` for waiting without a timeout
+pub unsafe extern "sysv64" fn sys_poll(fds: *mut PollFd, count: usize, timeout_ms: usize) -> isize {
+    if fds.is_null() && count > 0 {
+        return Errno::EINVAL as isize;
+    }
+    let fds = if count == 0 { &mut [] } else { unsafe { slice::from_raw_parts_mut(fds, count) } };
+    let timeout_ms = if timeout_ms == usize::MAX { None } else { Some(timeout_ms) };
+    return_vals::convert_syscall_result_to_ret_code(api::poll(fds, timeout_ms))
+}
@@ -2,10 +2,10 @@ use core::str::FromStr;
 
 use alloc::{ffi::CString, string::ToString};
 use log::{debug, info, warn};
-use smoltcp::{iface::SocketHandle, socket::{icmp, tcp, udp}, wire::IpAddress};
+use smoltcp::{socket::{icmp, tcp, udp}, wire::IpAddress};
 use syscall::return_vals::Errno;
 
-use crate::{network::{accept_tcp, bind_icmp, bind_tcp, bind_udp, close_socket, connect_tcp, get_ip_addresses, open_icmp, open_tcp, open_udp, receive_datagram, receive_icmp, receive_tcp, send_datagram, send_icmp, send_tcp, SocketType}, syscall::sys_naming::ptr_to_string};
+use crate::{network::{accept_tcp, bind_icmp, bind_tcp, bind_udp, close_socket, connect_tcp, get_ip_addresses, open_icmp, open_tcp, open_udp, receive_datagram, receive_icmp, receive_tcp, send_datagram, send_icmp, send_tcp, SocketId, SocketType}, syscall::sys_naming::ptr_to_string};
 
 /// This module contains all network-related system calls.
 
@@ -13,18 +13,21 @@ pub fn sys_sock_open(protocol: SocketType) -> isize {
     info!("opening a {protocol:?} socket");
     // TODO: what happens when we get a type thats not in the enum?
     #[allow(unreachable_patterns)]
-    let handle = match protocol {
+    let id = match protocol {
         SocketType::Udp => open_udp(),
         SocketType::Tcp => open_tcp(),
         SocketType::Icmp => open_icmp(),
         _ => return Errno::ENOTSUP.into(),
     };
-    // handle.0 is private, sadly, so just hope this works
-    unsafe { core::mem::transmute::<SocketHandle, usize>(handle) }.try_into().unwrap()
+    match id {
+        Some(id) => id.try_into().unwrap(),
+        // sockets are kept at a network interface
+        None => Errno::ENETDOWN.into(),
+    }
 }
 
 pub unsafe fn sys_sock_bind(
-    handle: SocketHandle, protocol: SocketType, addr_ptr: *const u8, port: u16,
+    handle: SocketId, protocol: SocketType, addr_ptr: *const u8, port: u16,
 ) -> isize {
     // TODO: somehow check that the protocol is correct for handle?
     if let Ok(addr_str) = unsafe { ptr_to_string(addr_ptr) } && let Ok(addr) = IpAddress::from_str(&addr_str) {
@@ -61,7 +64,7 @@ pub unsafe fn sys_sock_bind(
 }
 
 pub unsafe fn sys_sock_accept(
-    handle: SocketHandle,
+    handle: SocketId,
     protocol: SocketType,
     addr_buf: *mut u8,
 ) -> isize {
@@ -86,7 +89,7 @@ pub unsafe fn sys_sock_accept(
 }
 
 pub unsafe fn sys_sock_connect(
-    handle: SocketHandle,
+    handle: SocketId,
     protocol: SocketType,
     remote_addr_ptr: *const u8,
     port: u16,
@@ -117,7 +120,7 @@ pub unsafe fn sys_sock_connect(
 }
 
 pub unsafe fn sys_sock_send(
-    handle: SocketHandle,
+    handle: SocketId,
     protocol: SocketType,
     data: *const u8,
     len: usize,
@@ -164,7 +167,7 @@ pub unsafe fn sys_sock_send(
 }
 
 pub unsafe fn sys_sock_receive(
-    handle: SocketHandle,
+    handle: SocketId,
     protocol: SocketType,
     data_ptr: *mut u8,
     data_len: usize,
@@ -228,7 +231,7 @@ pub unsafe fn sys_sock_receive(
     }
 }
 
-pub fn sys_sock_close(handle: SocketHandle) -> isize {
+pub fn sys_sock_close(handle: SocketId) -> isize {
     info!("closing {handle} socket");
     close_socket(handle);
     0
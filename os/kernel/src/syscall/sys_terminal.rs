@@ -9,24 +9,23 @@
 use core::slice::from_raw_parts;
 use core::slice::from_raw_parts_mut;
 use log::error;
-use syscall::return_vals::Errno;
+use syscall::return_vals::{self, Errno};
 use terminal::{TerminalInputState, TerminalMode};
 
 use crate::device::tty::TtyInputState;
+use crate::naming::{api, poll};
 use crate::{tty_input, tty_output};
 
-/// SystemCall implementation for SystemCall::TerminalWriteOutput.
-/// Used by applications to write output in the terminal.
-///
-/// Author: Sebastian Keller
-pub fn sys_terminal_write_output(address: *const u8, length: usize) -> isize {
-    if address.is_null() {
-        error!("Output buffer must not be null");
+/// SystemCall implementation for SystemCall::TerminalRead.
+/// Used by applications to read from the object `fh` (usually stdin), using `mode`, if it is the terminal.
+pub fn sys_terminal_read(fh: usize, address: *mut u8, length: usize, mode: usize) -> isize {
+    if address.is_null() || length == 0 {
+        error!("Input buffer must not be null");
         return Errno::EINVAL as isize;
     }
 
-    let bytes = unsafe { from_raw_parts(address, length) };
-    tty_output().write(bytes) as isize
+    let buffer = unsafe { from_raw_parts_mut(address, length) };
+    return_vals::convert_syscall_result_to_ret_code(api::read_terminal(fh, buffer, TerminalMode::from(mode)))
 }
 
 /// SystemCall implementation for SystemCall::TerminalReadOutput.
@@ -55,22 +54,11 @@ pub fn sys_terminal_write_input(address: *mut u8, length: usize, mode: usize) ->
 
     let mode = TerminalMode::from(mode);
     let bytes = unsafe { from_raw_parts(address, length) };
-    tty_input().write(bytes, mode) as isize
-}
-
-/// SystemCall implementation for SystemCall::TerminalReadInput.
-/// Used by applications to read input from the terminal.
-///
-/// Author: Sebastian Keller
-pub fn sys_terminal_read_input(address: *mut u8, length: usize, mode: usize) -> isize {
-    if address.is_null() {
-        error!("Input buffer must not be null");
-        return Errno::EINVAL as isize;
+    let count = tty_input().write(bytes, mode);
+    if count > 0 {
+        poll::notify(); // applications may wait for input in `poll`
     }
-
-    let mode = TerminalMode::from(mode);
-    let buffer = unsafe { from_raw_parts_mut(address, length) };
-    tty_input().read(buffer, mode) as isize
+    count as isize
 }
 
 /// SystemCall implementation for SystemCall::TerminalCheckInputState.
@@ -7,11 +7,9 @@
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 
-use alloc::format;
-use alloc::string::ToString;
-use chrono::{DateTime, Datelike, TimeDelta, Timelike};
+use chrono::{DateTime, Datelike, Timelike};
 use uefi::runtime::{Time, TimeParams};
-use crate::{efi_services_available, timer};
+use crate::timer;
 
 
 pub extern "sysv64" fn sys_get_system_time() -> isize {
@@ -19,34 +17,7 @@ pub extern "sysv64" fn sys_get_system_time() -> isize {
 }
 
 pub extern "sysv64" fn sys_get_date() -> isize {
-    if !efi_services_available() {
-        return 0;
-    }
-    
-    match uefi::runtime::get_time() {
-        Ok(time) => {
-            if time.is_valid().is_ok() {
-                let timezone = match time.time_zone() {
-                    Some(timezone) => {
-                        let delta = TimeDelta::try_minutes(timezone as i64).expect("Failed to create TimeDelta struct from timezone");
-                        if timezone >= 0 {
-                            format!("+{:0>2}:{:0>2}", delta.num_hours(), delta.num_minutes() % 60)
-                        } else {
-                            format!("-{:0>2}:{:0>2}", delta.num_hours(), delta.num_minutes() % 60)
-                        }
-                    }
-                    None => "Z".to_string(),
-                };
-
-                DateTime::parse_from_rfc3339(format!("{}-{:0>2}-{:0>2}T{:0>2}:{:0>2}:{:0>2}.{:0>9}{}", time.year(), time.month(), time.day(), time.hour(), time.minute(), time.second(), time.nanosecond(), timezone).as_str())
-                    .expect("Failed to parse date from EFI runtime services")
-                    .timestamp_millis() as isize
-            } else {
-                0
-            }
-        }
-        Err(_) => 0
-    }
+    timer().date_ms() as isize
 }
 
 pub extern "sysv64" fn sys_set_date(date_ms: usize) -> isize {
@@ -64,7 +35,10 @@ pub extern "sysv64" fn sys_set_date(date_ms: usize) -> isize {
     }).expect("Failed to create EFI date");
 
     match unsafe { uefi::runtime::set_time(&uefi_date) } {
-        Ok(_) => true as isize,
+        Ok(_) => {
+            timer().set_date_ms(date_ms as u64);
+            true as isize
+        }
         Err(_) => false as isize,
     }
 }
@@ -6,15 +6,19 @@
    ║ Author: Fabian Ruhland & Michael Schoettner, 24.5.2025, HHU             ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
+use core::ptr;
 use multiboot2::FramebufferTag;
 use spin::once::Once;
 use x86_64::structures::paging::frame::PhysFrameRange;
+use x86_64::structures::paging::page::PageRange;
 use x86_64::{PhysAddr, VirtAddr};
 use x86_64::structures::paging::{Page, PageTableFlags, PhysFrame};
 use graphic::lfb::FramebufferInfo;
+use crate::memory::{frames, tlb};
 use crate::memory::vma::VmaType;
 use crate::memory::{MemorySpace, PAGE_SIZE};
-use crate::process_manager;
+use crate::{cpu, process_manager};
+use syscall::memory::Protection;
 use syscall::return_vals::Errno;
 
 static FB_INFO: Once<FramebufferInfo> = Once::new();
@@ -58,6 +62,96 @@ pub extern "sysv64" fn sys_map_memory(start: usize, size: usize) -> isize {
     }
 }
 
+/// Map `size` bytes of zeroed memory with the access rights `protection` (see [`Protection`])
+/// into a free range of the calling process's address space. \
+/// In contrast to `sys_map_memory`, all frames are allocated immediately. Returns the start address of the mapping.
+pub extern "sysv64" fn sys_mmap(size: usize, protection: usize) -> isize {
+    let Some(protection) = Protection::from_bits(protection) else {
+        return Errno::EINVAL.into();
+    };
+    if size == 0 {
+        return Errno::EINVAL.into();
+    }
+
+    // Refuse requests which cannot be satisfied, as the frame allocator panics if it runs out of memory
+    let num_pages = size.div_ceil(PAGE_SIZE);
+    if num_pages >= frames::free_frame_count() {
+        return Errno::ENOMEM.into();
+    }
+
+    let process = process_manager().read().current_process();
+    let Some(vma) = process.virtual_address_space.user_alloc_map_full(None, num_pages as u64, VmaType::Anonymous, "anonymous") else {
+        return Errno::ENOMEM.into();
+    };
+
+    // The mapping is writable and belongs to the calling process, so it can be cleared before applying `protection`
+    unsafe { ptr::write_bytes(vma.start().as_mut_ptr::<u8>(), 0, num_pages * PAGE_SIZE) };
+    process.virtual_address_space.set_flags(vma.range(), protection_flags(protection));
+    tlb::shootdown(process.virtual_address_space.page_table_address(), vma.range());
+
+    vma.start().as_u64() as isize
+}
+
+/// Unmap `size` bytes starting at `address` (page aligned) and free the frames.
+/// The range must have been mapped by `sys_mmap` (possibly by several calls), but may cover parts of mappings only.
+pub extern "sysv64" fn sys_munmap(address: usize, size: usize) -> isize {
+    let Some(pages) = user_pages(address, size) else {
+        return Errno::EINVAL.into();
+    };
+
+    let process = process_manager().read().current_process();
+    if !process.virtual_address_space.free_vma_range(pages, VmaType::Anonymous) {
+        return Errno::EINVAL.into();
+    }
+
+    0
+}
+
+/// Change the access rights of `size` bytes starting at `address` (page aligned) to `protection` (see [`Protection`]).
+/// The range must have been mapped by `sys_mmap`.
+pub extern "sysv64" fn sys_mprotect(address: usize, size: usize, protection: usize) -> isize {
+    let (Some(pages), Some(protection)) = (user_pages(address, size), Protection::from_bits(protection)) else {
+        return Errno::EINVAL.into();
+    };
+
+    let process = process_manager().read().current_process();
+    if !process.virtual_address_space.set_vma_range_flags(pages, VmaType::Anonymous, protection_flags(protection)) {
+        return Errno::EINVAL.into();
+    }
+
+    0
+}
+
+/// Return the pages covering `size` bytes starting at `address`, if `address` is page aligned and `size` is not 0
+fn user_pages(address: usize, size: usize) -> Option<PageRange> {
+    if size == 0 {
+        return None;
+    }
+
+    let start = Page::from_start_address(VirtAddr::try_new(address as u64).ok()?).ok()?;
+    let end = (address as u64).checked_add((size.div_ceil(PAGE_SIZE) * PAGE_SIZE) as u64)?;
+    Some(PageRange { start, end: Page::containing_address(VirtAddr::try_new(end).ok()?) })
+}
+
+/// Convert `protection` to page table flags for user space pages. Without any access rights, the pages are not present.
+/// Executing code can only be forbidden, if the cpu supports the no-execute bit.
+fn protection_flags(protection: Protection) -> PageTableFlags {
+    let mut flags = PageTableFlags::USER_ACCESSIBLE;
+    if protection.is_empty() {
+        return flags;
+    }
+
+    flags |= PageTableFlags::PRESENT;
+    if protection.contains(Protection::WRITE) {
+        flags |= PageTableFlags::WRITABLE;
+    }
+    if !protection.contains(Protection::EXEC) && cpu().supports_no_execute() {
+        flags |= PageTableFlags::NO_EXECUTE;
+    }
+
+    flags
+}
+
 pub extern "sysv64" fn sys_map_frame_buffer(fb_info_user: *mut FramebufferInfo) -> isize {
     let process = process_manager().read().current_process();
 
@@ -12,7 +12,7 @@ use core::ops::Deref;
 use core::ptr;
 use syscall::NUM_SYSCALLS;
 use x86_64::registers::control::{Efer, EferFlags};
-use x86_64::registers::model_specific::{KernelGsBase, LStar, SFMask, Star};
+use x86_64::registers::model_specific::{LStar, SFMask, Star};
 use x86_64::structures::gdt::SegmentSelector;
 use x86_64::{PrivilegeLevel, VirtAddr};
 
@@ -21,17 +21,20 @@ use log::info;
 use x86_64::registers::rflags::RFlags;
 
 use super::sys_concurrent::{
-    sys_process_count, sys_process_execute_binary, sys_process_exit,
-    sys_process_id, sys_thread_count, sys_thread_create, sys_thread_exit,
-    sys_thread_id, sys_thread_join, sys_thread_kill, sys_thread_sleep,
-    sys_thread_switch,
+    sys_futex_wait, sys_futex_wake, sys_process_count, sys_process_execute_binary, sys_process_exit,
+    sys_process_id, sys_process_set_env, sys_process_wait, sys_thread_count,
+    sys_thread_cpu_time, sys_thread_create, sys_thread_exit, sys_thread_get_priority,
+    sys_thread_id, sys_thread_join, sys_thread_kill,
+    sys_thread_set_priority, sys_thread_sleep, sys_thread_switch,
 };
 use super::sys_graphic::{sys_get_graphic_resolution, sys_write_graphic};
 use super::sys_input::{sys_read_keyboard, sys_read_mouse};
 use super::sys_logger::sys_log;
 use super::sys_naming::{
-    sys_close, sys_cd, sys_cwd, sys_mkdir, sys_mkfifo, sys_open, sys_read,
-    sys_readdir, sys_seek, sys_touch, sys_write,
+    sys_close, sys_cd, sys_cwd, sys_dup, sys_dup2, sys_fstat, sys_mkdir,
+    sys_mkfifo, sys_mount, sys_open, sys_poll, sys_read, sys_readdir,
+    sys_readlink, sys_rename, sys_rmdir, sys_seek, sys_stat, sys_symlink,
+    sys_sync, sys_touch, sys_umount, sys_unlink, sys_write,
 };
 use super::sys_net::{
     sys_sock_accept, sys_sock_bind, sys_sock_close, sys_sock_connect,
@@ -39,12 +42,11 @@ use super::sys_net::{
 };
 use super::sys_system_info::sys_map_build_info;
 use super::sys_terminal::{
-    sys_terminal_check_input_state, sys_terminal_read_input,
+    sys_terminal_check_input_state, sys_terminal_read,
     sys_terminal_read_output, sys_terminal_write_input,
-    sys_terminal_write_output,
 };
 use super::sys_time::{sys_get_date, sys_get_system_time, sys_set_date};
-use super::sys_vmem::{sys_map_memory, sys_map_frame_buffer};
+use super::sys_vmem::{sys_map_memory, sys_map_frame_buffer, sys_mmap, sys_mprotect, sys_munmap};
 
 pub const CORE_LOCAL_STORAGE_TSS_RSP0_PTR_INDEX: u64 = 0x00;
 pub const CORE_LOCAL_STORAGE_USER_RSP_INDEX: u64 = 0x08;
@@ -53,6 +55,7 @@ pub const CORE_LOCAL_STORAGE_USER_RSP_INDEX: u64 = 0x08;
 pub struct CoreLocalStorage {
     tss_rsp0_ptr: VirtAddr,
     user_rsp: VirtAddr,
+    core_id: usize,
 }
 
 impl CoreLocalStorage {
@@ -60,10 +63,20 @@ impl CoreLocalStorage {
         Self {
             tss_rsp0_ptr: VirtAddr::zero(),
             user_rsp: VirtAddr::zero(),
+            core_id: 0,
         }
     }
+
+    pub fn core_id(&self) -> usize {
+        self.core_id
+    }
+
+    pub fn set_core_id(&mut self, core_id: usize) {
+        self.core_id = core_id;
+    }
 }
 
+/// Initialize system calls on the calling core (the registers are core local)
 pub fn init() {
     info!("Initializing system calls");
 
@@ -91,13 +104,10 @@ pub fn init() {
     // The CPU clears every flag that is set in the SFMask register
     SFMask::write(RFlags::INTERRUPT_FLAG);
 
-    // Initialize core local storage (accessible via 'swapgs')
+    // Initialize core local storage (accessible via 'swapgs', the kernel gs base has been set up by 'init_core_local_storage()')
     let mut core_local_storage = core_local_storage().lock();
     core_local_storage.tss_rsp0_ptr =
         VirtAddr::new(ptr::from_ref(tss().lock().deref()) as u64 + size_of::<u32>() as u64);
-    KernelGsBase::write(VirtAddr::new(
-        ptr::from_ref(core_local_storage.deref()) as u64
-    ));
 }
 
 #[unsafe(no_mangle)]
@@ -112,10 +122,9 @@ impl SyscallTable {
     pub const fn new() -> Self {
         SyscallTable {
             handle: [
-                sys_terminal_read_input as *const _,
+                sys_terminal_read as *const _,
                 sys_terminal_write_input as *const _,
                 sys_terminal_check_input_state as *const _,
-                sys_terminal_write_output as *const _,
                 sys_terminal_read_output as *const _,
                 sys_map_memory as *const _,
                 sys_map_frame_buffer as *const _,
@@ -159,6 +168,29 @@ impl SyscallTable {
                 sys_read_keyboard as *const _,
                 sys_map_build_info as *const _,
                 sys_log as *const _,
+                sys_mount as *const _,
+                sys_umount as *const _,
+                sys_unlink as *const _,
+                sys_rmdir as *const _,
+                sys_rename as *const _,
+                sys_dup as *const _,
+                sys_dup2 as *const _,
+                sys_process_wait as *const _,
+                sys_process_set_env as *const _,
+                sys_symlink as *const _,
+                sys_readlink as *const _,
+                sys_stat as *const _,
+                sys_fstat as *const _,
+                sys_poll as *const _,
+                sys_sync as *const _,
+                sys_thread_get_priority as *const _,
+                sys_thread_set_priority as *const _,
+                sys_thread_cpu_time as *const _,
+                sys_futex_wait as *const _,
+                sys_futex_wake as *const _,
+                sys_mmap as *const _,
+                sys_munmap as *const _,
+                sys_mprotect as *const _,
             ],
         }
     }
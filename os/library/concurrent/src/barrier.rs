@@ -0,0 +1,63 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: barrier                                                         ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Barrier blocking threads until a given number of threads has    ║
+   ║         reached it. The barrier can be reused afterwards.               ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use crate::condvar::Condvar;
+use crate::mutex::Mutex;
+
+struct BarrierState {
+    arrived: usize,    // threads waiting in the current generation
+    generation: usize, // incremented each time all threads have arrived
+}
+
+pub struct Barrier {
+    state: Mutex<BarrierState>,
+    all_arrived: Condvar,
+    count: usize,
+}
+
+impl Barrier {
+    /// Create a barrier for `count` threads
+    pub const fn new(count: usize) -> Self {
+        Self {
+            state: Mutex::new(BarrierState { arrived: 0, generation: 0 }),
+            all_arrived: Condvar::new(),
+            count,
+        }
+    }
+
+    /// Block until `count` threads have called `wait()`.
+    /// Returns `true` for exactly one of them (the last one to arrive).
+    pub fn wait(&self) -> bool {
+        let mut state = self.state.lock();
+        state.arrived += 1;
+        if state.arrived >= self.count {
+            state.arrived = 0;
+            state.generation = state.generation.wrapping_add(1);
+            self.all_arrived.notify_all();
+            return true;
+        }
+
+        let generation = state.generation;
+        drop(self.all_arrived.wait_while(state, |state| state.generation == generation));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_thread() {
+        // A barrier for one thread never blocks and can be reused
+        let barrier = Barrier::new(1);
+        assert!(barrier.wait());
+        assert!(barrier.wait());
+    }
+}
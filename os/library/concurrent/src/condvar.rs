@@ -0,0 +1,105 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: condvar                                                         ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Condition variable for use with `Mutex`. The futex word is a    ║
+   ║         sequence number, incremented by each notification. Thus, a      ║
+   ║         notification between unlocking the mutex and blocking is not    ║
+   ║         lost. Like with any condition variable, the condition must be   ║
+   ║         re-checked after waking up (or use `wait_while()`). The kernel  ║
+   ║         is only called for notifying, if threads are waiting.           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering::{Relaxed, SeqCst};
+use core::time::Duration;
+use crate::futex;
+use crate::mutex::MutexGuard;
+
+pub struct Condvar {
+    sequence: AtomicU32,
+    waiters: AtomicU32, // number of threads (about to be) blocked on `sequence`
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self { sequence: AtomicU32::new(0), waiters: AtomicU32::new(0) }
+    }
+
+    /// Unlock the mutex of `guard` and block until notified. The mutex is locked again before returning.
+    pub fn wait<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        // Registering as waiter before unlocking ensures, that the notifying thread sees us
+        self.waiters.fetch_add(1, SeqCst);
+        let sequence = self.sequence.load(SeqCst);
+        let mutex = guard.mutex;
+        drop(guard);
+
+        futex::wait(&self.sequence, sequence);
+        self.waiters.fetch_sub(1, Relaxed);
+        mutex.lock()
+    }
+
+    /// Block (like `wait()`), as long as `condition` returns `true` for the protected data
+    pub fn wait_while<'a, T: ?Sized, F>(&self, mut guard: MutexGuard<'a, T>, mut condition: F) -> MutexGuard<'a, T>
+    where F: FnMut(&mut T) -> bool {
+        while condition(&mut *guard) {
+            guard = self.wait(guard);
+        }
+
+        guard
+    }
+
+    /// Like `wait()`, but gives up after `timeout`. The returned flag is `true`, if the timeout has passed.
+    pub fn wait_timeout<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>, timeout: Duration) -> (MutexGuard<'a, T>, bool) {
+        self.waiters.fetch_add(1, SeqCst);
+        let sequence = self.sequence.load(SeqCst);
+        let mutex = guard.mutex;
+        drop(guard);
+
+        let notified = futex::wait_timeout(&self.sequence, sequence, timeout);
+        self.waiters.fetch_sub(1, Relaxed);
+        (mutex.lock(), !notified)
+    }
+
+    /// Wake up one waiting thread (if any)
+    pub fn notify_one(&self) {
+        self.sequence.fetch_add(1, SeqCst);
+        if self.waiters.load(SeqCst) > 0 {
+            futex::wake_one(&self.sequence);
+        }
+    }
+
+    /// Wake up all waiting threads
+    pub fn notify_all(&self) {
+        self.sequence.fetch_add(1, SeqCst);
+        if self.waiters.load(SeqCst) > 0 {
+            futex::wake_all(&self.sequence);
+        }
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mutex::Mutex;
+    use super::*;
+
+    #[test]
+    fn test_wait_while_condition_met() {
+        let mutex = Mutex::new(true);
+        let condvar = Condvar::new();
+
+        // Notifying without waiters must not block or fail
+        condvar.notify_one();
+        condvar.notify_all();
+
+        let guard = condvar.wait_while(mutex.lock(), |ready| !*ready);
+        assert!(*guard);
+    }
+}
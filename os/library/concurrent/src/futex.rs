@@ -0,0 +1,43 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: futex                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Syscalls for futexes (32-bit words, threads can wait on until   ║
+   ║         they are woken up by another thread). Futexes are private to    ║
+   ║         a process and used to build the blocking synchronization        ║
+   ║         primitives of this crate.                                       ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use core::sync::atomic::AtomicU32;
+use core::time::Duration;
+use syscall::return_vals::Errno;
+use syscall::{syscall, SystemCall};
+
+/// Block the calling thread, if `word` contains `expected`, until it is woken up by `wake()`.
+/// Returns immediately, if `word` does not contain `expected`. The caller must check `word` again after returning.
+pub fn wait(word: &AtomicU32, expected: u32) {
+    let _ = syscall(SystemCall::FutexWait, &[word.as_ptr() as usize, expected as usize, 0]);
+}
+
+/// Like `wait()`, but gives up after `timeout`. Returns `false` on timeout.
+pub fn wait_timeout(word: &AtomicU32, expected: u32, timeout: Duration) -> bool {
+    // A timeout of 0 means no timeout for the syscall
+    let timeout_ms = timeout.as_millis().clamp(1, usize::MAX as u128) as usize;
+    syscall(SystemCall::FutexWait, &[word.as_ptr() as usize, expected as usize, timeout_ms]) != Err(Errno::ETIMEDOUT)
+}
+
+/// Wake up to `count` threads waiting on `word`. Returns the number of woken threads.
+pub fn wake(word: &AtomicU32, count: usize) -> usize {
+    syscall(SystemCall::FutexWake, &[word.as_ptr() as usize, count]).unwrap_or(0)
+}
+
+/// Wake up one thread waiting on `word`. Returns `true`, if a thread has been woken up.
+pub fn wake_one(word: &AtomicU32) -> bool {
+    wake(word, 1) == 1
+}
+
+/// Wake up all threads waiting on `word`. Returns the number of woken threads.
+pub fn wake_all(word: &AtomicU32) -> usize {
+    wake(word, usize::MAX)
+}
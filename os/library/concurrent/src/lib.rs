@@ -3,4 +3,11 @@
 extern crate alloc;
 
 pub mod process;
-pub mod thread;
\ No newline at end of file
+pub mod thread;
+pub mod futex;
+pub mod mutex;
+pub mod rwlock;
+pub mod condvar;
+pub mod semaphore;
+pub mod barrier;
+pub mod mpsc;
@@ -0,0 +1,195 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: mpsc                                                            ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Unbounded multi-producer, single-consumer channel. Receiving    ║
+   ║         blocks until a message is available. Sending fails, if the      ║
+   ║         receiver has been dropped, and receiving fails, if the channel  ║
+   ║         is empty and all senders have been dropped.                     ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use core::time::Duration;
+use crate::condvar::Condvar;
+use crate::mutex::Mutex;
+
+struct ChannelState<T> {
+    messages: VecDeque<T>,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+struct Channel<T> {
+    state: Mutex<ChannelState<T>>,
+    available: Condvar, // notified, when a message is sent or the last sender is dropped
+}
+
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+/// Returned by `Sender::send()`, if the receiver has been dropped (contains the message)
+#[derive(Debug, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+/// Returned by `Receiver::recv()`, if the channel is empty and all senders have been dropped
+#[derive(Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+/// Create a new channel and return its sending and receiving half
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Channel {
+        state: Mutex::new(ChannelState { messages: VecDeque::new(), senders: 1, receiver_alive: true }),
+        available: Condvar::new(),
+    });
+
+    (Sender { channel: Arc::clone(&channel) }, Receiver { channel })
+}
+
+impl<T> Sender<T> {
+    /// Append `message` to the channel (never blocks)
+    pub fn send(&self, message: T) -> Result<(), SendError<T>> {
+        let mut state = self.channel.state.lock();
+        if !state.receiver_alive {
+            return Err(SendError(message));
+        }
+
+        state.messages.push_back(message);
+        drop(state);
+        self.channel.available.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.state.lock().senders += 1;
+        Self { channel: Arc::clone(&self.channel) }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.channel.available.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Take the next message, blocking until one is available
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let state = self.channel.state.lock();
+        let mut state = self.channel.available.wait_while(state, |state| state.messages.is_empty() && state.senders > 0);
+        state.messages.pop_front().ok_or(RecvError)
+    }
+
+    /// Take the next message, if one is available (never blocks)
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.channel.state.lock();
+        match state.messages.pop_front() {
+            Some(message) => Ok(message),
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Like `recv()`, but gives up after `timeout`
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let mut state = self.channel.state.lock();
+        while state.messages.is_empty() && state.senders > 0 {
+            let (guard, timed_out) = self.channel.available.wait_timeout(state, timeout);
+            state = guard;
+            if timed_out {
+                break;
+            }
+        }
+
+        match state.messages.pop_front() {
+            Some(message) => Ok(message),
+            None if state.senders == 0 => Err(RecvTimeoutError::Disconnected),
+            None => Err(RecvTimeoutError::Timeout),
+        }
+    }
+
+    /// Iterate over received messages, until all senders have been dropped
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        core::iter::from_fn(|| self.recv().ok())
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.channel.state.lock().receiver_alive = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn test_send_recv() {
+        let (sender, receiver) = channel();
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(receiver.recv(), Ok(1));
+        assert_eq!(receiver.try_recv(), Ok(2));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_multiple_senders() {
+        let (sender, receiver) = channel();
+        let other = sender.clone();
+        sender.send(1).unwrap();
+        other.send(2).unwrap();
+        drop(sender);
+        other.send(3).unwrap();
+        drop(other);
+
+        assert_eq!(receiver.iter().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_disconnected() {
+        let (sender, receiver) = channel();
+        sender.send(1).unwrap();
+        drop(sender);
+
+        // Remaining messages are received before the disconnect is reported
+        assert_eq!(receiver.recv_timeout(Duration::from_millis(10)), Ok(1));
+        assert_eq!(receiver.recv(), Err(RecvError));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(receiver.recv_timeout(Duration::from_millis(10)), Err(RecvTimeoutError::Disconnected));
+    }
+
+    #[test]
+    fn test_receiver_dropped() {
+        let (sender, receiver) = channel();
+        drop(receiver);
+        assert_eq!(sender.send(1), Err(SendError(1)));
+    }
+}
@@ -0,0 +1,159 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: mutex                                                           ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Blocking mutex based on a futex. The futex word is 0 if the     ║
+   ║         mutex is unlocked, 1 if it is locked and 2 if it is locked and  ║
+   ║         there may be waiting threads. Only in the latter case, the      ║
+   ║         kernel is called for unlocking.                                 ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::futex;
+
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+const CONTENDED: u32 = 2;
+
+/// Number of attempts to get a locked mutex, before the thread is blocked
+const SPIN_LIMIT: usize = 100;
+
+pub struct Mutex<T: ?Sized> {
+    state: AtomicU32,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+/// Gives access to the data protected by the mutex and unlocks it, when dropped
+pub struct MutexGuard<'a, T: ?Sized> {
+    pub(crate) mutex: &'a Mutex<T>,
+}
+
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(data: T) -> Self {
+        Self { state: AtomicU32::new(UNLOCKED), data: UnsafeCell::new(data) }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Lock the mutex, blocking the calling thread until it is available
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        if self.state.compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed).is_err() {
+            self.lock_contended();
+        }
+
+        MutexGuard { mutex: self }
+    }
+
+    /// Lock the mutex, if it is available (never blocks)
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.state.compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed).ok()
+            .map(|_| MutexGuard { mutex: self })
+    }
+
+    /// Check if the mutex is currently locked
+    pub fn is_locked(&self) -> bool {
+        self.state.load(Relaxed) != UNLOCKED
+    }
+
+    /// Access the data without locking (the mutable reference guarantees exclusive access)
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    fn lock_contended(&self) {
+        // The lock is often held only shortly, so spin a little before calling the kernel
+        for _ in 0..SPIN_LIMIT {
+            if self.state.load(Relaxed) == UNLOCKED
+                && self.state.compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed).is_ok() {
+                return;
+            }
+            core::hint::spin_loop();
+        }
+
+        // Mark the mutex as contended, so that the current owner wakes us up when unlocking
+        while self.state.swap(CONTENDED, Acquire) != UNLOCKED {
+            futex::wait(&self.state, CONTENDED);
+        }
+    }
+
+    fn unlock(&self) {
+        if self.state.swap(UNLOCKED, Release) == CONTENDED {
+            futex::wake_one(&self.state);
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_unlock() {
+        let mutex = Mutex::new(1);
+        assert!(!mutex.is_locked());
+        {
+            let mut guard = mutex.lock();
+            *guard += 1;
+            assert!(mutex.is_locked());
+        }
+        assert!(!mutex.is_locked());
+        assert_eq!(mutex.into_inner(), 2);
+    }
+
+    #[test]
+    fn test_try_lock() {
+        let mutex = Mutex::new(0);
+        let guard = mutex.try_lock();
+        assert!(guard.is_some());
+        assert!(mutex.try_lock().is_none());
+
+        drop(guard);
+        assert!(mutex.try_lock().is_some());
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut mutex = Mutex::new(5);
+        *mutex.get_mut() = 7;
+        assert_eq!(*mutex.lock(), 7);
+    }
+}
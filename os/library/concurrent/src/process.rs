@@ -6,7 +6,18 @@
    ║ Author: Fabian Ruhland, Michael Schoettner, 31.8.2024, HHU              ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
-use syscall::{syscall, SystemCall};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ptr;
+use spin::Mutex;
+use syscall::{return_vals::Errno, syscall, SystemCall};
+
+/// Directories searched for applications by default (the initial ramdisk is at `/bin`)
+pub const DEFAULT_SEARCH_PATH: &str = "/bin";
+
+/// Directories searched by `start_application`, separated by `:` (`None` means `DEFAULT_SEARCH_PATH`)
+static SEARCH_PATH: Mutex<Option<String>> = Mutex::new(None);
 
 pub struct Process {
     id: usize,
@@ -20,6 +31,21 @@ impl Process {
     pub fn id(&self) -> usize {
         self.id
     }
+
+    /// Block until the process has terminated and return its exit code.
+    /// Only the parent may wait for a process (`ECHILD` otherwise) and the exit code can only be fetched once.
+    pub fn wait(&self) -> Result<usize, Errno> {
+        syscall(SystemCall::ProcessWait, &[self.id, 0])
+    }
+
+    /// Return the exit code, if the process has terminated, or `None` if it is still running
+    pub fn try_wait(&self) -> Result<Option<usize>, Errno> {
+        match syscall(SystemCall::ProcessWait, &[self.id, 1]) {
+            Ok(exit_code) => Ok(Some(exit_code)),
+            Err(Errno::EAGAIN) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 }
 
 pub fn current() -> Option<Process> {
@@ -30,8 +56,10 @@ pub fn current() -> Option<Process> {
     }    
 }
 
-pub fn exit() {
-    syscall(SystemCall::ProcessExit, &[]).expect("Failed to exit process");
+/// Terminate the calling process with `exit_code` (only the lowest 8 bits are kept)
+pub fn exit(exit_code: usize) -> ! {
+    let _ = syscall(SystemCall::ProcessExit, &[exit_code]);
+    panic!("System call 'ProcessExit' has returned!")
 }
 
 pub fn count() -> usize {
@@ -40,4 +68,39 @@ pub fn count() -> usize {
         Err(_) => 0,
     }
     
-}
\ No newline at end of file
+}
+
+/// Return the directories searched for applications, separated by `:`
+pub fn search_path() -> String {
+    SEARCH_PATH.lock().clone().unwrap_or_else(|| DEFAULT_SEARCH_PATH.to_string())
+}
+
+/// Set the directories searched for applications, separated by `:`
+pub fn set_search_path(search_path: &str) {
+    *SEARCH_PATH.lock() = Some(search_path.to_string());
+}
+
+/// Start the application `name` in a new process. \
+/// If `name` contains a `/`, it is the path of the executable (absolute or relative to the working directory).
+/// Otherwise the directories of the search path are tried in order.
+pub fn start_application(name: &str, args: Vec<&str>) -> Result<Process, Errno> {
+    if name.contains('/') {
+        return execute_binary(name, &args);
+    }
+
+    for dir in search_path().split(':').filter(|dir| !dir.is_empty()) {
+        let path = format!("{}/{}", dir.trim_end_matches('/'), name);
+        match execute_binary(&path, &args) {
+            Err(Errno::ENOENT) => continue,
+            result => return result,
+        }
+    }
+    Err(Errno::ENOENT)
+}
+
+fn execute_binary(path: &str, args: &Vec<&str>) -> Result<Process, Errno> {
+    let res = syscall(SystemCall::ProcessExecuteBinary, &[path.as_bytes().as_ptr() as usize,
+    path.len(),
+    ptr::from_ref(args) as usize,]);
+    res.map(Process::new)
+}
@@ -0,0 +1,199 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: rwlock                                                          ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Blocking reader-writer lock based on a futex. The futex word    ║
+   ║         holds the number of readers or `WRITE_LOCKED`. The kernel is    ║
+   ║         only called for unlocking, if threads are waiting. Readers are  ║
+   ║         preferred, so a steady stream of readers may starve writers.    ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering::{Acquire, Relaxed, SeqCst};
+use crate::futex;
+
+const UNLOCKED: u32 = 0;
+const WRITE_LOCKED: u32 = u32::MAX;
+
+pub struct RwLock<T: ?Sized> {
+    state: AtomicU32,
+    waiters: AtomicU32, // number of threads (about to be) blocked on `state`
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+/// Gives shared access to the data protected by the lock and unlocks it, when dropped
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+/// Gives exclusive access to the data protected by the lock and unlocks it, when dropped
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T> RwLock<T> {
+    pub const fn new(data: T) -> Self {
+        Self { state: AtomicU32::new(UNLOCKED), waiters: AtomicU32::new(0), data: UnsafeCell::new(data) }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Lock for reading, blocking the calling thread while a writer holds the lock
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_read() {
+                return guard;
+            }
+            self.wait_while_locked();
+        }
+    }
+
+    /// Lock for reading, if no writer holds the lock (never blocks)
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        let mut state = self.state.load(Relaxed);
+        while state < WRITE_LOCKED - 1 {
+            match self.state.compare_exchange_weak(state, state + 1, Acquire, Relaxed) {
+                Ok(_) => return Some(RwLockReadGuard { lock: self }),
+                Err(current) => state = current,
+            }
+        }
+
+        None
+    }
+
+    /// Lock for writing, blocking the calling thread while other threads hold the lock
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_write() {
+                return guard;
+            }
+            self.wait_while_locked();
+        }
+    }
+
+    /// Lock for writing, if no other thread holds the lock (never blocks)
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.state.compare_exchange(UNLOCKED, WRITE_LOCKED, Acquire, Relaxed).ok()
+            .map(|_| RwLockWriteGuard { lock: self })
+    }
+
+    /// Access the data without locking (the mutable reference guarantees exclusive access)
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Block until the state has changed (at the latest, when the lock becomes unlocked)
+    fn wait_while_locked(&self) {
+        // Registering as waiter before reading the state ensures, that the unlocking thread sees us
+        self.waiters.fetch_add(1, SeqCst);
+        let state = self.state.load(SeqCst);
+        if state != UNLOCKED {
+            futex::wait(&self.state, state);
+        }
+        self.waiters.fetch_sub(1, Relaxed);
+    }
+
+    fn wake_waiters(&self) {
+        if self.waiters.load(SeqCst) > 0 {
+            futex::wake_all(&self.state);
+        }
+    }
+
+    fn read_unlock(&self) {
+        if self.state.fetch_sub(1, SeqCst) == 1 {
+            self.wake_waiters();
+        }
+    }
+
+    fn write_unlock(&self) {
+        self.state.store(UNLOCKED, SeqCst);
+        self.wake_waiters();
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.read_unlock();
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.write_unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiple_readers() {
+        let lock = RwLock::new(1);
+        let first = lock.read();
+        let second = lock.try_read();
+        assert!(second.is_some());
+        assert_eq!(*first + *second.unwrap(), 2);
+        assert!(lock.try_write().is_none());
+    }
+
+    #[test]
+    fn test_writer_excludes_others() {
+        let lock = RwLock::new(1);
+        {
+            let mut guard = lock.write();
+            *guard = 2;
+            assert!(lock.try_read().is_none());
+            assert!(lock.try_write().is_none());
+        }
+        assert_eq!(*lock.read(), 2);
+    }
+
+    #[test]
+    fn test_unlock() {
+        let lock = RwLock::new(0);
+        drop(lock.read());
+        drop(lock.read());
+        assert!(lock.try_write().is_some());
+        assert!(lock.try_read().is_some());
+        assert_eq!(lock.into_inner(), 0);
+    }
+}
@@ -0,0 +1,100 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: semaphore                                                       ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Counting semaphore based on a futex holding the number of       ║
+   ║         available permits.                                              ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering::{Acquire, Relaxed, SeqCst};
+use core::time::Duration;
+use crate::futex;
+
+pub struct Semaphore {
+    permits: AtomicU32,
+    waiters: AtomicU32, // number of threads (about to be) blocked on `permits`
+}
+
+impl Semaphore {
+    pub const fn new(permits: u32) -> Self {
+        Self { permits: AtomicU32::new(permits), waiters: AtomicU32::new(0) }
+    }
+
+    /// Take a permit, blocking the calling thread until one is available
+    pub fn acquire(&self) {
+        while !self.try_acquire() {
+            self.waiters.fetch_add(1, SeqCst);
+            futex::wait(&self.permits, 0);
+            self.waiters.fetch_sub(1, Relaxed);
+        }
+    }
+
+    /// Like `acquire()`, but gives up after `timeout`. Returns `false` on timeout.
+    pub fn acquire_timeout(&self, timeout: Duration) -> bool {
+        while !self.try_acquire() {
+            self.waiters.fetch_add(1, SeqCst);
+            let notified = futex::wait_timeout(&self.permits, 0, timeout);
+            self.waiters.fetch_sub(1, Relaxed);
+            if !notified {
+                return self.try_acquire();
+            }
+        }
+
+        true
+    }
+
+    /// Take a permit, if one is available (never blocks)
+    pub fn try_acquire(&self) -> bool {
+        let mut permits = self.permits.load(Relaxed);
+        while permits > 0 {
+            match self.permits.compare_exchange_weak(permits, permits - 1, Acquire, Relaxed) {
+                Ok(_) => return true,
+                Err(current) => permits = current,
+            }
+        }
+
+        false
+    }
+
+    /// Return a permit and wake up a waiting thread (if any)
+    pub fn release(&self) {
+        self.permits.fetch_add(1, SeqCst);
+        if self.waiters.load(SeqCst) > 0 {
+            futex::wake_one(&self.permits);
+        }
+    }
+
+    /// Return the number of currently available permits
+    pub fn available_permits(&self) -> u32 {
+        self.permits.load(Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_release() {
+        let semaphore = Semaphore::new(2);
+        semaphore.acquire();
+        assert!(semaphore.try_acquire());
+        assert_eq!(semaphore.available_permits(), 0);
+        assert!(!semaphore.try_acquire());
+
+        semaphore.release();
+        assert_eq!(semaphore.available_permits(), 1);
+        assert!(semaphore.acquire_timeout(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_no_permits() {
+        let semaphore = Semaphore::new(0);
+        assert!(!semaphore.try_acquire());
+        semaphore.release();
+        semaphore.release();
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+}
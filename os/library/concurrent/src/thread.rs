@@ -6,10 +6,16 @@
    ║ Author: Fabian Ruhland, Michael Schoettner, 31.8.2024, HHU              ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
-use alloc::vec::Vec;
-use core::ptr;
+use core::time::Duration;
+use syscall::return_vals::Errno;
 use syscall::{syscall, SystemCall};
 
+/// Highest thread priority an application may set (0 is the lowest priority, must match the kernel).
+/// Higher priorities are reserved for the kernel.
+pub const MAX_PRIORITY: usize = 4;
+/// Priority of new threads
+pub const DEFAULT_PRIORITY: usize = 4;
+
 pub struct Thread {
     id: usize,
 }
@@ -30,6 +36,23 @@ impl Thread {
     pub fn kill(&self) {
         let _ = syscall(SystemCall::ThreadKill, &[self.id]);
     }
+
+    /// Get the priority of the thread (0 is the lowest, `MAX_PRIORITY` the highest priority)
+    pub fn priority(&self) -> Result<usize, Errno> {
+        syscall(SystemCall::ThreadGetPriority, &[self.id])
+    }
+
+    /// Set the priority of the thread (only possible for threads of the own process).
+    /// The thread is only preempted by threads with the same or a higher priority.
+    /// Priorities above `MAX_PRIORITY` fail with `EACCES`.
+    pub fn set_priority(&self, priority: usize) -> Result<(), Errno> {
+        syscall(SystemCall::ThreadSetPriority, &[self.id, priority]).map(|_| ())
+    }
+
+    /// Get the CPU time consumed by the thread so far
+    pub fn cpu_time(&self) -> Result<Duration, Errno> {
+        syscall(SystemCall::ThreadCpuTime, &[self.id]).map(|ns| Duration::from_nanos(ns as u64))
+    }
 }
 
 extern "sysv64" fn kickoff_user_thread(entry: extern "sysv64" fn()) {
@@ -77,13 +100,3 @@ pub fn count() -> usize {
     }
     
 }
-
-pub fn start_application(name: &str, args: Vec<&str>) -> Option<Thread> {
-    let res = syscall(SystemCall::ProcessExecuteBinary, &[name.as_bytes().as_ptr() as usize,
-    name.len(),
-    ptr::from_ref(&args) as usize,]);
-    match res {
-        Ok(id) => Some(Thread::new(id)),
-        Err(_) => None,
-    }    
-}
@@ -53,8 +53,28 @@ pub const APPLICATION_REGISTRY: &'static [Application] = &[
         single_value: &["ARG"],
         key_value_pair: &[],
     },
+    Application {
+        namespace: "env",
+        single_value: &[],
+        key_value_pair: &[],
+    },
     Application {
         namespace: "exit",
+        single_value: &["CODE"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "export",
+        single_value: &["KEY=VALUE"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "fg",
+        single_value: &["JOB"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "jobs",
         single_value: &[],
         key_value_pair: &[],
     },
@@ -63,16 +83,56 @@ pub const APPLICATION_REGISTRY: &'static [Application] = &[
         single_value: &["DIR"],
         key_value_pair: &[],
     },
+    Application {
+        namespace: "mount",
+        single_value: &["fat32", "tmpfs", "devfs", "procfs"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "ln",
+        single_value: &["-s", "TARGET", "LINK"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "mv",
+        single_value: &["SOURCE", "DEST"],
+        key_value_pair: &[],
+    },
     Application {
         namespace: "pwd",
         single_value: &[],
         key_value_pair: &[],
     },
+    Application {
+        namespace: "rm",
+        single_value: &["FILE"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "rmdir",
+        single_value: &["DIR"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "sync",
+        single_value: &[],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "umount",
+        single_value: &["DIR"],
+        key_value_pair: &[],
+    },
     Application {
         namespace: "unalias",
         single_value: &["'KEY'"],
         key_value_pair: &[],
     },
+    Application {
+        namespace: "wait",
+        single_value: &["JOB"],
+        key_value_pair: &[],
+    },
     Application {
         namespace: "theme",
         single_value: &["d3os", "plain", "debug"],
@@ -95,7 +155,7 @@ pub const APPLICATION_REGISTRY: &'static [Application] = &[
     },
     Application {
         namespace: "help",
-        single_value: &["controls", "tokens", "built-in-1", "built-in-2"],
+        single_value: &["controls", "tokens", "built-in-1", "built-in-2", "built-in-3", "built-in-4"],
         key_value_pair: &[],
     },
     //////////////////////
@@ -132,7 +192,17 @@ pub const APPLICATION_REGISTRY: &'static [Application] = &[
     },
     Application {
         namespace: "ls",
-        single_value: &[],
+        single_value: &["-l"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "cat",
+        single_value: &["FILE"],
+        key_value_pair: &[],
+    },
+    Application {
+        namespace: "grep",
+        single_value: &["PATTERN", "FILE"],
         key_value_pair: &[],
     },
     Application {
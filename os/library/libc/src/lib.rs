@@ -19,12 +19,18 @@ pub mod string;
 pub mod time;
 
 use core::ffi::c_char;
+use naming::shared_types::STDOUT;
 use syscall::{syscall, SystemCall};
 use crate::string::string::strlen;
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn terminal_write(buffer: *const c_char) {
-    let res = syscall(SystemCall::TerminalWriteOutput, &[buffer as usize, unsafe { strlen(buffer) }]);
+    let len = unsafe { strlen(buffer) };
+    if len == 0 {
+        return;
+    }
+
+    let res = syscall(SystemCall::Write, &[STDOUT, buffer as usize, len]);
     if res.is_err() {
         panic!("Error while writing to the terminal!");
     }
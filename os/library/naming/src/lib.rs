@@ -14,6 +14,7 @@ extern crate alloc;
 extern crate bitflags;
 
 pub mod shared_types;
+pub mod path;
 
 #[cfg(feature = "userspace")]
 use alloc::string::String;
@@ -23,7 +24,7 @@ use alloc::ffi::CString;
 use core::mem;
 
 #[cfg(feature = "userspace")]
-use shared_types::{DirEntry, FileType, OpenOptions, RawDirent, SeekOrigin};
+use shared_types::{DirEntry, FileType, Metadata, OpenOptions, PollFd, RawDirent, RawStat, SeekOrigin};
 #[cfg(feature = "userspace")]
 use syscall::{SystemCall, return_vals::Errno, syscall};
 
@@ -63,6 +64,16 @@ pub fn close(fh: usize) -> Result<usize, Errno> {
     syscall(SystemCall::Close, &[fh])
 }
 
+#[cfg(feature = "userspace")]
+pub fn dup(fh: usize) -> Result<usize, Errno> {
+    syscall(SystemCall::Dup, &[fh])
+}
+
+#[cfg(feature = "userspace")]
+pub fn dup2(fh: usize, new_fh: usize) -> Result<usize, Errno> {
+    syscall(SystemCall::Dup2, &[fh, new_fh])
+}
+
 #[cfg(feature = "userspace")]
 pub fn mkdir(path: &str) -> Result<usize, Errno> {
     match CString::new(path) {
@@ -97,14 +108,8 @@ pub fn readdir(fh: usize) -> Result<Option<DirEntry>, Errno> {
 #[cfg(feature = "userspace")]
 impl DirEntry {
     pub fn from_dirent(dirent: &RawDirent) -> Option<Self> {
-        // Convert d_type to a FileType enum
-        let file_type = match dirent.d_type {
-            1 => FileType::NamedPipe,
-            4 => FileType::Directory,
-            8 => FileType::Regular,
-            10 => FileType::Link,
-            _ => return None, // Return None for unsupported file types
-        };
+        // Convert d_type to a FileType enum (None for unsupported file types)
+        let file_type = file_type_from_raw(dirent.d_type)?;
 
         // Convert d_name (null-terminated) to a Rust String
         let name = dirent
@@ -123,6 +128,49 @@ impl DirEntry {
     }
 }
 
+#[cfg(feature = "userspace")]
+pub fn stat(path: &str) -> Result<Metadata, Errno> {
+    let c_path = CString::new(path).map_err(|_| Errno::EBADSTR)?;
+    let mut raw_stat = RawStat::new();
+    syscall(SystemCall::Stat, &[
+        c_path.as_bytes().as_ptr() as usize,
+        raw_stat.as_mut_ptr() as usize,
+        mem::size_of::<RawStat>(),
+    ])?;
+    Metadata::from_raw_stat(&raw_stat).ok_or(Errno::EUNKN)
+}
+
+#[cfg(feature = "userspace")]
+pub fn fstat(fh: usize) -> Result<Metadata, Errno> {
+    let mut raw_stat = RawStat::new();
+    syscall(SystemCall::Fstat, &[fh, raw_stat.as_mut_ptr() as usize, mem::size_of::<RawStat>()])?;
+    Metadata::from_raw_stat(&raw_stat).ok_or(Errno::EUNKN)
+}
+
+#[cfg(feature = "userspace")]
+impl Metadata {
+    pub fn from_raw_stat(raw_stat: &RawStat) -> Option<Self> {
+        Some(Metadata {
+            file_type: file_type_from_raw(raw_stat.st_type)?,
+            size: raw_stat.st_size,
+            created_time: raw_stat.st_ctime,
+            modified_time: raw_stat.st_mtime,
+            accessed_time: raw_stat.st_atime,
+        })
+    }
+}
+
+#[cfg(feature = "userspace")]
+fn file_type_from_raw(raw_type: usize) -> Option<FileType> {
+    match raw_type {
+        1 => Some(FileType::NamedPipe),
+        4 => Some(FileType::Directory),
+        8 => Some(FileType::Regular),
+        10 => Some(FileType::Link),
+        _ => None,
+    }
+}
+
 #[cfg(feature = "userspace")]
 pub fn cwd() -> Result<String, Errno> {
     let buf: [u8; 512] = [0; 512]; // buffer for the path
@@ -156,3 +204,87 @@ pub fn mkfifo(path: &str) -> Result<usize, Errno> {
         Err(_) => Err(Errno::EBADSTR),
     }
 }
+
+#[cfg(feature = "userspace")]
+pub fn mount(source: &str, target: &str, fs_type: &str) -> Result<usize, Errno> {
+    match (CString::new(source), CString::new(target), CString::new(fs_type)) {
+        (Ok(c_source), Ok(c_target), Ok(c_fs_type)) => syscall(SystemCall::Mount, &[
+            c_source.as_bytes().as_ptr() as usize,
+            c_target.as_bytes().as_ptr() as usize,
+            c_fs_type.as_bytes().as_ptr() as usize,
+        ]),
+        _ => Err(Errno::EBADSTR),
+    }
+}
+
+#[cfg(feature = "userspace")]
+pub fn umount(target: &str) -> Result<usize, Errno> {
+    match CString::new(target) {
+        Ok(c_target) => syscall(SystemCall::Umount, &[c_target.as_bytes().as_ptr() as usize]),
+        Err(_) => Err(Errno::EBADSTR),
+    }
+}
+
+#[cfg(feature = "userspace")]
+pub fn unlink(path: &str) -> Result<usize, Errno> {
+    match CString::new(path) {
+        Ok(c_path) => syscall(SystemCall::Unlink, &[c_path.as_bytes().as_ptr() as usize]),
+        Err(_) => Err(Errno::EBADSTR),
+    }
+}
+
+#[cfg(feature = "userspace")]
+pub fn rmdir(path: &str) -> Result<usize, Errno> {
+    match CString::new(path) {
+        Ok(c_path) => syscall(SystemCall::Rmdir, &[c_path.as_bytes().as_ptr() as usize]),
+        Err(_) => Err(Errno::EBADSTR),
+    }
+}
+
+#[cfg(feature = "userspace")]
+pub fn rename(old_path: &str, new_path: &str) -> Result<usize, Errno> {
+    match (CString::new(old_path), CString::new(new_path)) {
+        (Ok(c_old_path), Ok(c_new_path)) => syscall(SystemCall::Rename, &[
+            c_old_path.as_bytes().as_ptr() as usize,
+            c_new_path.as_bytes().as_ptr() as usize,
+        ]),
+        _ => Err(Errno::EBADSTR),
+    }
+}
+
+#[cfg(feature = "userspace")]
+pub fn symlink(target: &str, link_path: &str) -> Result<usize, Errno> {
+    match (CString::new(target), CString::new(link_path)) {
+        (Ok(c_target), Ok(c_link_path)) => syscall(SystemCall::Symlink, &[
+            c_target.as_bytes().as_ptr() as usize,
+            c_link_path.as_bytes().as_ptr() as usize,
+        ]),
+        _ => Err(Errno::EBADSTR),
+    }
+}
+
+#[cfg(feature = "userspace")]
+pub fn readlink(path: &str) -> Result<String, Errno> {
+    let c_path = CString::new(path).map_err(|_| Errno::EBADSTR)?;
+    let mut buf = [0u8; 512];
+    let len = syscall(SystemCall::Readlink, &[
+        c_path.as_bytes().as_ptr() as usize,
+        buf.as_mut_ptr() as usize,
+        buf.len(),
+    ])?;
+    String::from_utf8(buf[..len].to_vec()).map_err(|_| Errno::EBADSTR)
+}
+
+#[cfg(feature = "userspace")]
+pub fn poll(fds: &mut [PollFd], timeout_ms: Option<usize>) -> Result<usize, Errno> {
+    syscall(SystemCall::Poll, &[
+        fds.as_mut_ptr() as usize,
+        fds.len(),
+        timeout_ms.unwrap_or(usize::MAX),
+    ])
+}
+
+#[cfg(feature = "userspace")]
+pub fn sync() -> Result<usize, Errno> {
+    syscall(SystemCall::Sync, &[])
+}
@@ -0,0 +1,60 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: path                                                            ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Path handling shared by the kernel and user space.              ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Return `path` as canonical absolute path, a relative `path` is resolved against the absolute directory `cwd`. \
+/// Repeated slashes and `.` components are removed and `..` removes the preceding component (symbolic links are not resolved).
+pub fn normalize(cwd: &str, path: &str) -> String {
+    let cwd = if path.starts_with('/') { "" } else { cwd };
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in cwd.split('/').chain(path.split('/')) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            name => components.push(name),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_absolute_path() {
+        assert_eq!(normalize("/home", "/"), "/");
+        assert_eq!(normalize("/home", "/bin/ls"), "/bin/ls");
+        assert_eq!(normalize("/home", "//bin///ls/"), "/bin/ls");
+        assert_eq!(normalize("/home", "/./bin/./ls"), "/bin/ls");
+    }
+
+    #[test]
+    fn test_normalize_relative_path() {
+        assert_eq!(normalize("/home/user", "docs"), "/home/user/docs");
+        assert_eq!(normalize("/home/user", "./docs/"), "/home/user/docs");
+        assert_eq!(normalize("/home/user", ""), "/home/user");
+        assert_eq!(normalize("/", "docs"), "/docs");
+    }
+
+    #[test]
+    fn test_normalize_parent_components() {
+        assert_eq!(normalize("/home/user", ".."), "/home");
+        assert_eq!(normalize("/home/user", "../other/./file"), "/home/other/file");
+        assert_eq!(normalize("/", "/a/b/../../c"), "/c");
+        // `..` does not go above the root directory
+        assert_eq!(normalize("/home", "../../.."), "/");
+        assert_eq!(normalize("/", "/../bin"), "/bin");
+    }
+}
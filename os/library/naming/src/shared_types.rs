@@ -10,17 +10,86 @@ use alloc::string::String;
 use num_enum::{FromPrimitive, IntoPrimitive};
 
 bitflags! {
-    /// Description: Option flags for opening objects
+    /// Description: Option flags for opening objects \
+    /// At most one of the access modes `READONLY`, `WRITEONLY` and `READWRITE` may be given.
     pub struct OpenOptions: usize {
-        const READONLY  = 1;
-        const READWRITE = 2;
-        const CREATE    = 3;
-        const EXCLUSIVE = 4;
-        const DIRECTORY = 5;
-        const WRITEONLY = 6; // relevant for pipes
+        const READONLY  = 1 << 0;
+        const WRITEONLY = 1 << 1;
+        const READWRITE = 1 << 2;
+        const CREATE    = 1 << 3; // create a file, if it does not exist
+        const EXCLUSIVE = 1 << 4; // together with `CREATE`: fail, if the file exists
+        const DIRECTORY = 1 << 5; // fail, if the object is not a directory
+        const APPEND    = 1 << 6; // every write appends to the end of the file
+        const TRUNCATE  = 1 << 7; // truncate a file opened for writing to length 0
+        const NONBLOCK  = 1 << 8; // reads and writes fail with `EAGAIN` instead of blocking
     }
 }
 
+impl OpenOptions {
+    /// Returns `true` if the object may be read
+    pub fn is_readable(&self) -> bool {
+        self.intersects(OpenOptions::READONLY | OpenOptions::READWRITE)
+    }
+
+    /// Returns `true` if the object may be written
+    pub fn is_writable(&self) -> bool {
+        self.intersects(OpenOptions::WRITEONLY | OpenOptions::READWRITE)
+    }
+}
+
+bitflags! {
+    /// Description: Events for `poll` \
+    /// `ERR`, `HUP` and `NVAL` are only reported and need not be requested.
+    pub struct PollEvents: u16 {
+        const IN   = 1 << 0; // data can be read without blocking
+        const OUT  = 1 << 1; // data can be written without blocking
+        const ERR  = 1 << 2; // error condition (e.g. no reader for a pipe)
+        const HUP  = 1 << 3; // other end has been closed
+        const NVAL = 1 << 4; // invalid handle
+    }
+}
+
+/// Description: kinds of handles, that can be polled
+#[derive(Debug, Copy, Clone, Eq, PartialEq, IntoPrimitive, FromPrimitive)]
+#[repr(u16)]
+pub enum PollKind {
+    #[num_enum(default)]
+    Object = 0, // handle of an opened named object
+    Socket = 1, // handle of a network socket
+}
+
+/// Description: entry passed to `poll` \
+/// `revents` is set by the kernel.
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct PollFd {
+    pub handle: usize,
+    pub kind: u16,    // PollKind
+    pub events: u16,  // requested PollEvents
+    pub revents: u16, // returned PollEvents
+}
+
+impl PollFd {
+    pub fn new(kind: PollKind, handle: usize, events: PollEvents) -> Self {
+        PollFd {
+            handle,
+            kind: kind.into(),
+            events: events.bits(),
+            revents: 0,
+        }
+    }
+
+    /// Events reported by the last `poll`
+    pub fn revents(&self) -> PollEvents {
+        PollEvents::from_bits_truncate(self.revents)
+    }
+}
+
+/// Handles of the standard streams, opened in every process
+pub const STDIN: usize = 0;
+pub const STDOUT: usize = 1;
+pub const STDERR: usize = 2;
+
 /// Description: origin for `seek` 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, IntoPrimitive, FromPrimitive)]
 #[repr(usize)]
@@ -72,3 +141,33 @@ impl RawDirent {
     }
 }
 
+/// Meta data of a named object, as returned by `stat` and `fstat`
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub file_type: FileType,
+    pub size: usize,        // size in bytes
+    pub created_time: u64,  // milliseconds since the Unix epoch (0 = unknown)
+    pub modified_time: u64, // milliseconds since the Unix epoch (0 = unknown)
+    pub accessed_time: u64, // milliseconds since the Unix epoch (0 = unknown)
+}
+
+/// Description: internally used for `stat` and `fstat` syscalls for passing data between kernel and user space
+#[derive(Debug, Default)]
+#[repr(C)]
+pub struct RawStat {
+    pub st_type: usize, // type of file
+    pub st_size: usize, // size in bytes
+    pub st_ctime: u64,  // creation time
+    pub st_mtime: u64,  // modification time
+    pub st_atime: u64,  // access time
+}
+
+impl RawStat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self as *mut RawStat as *mut u8
+    }
+}
@@ -6,6 +6,7 @@ extern crate alloc;
 use core::{ffi::CStr, net::{IpAddr, Ipv6Addr, SocketAddr}, str::FromStr};
 
 use alloc::{ffi::CString, format, string::ToString, vec::Vec, vec};
+use naming::shared_types::{PollEvents, PollFd, PollKind};
 use syscall::{return_vals::Errno, syscall, SystemCall};
 
 pub struct UdpSocket {
@@ -87,6 +88,11 @@ impl UdpSocket {
         };
         Ok((num_bytes, remote_addr))
     }
+
+    /// Create an entry for `naming::poll`, waiting for `events` on this socket.
+    pub fn poll_fd(&self, events: PollEvents) -> PollFd {
+        PollFd::new(PollKind::Socket, self.handle, events)
+    }
 }
 
 impl Drop for UdpSocket {
@@ -149,6 +155,11 @@ impl TcpListener {
         );
         Ok(TcpStream { handle: self.handle, local_address: self.address, peer_address: remote_addr })
     }
+
+    /// Create an entry for `naming::poll`, which becomes ready once `accept` does not block.
+    pub fn poll_fd(&self) -> PollFd {
+        PollFd::new(PollKind::Socket, self.handle, PollEvents::IN | PollEvents::OUT)
+    }
 }
 
 impl Drop for TcpListener {
@@ -227,6 +238,11 @@ impl TcpStream {
 
         Ok(num_bytes)
     }
+
+    /// Create an entry for `naming::poll`, waiting for `events` on this connection.
+    pub fn poll_fd(&self, events: PollEvents) -> PollFd {
+        PollFd::new(PollKind::Socket, self.handle, events)
+    }
 }
 
 impl Drop for TcpStream {
@@ -309,6 +325,11 @@ impl IcmpSocket {
 
         Ok((num_bytes, address))
     }
+
+    /// Create an entry for `naming::poll`, waiting for `events` on this socket.
+    pub fn poll_fd(&self, events: PollEvents) -> PollFd {
+        PollFd::new(PollKind::Socket, self.handle, events)
+    }
 }
 
 impl Drop for IcmpSocket {
@@ -1,6 +1,10 @@
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use concurrent::process;
 use core::ffi::{c_char, CStr};
 use core::ptr::slice_from_raw_parts;
+use spin::Mutex;
+use syscall::{syscall, SystemCall};
 
 unsafe extern "C" {
     fn strlen(str: *const c_char) -> usize;
@@ -56,4 +60,59 @@ impl Iterator for Args {
                 .ok()
         }
     }
-}
\ No newline at end of file
+}
+
+/// Environment variables of the process, initialized from the envp array (behind argv). \
+/// This is a copy of the environment kept by the kernel, which is only changed by `set_var` (updating both).
+static VARS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Read the environment variables passed by the kernel (called once before `main`)
+pub(crate) fn init() {
+    let mut vars = VARS.lock();
+    unsafe {
+        let mut envp = ARGV_PTR.add(*ARGC_PTR + 1);
+        while !(*envp).is_null() {
+            let len = strlen(*envp as *const c_char);
+            let var = String::from_utf8_lossy(slice_from_raw_parts(*envp, len).as_ref().unwrap());
+            if let Some((key, value)) = var.split_once('=') {
+                vars.push((key.to_string(), value.to_string()));
+            }
+            envp = envp.add(1);
+        }
+    }
+
+    if let Some((_, path)) = vars.iter().find(|(key, _)| key == "PATH") {
+        process::set_search_path(path);
+    }
+}
+
+/// Return all environment variables as (key, value) pairs
+pub fn vars() -> Vec<(String, String)> {
+    VARS.lock().clone()
+}
+
+/// Return the value of the environment variable `key`
+pub fn var(key: &str) -> Option<String> {
+    VARS.lock().iter().find(|(name, _)| name == key).map(|(_, value)| value.clone())
+}
+
+/// Set the environment variable `key` to `value`. Processes started afterwards inherit it. \
+/// `PATH` is used as search path for applications. \
+/// Panics, if `key` is empty or contains `=` or a null character, or if `value` contains a null character.
+pub fn set_var(key: &str, value: &str) {
+    assert!(!key.is_empty() && !key.contains(['=', '\0']), "Invalid environment variable name: {}", key);
+    assert!(!value.contains('\0'), "Invalid environment variable value: {}", value);
+
+    // The kernel is updated while holding the lock, so both copies see concurrent updates in the same order
+    let mut vars = VARS.lock();
+    syscall(SystemCall::ProcessSetEnv, &[key.as_ptr() as usize, key.len(), value.as_ptr() as usize, value.len()])
+        .expect("Failed to set environment variable");
+    match vars.iter_mut().find(|(name, _)| name == key) {
+        Some((_, old_value)) => *old_value = value.to_string(),
+        None => vars.push((key.to_string(), value.to_string())),
+    }
+
+    if key == "PATH" {
+        process::set_search_path(value);
+    }
+}
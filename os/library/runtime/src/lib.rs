@@ -13,13 +13,16 @@
 extern crate alloc;
 
 pub mod env;
+pub mod memory;
 
-use concurrent::{process, thread};
+use concurrent::process;
 use core::panic::PanicInfo;
-use terminal::println;
 use linked_list_allocator::LockedHeap;
 use syscall::{syscall, SystemCall};
 
+/// Exit code of a process terminated by a panic (same as in Rust's std)
+const PANIC_EXIT_CODE: usize = 101;
+
 unsafe extern "C" {
     fn main(argc: isize, argv: *const *const u8) -> isize;
 }
@@ -30,8 +33,10 @@ static ALLOCATOR: LockedHeap = LockedHeap::empty();
 #[cfg(not(any(test, feature = "std")))]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    println!("Panic: {}!", info);
-    thread::exit();
+    // The lock of `println!` may be held by the panicking thread
+    terminal::write::print_unlocked(format_args!("Panic: {}!\n", info));
+    // Panics abort, so the whole process is terminated (otherwise it would never exit)
+    process::exit(PANIC_EXIT_CODE);
 }
 
 #[unsafe(no_mangle)]
@@ -42,9 +47,8 @@ extern "sysv64" fn entry() {
     unsafe {
         ALLOCATOR.lock().init(env::HEAP_START as *mut u8, env::HEAP_SIZE);
     }
+    env::init();
 
-    unsafe {
-        main(*env::ARGC_PTR as isize, env::ARGV_PTR);
-    }
-    process::exit();
+    let exit_code = unsafe { main(*env::ARGC_PTR as isize, env::ARGV_PTR) };
+    process::exit(exit_code as usize);
 }
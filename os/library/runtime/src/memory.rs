@@ -0,0 +1,34 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: memory                                                          ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Syscalls for mapping anonymous memory outside of the heap and   ║
+   ║         changing its access rights.                                     ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use syscall::return_vals::Errno;
+use syscall::{syscall, SystemCall};
+
+pub use syscall::memory::Protection;
+
+/// Map `size` bytes (rounded up to whole pages) of zeroed memory with the access rights `protection`.
+/// Returns the start address of the mapping.
+pub fn mmap(size: usize, protection: Protection) -> Result<*mut u8, Errno> {
+    syscall(SystemCall::Mmap, &[size, protection.bits()]).map(|address| address as *mut u8)
+}
+
+/// Unmap `size` bytes starting at the page aligned `address`. The range must have been mapped by `mmap()`,
+/// but may cover only parts of a mapping or several mappings.
+///
+/// # Safety
+/// The memory must not be accessed afterwards.
+pub unsafe fn munmap(address: *mut u8, size: usize) -> Result<(), Errno> {
+    syscall(SystemCall::Munmap, &[address as usize, size]).map(|_| ())
+}
+
+/// Change the access rights of `size` bytes starting at the page aligned `address` to `protection`.
+/// The range must have been mapped by `mmap()`. Accessing memory in a way not allowed terminates the process.
+pub fn mprotect(address: *mut u8, size: usize, protection: Protection) -> Result<(), Errno> {
+    syscall(SystemCall::Mprotect, &[address as usize, size, protection.bits()]).map(|_| ())
+}
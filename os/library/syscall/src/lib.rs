@@ -10,16 +10,16 @@
 
 use crate::return_vals::SyscallResult;
 
+pub mod memory;
 pub mod return_vals;
 
 /// Enum with all known system calls
 #[repr(usize)]
 #[allow(dead_code)]
 pub enum SystemCall {
-    TerminalReadInput = 0,
+    TerminalRead = 0,
     TerminalWriteInput,
     TerminalCheckInputState,
-    TerminalWriteOutput,
     TerminalReadOutput,
     MapMemory,
     MapFrameBuffer,
@@ -63,6 +63,29 @@ pub enum SystemCall {
     KeyboardRead,
     MapSystemInfo,
     Log,
+    Mount,
+    Umount,
+    Unlink,
+    Rmdir,
+    Rename,
+    Dup,
+    Dup2,
+    ProcessWait,
+    ProcessSetEnv,
+    Symlink,
+    Readlink,
+    Stat,
+    Fstat,
+    Poll,
+    Sync,
+    ThreadGetPriority,
+    ThreadSetPriority,
+    ThreadCpuTime,
+    FutexWait,
+    FutexWake,
+    Mmap,
+    Munmap,
+    Mprotect,
     // no syscall, just marking last number, see NUM_SYSCALLS
     // insert any new system calls before this marker
     LastEntryMarker,
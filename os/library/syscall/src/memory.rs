@@ -0,0 +1,19 @@
+/* ╔═════════════════════════════════════════════════════════════════════════╗
+   ║ Module: memory                                                          ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Descr.: Types for memory mapping syscalls (user and kernel mode).       ║
+   ╟─────────────────────────────────────────────────────────────────────────╢
+   ║ Author: agent, 17.10.2026                                               ║
+   ╚═════════════════════════════════════════════════════════════════════════╝
+*/
+use bitflags::bitflags;
+
+bitflags! {
+    /// Description: Access rights for memory mapped with `Mmap` and changed with `Mprotect` \
+    /// Writable memory is always readable (x86 has no write-only pages) and no flags forbid any access.
+    pub struct Protection: usize {
+        const READ  = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXEC  = 1 << 2;
+    }
+}
@@ -30,6 +30,19 @@ pub enum Errno {
     ECONNRESET = -14, // Connection reset by peer
     ERDONLY    = -15, // Read-only file system
     EAGAIN     = -16, // Resource unavailable
+    EIO        = -17, // Input/output error
+    ENOSPC     = -18, // No space left on device
+    EISDIR     = -19, // Is a directory
+    EXDEV      = -20, // Cross-device link
+    EPIPE      = -21, // Broken pipe
+    ESRCH      = -22, // No such process
+    ENOEXEC    = -23, // Exec format error
+    ELOOP      = -24, // Too many levels of symbolic links
+    ETIMEDOUT  = -25, // Operation timed out
+    ENOMEM     = -26, // Not enough memory
+    EFAULT     = -27, // Bad address
+    ENETDOWN   = -28, // Network is down (no network interface)
+    ECHILD     = -29, // Not a child process
 }
 
 
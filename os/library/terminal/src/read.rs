@@ -9,7 +9,8 @@ use stream::event_from_u16;
    ║ Author: Fabian Ruhland, 31.8.2024, HHU                                  ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
-use syscall::{SystemCall, syscall};
+use naming::shared_types::{PollEvents, PollFd, PollKind, STDIN};
+use syscall::{SystemCall, return_vals::Errno, syscall};
 
 use crate::{DecodedKeyType, TerminalMode};
 
@@ -24,15 +25,7 @@ use crate::{DecodedKeyType, TerminalMode};
 pub fn read() -> String {
     let mut buffer: [u8; 128] = [0; 128];
 
-    let read_bytes = syscall(
-        SystemCall::TerminalReadInput,
-        &[
-            buffer.as_mut_ptr() as usize,
-            buffer.len(),
-            TerminalMode::Canonical as usize,
-        ],
-    )
-    .expect("Unable to read input");
+    let read_bytes = read_stdin(&mut buffer, TerminalMode::Canonical).expect("Unable to read input");
 
     String::from_utf8_lossy(&buffer[0..read_bytes]).to_string()
 }
@@ -47,11 +40,7 @@ pub fn read() -> String {
 pub fn read_fluid() -> Option<DecodedKey> {
     let mut buffer: [u8; 2] = [0; 2];
 
-    let written_bytes = syscall(
-        SystemCall::TerminalReadInput,
-        &[buffer.as_mut_ptr() as usize, buffer.len(), TerminalMode::Fluid as usize],
-    )
-    .expect("Unable to read input");
+    let written_bytes = read_stdin(&mut buffer, TerminalMode::Fluid).expect("Unable to read input");
 
     if written_bytes != 2 {
         return None;
@@ -78,11 +67,7 @@ pub fn read_fluid() -> Option<DecodedKey> {
 pub fn read_raw() -> Option<KeyEvent> {
     let mut buffer: [u8; 2] = [0; 2];
 
-    let len = syscall(
-        SystemCall::TerminalReadInput,
-        &[buffer.as_mut_ptr() as usize, buffer.len(), TerminalMode::Raw as usize],
-    )
-        .expect("Unable to read input");
+    let len = read_stdin(&mut buffer, TerminalMode::Raw).expect("Unable to read input");
     if len > 0 {
         assert_eq!(len, 2);
         let raw = u16::from_ne_bytes(buffer);
@@ -91,3 +76,21 @@ pub fn read_raw() -> Option<KeyEvent> {
         None
     }
 }
+
+/// Read bytes from stdin into `buffer` (canonical mode, if stdin is the terminal).
+/// Returns 0 at the end of the input (e.g. if all writers of a pipe are gone).
+pub fn read_bytes(buffer: &mut [u8]) -> Result<usize, Errno> {
+    read_stdin(buffer, TerminalMode::Canonical)
+}
+
+/// Create an entry for `naming::poll`, which becomes ready once a line can be read with `read` without blocking.
+/// The terminal collects a line (canonical mode) while the application is polling.
+pub fn poll_fd() -> PollFd {
+    PollFd::new(PollKind::Object, STDIN, PollEvents::IN)
+}
+
+/// Read from stdin (handle 0) into `buffer`.
+/// If stdin is the terminal, `mode` is used for reading.
+fn read_stdin(buffer: &mut [u8], mode: TerminalMode) -> Result<usize, Errno> {
+    syscall(SystemCall::TerminalRead, &[STDIN, buffer.as_mut_ptr() as usize, buffer.len(), mode as usize])
+}
@@ -1,15 +1,17 @@
 /* ╔═════════════════════════════════════════════════════════════════════════╗
    ║ Module: write                                                           ║
    ╟─────────────────────────────────────────────────────────────────────────╢
-   ║ Descr.: Write a char to the terminal.                                   ║
+   ║ Descr.: Write a char to stdout (terminal).                              ║
    ╟─────────────────────────────────────────────────────────────────────────╢
    ║ Author: Fabian Ruhland, 31.8.2024, HHU                                  ║
    ╚═════════════════════════════════════════════════════════════════════════╝
 */
 use core::fmt;
 use core::fmt::Write;
+use naming::shared_types::{STDERR, STDOUT};
 use spin::Mutex;
-use syscall::{SystemCall, syscall};
+use syscall::return_vals::Errno;
+use syscall::{syscall, SystemCall};
 
 #[macro_export]
 macro_rules! print {
@@ -28,29 +30,53 @@ macro_rules! println {
     });
 }
 
-static WRITER: Mutex<Writer> = Mutex::new(Writer::new());
+/// Exit code of a process writing to a pipe without reader (128 + SIGPIPE, like on Unix)
+const BROKEN_PIPE_EXIT_CODE: usize = 141;
 
+static WRITER: Mutex<Writer> = Mutex::new(Writer::new(STDOUT));
+
+/// Write to stdout. Errors are dropped, except for writing to a pipe without reader (`EPIPE`), which terminates the process.
 pub fn print(args: fmt::Arguments) {
-    WRITER.lock().write_fmt(args).unwrap();
+    let mut writer = WRITER.lock();
+    let _ = writer.write_fmt(args);
+    let error = writer.error.take();
+    drop(writer);
+
+    if error == Some(Errno::EPIPE) {
+        let _ = syscall(SystemCall::ProcessExit, &[BROKEN_PIPE_EXIT_CODE]);
+    }
 }
 
-struct Writer {}
+/// Write to stderr without locking, so that it also works while `print` has been interrupted (e.g. by a panic).
+/// Errors are dropped.
+pub fn print_unlocked(args: fmt::Arguments) {
+    let _ = Writer::new(STDERR).write_fmt(args);
+}
+
+struct Writer {
+    fd: usize,
+    error: Option<Errno>, // the error of the last failed write
+}
 
 impl Writer {
-    const fn new() -> Self {
-        Self {}
+    const fn new(fd: usize) -> Self {
+        Self { fd, error: None }
     }
 }
 
 impl Write for Writer {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        let res = syscall(
-            SystemCall::TerminalWriteOutput,
-            &[s.as_bytes().as_ptr() as usize, s.len()],
-        );
-        match res {
-            Ok(_) => Ok(()),
-            Err(_) => Err(fmt::Error),
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            match naming::write(self.fd, bytes) {
+                Ok(0) => return Err(fmt::Error),
+                Ok(written) => bytes = &bytes[written..],
+                Err(error) => {
+                    self.error = Some(error);
+                    return Err(fmt::Error);
+                }
+            }
         }
+        Ok(())
     }
 }